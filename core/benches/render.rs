@@ -0,0 +1,85 @@
+//! Timings of the pixel loop: the inner-border distance every pixel starts
+//! with, and whole frames at common monitor sizes
+//!
+//! `cargo bench -p hypr-ringlight-core`, optionally with a filter on the
+//! names, e.g. `cargo bench -p hypr-ringlight-core -- 4k`. criterion keeps
+//! the results of the last run under `target/criterion` and reports the
+//! change against them; `hypr-ringlight --bench-frame` times a frame of your
+//! own config instead.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use hypr_ringlight_core::config::{CornerStyle, GlowFalloff, Pattern, Taper};
+use hypr_ringlight_core::render::{render, Ring};
+use hypr_ringlight_core::shape::{distance_to_inner_rounded_border, Shape};
+
+const SIZES: [(&str, u32, u32); 3] = [("1080p", 1920, 1080), ("1440p", 2560, 1440), ("4k", 3840, 2160)];
+
+fn shape(style: CornerStyle) -> Shape {
+    Shape { radii: [200.0; 4], style, notch: None, taper: Taper::default() }
+}
+
+fn ring(style: CornerStyle) -> Ring<'static> {
+    Ring {
+        inset: 0.0,
+        thickness: 80.0,
+        glow: 80.0,
+        falloff: GlowFalloff::Cubic,
+        inner_glow: 0.0,
+        vignette: None,
+        fill: None,
+        catchlight: None,
+        shape: shape(style),
+        color: (255, 255, 255),
+        texture: None,
+        opacity: 1.0,
+        progress: None,
+        comet: None,
+        spectrum: None,
+        segments: &[],
+        pattern: Pattern::Solid,
+        backdrop: false,
+        dither: true,
+        gamma_correct: false,
+    }
+}
+
+fn distance_to_inner(c: &mut Criterion) {
+    let (w, h) = (1920.0, 1080.0);
+    let mut group = c.benchmark_group("distance_to_inner");
+    // A 1080p frame's worth of points per iteration
+    group.throughput(Throughput::Elements(1920 * 1080));
+    for (style, name) in [(CornerStyle::Round, "round"), (CornerStyle::Squircle, "squircle"), (CornerStyle::Chamfer, "chamfer")] {
+        let shape = shape(style);
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for y in 0..1080 {
+                    for x in 0..1920 {
+                        black_box(distance_to_inner_rounded_border(x as f64, y as f64, w, h, 160.0, black_box(&shape)));
+                    }
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn frame(c: &mut Criterion) {
+    for (size, width, height) in SIZES {
+        let mut group = c.benchmark_group(format!("frame/{}", size));
+        let mut canvas = vec![0u8; width as usize * height as usize * 4];
+        let rings = [
+            ("round", ring(CornerStyle::Round)),
+            ("squircle", ring(CornerStyle::Squircle)),
+            ("gamma_correct", Ring { gamma_correct: true, ..ring(CornerStyle::Round) }),
+        ];
+        for (name, ring) in &rings {
+            group.bench_function(*name, |b| b.iter(|| render(black_box(&mut canvas), width, height, black_box(ring))));
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, distance_to_inner, frame);
+criterion_main!(benches);
@@ -0,0 +1,512 @@
+//! Config validation: what `Config::load` forgives but the user should fix
+//!
+//! Loading is lenient on purpose, so a typo never keeps the ring from
+//! starting: unknown keys are ignored and unknown names fall back to a
+//! default. `check` reports those, along with values out of range, each with
+//! the line it's on and, where possible, a suggested fix.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use chrono::format::{Item, StrftimeItems};
+use toml::Value;
+
+use crate::color::is_color;
+use crate::config::{parse_duration_ms, Config, ExcludeZone, MonitorConfig, OnAir, Profile, WindowRule};
+use crate::render::ANIMATIONS;
+
+/// One problem found in a config
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// 1-based line in the file, when known
+    pub line: Option<usize>,
+    /// Dotted path of the offending key, e.g. `profiles.call.opacity`
+    pub key: String,
+    pub message: String,
+    /// How to fix it
+    pub hint: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if !self.key.is_empty() {
+            write!(f, "{}: ", self.key)?;
+        }
+        f.write_str(&self.message)?;
+        if let Some(hint) = &self.hint {
+            write!(f, " ({})", hint)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check the config file at `path`; a missing file is a valid (default) config
+pub fn check_file(path: &Path) -> Result<Vec<Diagnostic>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(check_str(&content))
+}
+
+/// Check the text of a config file: syntax, unknown keys, then values
+pub fn check_str(content: &str) -> Vec<Diagnostic> {
+    let mut unknown = Vec::new();
+    let config: Result<Config, _> =
+        serde_ignored::deserialize(toml::Deserializer::new(content), |path| unknown.push(path.to_string()));
+    let config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            return vec![Diagnostic {
+                line: e.span().map(|span| line_of(content, span.start)),
+                key: String::new(),
+                message: e.message().to_string(),
+                hint: None,
+            }]
+        }
+    };
+
+    let schema = schema();
+    let mut diagnostics: Vec<Diagnostic> = unknown
+        .into_iter()
+        // Read only to migrate old configs
+        .filter(|key| key != "animation_speed")
+        .map(|key| {
+            let (parent, name) = key.rsplit_once('.').unwrap_or(("", &key));
+            let hint = closest(name, &known_keys(&schema, parent)).map(|k| format!("did you mean `{}`?", k));
+            Diagnostic { line: None, key, message: "unknown key, ignored".to_string(), hint }
+        })
+        .collect();
+    diagnostics.extend(check_config(&config));
+    for d in &mut diagnostics {
+        d.line = find_line(content, &d.key);
+    }
+    diagnostics.sort_by_key(|d| d.line);
+    diagnostics
+}
+
+/// Check the values of a loaded config
+pub fn check_config(config: &Config) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut report = |key: &str, message: String, hint: Option<String>| {
+        diagnostics.push(Diagnostic { line: None, key: key.to_string(), message, hint });
+    };
+
+    for (name, value) in &config.palette {
+        if !is_color(value) {
+            report(&format!("palette.{}", name), format!("'{}' is not a color", value), Some("use hex or CSS, e.g. \"ff8800\" or \"orange\"".to_string()));
+        }
+    }
+    let mut color = |key: &str, value: &str| {
+        if !is_color(value) && !config.palette.contains_key(value) {
+            let names: Vec<&str> = config.palette.keys().map(String::as_str).collect();
+            let hint = match closest(value, &names) {
+                Some(name) => format!("did you mean `{}`?", name),
+                None => "use hex or CSS, e.g. \"ff8800\" or \"orange\", or a name from [palette]".to_string(),
+            };
+            report(key, format!("'{}' is not a color", value), Some(hint));
+        }
+    };
+    color("color", &config.color);
+    color("lock.color", &config.lock.color);
+    if !config.strobe.color.is_empty() {
+        color("strobe.color", &config.strobe.color);
+    }
+    color("watch.success_color", &config.watch.success_color);
+    color("watch.failure_color", &config.watch.failure_color);
+    if !config.label.color.is_empty() {
+        color("label.color", &config.label.color);
+    }
+    color("fill.color", &config.fill.color);
+    if !config.obs.dropped_frames.is_empty() {
+        color("obs.dropped_frames", &config.obs.dropped_frames);
+    }
+    for (name, profile) in &config.profiles {
+        if let Some(value) = &profile.color {
+            color(&format!("profiles.{}.color", name), value);
+        }
+    }
+    for (class, value) in &config.hyprland.accent.colors {
+        color(&format!("hyprland.accent.colors.{}", class), value);
+    }
+
+    let mut range = |key: &str, value: f64, min: f64, max: f64| {
+        if !(min..=max).contains(&value) {
+            report(key, format!("{} is out of range", value), Some(format!("use {} to {}", min, max)));
+        }
+    };
+    range("thickness", config.thickness as f64, 1.0, 1000.0);
+    range("opacity", config.opacity, 0.0, 1.0);
+    range("corner_radius", config.corner_radius, 0.0, 10.0);
+    range("taper", config.taper, 0.0, 1.0);
+    range("render_scale", config.render_scale, 0.25, 1.0);
+    range("idle.brightness", config.idle.brightness, 0.0, 1.0);
+    range("progress.track", config.progress.track, 0.0, 1.0);
+    range("spectrum.floor", config.spectrum.floor, 0.0, 1.0);
+    range("spectrum.bars", config.spectrum.bars as f64, 1.0, 256.0);
+    range("system.floor", config.system.floor, 0.0, 1.0);
+    range("typing.rest", config.typing.rest, 0.0, 1.0);
+    range("hyprland.special_dim", config.hyprland.special_dim, 0.0, 1.0);
+    range("openrgb.rate", config.openrgb.rate as f64, 1.0, 60.0);
+    range("framing.opacity", config.framing.opacity, 0.0, 1.0);
+    range("fill.opacity", config.fill.opacity, 0.0, 1.0);
+    range("webcam.floor", config.webcam.floor, 0.0, 1.0);
+    range("label.backing", config.label.backing, 0.0, 1.0);
+    for (name, profile) in &config.profiles {
+        if let Some(v) = profile.opacity {
+            range(&format!("profiles.{}.opacity", name), v, 0.0, 1.0);
+        }
+        if let Some(v) = profile.thickness {
+            range(&format!("profiles.{}.thickness", name), v as f64, 1.0, 1000.0);
+        }
+    }
+
+    let mut name = |key: &str, value: &str, names: &[&str]| {
+        if !names.contains(&value.to_lowercase().as_str()) {
+            let hint = match closest(value, names) {
+                Some(name) => format!("did you mean \"{}\"?", name),
+                None => format!("one of {}", names.join(", ")),
+            };
+            report(key, format!("unknown value '{}', the default is used", value), Some(hint));
+        }
+    };
+    const PATTERNS: [&str; 3] = ["solid", "dashed", "dotted"];
+    name("animation", &config.animation, &ANIMATIONS);
+    name("corner_style", &config.corner_style, &["round", "squircle", "chamfer"]);
+    name("glow_falloff", &config.glow_falloff, &["linear", "quadratic", "cubic", "exponential", "gaussian"]);
+    name("warmup_curve", &config.warmup_curve, &["linear", "ease-in", "ease-out", "smoothstep"]);
+    name("pattern", &config.pattern, &PATTERNS);
+    name("taper_anchor", &config.taper_anchor, &["corners", "centers", "center"]);
+    name("bar_position", &config.bar_position, &["top", "bottom", "left", "right"]);
+    name("layer", &config.layer, &["background", "bottom", "top", "overlay"]);
+    name("texture.mode", &config.texture.mode, &["tile", "stretch"]);
+    name("system.source", &config.system.source, &["cpu", "gpu", "temperature", "temp"]);
+    name("system.map", &config.system.map, &["hue", "brightness"]);
+    name(
+        "spectrum.origin",
+        &config.spectrum.origin,
+        &["top-left", "top", "top-right", "right", "bottom-right", "bottom", "bottom-left", "left"],
+    );
+    if !config.webcam.position.is_empty() {
+        name(
+            "webcam.position",
+            &config.webcam.position,
+            &["top-left", "top", "top-center", "top-right", "right", "bottom-right", "bottom", "bottom-center", "bottom-left", "left"],
+        );
+    }
+    for (button, action) in [("left", &config.click.left), ("right", &config.click.right), ("middle", &config.click.middle)] {
+        if !action.is_empty() {
+            name(&format!("click.{}", button), action, &["toggle", "next-profile"]);
+        }
+    }
+    name("camera.urgency", &config.camera.urgency, &["low", "normal", "critical"]);
+    name("theme.source", &config.theme.source, &["omarchy", "base16", "pywal", "wal", "hyprland"]);
+    for (monitor, overrides) in &config.monitor {
+        if let Some(position) = &overrides.bar_position {
+            name(&format!("monitor.{}.bar_position", monitor), position, &["top", "bottom", "left", "right"]);
+        }
+    }
+    for (profile, animation) in config.profiles.iter().filter_map(|(n, p)| Some((n, p.animation.as_ref()?))) {
+        name(&format!("profiles.{}.animation", profile), animation, &ANIMATIONS);
+    }
+    for (profile, pattern) in config.profiles.iter().filter_map(|(n, p)| Some((n, p.pattern.as_ref()?))) {
+        name(&format!("profiles.{}.pattern", profile), pattern, &PATTERNS);
+    }
+    for source in config.on_air.priority.iter().filter(|s| OnAir::from_name(s).is_none()) {
+        let hint = closest(source, &["camera", "share", "mic"]).map(|name| format!("did you mean \"{}\"?", name));
+        report("on_air.priority", format!("unknown source '{}', ignored", source), hint);
+    }
+
+    let durations = [
+        ("cycle_duration", &config.cycle_duration),
+        ("warmup", &config.warmup),
+        ("idle.timeout", &config.idle.timeout),
+        ("idle.fade", &config.idle.fade),
+        ("transition.fade_in", &config.transition.fade_in),
+        ("transition.fade_out", &config.transition.fade_out),
+        ("typing.pause", &config.typing.pause),
+        ("typing.fade", &config.typing.fade),
+        ("camera.timeout", &config.camera.timeout),
+    ];
+    for (key, value) in durations {
+        if parse_duration_ms(value).is_none() {
+            report(key, format!("'{}' is not a duration", value), Some("e.g. \"2s\", \"500ms\" or \"1m 30s\"".to_string()));
+        }
+    }
+    if StrftimeItems::new(&config.label.clock).any(|item| item == Item::Error) {
+        report("label.clock", format!("'{}' is not a time format", config.label.clock), Some("e.g. \"%H:%M\" or \"%-I:%M %p\"".to_string()));
+    }
+
+    let profile_hint = || match config.profiles.keys().map(|k| k.as_str()).collect::<Vec<_>>() {
+        names if names.is_empty() => "define it under [profiles]".to_string(),
+        names => format!("defined profiles: {}", names.join(", ")),
+    };
+    for (i, rule) in config.hyprland.window_rules.iter().enumerate() {
+        if let Some(profile) = rule.profile.as_ref().filter(|p| !config.profiles.contains_key(*p)) {
+            report(
+                &format!("hyprland.window_rules.{}.profile", i),
+                format!("unknown profile '{}', the rule is ignored", profile),
+                Some(profile_hint()),
+            );
+        }
+    }
+    let workspaces = config.hyprland.workspaces.iter().map(|(w, p)| (format!("hyprland.workspaces.{}", w), p, "hide"));
+    let special = config.hyprland.special.iter().map(|(w, p)| (format!("hyprland.special.{}", w), p, "dim"));
+    for (key, profile, builtin) in workspaces.chain(special) {
+        if profile != "hide" && profile != builtin && !config.profiles.contains_key(profile) {
+            report(
+                &key,
+                format!("unknown profile '{}', ignored", profile),
+                Some(profile_hint()),
+            );
+        }
+    }
+    let schemes = [
+        ("color_scheme.dark", &config.color_scheme.dark),
+        ("color_scheme.light", &config.color_scheme.light),
+        ("media.playing", &config.media.playing),
+        ("media.paused", &config.media.paused),
+        ("dnd.profile", &config.dnd.profile),
+        ("obs.recording", &config.obs.recording),
+        ("obs.streaming", &config.obs.streaming),
+        ("on_air.camera", &config.on_air.camera),
+        ("on_air.share", &config.on_air.share),
+        ("on_air.mic", &config.on_air.mic),
+    ];
+    for (key, profile) in schemes {
+        if !profile.is_empty() && !config.profiles.contains_key(profile) {
+            report(key, format!("unknown profile '{}', ignored", profile), Some(profile_hint()));
+        }
+    }
+    match config.http.listen.parse::<std::net::SocketAddr>() {
+        Err(_) => report(
+            "http.listen",
+            format!("'{}' is not an address", config.http.listen),
+            Some("e.g. \"127.0.0.1:7272\"".to_string()),
+        ),
+        Ok(addr) if !addr.ip().is_loopback() && config.http.token.is_empty() => report(
+            "http.listen",
+            format!("{} is reachable from other machines without a token", addr),
+            Some("set http.token".to_string()),
+        ),
+        Ok(_) => {}
+    }
+    if config.system.temp_max <= config.system.temp_min {
+        report(
+            "system.temp_max",
+            format!("{} is not above temp_min ({})", config.system.temp_max, config.system.temp_min),
+            None,
+        );
+    }
+    diagnostics
+}
+
+/// Every key the config understands, as a TOML tree. Maps keyed by the
+/// user (profiles, monitors) have their entry under `*`.
+fn schema() -> Value {
+    let mut schema = Value::try_from(Config::default()).unwrap_or(Value::Table(Default::default()));
+    let profile = Profile {
+        color: Some(String::new()),
+        thickness: Some(0),
+        opacity: Some(0.0),
+        glow: Some(0),
+        corner_radius: Some(0.0),
+        animation: Some(String::new()),
+        cycle_duration: Some(String::new()),
+        pattern: Some(String::new()),
+        visible: Some(false),
+    };
+    let monitor = MonitorConfig { bar_height: Some(0), bar_position: Some(String::new()) };
+    let rule = WindowRule {
+        class: Some(String::new()),
+        title: Some(String::new()),
+        fullscreen: Some(false),
+        profile: Some(String::new()),
+        hide: false,
+    };
+    let entries = [
+        ("profiles.*", Value::try_from(profile)),
+        ("monitor.*", Value::try_from(monitor)),
+        ("exclude.0", Value::try_from(ExcludeZone::default())),
+        ("hyprland.window_rules.0", Value::try_from(rule)),
+        ("corner_radii", Ok(Value::Array(Vec::new()))),
+        ("palette.*", Ok(Value::String(String::new()))),
+    ];
+    for (path, value) in entries {
+        let Ok(value) = value else { continue };
+        let mut node = &mut schema;
+        for part in path.split('.') {
+            node = match node {
+                Value::Array(items) => {
+                    if items.is_empty() {
+                        items.push(Value::Table(Default::default()));
+                    }
+                    &mut items[0]
+                }
+                Value::Table(table) => table.entry(part).or_insert(Value::Table(Default::default())),
+                _ => break,
+            };
+        }
+        *node = value;
+    }
+    schema
+}
+
+/// Whether the config understands the dotted `key`
+pub(crate) fn is_known_key(key: &str) -> bool {
+    let (parent, name) = key.rsplit_once('.').unwrap_or(("", key));
+    let schema = schema();
+    let keys = known_keys(&schema, parent);
+    keys.contains(&name) || keys.contains(&"*")
+}
+
+/// Keys allowed under the table at dotted `path` in `schema`
+fn known_keys<'a>(schema: &'a Value, path: &str) -> Vec<&'a str> {
+    let mut node = schema;
+    for part in path.split('.').filter(|p| !p.is_empty()) {
+        node = match node {
+            Value::Table(table) => match table.get(part).or_else(|| table.get("*")) {
+                Some(next) => next,
+                None => return Vec::new(),
+            },
+            Value::Array(items) => match items.first() {
+                Some(next) => next,
+                None => return Vec::new(),
+            },
+            _ => return Vec::new(),
+        };
+    }
+    match node {
+        Value::Table(table) => table.keys().map(|k| k.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The candidate closest to `word`, if it's a plausible typo
+fn closest<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let word = word.to_lowercase();
+    candidates
+        .iter()
+        .map(|c| (edit_distance(&word, c), *c))
+        .filter(|(d, c)| *d <= (c.len() / 3).max(1))
+        .min()
+        .map(|(_, c)| c)
+}
+
+/// Edit distance, counting a swap of neighbouring letters as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+/// Line defining the dotted `key`, found by following table headers.
+/// Array entries (`exclude.0`) count `[[exclude]]` headers.
+fn find_line(content: &str, key: &str) -> Option<usize> {
+    let parts: Vec<&str> = key.split('.').collect();
+    let (name, parents) = parts.split_last()?;
+    let unquote = |s: &str| s.trim().trim_matches('"').to_string();
+
+    // Normalize the path to a header name plus the entry index for arrays of tables
+    let mut header = Vec::new();
+    let mut index = None;
+    for part in parents {
+        match part.parse::<usize>() {
+            Ok(i) => index = Some(i),
+            Err(_) => header.push(part.to_string()),
+        }
+    }
+
+    let mut table: Vec<String> = Vec::new();
+    let mut seen = 0;
+    let mut in_target = header.is_empty();
+    for (n, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(inner) = line.strip_prefix("[[").and_then(|l| l.split("]]").next()) {
+            table = inner.split('.').map(unquote).collect();
+            in_target = table == header && index == Some(seen);
+            if table == header {
+                seen += 1;
+            }
+            continue;
+        }
+        if let Some(inner) = line.strip_prefix('[').and_then(|l| l.split(']').next()) {
+            table = inner.split('.').map(unquote).collect();
+            in_target = table == header;
+            continue;
+        }
+        let Some((k, _)) = line.split_once('=') else { continue };
+        // Inline dotted keys, e.g. `idle.timeout = "5m"` at the top level
+        let mut full: Vec<String> = table.clone();
+        full.extend(k.split('.').map(unquote));
+        let target: Vec<String> = header.iter().cloned().chain(std::iter::once(name.to_string())).collect();
+        if (in_target && unquote(k) == *name) || (index.is_none() && full == target) {
+            return Some(n + 1);
+        }
+    }
+    // A whole table, e.g. an unknown `[colour]` section
+    content.lines().position(|l| {
+        let l = l.trim();
+        l.starts_with('[') && l.trim_matches(|c| c == '[' || c == ']').split('.').map(unquote).eq(parts.iter().map(|p| p.to_string()))
+    }).map(|n| n + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_str() {
+        let content = "colr = \"ff0000\"\nopacity = 1.5\n\n[profiles.call]\nthicknes = 120\n\n[[hyprland.window_rules]]\nclass = \"zoom\"\nprofile = \"meeting\"\n";
+        let diagnostics = check_str(content);
+        let find = |key: &str| diagnostics.iter().find(|d| d.key == key).unwrap_or_else(|| panic!("{}", key));
+
+        assert_eq!(find("colr").line, Some(1));
+        assert_eq!(find("colr").hint.as_deref(), Some("did you mean `color`?"));
+        assert_eq!(find("opacity").line, Some(2));
+        assert_eq!(find("profiles.call.thicknes").line, Some(5));
+        assert_eq!(find("profiles.call.thicknes").hint.as_deref(), Some("did you mean `thickness`?"));
+        assert_eq!(find("hyprland.window_rules.0.profile").line, Some(9));
+        assert_eq!(diagnostics.len(), 4);
+
+        let broken = check_str("thickness = 80\nopacity = \"full\"\n");
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].line, Some(2));
+
+        let zones = check_str("[[exclude]]\nx = 1\n[[exclude]]\nwidht = 10\n");
+        assert_eq!(zones[0].key, "exclude.1.widht");
+        assert_eq!(zones[0].line, Some(4));
+        assert_eq!(zones[0].hint.as_deref(), Some("did you mean `width`?"));
+
+        assert!(check_str("color = \"ff8800\"\nanimation = \"comet\"\n").is_empty());
+        assert!(check_str("[label]\nclock = \"%H:%M\"\n").is_empty());
+        assert_eq!(check_str("[label]\nclock = \"%H:%Q\"\n")[0].key, "label.clock");
+
+        assert!(check_str("color = \"call\"\n[palette]\ncall = \"f38ba8\"\n").is_empty());
+        let palette = check_str("color = \"cal\"\n[palette]\ncall = \"rouge\"\n");
+        assert_eq!(palette.len(), 2);
+        assert_eq!(palette.iter().find(|d| d.key == "color").unwrap().hint.as_deref(), Some("did you mean `call`?"));
+        assert_eq!(palette.iter().find(|d| d.key == "palette.call").unwrap().line, Some(3));
+    }
+}
@@ -0,0 +1,266 @@
+//! Color conversions
+
+use std::collections::BTreeMap;
+
+/// Parse a color: hex with 3, 4, 6 or 8 digits (the '#' is optional), a CSS
+/// name like `rebeccapurple`, or CSS `rgb()`/`hsl()`. Any alpha is dropped.
+pub fn parse_color(value: &str) -> Option<(u8, u8, u8)> {
+    parse_color_alpha(value).map(|(rgb, _)| rgb)
+}
+
+/// Whether `value` is a color `parse_color` understands
+pub fn is_color(value: &str) -> bool {
+    parse_color_alpha(value).is_some()
+}
+
+/// Parse a color like `parse_color`, with its alpha (0.0 - 1.0; 1.0 if it has none)
+pub fn parse_color_alpha(value: &str) -> Option<((u8, u8, u8), f64)> {
+    let value = value.trim().to_ascii_lowercase();
+    if let Some((function, args)) = value.strip_suffix(')').and_then(|v| v.split_once('(')) {
+        return parse_function(function.trim(), args);
+    }
+    if let Ok(i) = NAMED_COLORS.binary_search_by_key(&value.as_str(), |&(name, _)| name) {
+        let [_, r, g, b] = NAMED_COLORS[i].1.to_be_bytes();
+        return Some(((r, g, b), 1.0));
+    }
+    parse_hex(value.strip_prefix('#').unwrap_or(&value))
+}
+
+fn parse_hex(hex: &str) -> Option<((u8, u8, u8), f64)> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let (rgb, alpha) = match hex.len() {
+        3 => ((digit(0)?, digit(1)?, digit(2)?), 255),
+        4 => ((digit(0)?, digit(1)?, digit(2)?), digit(3)?),
+        6 => ((pair(0)?, pair(2)?, pair(4)?), 255),
+        8 => ((pair(0)?, pair(2)?, pair(4)?), pair(6)?),
+        _ => return None,
+    };
+    Some((rgb, alpha as f64 / 255.0))
+}
+
+/// `rgb(255, 136, 0)`, `rgb(100% 50% 0% / 0.5)`, `hsl(200, 80%, 60%)` and their `a` variants
+fn parse_function(function: &str, args: &str) -> Option<((u8, u8, u8), f64)> {
+    let (channels, alpha) = match args.split_once('/') {
+        Some((channels, alpha)) => (channels, Some(alpha)),
+        None => (args, None),
+    };
+    let mut parts: Vec<&str> = channels.split([',', ' ']).map(str::trim).filter(|p| !p.is_empty()).collect();
+    let alpha = match (alpha, parts.len()) {
+        (Some(alpha), 3) => alpha.trim(),
+        (None, 4) => parts.pop()?,
+        (None, 3) => "1",
+        _ => return None,
+    };
+    let alpha = match alpha.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().ok()? / 100.0,
+        None => alpha.parse::<f64>().ok()?,
+    };
+    // Percentages, and plain numbers out of `max`
+    let number = |part: &str, max: f64| -> Option<f64> {
+        let fraction = match part.strip_suffix('%') {
+            Some(percent) => percent.parse::<f64>().ok()? / 100.0,
+            None => part.parse::<f64>().ok()? / max,
+        };
+        fraction.is_finite().then(|| fraction.clamp(0.0, 1.0))
+    };
+    let rgb = match function {
+        "rgb" | "rgba" => {
+            let channel = |part: &str| number(part, 255.0).map(|c| (c * 255.0).round() as u8);
+            (channel(parts[0])?, channel(parts[1])?, channel(parts[2])?)
+        }
+        "hsl" | "hsla" => {
+            let hue = parts[0].strip_suffix("deg").unwrap_or(parts[0]).parse::<f64>().ok()?;
+            if !hue.is_finite() {
+                return None;
+            }
+            hsl_to_rgb(hue.rem_euclid(360.0) / 360.0, number(parts[1], 100.0)?, number(parts[2], 100.0)?)
+        }
+        _ => return None,
+    };
+    alpha.is_finite().then(|| (rgb, alpha.clamp(0.0, 1.0)))
+}
+
+/// Whether `value` is a 6-digit hex color, with or without the leading '#'
+pub fn is_hex_color(value: &str) -> bool {
+    let hex = value.trim_start_matches('#');
+    hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Parse an 8-digit `AARRGGBB` hex color, as Hyprland writes them; the alpha is dropped
+pub fn parse_argb(argb: &str) -> Option<(u8, u8, u8)> {
+    argb.get(2..).filter(|rgb| argb.len() == 8 && is_hex_color(rgb)).and_then(parse_color)
+}
+
+/// Parse a color or a name from `palette`; unknown colors are white
+pub fn resolve_color(value: &str, palette: &BTreeMap<String, String>) -> (u8, u8, u8) {
+    resolve_color_alpha(value, palette).0
+}
+
+/// `resolve_color` with the alpha
+pub fn resolve_color_alpha(value: &str, palette: &BTreeMap<String, String>) -> ((u8, u8, u8), f64) {
+    parse_color_alpha(palette.get(value).map_or(value, String::as_str)).unwrap_or(((255, 255, 255), 1.0))
+}
+
+/// Format a color as lowercase hex without the leading '#'
+pub fn color_to_hex(r: u8, g: u8, b: u8) -> String {
+    format!("{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Format a color as "rrggbb", or "rrggbbaa" when it isn't opaque
+pub fn color_to_hex_alpha((r, g, b): (u8, u8, u8), alpha: f64) -> String {
+    match (alpha.clamp(0.0, 1.0) * 255.0).round() as u8 {
+        255 => color_to_hex(r, g, b),
+        a => format!("{}{:02x}", color_to_hex(r, g, b), a),
+    }
+}
+
+/// Black or white, whichever stands out against `color`
+pub fn contrast_color((r, g, b): (u8, u8, u8)) -> (u8, u8, u8) {
+    let luma = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+    if luma > 140.0 { (0, 0, 0) } else { (255, 255, 255) }
+}
+
+/// Convert hue, saturation and lightness (all 0.0 - 1.0) to RGB
+pub fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (u8, u8, u8) {
+    if s == 0.0 {
+        let v = (l * 255.0) as u8;
+        return (v, v, v);
+    }
+
+    let q = if l < 0.5 { l * (1.0 + s) } else { l + s - l * s };
+    let p = 2.0 * l - q;
+
+    let hue_to_rgb = |p: f64, q: f64, mut t: f64| -> f64 {
+        if t < 0.0 { t += 1.0; }
+        if t > 1.0 { t -= 1.0; }
+        if t < 1.0 / 6.0 { return p + (q - p) * 6.0 * t; }
+        if t < 1.0 / 2.0 { return q; }
+        if t < 2.0 / 3.0 { return p + (q - p) * (2.0 / 3.0 - t) * 6.0; }
+        p
+    };
+
+    (
+        (hue_to_rgb(p, q, h + 1.0 / 3.0) * 255.0) as u8,
+        (hue_to_rgb(p, q, h) * 255.0) as u8,
+        (hue_to_rgb(p, q, h - 1.0 / 3.0) * 255.0) as u8,
+    )
+}
+
+/// Decode an sRGB channel (0.0 - 1.0) to linear light
+pub fn srgb_to_linear(v: f64) -> f64 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a linear-light channel (0.0 - 1.0) as sRGB
+pub fn linear_to_srgb(v: f64) -> f64 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// The CSS named colors, sorted by name
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff), ("antiquewhite", 0xfaebd7), ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4), ("azure", 0xf0ffff), ("beige", 0xf5f5dc), ("bisque", 0xffe4c4),
+    ("black", 0x000000), ("blanchedalmond", 0xffebcd), ("blue", 0x0000ff), ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a), ("burlywood", 0xdeb887), ("cadetblue", 0x5f9ea0), ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e), ("coral", 0xff7f50), ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc), ("crimson", 0xdc143c), ("cyan", 0x00ffff), ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b), ("darkgoldenrod", 0xb8860b), ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400), ("darkgrey", 0xa9a9a9), ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b), ("darkolivegreen", 0x556b2f), ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc), ("darkred", 0x8b0000), ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f), ("darkslateblue", 0x483d8b), ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f), ("darkturquoise", 0x00ced1), ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493), ("deepskyblue", 0x00bfff), ("dimgray", 0x696969), ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff), ("firebrick", 0xb22222), ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22), ("fuchsia", 0xff00ff), ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff), ("gold", 0xffd700), ("goldenrod", 0xdaa520), ("gray", 0x808080),
+    ("green", 0x008000), ("greenyellow", 0xadff2f), ("grey", 0x808080), ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4), ("indianred", 0xcd5c5c), ("indigo", 0x4b0082), ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c), ("lavender", 0xe6e6fa), ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00), ("lemonchiffon", 0xfffacd), ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080), ("lightcyan", 0xe0ffff), ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3), ("lightgreen", 0x90ee90), ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1), ("lightsalmon", 0xffa07a), ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa), ("lightslategray", 0x778899), ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de), ("lightyellow", 0xffffe0), ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32), ("linen", 0xfaf0e6), ("magenta", 0xff00ff), ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa), ("mediumblue", 0x0000cd), ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db), ("mediumseagreen", 0x3cb371), ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a), ("mediumturquoise", 0x48d1cc), ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970), ("mintcream", 0xf5fffa), ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5), ("navajowhite", 0xffdead), ("navy", 0x000080), ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000), ("olivedrab", 0x6b8e23), ("orange", 0xffa500), ("orangered", 0xff4500),
+    ("orchid", 0xda70d6), ("palegoldenrod", 0xeee8aa), ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee), ("palevioletred", 0xdb7093), ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9), ("peru", 0xcd853f), ("pink", 0xffc0cb), ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6), ("purple", 0x800080), ("rebeccapurple", 0x663399), ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f), ("royalblue", 0x4169e1), ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072), ("sandybrown", 0xf4a460), ("seagreen", 0x2e8b57), ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d), ("silver", 0xc0c0c0), ("skyblue", 0x87ceeb), ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090), ("slategrey", 0x708090), ("snow", 0xfffafa), ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4), ("tan", 0xd2b48c), ("teal", 0x008080), ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347), ("turquoise", 0x40e0d0), ("violet", 0xee82ee), ("wheat", 0xf5deb3),
+    ("white", 0xffffff), ("whitesmoke", 0xf5f5f5), ("yellow", 0xffff00), ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_conversions() {
+        assert_eq!(parse_color("#89b4fa"), Some((137, 180, 250)));
+        assert_eq!(parse_color("oops"), None);
+        assert_eq!(color_to_hex(137, 180, 250), "89b4fa");
+        let palette = BTreeMap::from([("call".to_string(), "f38ba8".to_string())]);
+        assert_eq!(resolve_color("call", &palette), (243, 139, 168));
+        assert_eq!(resolve_color("#89b4fa", &palette), (137, 180, 250));
+        assert!(is_hex_color("#89b4fa") && !is_hex_color("call"));
+        assert_eq!(contrast_color((255, 255, 255)), (0, 0, 0));
+        assert_eq!(contrast_color((255, 0, 0)), (255, 255, 255));
+        assert_eq!(hsl_to_rgb(0.0, 1.0, 0.5), (255, 0, 0));
+        assert_eq!(hsl_to_rgb(0.5, 0.0, 1.0), (255, 255, 255));
+        for v in [0.0, 0.002, 0.2, 0.5, 1.0] {
+            assert!((linear_to_srgb(srgb_to_linear(v)) - v).abs() < 1e-9);
+        }
+        assert!((linear_to_srgb(0.214) - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("89B4FA"), Some((137, 180, 250)));
+        assert_eq!(parse_color("#f80"), Some((255, 136, 0)));
+        assert_eq!(parse_color("#f80c"), Some((255, 136, 0)));
+        assert_eq!(parse_color("89b4fa80"), Some((137, 180, 250)));
+        assert_eq!(parse_color("Red"), Some((255, 0, 0)));
+        assert_eq!(parse_color("rebeccapurple"), Some((102, 51, 153)));
+        assert_eq!(parse_color("rgb(255,136,0)"), Some((255, 136, 0)));
+        assert_eq!(parse_color("rgba(255, 136, 0, 0.5)"), Some((255, 136, 0)));
+        assert_eq!(parse_color("rgb(100% 0% 50% / 50%)"), Some((255, 0, 128)));
+        assert_eq!(parse_color("hsl(0, 100%, 50%)"), Some((255, 0, 0)));
+        assert_eq!(parse_color("hsl(200deg 80% 60%)"), parse_color("hsl(560, 80%, 60%)"));
+        assert!(is_color(" hsla(200, 80%, 60%, 1) "));
+        assert_eq!(parse_color_alpha("ff880080"), Some(((255, 136, 0), 128.0 / 255.0)));
+        assert_eq!(parse_color_alpha("rgb(255 136 0 / 25%)"), Some(((255, 136, 0), 0.25)));
+        assert_eq!(parse_color_alpha("orange").map(|(_, a)| a), Some(1.0));
+        assert_eq!(color_to_hex_alpha((255, 136, 0), 128.0 / 255.0), "ff880080");
+        assert_eq!(color_to_hex_alpha((255, 136, 0), 1.0), "ff8800");
+        assert_eq!(parse_argb("ee33ccff"), Some((51, 204, 255)));
+        assert_eq!(parse_argb("33ccff"), None);
+        for bad in ["", "#12345", "ff88zz", "rgb(1, 2)", "rgb(1, 2, 3, 4, 5)", "cmyk(0, 0, 0)", "rgb(a, b, c)", "notacolor"] {
+            assert!(!is_color(bad), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,2466 @@
+//! Config file format (`~/.config/hypr-ringlight/config.toml`) and the
+//! enums and helpers derived from it
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Prefix of environment variables overriding config keys
+pub const ENV_PREFIX: &str = "HYPR_RINGLIGHT_";
+
+/// Variables with the prefix that are command line options, not config keys
+const ENV_RESERVED: &[&str] = &["SAFE", "LOG_FORMAT", "INSTANCE"];
+
+/// Color presets with hex values (shared by the TUI and the tray)
+pub const COLOR_PRESETS: &[(&str, &str)] = &[
+    ("White", "ffffff"),
+    ("Red", "ff0000"),
+    ("Green", "00ff00"),
+    ("Blue", "0000ff"),
+    ("Cyan", "00ffff"),
+    ("Magenta", "ff00ff"),
+    ("Yellow", "ffff00"),
+    ("Orange", "ff6600"),
+    ("Pink", "ff1493"),
+    ("Catppuccin Blue", "89b4fa"),
+    ("Catppuccin Mauve", "cba6f7"),
+    ("Catppuccin Green", "a6e3a1"),
+    ("Catppuccin Red", "f38ba8"),
+    ("Catppuccin Peach", "fab387"),
+    ("Catppuccin Yellow", "f9e2af"),
+    ("Catppuccin Teal", "94e2d5"),
+];
+
+/// Ring light configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// Ring color: hex (e.g., ff0000), a CSS color like red or rgb(255,0,0), or a palette name
+    #[serde(default = "default_color")]
+    pub color: String,
+
+    /// Ring thickness in pixels
+    #[serde(default = "default_thickness")]
+    pub thickness: u32,
+
+    /// Ring opacity (0.0 - 1.0)
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,
+
+    /// Blur/glow radius (softness)
+    #[serde(default = "default_glow")]
+    pub glow: u32,
+
+    /// Glow falloff curve: linear, quadratic, cubic, exponential, gaussian
+    #[serde(default = "default_glow_falloff")]
+    pub glow_falloff: String,
+
+    /// Gap in pixels between the screen edge and the ring, which then glows
+    /// on both sides
+    #[serde(default)]
+    pub inset: u32,
+
+    /// Feathers the inside edge of the solid band: the glow fades this many
+    /// pixels into it instead of stopping at its edge
+    #[serde(default)]
+    pub inner_glow: u32,
+
+    /// Dither the glow to hide banding on large soft gradients
+    #[serde(default = "default_dither")]
+    pub dither: bool,
+
+    /// Fade the glow in linear light rather than sRGB
+    #[serde(default)]
+    pub gamma_correct: bool,
+
+    /// Resolution the ring is drawn at, scaled up by the compositor (0.25 - 1.0)
+    #[serde(default = "default_render_scale")]
+    pub render_scale: f64,
+
+    /// Corner radius multiplier (relative to thickness)
+    #[serde(default = "default_corner_radius")]
+    pub corner_radius: f64,
+
+    /// Corner geometry: round, squircle, chamfer
+    #[serde(default = "default_corner_style")]
+    pub corner_style: String,
+
+    /// Per-corner radius multipliers (top-left, top-right, bottom-right, bottom-left),
+    /// replacing `corner_radius` when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corner_radii: Option<[f64; 4]>,
+
+    /// How much the ring thins away from the taper anchor (0.0 = uniform, 1.0 = vanishes)
+    #[serde(default)]
+    pub taper: f64,
+
+    /// Where the tapered ring is thickest: corners, centers (of the edges)
+    #[serde(default = "default_taper_anchor")]
+    pub taper_anchor: String,
+
+    /// Camera notch the ring flows around
+    #[serde(default)]
+    pub notch: NotchConfig,
+
+    /// Rectangles the ring is never drawn over, e.g. an always-on-top widget
+    #[serde(default)]
+    pub exclude: Vec<ExcludeZone>,
+
+    /// Image filling the ring instead of the flat color
+    #[serde(default)]
+    pub texture: TextureConfig,
+
+    /// Animation mode: none, pulse, rainbow, breathe, comet, heartbeat, spectrum, system, typing
+    #[serde(default = "default_animation")]
+    pub animation: String,
+
+    /// Duration of one animation cycle (e.g. "2s", "500ms", "1m")
+    #[serde(default = "default_cycle_duration")]
+    pub cycle_duration: String,
+
+    /// Legacy animation speed in frames per cycle (at 60 FPS).
+    /// Only read to migrate old configs to `cycle_duration`.
+    #[serde(default, skip_serializing)]
+    pub animation_speed: Option<u32>,
+
+    /// Dash pattern along the band: solid, dashed, dotted. Gives profiles a
+    /// look that tells them apart without relying on color
+    #[serde(default = "default_pattern")]
+    pub pattern: String,
+
+    /// Warm-up ramp when the ring turns on (e.g. "3s"; "0s" switches on instantly)
+    #[serde(default = "default_warmup")]
+    pub warmup: String,
+
+    /// Warm-up easing curve: linear, ease-in, ease-out, smoothstep
+    #[serde(default = "default_warmup_curve")]
+    pub warmup_curve: String,
+
+    /// Waybar/bar height in pixels
+    #[serde(default = "default_bar_height")]
+    pub bar_height: u32,
+
+    /// Waybar/bar position: top, bottom, left, right
+    #[serde(default = "default_bar_position")]
+    pub bar_position: String,
+
+    /// `bar_position` is an edge of the physical panel, following its rotation
+    #[serde(default)]
+    pub bar_physical: bool,
+
+    /// Layer-shell layer: background, bottom, top, overlay
+    #[serde(default = "default_layer")]
+    pub layer: String,
+
+    /// Use the space the compositor reserves for bars instead of `bar_height` (Hyprland)
+    #[serde(default)]
+    pub bar_auto: bool,
+
+    /// Stop drawing on screens the compositor blanks (DPMS). Opt-in: following
+    /// an output's power takes its power control from other clients on wlroots
+    #[serde(default)]
+    pub dpms: bool,
+
+    /// Per-monitor overrides, e.g. `[monitor."DP-2"]`
+    #[serde(default)]
+    pub monitor: BTreeMap<String, MonitorConfig>,
+
+    /// List of disabled monitor IDs (connector names like "DP-2", "HDMI-1")
+    #[serde(default)]
+    pub disabled_monitors: Vec<String>,
+
+    /// Fade the ring out while the user is idle
+    #[serde(default)]
+    pub idle: IdleConfig,
+
+    /// Fades when the ring is shown, hidden or changes color
+    #[serde(default)]
+    pub transition: TransitionConfig,
+
+    /// Progress bar mode (driven by `SetProgress`)
+    #[serde(default)]
+    pub progress: ProgressConfig,
+
+    /// Comets chasing around the ring in the `comet` animation
+    #[serde(default)]
+    pub comet: CometConfig,
+
+    /// Audio spectrum around the ring in the `spectrum` animation
+    #[serde(default)]
+    pub spectrum: SpectrumConfig,
+
+    /// CPU/GPU load or temperature gauge in the `system` animation
+    #[serde(default)]
+    pub system: SystemConfig,
+
+    /// Glow on input activity in the `typing` animation
+    #[serde(default)]
+    pub typing: TypingConfig,
+
+    /// Framing guides drawn inside the ring during calls
+    #[serde(default)]
+    pub framing: FramingConfig,
+
+    /// Light fading in from the screen edges instead of a ring
+    #[serde(default)]
+    pub vignette: VignetteConfig,
+
+    /// A low-opacity wash over the whole screen, as a key light
+    #[serde(default)]
+    pub fill: FillConfig,
+
+    /// Where the webcam sits, to light the ring brighter near it
+    #[serde(default)]
+    pub webcam: WebcamConfig,
+
+    /// Text shown in the top band of the ring
+    #[serde(default)]
+    pub label: LabelConfig,
+
+    /// What to do while the screen is locked
+    #[serde(default)]
+    pub lock: LockConfig,
+
+    /// Power saving on a low battery
+    #[serde(default)]
+    pub battery: BatteryConfig,
+
+    /// Suspend rendering while a game runs (Hyprland)
+    #[serde(default)]
+    pub gaming: GamingConfig,
+
+    /// `hypr-ringlight watch` result colors
+    #[serde(default)]
+    pub watch: WatchConfig,
+
+    /// Defaults for strobe alerts (`StartStrobe`)
+    #[serde(default)]
+    pub strobe: StrobeConfig,
+
+    /// Where the ring takes its color from when `color` is left at the default
+    #[serde(default)]
+    pub theme: ThemeConfig,
+
+    /// Profiles following the system's light or dark preference
+    #[serde(default)]
+    pub color_scheme: ColorSchemeConfig,
+
+    /// Profiles and progress following media players
+    #[serde(default)]
+    pub media: MediaConfig,
+
+    /// Camera detection and its notification
+    #[serde(default)]
+    pub camera: CameraConfig,
+
+    /// One on-air state from the camera, screen sharing and the microphone
+    #[serde(default)]
+    pub on_air: OnAirConfig,
+
+    /// Following the notification daemon's Do-Not-Disturb state
+    #[serde(default)]
+    pub dnd: DndConfig,
+
+    /// Local HTTP control endpoint
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// Keyboard, mouse and case lighting following the ring through OpenRGB
+    #[serde(default)]
+    pub openrgb: OpenRgbConfig,
+
+    /// Home Assistant light over MQTT
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+
+    /// Profiles while OBS Studio records or streams
+    #[serde(default)]
+    pub obs: ObsConfig,
+
+    /// Clicks on the ring band, for setups without a tray
+    #[serde(default)]
+    pub click: ClickConfig,
+
+    /// Touch gestures on the ring band, for tablet mode
+    #[serde(default)]
+    pub touch: TouchConfig,
+
+    /// Global shortcuts registered through the desktop portal
+    #[serde(default)]
+    pub shortcuts: ShortcutsConfig,
+
+    /// Hyprland workspace/focus rules
+    #[serde(default)]
+    pub hyprland: HyprlandConfig,
+
+    /// Debounce/cooldown settings for automatic triggers
+    #[serde(default)]
+    pub triggers: TriggersConfig,
+
+    /// Named sets of overrides that rules can switch to, e.g. `[profiles.call]`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+
+    /// Named colors, usable wherever a color is accepted, e.g. `[palette] call = "ffffff"`
+    #[serde(default)]
+    pub palette: BTreeMap<String, String>,
+}
+
+/// A named set of overrides; unset fields keep the current value
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thickness: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glow: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corner_radius: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle_duration: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visible: Option<bool>,
+}
+
+/// Show/hide transitions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitionConfig {
+    /// How long the ring takes to appear, or to take on a new color
+    #[serde(default = "default_zero_duration")]
+    pub fade_in: String,
+
+    /// How long the ring takes to disappear
+    #[serde(default = "default_zero_duration")]
+    pub fade_out: String,
+
+    /// Grow the band out of the screen edge while fading in (and shrink it back)
+    #[serde(default)]
+    pub scale_in: bool,
+}
+
+impl Default for TransitionConfig {
+    fn default() -> Self {
+        Self {
+            fade_in: default_zero_duration(),
+            fade_out: default_zero_duration(),
+            scale_in: false,
+        }
+    }
+}
+
+impl TransitionConfig {
+    pub fn fade_in(&self) -> Duration {
+        Duration::from_millis(parse_duration_ms(&self.fade_in).unwrap_or(0) as u64)
+    }
+
+    pub fn fade_out(&self) -> Duration {
+        Duration::from_millis(parse_duration_ms(&self.fade_out).unwrap_or(0) as u64)
+    }
+}
+
+/// Idle fade-out settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdleConfig {
+    /// How long without input before the ring fades out ("0s" = never)
+    #[serde(default = "default_zero_duration")]
+    pub timeout: String,
+
+    /// Brightness while idle (0.0 hides the ring, 1.0 keeps it as is)
+    #[serde(default)]
+    pub brightness: f64,
+
+    /// Duration of the fade out and back in
+    #[serde(default = "default_idle_fade")]
+    pub fade: String,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            timeout: default_zero_duration(),
+            brightness: 0.0,
+            fade: default_idle_fade(),
+        }
+    }
+}
+
+impl IdleConfig {
+    /// Idle timeout, or `None` when idle detection is off
+    pub fn timeout(&self) -> Option<Duration> {
+        parse_duration_ms(&self.timeout)
+            .filter(|&ms| ms > 0)
+            .map(|ms| Duration::from_millis(ms as u64))
+    }
+
+    pub fn fade(&self) -> Duration {
+        Duration::from_millis(parse_duration_ms(&self.fade).unwrap_or(0) as u64)
+    }
+}
+
+/// Typing glow: the ring brightens while you type and settles when you stop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypingConfig {
+    /// Pause in input after which typing counts as stopped
+    #[serde(default = "default_typing_pause")]
+    pub pause: String,
+
+    /// Brightness while not typing (0.0 - 1.0)
+    #[serde(default = "default_typing_rest")]
+    pub rest: f64,
+
+    /// How long the glow takes to settle back to `rest`
+    #[serde(default = "default_typing_fade")]
+    pub fade: String,
+}
+
+impl Default for TypingConfig {
+    fn default() -> Self {
+        Self {
+            pause: default_typing_pause(),
+            rest: default_typing_rest(),
+            fade: default_typing_fade(),
+        }
+    }
+}
+
+impl TypingConfig {
+    pub fn pause(&self) -> Duration {
+        Duration::from_millis(parse_duration_ms(&self.pause).filter(|&ms| ms > 0).unwrap_or(500) as u64)
+    }
+
+    pub fn fade(&self) -> Duration {
+        Duration::from_millis(parse_duration_ms(&self.fade).unwrap_or(0) as u64)
+    }
+}
+
+/// How `SetProgress` is drawn around the perimeter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressConfig {
+    /// Where the bar starts, going clockwise: top-left, top, top-right, right,
+    /// bottom-right, bottom, bottom-left, left
+    #[serde(default = "default_progress_origin")]
+    pub origin: String,
+
+    /// Brightness of the unfilled part (0.0 = off)
+    #[serde(default)]
+    pub track: f64,
+}
+
+impl Default for ProgressConfig {
+    fn default() -> Self {
+        Self {
+            origin: default_progress_origin(),
+            track: 0.0,
+        }
+    }
+}
+
+impl ProgressConfig {
+    pub fn origin_enum(&self) -> ProgressOrigin {
+        ProgressOrigin::from_name(&self.origin)
+    }
+}
+
+/// Corner or edge center where a perimeter progress bar starts
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ProgressOrigin {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+    Left,
+}
+
+impl ProgressOrigin {
+    pub fn from_name(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "top" => ProgressOrigin::Top,
+            "top-right" => ProgressOrigin::TopRight,
+            "right" => ProgressOrigin::Right,
+            "bottom-right" => ProgressOrigin::BottomRight,
+            "bottom" => ProgressOrigin::Bottom,
+            "bottom-left" => ProgressOrigin::BottomLeft,
+            "left" => ProgressOrigin::Left,
+            _ => ProgressOrigin::TopLeft,
+        }
+    }
+}
+
+/// Comet animation settings; one lap takes `cycle_duration`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CometConfig {
+    /// Length of the head and tail, as a fraction of the perimeter
+    #[serde(default = "default_comet_length")]
+    pub length: f64,
+
+    /// Comets evenly spaced around the ring
+    #[serde(default = "default_comet_count")]
+    pub count: u32,
+}
+
+fn default_comet_length() -> f64 { 0.25 }
+fn default_comet_count() -> u32 { 1 }
+
+impl Default for CometConfig {
+    fn default() -> Self {
+        Self {
+            length: default_comet_length(),
+            count: default_comet_count(),
+        }
+    }
+}
+
+/// Framing guides to help stay centered on camera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FramingConfig {
+    /// Guides to draw: thirds, head, both
+    #[serde(default = "default_framing_style")]
+    pub style: String,
+
+    /// Opacity of the guide lines (0.0 - 1.0)
+    #[serde(default = "default_framing_opacity")]
+    pub opacity: f64,
+
+    /// Show the guides automatically while the camera is in use
+    #[serde(default)]
+    pub on_camera: bool,
+}
+
+impl Default for FramingConfig {
+    fn default() -> Self {
+        Self {
+            style: default_framing_style(),
+            opacity: default_framing_opacity(),
+            on_camera: false,
+        }
+    }
+}
+
+/// A soft vignette: the glow fades in from the screen edges with no solid
+/// band, like a bias light behind the monitor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VignetteConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Pixels from the edge over which the light fades out, along `glow_falloff`
+    #[serde(default = "default_vignette_distance")]
+    pub distance: u32,
+}
+
+impl Default for VignetteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            distance: default_vignette_distance(),
+        }
+    }
+}
+
+/// Covers the whole screen in one faint color, lighting a face more evenly
+/// than the ring alone; drawn under the ring, so both can be on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Color name or hex, from `[palette]` too
+    #[serde(default = "default_fill_color")]
+    pub color: String,
+
+    /// 0.0 - 1.0; past a few tenths it hides the screen
+    #[serde(default = "default_fill_opacity")]
+    pub opacity: f64,
+}
+
+impl Default for FillConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: default_fill_color(),
+            opacity: default_fill_opacity(),
+        }
+    }
+}
+
+/// The ring is brightest next to the webcam and dims towards the far side,
+/// putting catchlights in the eyes without glaring everywhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebcamConfig {
+    /// top-left, top-center, top-right, left, right, bottom-left,
+    /// bottom-center, bottom-right; empty lights the ring evenly
+    #[serde(default)]
+    pub position: String,
+
+    /// Output the webcam sits on; empty for every monitor
+    #[serde(default)]
+    pub monitor: String,
+
+    /// Brightness of the point farthest from the webcam, 0.0 - 1.0
+    #[serde(default = "default_webcam_floor")]
+    pub floor: f64,
+}
+
+impl Default for WebcamConfig {
+    fn default() -> Self {
+        Self {
+            position: String::new(),
+            monitor: String::new(),
+            floor: default_webcam_floor(),
+        }
+    }
+}
+
+impl WebcamConfig {
+    /// The webcam's corner or edge center on `monitor`, if it's there
+    pub fn position_on(&self, monitor: &str) -> Option<ProgressOrigin> {
+        if self.position.is_empty() || !(self.monitor.is_empty() || self.monitor == monitor) {
+            return None;
+        }
+        Some(ProgressOrigin::from_name(self.position.to_lowercase().trim_end_matches("-center")))
+    }
+}
+
+impl FramingConfig {
+    pub fn style_enum(&self) -> FramingStyle {
+        match self.style.to_lowercase().as_str() {
+            "head" => FramingStyle::Head,
+            "both" => FramingStyle::Both,
+            _ => FramingStyle::Thirds,
+        }
+    }
+}
+
+/// Audio spectrum analyzer, fed by cava
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpectrumConfig {
+    /// Frequency bands, each lighting an equal share of the perimeter
+    #[serde(default = "default_spectrum_bars")]
+    pub bars: u32,
+
+    /// Where the lowest band starts, going clockwise (as for `progress.origin`)
+    #[serde(default = "default_spectrum_origin")]
+    pub origin: String,
+
+    /// Brightness of a silent band (0.0 - 1.0)
+    #[serde(default = "default_spectrum_floor")]
+    pub floor: f64,
+
+    /// The cava executable
+    #[serde(default = "default_spectrum_command")]
+    pub command: String,
+}
+
+impl Default for SpectrumConfig {
+    fn default() -> Self {
+        Self {
+            bars: default_spectrum_bars(),
+            origin: default_spectrum_origin(),
+            floor: default_spectrum_floor(),
+            command: default_spectrum_command(),
+        }
+    }
+}
+
+impl SpectrumConfig {
+    pub fn origin_enum(&self) -> ProgressOrigin {
+        ProgressOrigin::from_name(&self.origin)
+    }
+}
+
+/// System gauge: the whole ring shows a load or a temperature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemConfig {
+    /// What to show: cpu, gpu, temperature
+    #[serde(default = "default_system_source")]
+    pub source: String,
+
+    /// How it's shown: hue (green to red), brightness
+    #[serde(default = "default_system_map")]
+    pub map: String,
+
+    /// Brightness at no load, with `map = "brightness"` (0.0 - 1.0)
+    #[serde(default = "default_spectrum_floor")]
+    pub floor: f64,
+
+    /// hwmon sensor to read temperatures from (e.g. k10temp, coretemp, amdgpu);
+    /// empty picks the CPU's
+    #[serde(default)]
+    pub sensor: String,
+
+    /// Temperature shown as idle, in °C
+    #[serde(default = "default_system_temp_min")]
+    pub temp_min: f64,
+
+    /// Temperature shown as full, in °C
+    #[serde(default = "default_system_temp_max")]
+    pub temp_max: f64,
+}
+
+impl Default for SystemConfig {
+    fn default() -> Self {
+        Self {
+            source: default_system_source(),
+            map: default_system_map(),
+            floor: default_spectrum_floor(),
+            sensor: String::new(),
+            temp_min: default_system_temp_min(),
+            temp_max: default_system_temp_max(),
+        }
+    }
+}
+
+impl SystemConfig {
+    pub fn source_enum(&self) -> SystemSource {
+        match self.source.to_lowercase().as_str() {
+            "gpu" => SystemSource::Gpu,
+            "temperature" | "temp" => SystemSource::Temperature,
+            _ => SystemSource::Cpu,
+        }
+    }
+
+    pub fn map_enum(&self) -> GaugeMap {
+        match self.map.to_lowercase().as_str() {
+            "brightness" => GaugeMap::Brightness,
+            _ => GaugeMap::Hue,
+        }
+    }
+}
+
+/// What the system gauge shows
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SystemSource {
+    /// Share of CPU time not idle, all cores
+    #[default]
+    Cpu,
+    /// GPU busy percentage (amdgpu and others exposing `gpu_busy_percent`)
+    Gpu,
+    /// Hottest sensor reading between `temp_min` and `temp_max`
+    Temperature,
+}
+
+/// How a gauge level shows on the ring
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GaugeMap {
+    /// Green when idle, through yellow, to red at full
+    #[default]
+    Hue,
+    /// The ring's own color, brighter with the level
+    Brightness,
+}
+
+/// Text label in the top band
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelConfig {
+    /// Text to show (e.g. "ON AIR"), empty for none
+    #[serde(default)]
+    pub text: String,
+
+    /// Local time in this strftime format (e.g. "%H:%M"), shown while there
+    /// is no text; empty for none
+    #[serde(default)]
+    pub clock: String,
+
+    /// Height of capital letters in pixels; 0 fits the label to the ring thickness
+    #[serde(default)]
+    pub size: u32,
+
+    /// Text color (hex or palette name); empty picks black or white,
+    /// whichever stands out against the ring
+    #[serde(default)]
+    pub color: String,
+
+    /// Opacity of the box behind the text (0.0 - 1.0)
+    #[serde(default = "default_label_backing")]
+    pub backing: f64,
+}
+
+impl Default for LabelConfig {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            clock: String::new(),
+            size: 0,
+            color: String::new(),
+            backing: default_label_backing(),
+        }
+    }
+}
+
+/// Which framing guides to draw
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FramingStyle {
+    /// Rule-of-thirds grid
+    #[default]
+    Thirds,
+    /// Box where the head should be
+    Head,
+    Both,
+}
+
+/// Screen lock behavior
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockConfig {
+    /// While locked: "hide" the ring, switch to the lock "color", or "none" to ignore locking
+    #[serde(default = "default_lock_action")]
+    pub action: String,
+
+    /// Ring color while locked, for `action = "color"`
+    #[serde(default = "default_lock_color")]
+    pub color: String,
+
+    /// Screen locker process names
+    #[serde(default = "default_lockers")]
+    pub lockers: Vec<String>,
+}
+
+impl Default for LockConfig {
+    fn default() -> Self {
+        Self {
+            action: default_lock_action(),
+            color: default_lock_color(),
+            lockers: default_lockers(),
+        }
+    }
+}
+
+impl LockConfig {
+    /// Overrides applied while locked, or `None` if locking is ignored
+    pub fn profile(&self) -> Option<Profile> {
+        match self.action.to_lowercase().as_str() {
+            "none" => None,
+            "color" => Some(Profile {
+                color: Some(self.color.clone()),
+                animation: Some("none".to_string()),
+                visible: Some(true),
+                ..Profile::default()
+            }),
+            _ => Some(Profile { visible: Some(false), ..Profile::default() }),
+        }
+    }
+}
+
+/// Power saving while discharging below a battery level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatteryConfig {
+    /// Battery percentage at or below which power saving starts (0 = never)
+    #[serde(default = "default_battery_threshold")]
+    pub threshold: u8,
+
+    /// "throttle" caps the frame rate, "no-glow" also drops the glow,
+    /// "hide" hides the ring
+    #[serde(default = "default_battery_action")]
+    pub action: String,
+
+    /// Frame rate cap while saving power
+    #[serde(default = "default_battery_fps")]
+    pub fps: u32,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self {
+            threshold: default_battery_threshold(),
+            action: default_battery_action(),
+            fps: default_battery_fps(),
+        }
+    }
+}
+
+impl BatteryConfig {
+    /// Overrides applied while saving power, on top of the frame rate cap
+    pub fn profile(&self) -> Option<Profile> {
+        match self.action.to_lowercase().as_str() {
+            "no-glow" => Some(Profile { glow: Some(0), ..Profile::default() }),
+            "hide" => Some(Profile { visible: Some(false), ..Profile::default() }),
+            _ => None,
+        }
+    }
+
+    /// Minimum time between frames while saving power
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.fps.max(1) as f64)
+    }
+}
+
+/// Game detection: the rings are unmapped and their buffers freed while the
+/// focused window is a game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GamingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// A fullscreen window counts as a game while GameMode is active
+    #[serde(default = "default_gaming_gamemode")]
+    pub gamemode: bool,
+
+    /// Regexes matched against the window class, e.g. "^steam_app_"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub classes: Vec<String>,
+}
+
+impl Default for GamingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            gamemode: default_gaming_gamemode(),
+            classes: Vec::new(),
+        }
+    }
+}
+
+/// How the ring reports a watched command finishing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// Flash color when the command succeeds
+    #[serde(default = "default_watch_success_color")]
+    pub success_color: String,
+
+    /// Flash color when the command fails
+    #[serde(default = "default_watch_failure_color")]
+    pub failure_color: String,
+
+    /// How long the ring flashes
+    #[serde(default = "default_watch_flash")]
+    pub flash: String,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            success_color: default_watch_success_color(),
+            failure_color: default_watch_failure_color(),
+            flash: default_watch_flash(),
+        }
+    }
+}
+
+impl WatchConfig {
+    pub fn flash(&self) -> Duration {
+        Duration::from_millis(parse_duration_ms(&self.flash).unwrap_or(0) as u64)
+    }
+}
+
+/// Strobe alert defaults; requests can override each of them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrobeConfig {
+    /// Flashes per second (capped at 3 for photosensitive viewers)
+    #[serde(default = "default_strobe_frequency")]
+    pub frequency: f64,
+
+    /// Fraction of each flash the ring is lit (0.0 - 1.0)
+    #[serde(default = "default_strobe_duty")]
+    pub duty: f64,
+
+    /// Flashes before the strobe stops by itself
+    #[serde(default = "default_strobe_count")]
+    pub count: u32,
+
+    /// Flash color (hex or palette name); empty means the ring color
+    #[serde(default)]
+    pub color: String,
+}
+
+fn default_strobe_frequency() -> f64 { 2.0 }
+fn default_strobe_duty() -> f64 { 0.5 }
+fn default_strobe_count() -> u32 { 6 }
+
+impl Default for StrobeConfig {
+    fn default() -> Self {
+        Self {
+            frequency: default_strobe_frequency(),
+            duty: default_strobe_duty(),
+            count: default_strobe_count(),
+            color: String::new(),
+        }
+    }
+}
+
+/// Theme the ring's color follows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    /// "omarchy", "base16", "pywal" or "hyprland"
+    #[serde(default = "default_theme_source")]
+    pub source: String,
+
+    /// base16 scheme YAML for `source = "base16"`; empty for tinty's current scheme
+    #[serde(default)]
+    pub base16: String,
+
+    /// Reload when the theme files change, besides on SIGUSR2
+    #[serde(default = "default_theme_watch")]
+    pub watch: bool,
+}
+
+fn default_theme_source() -> String { "omarchy".to_string() }
+fn default_theme_watch() -> bool { true }
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            source: default_theme_source(),
+            base16: String::new(),
+            watch: default_theme_watch(),
+        }
+    }
+}
+
+impl ThemeConfig {
+    pub fn source_enum(&self) -> ThemeSource {
+        ThemeSource::from_name(&self.source)
+    }
+}
+
+/// A notch at the top center of the screen (laptops with a camera notch)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotchConfig {
+    /// Notch width in pixels (0 = no notch)
+    #[serde(default)]
+    pub width: u32,
+
+    /// Notch height in pixels
+    #[serde(default)]
+    pub height: u32,
+
+    /// Monitors that have the notch (connector names); empty means all
+    #[serde(default)]
+    pub monitors: Vec<String>,
+}
+
+impl NotchConfig {
+    /// Notch size in pixels on the given monitor, if it has one
+    pub fn size_for(&self, monitor: &str) -> Option<(f64, f64)> {
+        let applies = self.monitors.is_empty() || self.monitors.iter().any(|m| m == monitor);
+        (applies && self.width > 0 && self.height > 0).then_some((self.width as f64, self.height as f64))
+    }
+}
+
+/// PNG texture for the ring band
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextureConfig {
+    /// Image file; empty means the flat ring color
+    #[serde(default)]
+    pub path: String,
+
+    /// How the image covers the screen: tile, stretch
+    #[serde(default = "default_texture_mode")]
+    pub mode: String,
+}
+
+fn default_texture_mode() -> String {
+    "tile".to_string()
+}
+
+impl Default for TextureConfig {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            mode: default_texture_mode(),
+        }
+    }
+}
+
+impl TextureConfig {
+    pub fn mode_enum(&self) -> TextureMode {
+        match self.mode.to_lowercase().as_str() {
+            "stretch" => TextureMode::Stretch,
+            _ => TextureMode::Tile,
+        }
+    }
+}
+
+/// How a texture is mapped onto the screen
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextureMode {
+    /// Repeated at its own size from the top-left corner
+    #[default]
+    Tile,
+    /// Scaled to cover the whole screen once
+    Stretch,
+}
+
+/// A rectangle, in pixels from the monitor's top-left corner, left unlit
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExcludeZone {
+    #[serde(default)]
+    pub x: u32,
+
+    #[serde(default)]
+    pub y: u32,
+
+    #[serde(default)]
+    pub width: u32,
+
+    #[serde(default)]
+    pub height: u32,
+
+    /// Monitors the zone applies to (connector names); empty means all
+    #[serde(default)]
+    pub monitors: Vec<String>,
+}
+
+/// Settings for a single monitor; unset fields use the global value
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonitorConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bar_height: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bar_position: Option<String>,
+}
+
+impl ExcludeZone {
+    pub fn applies_to(&self, monitor: &str) -> bool {
+        self.monitors.is_empty() || self.monitors.iter().any(|m| m == monitor)
+    }
+}
+
+/// Per-trigger debounce settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggersConfig {
+    /// Webcam in use (drives camera notifications and the tray camera dot)
+    #[serde(default = "default_camera_trigger")]
+    pub camera: TriggerConfig,
+
+    /// Hyprland workspace/focus rules
+    #[serde(default)]
+    pub hyprland: TriggerConfig,
+}
+
+impl Default for TriggersConfig {
+    fn default() -> Self {
+        Self {
+            camera: default_camera_trigger(),
+            hyprland: TriggerConfig::default(),
+        }
+    }
+}
+
+/// Debounce settings for one trigger (durations like "2s")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerConfig {
+    /// The trigger must be on this long before it activates
+    #[serde(default = "default_zero_duration")]
+    pub debounce: String,
+
+    /// The trigger must be off this long before it deactivates
+    #[serde(default = "default_zero_duration")]
+    pub hold: String,
+
+    /// Minimum time between two activations
+    #[serde(default = "default_zero_duration")]
+    pub cooldown: String,
+}
+
+impl Default for TriggerConfig {
+    fn default() -> Self {
+        Self {
+            debounce: default_zero_duration(),
+            hold: default_zero_duration(),
+            cooldown: default_zero_duration(),
+        }
+    }
+}
+
+impl TriggerConfig {
+    pub fn debounce(&self) -> Duration {
+        Duration::from_millis(parse_duration_ms(&self.debounce).unwrap_or(0) as u64)
+    }
+
+    pub fn hold(&self) -> Duration {
+        Duration::from_millis(parse_duration_ms(&self.hold).unwrap_or(0) as u64)
+    }
+
+    pub fn cooldown(&self) -> Duration {
+        Duration::from_millis(parse_duration_ms(&self.cooldown).unwrap_or(0) as u64)
+    }
+}
+
+/// Rules evaluated against Hyprland's workspace and focus state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HyprlandConfig {
+    /// Hide the ring on monitors showing one of these workspaces (by name or id)
+    #[serde(default)]
+    pub hide_on_workspaces: Vec<String>,
+
+    /// Only show the ring on the focused monitor
+    #[serde(default)]
+    pub focused_monitor_only: bool,
+
+    /// Profile per workspace (name or id), applied while the focused monitor
+    /// shows it; "hide" hides the ring on monitors showing it instead
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub workspaces: BTreeMap<String, String>,
+
+    /// What to do while a special workspace (scratchpad) is shown, by name
+    /// without `special:` or `*` for any: "dim", "hide", or a profile
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub special: BTreeMap<String, String>,
+
+    /// Opacity of the ring dimmed for a special workspace (0.0 - 1.0)
+    #[serde(default = "default_special_dim")]
+    pub special_dim: f64,
+
+    /// Rules matched against the focused window, first match wins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub window_rules: Vec<WindowRule>,
+
+    /// Tint the ring to match the focused window
+    #[serde(default)]
+    pub accent: AccentConfig,
+}
+
+impl Default for HyprlandConfig {
+    fn default() -> Self {
+        Self {
+            hide_on_workspaces: Vec::new(),
+            focused_monitor_only: false,
+            workspaces: BTreeMap::new(),
+            special: BTreeMap::new(),
+            special_dim: default_special_dim(),
+            window_rules: Vec::new(),
+            accent: AccentConfig::default(),
+        }
+    }
+}
+
+impl HyprlandConfig {
+    /// Whether any rule needs the Hyprland event listener
+    pub fn is_active(&self) -> bool {
+        !self.hide_on_workspaces.is_empty()
+            || self.focused_monitor_only
+            || !self.workspaces.is_empty()
+            || !self.special.is_empty()
+            || !self.window_rules.is_empty()
+            || self.accent.enabled
+    }
+}
+
+/// Active-window accent: the ring takes the focused window's color
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccentConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Colors (hex or palette name) by window class, matched ignoring case
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub colors: BTreeMap<String, String>,
+
+    /// Use the active border color for windows without an entry
+    #[serde(default = "default_accent_border")]
+    pub border: bool,
+}
+
+impl Default for AccentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            colors: BTreeMap::new(),
+            border: default_accent_border(),
+        }
+    }
+}
+
+/// Switch profile or hide the ring while a matching window has focus
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowRule {
+    /// Regex matched against the window class
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+
+    /// Regex matched against the window title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Only match when the window is (or isn't) fullscreen
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fullscreen: Option<bool>,
+
+    /// Profile to apply while the window is focused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// Hide the ring while the window is focused
+    #[serde(default)]
+    pub hide: bool,
+}
+
+fn default_special_dim() -> f64 { 0.3 }
+fn default_accent_border() -> bool { true }
+fn default_gaming_gamemode() -> bool { true }
+fn default_camera_summary() -> String { "Camera Active".to_string() }
+fn default_camera_body() -> String { "Your webcam is now active. Consider enabling the ring light for better lighting!".to_string() }
+fn default_camera_icon() -> String { "camera-web".to_string() }
+fn default_camera_urgency() -> String { "low".to_string() }
+fn default_camera_timeout() -> String { "10s".to_string() }
+fn default_camera_actions() -> bool { true }
+fn default_on_air_priority() -> Vec<String> { vec!["camera".to_string(), "share".to_string(), "mic".to_string()] }
+fn default_dnd_mako_mode() -> String { "do-not-disturb".to_string() }
+fn default_obs_url() -> String { "ws://127.0.0.1:4455".to_string() }
+fn default_click_left() -> String { "toggle".to_string() }
+fn default_click_right() -> String { "next-profile".to_string() }
+fn default_color() -> String { "ffffff".to_string() }
+fn default_thickness() -> u32 { 80 }
+fn default_opacity() -> f64 { 1.0 }
+fn default_glow() -> u32 { 80 }
+fn default_glow_falloff() -> String { "cubic".to_string() }
+fn default_dither() -> bool { true }
+fn default_render_scale() -> f64 { 1.0 }
+fn default_corner_radius() -> f64 { 2.5 }
+fn default_corner_style() -> String { "round".to_string() }
+fn default_taper_anchor() -> String { "corners".to_string() }
+fn default_animation() -> String { "none".to_string() }
+fn default_cycle_duration() -> String { "2s".to_string() }
+fn default_warmup() -> String { "0s".to_string() }
+fn default_warmup_curve() -> String { "ease-out".to_string() }
+fn default_pattern() -> String { "solid".to_string() }
+fn default_bar_height() -> u32 { 35 }
+fn default_zero_duration() -> String { "0s".to_string() }
+fn default_idle_fade() -> String { "2s".to_string() }
+fn default_progress_origin() -> String { "top-left".to_string() }
+fn default_framing_style() -> String { "thirds".to_string() }
+fn default_framing_opacity() -> f64 { 0.35 }
+fn default_vignette_distance() -> u32 { 400 }
+fn default_fill_color() -> String { "ffffff".to_string() }
+fn default_fill_opacity() -> f64 { 0.2 }
+fn default_webcam_floor() -> f64 { 0.4 }
+fn default_label_backing() -> f64 { 0.6 }
+fn default_spectrum_bars() -> u32 { 32 }
+fn default_spectrum_origin() -> String { "bottom".to_string() }
+fn default_spectrum_floor() -> f64 { 0.15 }
+fn default_spectrum_command() -> String { "cava".to_string() }
+fn default_system_source() -> String { "cpu".to_string() }
+fn default_openrgb_server() -> String { "127.0.0.1:6742".to_string() }
+fn default_openrgb_rate() -> u32 { 10 }
+fn default_mqtt_discovery_prefix() -> String { "homeassistant".to_string() }
+fn default_http_listen() -> String { "127.0.0.1:7272".to_string() }
+fn default_typing_pause() -> String { "500ms".to_string() }
+fn default_typing_rest() -> f64 { 0.3 }
+fn default_typing_fade() -> String { "1500ms".to_string() }
+fn default_system_map() -> String { "hue".to_string() }
+fn default_system_temp_min() -> f64 { 40.0 }
+fn default_system_temp_max() -> f64 { 90.0 }
+fn default_lock_action() -> String { "hide".to_string() }
+fn default_lock_color() -> String { "ff0000".to_string() }
+fn default_battery_threshold() -> u8 { 20 }
+fn default_battery_action() -> String { "throttle".to_string() }
+fn default_battery_fps() -> u32 { 10 }
+fn default_watch_success_color() -> String { "00ff00".to_string() }
+fn default_watch_failure_color() -> String { "ff0000".to_string() }
+fn default_watch_flash() -> String { "3s".to_string() }
+fn default_lockers() -> Vec<String> {
+    ["hyprlock", "swaylock", "gtklock", "waylock"].map(String::from).to_vec()
+}
+fn default_camera_trigger() -> TriggerConfig {
+    TriggerConfig {
+        debounce: "2s".to_string(),
+        hold: "5s".to_string(),
+        cooldown: "1m".to_string(),
+    }
+}
+fn default_bar_position() -> String { "top".to_string() }
+
+fn default_layer() -> String { "overlay".to_string() }
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            color: default_color(),
+            thickness: default_thickness(),
+            opacity: default_opacity(),
+            glow: default_glow(),
+            glow_falloff: default_glow_falloff(),
+            inset: 0,
+            inner_glow: 0,
+            dither: true,
+            gamma_correct: false,
+            render_scale: default_render_scale(),
+            corner_radius: default_corner_radius(),
+            corner_style: default_corner_style(),
+            corner_radii: None,
+            taper: 0.0,
+            taper_anchor: default_taper_anchor(),
+            notch: NotchConfig::default(),
+            exclude: Vec::new(),
+            texture: TextureConfig::default(),
+            animation: default_animation(),
+            cycle_duration: default_cycle_duration(),
+            animation_speed: None,
+            warmup: default_warmup(),
+            warmup_curve: default_warmup_curve(),
+            pattern: default_pattern(),
+            bar_height: default_bar_height(),
+            bar_position: default_bar_position(),
+            bar_physical: false,
+            bar_auto: false,
+            dpms: false,
+            layer: default_layer(),
+            monitor: BTreeMap::new(),
+            disabled_monitors: Vec::new(),
+            idle: IdleConfig::default(),
+            transition: TransitionConfig::default(),
+            progress: ProgressConfig::default(),
+            comet: CometConfig::default(),
+            spectrum: SpectrumConfig::default(),
+            system: SystemConfig::default(),
+            typing: TypingConfig::default(),
+            framing: FramingConfig::default(),
+            vignette: VignetteConfig::default(),
+            fill: FillConfig::default(),
+            webcam: WebcamConfig::default(),
+            label: LabelConfig::default(),
+            lock: LockConfig::default(),
+            battery: BatteryConfig::default(),
+            gaming: GamingConfig::default(),
+            watch: WatchConfig::default(),
+            strobe: StrobeConfig::default(),
+            theme: ThemeConfig::default(),
+            color_scheme: ColorSchemeConfig::default(),
+            media: MediaConfig::default(),
+            camera: CameraConfig::default(),
+            on_air: OnAirConfig::default(),
+            dnd: DndConfig::default(),
+            http: HttpConfig::default(),
+            mqtt: MqttConfig::default(),
+            obs: ObsConfig::default(),
+            openrgb: OpenRgbConfig::default(),
+            click: ClickConfig::default(),
+            touch: TouchConfig::default(),
+            shortcuts: ShortcutsConfig::default(),
+            hyprland: HyprlandConfig::default(),
+            triggers: TriggersConfig::default(),
+            profiles: BTreeMap::new(),
+            palette: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Get the config file path; a named instance has its own `config-NAME.toml`
+    pub fn path() -> PathBuf {
+        let file = match crate::ipc::instance() {
+            Some(name) => format!("config-{}.toml", name),
+            None => "config.toml".to_string(),
+        };
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("hypr-ringlight")
+            .join(file)
+    }
+
+    /// Load config from file, or return default if not found or invalid
+    pub fn load() -> Self {
+        Self::try_load().unwrap_or_else(|e| {
+            log::warn!("{}", e);
+            Self::default()
+        })
+    }
+
+    /// Load config from file, or return default if not found.
+    /// Unlike `load`, an unreadable or invalid file is an error.
+    pub fn try_load() -> Result<Self, String> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read config: {}", e))?;
+        let mut config = toml::from_str::<Config>(&content).map_err(|e| format!("Failed to parse config: {}", e))?;
+        config.migrate_animation_speed(&content);
+        log::debug!("Loaded config from {}", path.display());
+        Ok(config)
+    }
+
+    /// Save config to file
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path();
+        
+        // Create parent directory if needed
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        
+        fs::write(&path, content)
+            .map_err(|e| format!("Failed to write config: {}", e))?;
+        
+        Ok(())
+    }
+
+    /// Parse animation string to u8
+    pub fn animation_mode(&self) -> u8 {
+        crate::render::animation_from_name(&self.animation)
+    }
+
+    /// Override keys from `HYPR_RINGLIGHT_*` variables, e.g. `HYPR_RINGLIGHT_COLOR=ff8800`.
+    /// `__` separates nested keys: `HYPR_RINGLIGHT_IDLE__TIMEOUT=5m`. Values are
+    /// TOML (`true`, `80`, `["DP-2"]`); keys holding strings take the text as is.
+    /// Returns the keys that were set.
+    pub fn apply_env(&mut self, vars: impl IntoIterator<Item = (String, String)>) -> Result<Vec<String>, String> {
+        let mut applied = Vec::new();
+        for (var, raw) in vars {
+            let Some(rest) = var.strip_prefix(ENV_PREFIX).filter(|r| !ENV_RESERVED.contains(r)) else {
+                continue;
+            };
+            let path: Vec<String> = rest.to_lowercase().split("__").map(str::to_string).collect();
+            let key = path.join(".");
+            if !crate::check::is_known_key(&key) {
+                log::warn!("Ignoring {}: no config key {}", var, key);
+                continue;
+            }
+
+            let mut tree = toml::Value::try_from(&*self).map_err(|e| e.to_string())?;
+            let (leaf, parents) = path.split_last().unwrap_or((&key, &[]));
+            let mut table = tree.as_table_mut().ok_or("config is not a table")?;
+            for part in parents {
+                table = table
+                    .entry(part.as_str())
+                    .or_insert_with(|| toml::Value::Table(Default::default()))
+                    .as_table_mut()
+                    .ok_or_else(|| format!("{}: {} is not a table", var, part))?;
+            }
+            let value = match table.get(leaf.as_str()) {
+                Some(toml::Value::String(_)) => toml::Value::String(raw),
+                _ => toml::from_str::<toml::Table>(&format!("v = {}", raw))
+                    .ok()
+                    .and_then(|mut t| t.remove("v"))
+                    .unwrap_or(toml::Value::String(raw)),
+            };
+            table.insert(leaf.clone(), value);
+            *self = tree.try_into().map_err(|e: toml::de::Error| format!("{}: {}", var, e.message()))?;
+            applied.push(key);
+        }
+        Ok(applied)
+    }
+
+    /// Convert a legacy `animation_speed` (frames per cycle) into `cycle_duration`,
+    /// unless the file already sets `cycle_duration` explicitly.
+    fn migrate_animation_speed(&mut self, content: &str) {
+        let Some(frames) = self.animation_speed.take() else {
+            return;
+        };
+        let has_duration = toml::from_str::<toml::Table>(content)
+            .map(|t| t.contains_key("cycle_duration"))
+            .unwrap_or(false);
+        if !has_duration {
+            self.cycle_duration = format_cycle_duration(frames_to_ms(frames));
+            log::info!("Migrated animation_speed = {} to cycle_duration = \"{}\"", frames, self.cycle_duration);
+        }
+    }
+
+    /// Animation cycle duration in milliseconds (falls back to the default if invalid)
+    pub fn cycle_ms(&self) -> u32 {
+        parse_cycle_duration(&self.cycle_duration)
+            .or_else(|| parse_cycle_duration(&default_cycle_duration()))
+            .unwrap_or(2000)
+    }
+
+    /// Warm-up ramp duration in milliseconds (0 if disabled or invalid)
+    pub fn warmup_ms(&self) -> u32 {
+        parse_duration_ms(&self.warmup).unwrap_or(0)
+    }
+
+    /// Parse warm-up curve string
+    pub fn warmup_curve_enum(&self) -> RampCurve {
+        RampCurve::from_name(&self.warmup_curve)
+    }
+
+    /// Parse pattern string
+    pub fn pattern_enum(&self) -> Pattern {
+        Pattern::from_name(&self.pattern)
+    }
+
+    /// Parse glow falloff string
+    pub fn glow_falloff_enum(&self) -> GlowFalloff {
+        GlowFalloff::from_name(&self.glow_falloff)
+    }
+
+    /// Parse layer string
+    pub fn layer_enum(&self) -> RingLayer {
+        RingLayer::from_name(&self.layer)
+    }
+
+    /// Parse corner style string
+    pub fn corner_style_enum(&self) -> CornerStyle {
+        CornerStyle::from_name(&self.corner_style)
+    }
+
+    /// Taper settings for the renderer
+    pub fn taper_enum(&self) -> Taper {
+        Taper {
+            amount: self.taper.clamp(0.0, 1.0),
+            anchor: match self.taper_anchor.to_lowercase().as_str() {
+                "centers" | "center" => TaperAnchor::Centers,
+                _ => TaperAnchor::Corners,
+            },
+        }
+    }
+
+    /// Radius multipliers for each corner (top-left, top-right, bottom-right, bottom-left)
+    pub fn corner_multipliers(&self) -> [f64; 4] {
+        self.corner_radii.unwrap_or([self.corner_radius; 4])
+    }
+
+    /// Parse bar position string
+    pub fn bar_position_enum(&self) -> BarPosition {
+        BarPosition::from_name(&self.bar_position)
+    }
+}
+
+/// Waybar position
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BarPosition {
+    #[default]
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl BarPosition {
+    pub fn from_name(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "bottom" => BarPosition::Bottom,
+            "left" => BarPosition::Left,
+            "right" => BarPosition::Right,
+            _ => BarPosition::Top,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BarPosition::Top => "top",
+            BarPosition::Bottom => "bottom",
+            BarPosition::Left => "left",
+            BarPosition::Right => "right",
+        }
+    }
+}
+
+/// Shape of the ring's inner corners
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CornerStyle {
+    #[default]
+    Round,
+    /// Superellipse, a smoother transition from edge to curve
+    Squircle,
+    /// Straight 45° cut
+    Chamfer,
+}
+
+impl CornerStyle {
+    pub fn from_name(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "squircle" => CornerStyle::Squircle,
+            "chamfer" => CornerStyle::Chamfer,
+            _ => CornerStyle::Round,
+        }
+    }
+
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            1 => CornerStyle::Squircle,
+            2 => CornerStyle::Chamfer,
+            _ => CornerStyle::Round,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CornerStyle::Round => "round",
+            CornerStyle::Squircle => "squircle",
+            CornerStyle::Chamfer => "chamfer",
+        }
+    }
+}
+
+/// Layer-shell layer the ring is drawn on, from below windows to above everything
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RingLayer {
+    Background,
+    Bottom,
+    Top,
+    #[default]
+    Overlay,
+}
+
+impl RingLayer {
+    pub fn from_name(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "background" => RingLayer::Background,
+            "bottom" => RingLayer::Bottom,
+            "top" => RingLayer::Top,
+            _ => RingLayer::Overlay,
+        }
+    }
+
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            0 => RingLayer::Background,
+            1 => RingLayer::Bottom,
+            2 => RingLayer::Top,
+            _ => RingLayer::Overlay,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RingLayer::Background => "background",
+            RingLayer::Bottom => "bottom",
+            RingLayer::Top => "top",
+            RingLayer::Overlay => "overlay",
+        }
+    }
+}
+
+/// Profiles to switch to when the system prefers a dark or a light look
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ColorSchemeConfig {
+    /// Profile applied in dark mode (empty = none)
+    #[serde(default)]
+    pub dark: String,
+
+    /// Profile applied in light mode, or with no preference (empty = none)
+    #[serde(default)]
+    pub light: String,
+}
+
+impl ColorSchemeConfig {
+    /// Whether any profile is set, i.e. the preference needs watching
+    pub fn is_active(&self) -> bool {
+        !self.dark.is_empty() || !self.light.is_empty()
+    }
+}
+
+/// Media players (MPRIS): profiles by playback state, and the track's
+/// progress around the ring
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MediaConfig {
+    /// Player to follow, matched against its bus name (e.g. "spotify");
+    /// empty follows whichever one is playing
+    #[serde(default)]
+    pub player: String,
+
+    /// Profile applied while a track plays (empty = none)
+    #[serde(default)]
+    pub playing: String,
+
+    /// Profile applied while paused (empty = none)
+    #[serde(default)]
+    pub paused: String,
+
+    /// Light the part of the ring the track has played
+    #[serde(default)]
+    pub progress: bool,
+}
+
+impl MediaConfig {
+    /// Whether anything follows the players, i.e. they need watching
+    pub fn is_active(&self) -> bool {
+        !self.playing.is_empty() || !self.paused.is_empty() || self.progress
+    }
+}
+
+/// Clicking the ring band; off, the ring lets every click through
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClickConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Action of each button: toggle, next-profile, or empty for none
+    #[serde(default = "default_click_left")]
+    pub left: String,
+
+    #[serde(default = "default_click_right")]
+    pub right: String,
+
+    #[serde(default)]
+    pub middle: String,
+}
+
+impl Default for ClickConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            left: default_click_left(),
+            right: default_click_right(),
+            middle: String::new(),
+        }
+    }
+}
+
+/// Touching the ring band: drag along an edge for the opacity, double-tap to toggle
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TouchConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Shortcuts bound through xdg-desktop-portal; each key is the suggested
+/// trigger, e.g. "LOGO+SHIFT+L", and empty leaves the choice to the portal
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShortcutsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default)]
+    pub toggle: String,
+
+    #[serde(default)]
+    pub brighter: String,
+
+    #[serde(default)]
+    pub dimmer: String,
+
+    #[serde(default)]
+    pub next_profile: String,
+}
+
+impl ShortcutsConfig {
+    /// Suggested trigger of the shortcut `id`
+    pub fn trigger(&self, id: &str) -> &str {
+        match id {
+            "toggle" => &self.toggle,
+            "brighter" => &self.brighter,
+            "dimmer" => &self.dimmer,
+            "next-profile" => &self.next_profile,
+            _ => "",
+        }
+    }
+}
+
+/// What clicking the ring does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClickAction {
+    None,
+    /// Hide or show the ring
+    Toggle,
+    /// Apply the next profile in `[profiles]`, then none
+    NextProfile,
+}
+
+impl ClickAction {
+    pub fn from_name(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "toggle" => ClickAction::Toggle,
+            "next-profile" => ClickAction::NextProfile,
+            _ => ClickAction::None,
+        }
+    }
+}
+
+/// Which cameras are watched, and the notification shown when one comes on
+/// while the ring is hidden
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraConfig {
+    #[serde(default = "default_camera_summary")]
+    pub summary: String,
+
+    #[serde(default = "default_camera_body")]
+    pub body: String,
+
+    /// Icon name or path
+    #[serde(default = "default_camera_icon")]
+    pub icon: String,
+
+    /// low, normal or critical
+    #[serde(default = "default_camera_urgency")]
+    pub urgency: String,
+
+    /// How long the notification stays ("0s" = until dismissed)
+    #[serde(default = "default_camera_timeout")]
+    pub timeout: String,
+
+    /// Offer "Enable ring" and "Ignore for 1h" buttons
+    #[serde(default = "default_camera_actions")]
+    pub actions: bool,
+
+    /// Devices to watch, by path ("/dev/video0") or part of the card name (empty = all)
+    #[serde(default)]
+    pub devices: Vec<String>,
+
+    /// Devices never to watch, e.g. a capture card
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Processes that use the camera without a notification, e.g. "wf-recorder"
+    #[serde(default)]
+    pub ignore_apps: Vec<String>,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            summary: default_camera_summary(),
+            body: default_camera_body(),
+            icon: default_camera_icon(),
+            urgency: default_camera_urgency(),
+            timeout: default_camera_timeout(),
+            actions: true,
+            devices: Vec::new(),
+            exclude: Vec::new(),
+            ignore_apps: Vec::new(),
+        }
+    }
+}
+
+impl CameraConfig {
+    /// How long the notification stays, zero for until dismissed
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(parse_duration_ms(&self.timeout).unwrap_or(10_000) as u64)
+    }
+}
+
+/// On air: the camera, a screen share or the microphone, whichever comes
+/// first in `priority`, each with its own profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnAirConfig {
+    /// Sources by priority, highest first; those left out are ignored
+    #[serde(default = "default_on_air_priority")]
+    pub priority: Vec<String>,
+
+    /// Profile while on camera (empty = none)
+    #[serde(default)]
+    pub camera: String,
+
+    /// Profile while sharing the screen (empty = none)
+    #[serde(default)]
+    pub share: String,
+
+    /// Profile while the microphone records (empty = none)
+    #[serde(default)]
+    pub mic: String,
+}
+
+impl Default for OnAirConfig {
+    fn default() -> Self {
+        Self {
+            priority: default_on_air_priority(),
+            camera: String::new(),
+            share: String::new(),
+            mic: String::new(),
+        }
+    }
+}
+
+impl OnAirConfig {
+    /// The sources in `priority`, highest first, without unknown names
+    pub fn sources(&self) -> Vec<OnAir> {
+        self.priority.iter().filter_map(|name| OnAir::from_name(name)).collect()
+    }
+
+    /// Profile name of `source`
+    pub fn profile(&self, source: OnAir) -> &str {
+        match source {
+            OnAir::Camera => &self.camera,
+            OnAir::Share => &self.share,
+            OnAir::Mic => &self.mic,
+        }
+    }
+}
+
+/// What puts the user on air
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnAir {
+    Camera,
+    /// Sharing the screen or a window
+    Share,
+    Mic,
+}
+
+impl OnAir {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "camera" => Some(OnAir::Camera),
+            "share" => Some(OnAir::Share),
+            "mic" => Some(OnAir::Mic),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OnAir::Camera => "camera",
+            OnAir::Share => "share",
+            OnAir::Mic => "mic",
+        }
+    }
+}
+
+/// Do-Not-Disturb of the notification daemon (mako, dunst or SwayNC)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DndConfig {
+    /// Profile applied while Do-Not-Disturb is on (empty = none)
+    #[serde(default)]
+    pub profile: String,
+
+    /// Skip the camera notification while Do-Not-Disturb is on
+    #[serde(default)]
+    pub mute_camera: bool,
+
+    /// mako mode that means Do-Not-Disturb
+    #[serde(default = "default_dnd_mako_mode")]
+    pub mako_mode: String,
+}
+
+impl Default for DndConfig {
+    fn default() -> Self {
+        Self {
+            profile: String::new(),
+            mute_camera: false,
+            mako_mode: default_dnd_mako_mode(),
+        }
+    }
+}
+
+impl DndConfig {
+    /// Whether anything depends on the Do-Not-Disturb state
+    pub fn is_active(&self) -> bool {
+        !self.profile.is_empty() || self.mute_camera
+    }
+}
+
+/// HTTP control endpoint, for clients that can't reach the Unix socket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Address and port to listen on
+    #[serde(default = "default_http_listen")]
+    pub listen: String,
+
+    /// Required as `Authorization: Bearer <token>` when set
+    #[serde(default)]
+    pub token: String,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self { enabled: false, listen: default_http_listen(), token: String::new() }
+    }
+}
+
+/// OBS Studio over obs-websocket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// obs-websocket server, from OBS's Tools > WebSocket Server Settings
+    #[serde(default = "default_obs_url")]
+    pub url: String,
+
+    /// Empty if authentication is off in OBS
+    #[serde(default)]
+    pub password: String,
+
+    /// Profile applied while recording (empty = none)
+    #[serde(default)]
+    pub recording: String,
+
+    /// Profile applied while streaming, over the recording one (empty = none)
+    #[serde(default)]
+    pub streaming: String,
+
+    /// Color to flash when OBS skips frames while live (empty = off)
+    #[serde(default)]
+    pub dropped_frames: String,
+}
+
+impl Default for ObsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: default_obs_url(),
+            password: String::new(),
+            recording: String::new(),
+            streaming: String::new(),
+            dropped_frames: String::new(),
+        }
+    }
+}
+
+/// Devices lit in the ring's color through an OpenRGB SDK server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenRgbConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// SDK server address (OpenRGB's "SDK Server" tab)
+    #[serde(default = "default_openrgb_server")]
+    pub server: String,
+
+    /// Devices to light, matched against their names (empty = all)
+    #[serde(default)]
+    pub devices: Vec<String>,
+
+    /// Most color updates sent per second, while animating
+    #[serde(default = "default_openrgb_rate")]
+    pub rate: u32,
+}
+
+impl Default for OpenRgbConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server: default_openrgb_server(),
+            devices: Vec::new(),
+            rate: default_openrgb_rate(),
+        }
+    }
+}
+
+/// MQTT broker connection, announcing the ring to Home Assistant as a light
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttConfig {
+    /// Broker as host:port (empty = off)
+    #[serde(default)]
+    pub broker: String,
+
+    #[serde(default)]
+    pub username: String,
+
+    #[serde(default)]
+    pub password: String,
+
+    /// Prefix of the ring's own topics, `<topic>/state` and `<topic>/set`
+    /// (empty = `hypr-ringlight/<hostname>`)
+    #[serde(default)]
+    pub topic: String,
+
+    /// Home Assistant's discovery prefix
+    #[serde(default = "default_mqtt_discovery_prefix")]
+    pub discovery_prefix: String,
+
+    /// Entity name in Home Assistant (empty = "Ring light on <hostname>")
+    #[serde(default)]
+    pub name: String,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            broker: String::new(),
+            username: String::new(),
+            password: String::new(),
+            topic: String::new(),
+            discovery_prefix: default_mqtt_discovery_prefix(),
+            name: String::new(),
+        }
+    }
+}
+
+impl MqttConfig {
+    pub fn is_active(&self) -> bool {
+        !self.broker.is_empty()
+    }
+}
+
+/// Where theme colors are read from
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ThemeSource {
+    /// `~/.config/omarchy/current/theme/colors.toml`
+    #[default]
+    Omarchy,
+    /// A base16 / tinted-theming scheme
+    Base16,
+    /// Colors pywal generated from the wallpaper
+    Pywal,
+    /// The window border color (`col.active_border`) in Hyprland's config
+    Hyprland,
+}
+
+impl ThemeSource {
+    pub fn from_name(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "base16" => ThemeSource::Base16,
+            "pywal" | "wal" => ThemeSource::Pywal,
+            "hyprland" => ThemeSource::Hyprland,
+            _ => ThemeSource::Omarchy,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemeSource::Omarchy => "omarchy",
+            ThemeSource::Base16 => "base16",
+            ThemeSource::Pywal => "pywal",
+            ThemeSource::Hyprland => "hyprland",
+        }
+    }
+}
+
+/// Expand a leading `~/` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Where a tapered ring is at full thickness
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TaperAnchor {
+    #[default]
+    Corners,
+    Centers,
+}
+
+/// Thickness modulation along the edges
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Taper {
+    /// 0.0 = uniform ring, 1.0 = thins to nothing opposite the anchor
+    pub amount: f64,
+    pub anchor: TaperAnchor,
+}
+
+/// Easing curve used by time-based ramps (warm-up)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RampCurve {
+    Linear,
+    EaseIn,
+    #[default]
+    EaseOut,
+    Smoothstep,
+}
+
+impl RampCurve {
+    pub fn from_name(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "linear" => RampCurve::Linear,
+            "ease-in" => RampCurve::EaseIn,
+            "smoothstep" => RampCurve::Smoothstep,
+            _ => RampCurve::EaseOut,
+        }
+    }
+
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            0 => RampCurve::Linear,
+            1 => RampCurve::EaseIn,
+            3 => RampCurve::Smoothstep,
+            _ => RampCurve::EaseOut,
+        }
+    }
+
+    /// Map progress `t` in 0..=1 onto the curve
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            RampCurve::Linear => t,
+            RampCurve::EaseIn => t * t,
+            RampCurve::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            RampCurve::Smoothstep => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// How the glow fades towards the center of the screen
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GlowFalloff {
+    Linear,
+    Quadratic,
+    #[default]
+    Cubic,
+    Exponential,
+    Gaussian,
+}
+
+impl GlowFalloff {
+    pub fn from_name(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "linear" => GlowFalloff::Linear,
+            "quadratic" => GlowFalloff::Quadratic,
+            "exponential" => GlowFalloff::Exponential,
+            "gaussian" => GlowFalloff::Gaussian,
+            _ => GlowFalloff::Cubic,
+        }
+    }
+
+    /// Brightness at `t` of the way from the glow's outer end (0) to the band (1)
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            GlowFalloff::Linear => t,
+            GlowFalloff::Quadratic => t * t,
+            GlowFalloff::Cubic => t * t * t,
+            GlowFalloff::Exponential => {
+                const K: f64 = 5.0;
+                ((K * t).exp() - 1.0) / (K.exp() - 1.0)
+            }
+            GlowFalloff::Gaussian => {
+                // Bell curve centered on the band, rescaled to reach 0 at the outer end
+                const SIGMA: f64 = 0.35;
+                let bell = |x: f64| (-(x * x) / (2.0 * SIGMA * SIGMA)).exp();
+                (bell(1.0 - t) - bell(1.0)) / (1.0 - bell(1.0))
+            }
+        }
+    }
+}
+
+/// Dash pattern along the ring band
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Pattern {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl Pattern {
+    pub fn from_name(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "dashed" => Pattern::Dashed,
+            "dotted" => Pattern::Dotted,
+            _ => Pattern::Solid,
+        }
+    }
+
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Pattern::Dashed,
+            2 => Pattern::Dotted,
+            _ => Pattern::Solid,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Pattern::Solid => "solid",
+            Pattern::Dashed => "dashed",
+            Pattern::Dotted => "dotted",
+        }
+    }
+}
+
+/// Shortest allowed animation cycle (faster looks like flicker)
+pub const MIN_CYCLE_MS: u32 = 100;
+
+/// Parse a human-readable duration ("2s", "750ms", "1m 30s") into milliseconds
+pub fn parse_duration_ms(s: &str) -> Option<u32> {
+    let duration = humantime::parse_duration(s.trim()).ok()?;
+    Some(duration.as_millis().min(u32::MAX as u128) as u32)
+}
+
+/// Parse an animation cycle duration, clamped to `MIN_CYCLE_MS`
+pub fn parse_cycle_duration(s: &str) -> Option<u32> {
+    parse_duration_ms(s).map(|ms| ms.max(MIN_CYCLE_MS))
+}
+
+/// Format milliseconds as a human-readable cycle duration
+pub fn format_cycle_duration(ms: u32) -> String {
+    humantime::format_duration(Duration::from_millis(ms as u64)).to_string()
+}
+
+/// Convert the legacy "frames per cycle" speed (rendered at 60 FPS) into milliseconds
+pub fn frames_to_ms(frames: u32) -> u32 {
+    (frames as u64 * 1000 / 60).clamp(MIN_CYCLE_MS as u64, u32::MAX as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cycle_duration() {
+        assert_eq!(parse_cycle_duration("2s"), Some(2000));
+        assert_eq!(parse_cycle_duration("750ms"), Some(750));
+        assert_eq!(parse_cycle_duration("1m 30s"), Some(90_000));
+        assert_eq!(parse_cycle_duration("1ms"), Some(MIN_CYCLE_MS));
+        assert_eq!(parse_cycle_duration("fast"), None);
+        assert_eq!(format_cycle_duration(2500), "2s 500ms");
+    }
+
+    #[test]
+    fn test_glow_falloff() {
+        for name in ["linear", "quadratic", "cubic", "exponential", "gaussian"] {
+            let falloff = GlowFalloff::from_name(name);
+            assert!(falloff.apply(0.0).abs() < 1e-9, "{}", name);
+            assert!((falloff.apply(1.0) - 1.0).abs() < 1e-9, "{}", name);
+            assert!(falloff.apply(0.3) < falloff.apply(0.6), "{}", name);
+        }
+        assert_eq!(GlowFalloff::from_name("bogus"), GlowFalloff::Cubic);
+        assert!((GlowFalloff::Cubic.apply(0.5) - 0.125).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_apply_env() {
+        let mut config = Config::default();
+        let vars = [
+            ("HYPR_RINGLIGHT_COLOR", "112233"),
+            ("HYPR_RINGLIGHT_THICKNESS", "120"),
+            ("HYPR_RINGLIGHT_IDLE__TIMEOUT", "5m"),
+            ("HYPR_RINGLIGHT_DISABLED_MONITORS", "[\"DP-2\"]"),
+            ("HYPR_RINGLIGHT_PROFILES__CALL__OPACITY", "0.5"),
+            ("HYPR_RINGLIGHT_SAFE", "1"),
+            ("HYPR_RINGLIGHT_INSTANCE", "studio"),
+            ("HYPR_RINGLIGHT_COLOUR", "ff0000"),
+            ("HOME", "/root"),
+        ];
+        let applied = config.apply_env(vars.map(|(k, v)| (k.to_string(), v.to_string()))).unwrap();
+        assert_eq!(applied.len(), 5);
+        assert_eq!(config.color, "112233");
+        assert_eq!(config.thickness, 120);
+        assert_eq!(config.idle.timeout, "5m");
+        assert_eq!(config.disabled_monitors, ["DP-2"]);
+        assert_eq!(config.profiles["call"].opacity, Some(0.5));
+
+        let bad = [("HYPR_RINGLIGHT_THICKNESS".to_string(), "thick".to_string())];
+        assert!(config.apply_env(bad).is_err());
+    }
+
+    #[test]
+    fn test_migrate_animation_speed() {
+        let content = "animation_speed = 240";
+        let mut config: Config = toml::from_str(content).unwrap();
+        config.migrate_animation_speed(content);
+        assert_eq!(config.cycle_duration, "4s");
+        assert_eq!(config.animation_speed, None);
+
+        let content = "animation_speed = 240\ncycle_duration = \"1s\"";
+        let mut config: Config = toml::from_str(content).unwrap();
+        config.migrate_animation_speed(content);
+        assert_eq!(config.cycle_duration, "1s");
+    }
+
+    #[test]
+    fn test_profiles_and_window_rules() {
+        let content = r#"
+[profiles.call]
+thickness = 120
+animation = "none"
+
+[[hyprland.window_rules]]
+class = "^zoom$"
+profile = "call"
+
+[[hyprland.window_rules]]
+class = "mpv"
+fullscreen = true
+hide = true
+"#;
+        let config: Config = toml::from_str(content).unwrap();
+        assert_eq!(config.profiles["call"].thickness, Some(120));
+        assert_eq!(config.profiles["call"].color, None);
+        assert!(config.hyprland.is_active());
+        assert_eq!(config.hyprland.window_rules[1].fullscreen, Some(true));
+
+        let saved: Config = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(saved.profiles, config.profiles);
+        assert_eq!(saved.hyprland.window_rules.len(), 2);
+    }
+}
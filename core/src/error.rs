@@ -0,0 +1,46 @@
+//! Errors and the exit codes they map to
+//!
+//! Exit codes are part of the CLI contract (documented in the README), so
+//! wrapper scripts and activation checks can tell failures apart.
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Invalid or unreadable config file (exit code 2)
+    Config(String),
+    /// Invalid command line argument (exit code 2, like clap's usage errors)
+    Usage(String),
+    /// No Wayland compositor, or it lacks a required protocol (exit code 3)
+    Compositor(String),
+    /// Another instance already owns the IPC socket (exit code 4)
+    AlreadyRunning,
+    /// The running instance can't be reached over IPC (exit code 5)
+    Ipc(String),
+    /// Anything else (exit code 1)
+    Other(String),
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Other(_) => 1,
+            Error::Config(_) | Error::Usage(_) => 2,
+            Error::Compositor(_) => 3,
+            Error::AlreadyRunning => 4,
+            Error::Ipc(_) => 5,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Config(msg) => write!(f, "{} (start with --safe-mode to ignore the config)", msg),
+            Error::AlreadyRunning => write!(f, "hypr-ringlight is already running"),
+            Error::Usage(msg) | Error::Compositor(msg) | Error::Ipc(msg) | Error::Other(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,128 @@
+//! Headless frames: the config's ring at rest, drawn without a compositor
+//!
+//! Uses the same renderer as the overlay, with the config's look at rest:
+//! no fades, warm-up or timers, and animations at the start of their cycle.
+//! The binary's `--render-to-file` and `--bench-frame` draw through here, and
+//! so do the golden-image tests in `tests/golden.rs`, which need nothing but
+//! this crate to build.
+
+use crate::color::{resolve_color, resolve_color_alpha};
+use crate::config::{BarPosition, Config};
+use crate::render::{self, Catchlight, Comet, Ring, Texture};
+use crate::shape::Shape;
+
+/// Where the ring surface starts on a monitor, and its size, leaving room for the bar
+fn surface_rect(cfg: &Config, (width, height): (u32, u32)) -> (u32, u32, u32, u32) {
+    let bar = cfg.bar_height;
+    let (x, y) = match cfg.bar_position_enum() {
+        BarPosition::Top => (0, bar),
+        BarPosition::Left => (bar, 0),
+        BarPosition::Bottom | BarPosition::Right => (0, 0),
+    };
+    let (w, h) = match cfg.bar_position_enum() {
+        BarPosition::Top | BarPosition::Bottom => (width, height.saturating_sub(bar)),
+        BarPosition::Left | BarPosition::Right => (width.saturating_sub(bar), height),
+    };
+    (x, y, w.max(1), h.max(1))
+}
+
+/// Size of the ring surface on a monitor of `size`, which leaves room for the bar
+pub fn surface_size(cfg: &Config, size: (u32, u32)) -> (u32, u32) {
+    let (_, _, width, height) = surface_rect(cfg, size);
+    (width, height)
+}
+
+/// The ring of `cfg` at rest, with sizes scaled by `scale`
+pub fn ring_at_rest<'a>(cfg: &Config, color: (u8, u8, u8), texture: Option<&'a Texture>, scale: f64) -> Ring<'a> {
+    let thickness = cfg.thickness as f64 * scale;
+    let mode = cfg.animation_mode();
+    let (_, alpha) = resolve_color_alpha(&cfg.color, &cfg.palette);
+    let (color, opacity) = render::animate(mode, color, cfg.opacity * alpha, 0.0);
+    Ring {
+        inset: cfg.inset as f64 * scale,
+        thickness,
+        glow: cfg.glow as f64 * scale,
+        falloff: cfg.glow_falloff_enum(),
+        inner_glow: cfg.inner_glow as f64 * scale,
+        vignette: cfg.vignette.enabled.then_some(cfg.vignette.distance as f64 * scale),
+        fill: cfg.fill.enabled.then(|| (resolve_color(&cfg.fill.color, &cfg.palette), cfg.fill.opacity.clamp(0.0, 1.0))),
+        // As seen on the monitor with the webcam
+        catchlight: cfg.webcam.position_on(&cfg.webcam.monitor).map(|origin| Catchlight {
+            origin,
+            floor: cfg.webcam.floor.clamp(0.0, 1.0),
+        }),
+        shape: Shape {
+            radii: cfg.corner_multipliers().map(|m| thickness * m),
+            style: cfg.corner_style_enum(),
+            notch: None,
+            taper: cfg.taper_enum(),
+        },
+        color,
+        texture,
+        opacity,
+        progress: None,
+        comet: (mode == 4).then_some(Comet { head: 0.0, length: cfg.comet.length, count: cfg.comet.count }),
+        spectrum: None,
+        segments: &[],
+        pattern: cfg.pattern_enum(),
+        backdrop: false,
+        dither: cfg.dither,
+        gamma_correct: cfg.gamma_correct,
+    }
+}
+
+/// Draw the ring surface of a monitor of `size`, premultiplied ARGB8888 of
+/// `surface_size`, so overlays can go on top before `to_monitor`
+pub fn render_surface(cfg: &Config, color: (u8, u8, u8), size: (u32, u32), texture: Option<&Texture>) -> Vec<u8> {
+    let (width, height) = surface_size(cfg, size);
+    let ring = ring_at_rest(cfg, color, texture, 1.0);
+    let mut canvas = vec![0u8; width as usize * height as usize * 4];
+    render::render(&mut canvas, width, height, &ring);
+    canvas
+}
+
+/// Place a surface from `render_surface` on its monitor of `size`, as
+/// straight (not premultiplied) RGBA, row by row
+pub fn to_monitor(cfg: &Config, canvas: &[u8], size: (u32, u32)) -> Vec<u8> {
+    let (monitor_w, monitor_h) = size;
+    let (dx, dy, width, _) = surface_rect(cfg, size);
+    let mut image = vec![0u8; monitor_w as usize * monitor_h as usize * 4];
+    for (index, chunk) in canvas.chunks_exact(4).enumerate() {
+        let (x, y) = ((index % width as usize) as u32 + dx, (index / width as usize) as u32 + dy);
+        if x >= monitor_w || y >= monitor_h {
+            continue;
+        }
+        let pixel = u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let a = pixel >> 24;
+        let unpremultiply = |c: u32| (c * 255).checked_div(a).map_or(0, |c| c.min(255) as u8);
+        let offset = (y as usize * monitor_w as usize + x as usize) * 4;
+        image[offset..offset + 4].copy_from_slice(&[
+            unpremultiply((pixel >> 16) & 0xff),
+            unpremultiply((pixel >> 8) & 0xff),
+            unpremultiply(pixel & 0xff),
+            a as u8,
+        ]);
+    }
+    image
+}
+
+/// A monitor of `size` with `cfg`'s ring at rest, straight RGBA, row by row
+pub fn render_frame(cfg: &Config, color: (u8, u8, u8), size: (u32, u32), texture: Option<&Texture>) -> Vec<u8> {
+    to_monitor(cfg, &render_surface(cfg, color, size, texture), size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_surface_size() {
+        let cfg = Config { bar_height: 30, bar_position: "left".to_string(), ..Config::default() };
+        assert_eq!(surface_size(&cfg, (640, 400)), (610, 400));
+        // The bar's strip stays transparent
+        let frame = render_frame(&cfg, (255, 255, 255), (640, 400), None);
+        let alpha = |x: usize| frame[(200 * 640 + x) * 4 + 3];
+        assert_eq!(alpha(0), 0);
+        assert_eq!(alpha(30), 255);
+    }
+}
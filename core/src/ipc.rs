@@ -0,0 +1,557 @@
+//! IPC protocol and client
+//!
+//! The daemon listens on a Unix socket for newline-delimited JSON commands.
+//! Most commands get no reply; `GetState`, `GetMonitors`, `GetStats`,
+//! `GetEvents` and `ApplyScene` are answered with one JSON line each. A connection can carry any number of
+//! commands; after `Subscribe` it carries states instead, one line per change.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::MonitorConfig;
+use crate::error::Error;
+use crate::scene::Scene;
+
+/// Environment variable naming the instance, set by `--instance`
+pub const INSTANCE_ENV: &str = "HYPR_RINGLIGHT_INSTANCE";
+
+/// Name of this instance, when several run side by side with their own
+/// socket and config
+pub fn instance() -> Option<String> {
+    std::env::var(INSTANCE_ENV).ok().filter(|name| !name.is_empty())
+}
+
+/// Whether `name` can name an instance: it ends up in file names
+pub fn is_valid_instance(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// "hypr-ringlight", or "hypr-ringlight-NAME" for a named instance
+pub fn instance_id() -> String {
+    match instance() {
+        Some(name) => format!("hypr-ringlight-{}", name),
+        None => "hypr-ringlight".to_string(),
+    }
+}
+
+/// Socket path
+pub fn socket_path() -> PathBuf {
+    std::env::var("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("/tmp"))
+        .join(format!("{}.sock", instance_id()))
+}
+
+/// Commands that can be sent via IPC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "cmd", content = "value")]
+pub enum Command {
+    /// Hex color, or a name from the palette
+    SetColor(String),
+    /// Change a palette color; everything using the name follows
+    SetPaletteColor { name: String, color: String },
+    SetThickness(u32),
+    SetOpacity(f64),
+    SetGlow(u32),
+    SetCornerRadius(f64),
+    /// Radius multiplier of each corner: top-left, top-right, bottom-right, bottom-left
+    SetCornerRadii([f64; 4]),
+    /// Corner geometry: round, squircle, chamfer
+    SetCornerStyle(String),
+    /// PNG file filling the ring band; empty for the flat color
+    SetTexture(String),
+    /// Room left for the status bar, in pixels
+    SetBarHeight(u32),
+    /// Edge the status bar sits on: top, bottom, left, right
+    SetBarPosition(String),
+    /// Layer the ring is drawn on: background, bottom, top, overlay
+    SetLayer(String),
+    SetAnimation(String),
+    /// Human-readable duration of one animation cycle, e.g. "2s"
+    SetCycleDuration(String),
+    /// Legacy frames-per-cycle speed (at 60 FPS), converted to a cycle duration
+    SetAnimationSpeed(u32),
+    /// Dash pattern along the band: solid, dashed, dotted
+    SetPattern(String),
+    SetVisible(bool),
+    /// Text shown in the top band of the ring; empty hides it
+    SetLabel(String),
+    GetState,
+    GetMonitors,
+    SetMonitorEnabled { id: String, enabled: bool },
+    SetDebugOverlay(bool),
+    /// Show the camera framing guide (also shown automatically during calls if configured)
+    SetFramingGuide(bool),
+    /// Draw the ring over an opaque black band, for clean screenshots and recordings
+    SetSolidBackdrop(bool),
+    /// Fade the glow in linear light (not persisted), to compare with sRGB
+    SetGammaCorrect(bool),
+    /// Light fading in from the edges instead of a band (not persisted)
+    SetVignette(bool),
+    /// Wash the whole screen in the `[fill]` color, under the ring (not persisted)
+    SetFill(bool),
+    /// Show the ring only on the monitor that has focus (Hyprland)
+    SetFollowFocus(bool),
+    /// Light only this fraction (0.0 - 1.0) of the perimeter; `null` goes back to a full ring
+    SetProgress(Option<f64>),
+    /// Start a focus timer, e.g. "25m"; replaces any running timer
+    StartTimer(String),
+    StopTimer,
+    /// Shrink the lit perimeter to nothing over this many seconds, then tell
+    /// subscribers with a `CountdownFinished` notice; replaces any running countdown
+    StartCountdown(u32),
+    StopCountdown,
+    /// Hold camera notifications back for a while, e.g. "1h"; "0s" ends a snooze
+    SnoozeCamera(String),
+    /// Flash the ring as an alert; unset fields come from the `[strobe]` config
+    StartStrobe(StrobeRequest),
+    StopStrobe,
+    /// Show a color (hex or palette name) for a while, e.g. "300ms", then restore the ring
+    Flash { color: String, duration: String },
+    /// Pulse while the process with this PID (a `hypr-ringlight watch`) is alive
+    StartWatch(u32),
+    /// The watched command exited; flash the success or failure color
+    FinishWatch(bool),
+    GetStats,
+    /// Recent state changes, after the event with this sequence number (`null` for all)
+    GetEvents(Option<u64>),
+    /// Apply a scene, changing only what differs from the current state
+    ApplyScene(Box<Scene>),
+    /// Several commands applied together between two frames; replies to queries are dropped
+    Batch(Vec<Command>),
+    /// Reply with the state now and again whenever it changes; the connection
+    /// takes no more commands
+    Subscribe,
+    Quit,
+}
+
+/// Version of the protocol, reported in `State`. Version 2 added the
+/// monitors, with their bars and overrides, to `GetState`.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+fn legacy_version() -> u32 {
+    1
+}
+
+/// Response from the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    /// `PROTOCOL_VERSION` of the daemon; 1 for those older than the field
+    #[serde(default = "legacy_version")]
+    pub version: u32,
+    pub color: String,
+    /// Palette name the color follows, if it was set by name
+    #[serde(default)]
+    pub color_name: Option<String>,
+    #[serde(default)]
+    pub palette: BTreeMap<String, String>,
+    pub thickness: u32,
+    pub opacity: f64,
+    pub glow: u32,
+    pub corner_radius: f64,
+    pub corner_style: String,
+    /// Per-corner radius multipliers, when they differ
+    #[serde(default)]
+    pub corner_radii: Option<[f64; 4]>,
+    pub animation: String,
+    pub cycle_duration: String,
+    #[serde(default)]
+    pub pattern: String,
+    pub visible: bool,
+    #[serde(default)]
+    pub debug_overlay: bool,
+    #[serde(default)]
+    pub framing_guide: bool,
+    /// Whether the ring is drawn over an opaque band (by request, or because
+    /// the compositor can't blend)
+    #[serde(default)]
+    pub solid_backdrop: bool,
+    #[serde(default)]
+    pub gamma_correct: bool,
+    /// Whether the ring is drawn as a vignette
+    #[serde(default)]
+    pub vignette: bool,
+    /// Whether the screen is filled as a key light
+    #[serde(default)]
+    pub fill: bool,
+    /// Whether only the focused monitor shows the ring
+    #[serde(default)]
+    pub follow_focus: bool,
+    /// Texture image filling the ring, empty for the flat color
+    #[serde(default)]
+    pub texture: String,
+    #[serde(default)]
+    pub bar_height: u32,
+    #[serde(default)]
+    pub bar_position: String,
+    #[serde(default)]
+    pub layer: String,
+    /// Connected monitors, each with what actually applies to it
+    #[serde(default)]
+    pub monitors: Vec<MonitorState>,
+    /// Monitors the ring is turned off on, including disconnected ones
+    #[serde(default)]
+    pub disabled_monitors: Vec<String>,
+    /// Profiles currently applied by rules, lowest priority first
+    #[serde(default)]
+    pub active_profiles: Vec<String>,
+    /// Whether the ring is faded out because the user is idle
+    #[serde(default)]
+    pub idle: bool,
+    /// Whether battery power saving is active
+    #[serde(default)]
+    pub battery_saving: bool,
+    /// Whether the notification daemon is in Do-Not-Disturb
+    #[serde(default)]
+    pub dnd: bool,
+    /// Whether rendering is suspended because a game has focus
+    #[serde(default)]
+    pub gaming: bool,
+    /// Command watched by `hypr-ringlight watch`
+    #[serde(default)]
+    pub watch: Option<WatchStatus>,
+    /// Running (or just finished) focus timer
+    #[serde(default)]
+    pub timer: Option<TimerStatus>,
+    /// Running countdown
+    #[serde(default)]
+    pub countdown: Option<TimerStatus>,
+    /// What puts the user on air: "camera", "share" or "mic", empty when off air
+    #[serde(default)]
+    pub on_air: String,
+    /// When camera notifications are shown again, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub camera_snoozed_until_unix_ms: Option<u64>,
+    /// Whether a strobe alert is flashing
+    #[serde(default)]
+    pub strobe: bool,
+    /// Fraction of the perimeter lit in progress mode
+    #[serde(default)]
+    pub progress: Option<f64>,
+    /// Text shown in the top band, empty for none
+    #[serde(default)]
+    pub label: String,
+}
+
+/// Strobe alert settings, each falling back to the config
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StrobeRequest {
+    /// Flashes per second
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<f64>,
+    /// Lit fraction of each flash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duty: Option<f64>,
+    /// Number of flashes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+    /// Hex color of the flashes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+/// State of a `hypr-ringlight watch`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchStatus {
+    /// "running", "succeeded" or "failed" (flashing the result)
+    pub state: String,
+    pub elapsed_ms: u64,
+}
+
+/// Progress of the focus timer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimerStatus {
+    pub duration_ms: u64,
+    pub remaining_ms: u64,
+}
+
+/// A moment pushed to subscribers between states
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum Notice {
+    /// A countdown ran out (a stopped one doesn't)
+    CountdownFinished,
+}
+
+/// A line pushed to subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Update {
+    Notice(Notice),
+    State(Box<State>),
+}
+
+/// Monitor info for IPC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorState {
+    pub id: String,
+    pub display_name: String,
+    pub enabled: bool,
+    /// Hidden by an automatic rule (a workspace, focus-follow)
+    #[serde(default)]
+    pub suppressed: bool,
+    /// Whether the ring shows on it: visible, enabled and not suppressed
+    #[serde(default)]
+    pub visible: bool,
+    /// Room left for bars, after overrides and detection
+    #[serde(default)]
+    pub bar: BarMargin,
+    /// Its `[monitor."ID"]` settings
+    #[serde(default)]
+    pub overrides: MonitorConfig,
+}
+
+/// Room left for bars on a monitor, per logical edge, in pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BarMargin {
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub left: i32,
+}
+
+/// Response with monitors list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorsResponse {
+    pub monitors: Vec<MonitorState>,
+}
+
+/// Debounce state of one automatic trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerStats {
+    pub name: String,
+    /// Latest raw (undebounced) signal
+    pub raw: bool,
+    /// Debounced state
+    pub active: bool,
+    /// How long the raw signal has disagreed with the debounced state
+    pub pending_ms: u64,
+    /// Time left before the trigger may activate again
+    pub cooldown_remaining_ms: u64,
+}
+
+/// Rendering cost of one monitor's ring
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderStats {
+    pub monitor: String,
+    pub width: u32,
+    pub height: u32,
+    /// Size of the shared memory pool holding its buffers
+    pub buffer_bytes: u64,
+    /// Frames rendered since the ring was created
+    pub frames: u64,
+    pub fps: f64,
+    /// Average render time over the last frames, in microseconds
+    pub avg_render_us: u64,
+    /// Slowest frame since the ring was created
+    pub max_render_us: u64,
+}
+
+/// Response to `GetStats`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stats {
+    pub triggers: Vec<TriggerStats>,
+    #[serde(default)]
+    pub monitors: Vec<RenderStats>,
+}
+
+/// An entry of the daemon's event log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    /// Increases by one per event, to poll for new ones
+    pub seq: u64,
+    /// Milliseconds since the Unix epoch
+    pub unix_ms: u64,
+    /// What made the change: ipc, tray, camera, hyprland, battery, lock, ...
+    pub source: String,
+    pub message: String,
+}
+
+/// Response to `GetEvents`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsResponse {
+    pub events: Vec<Event>,
+}
+
+/// Response to `ApplyScene`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneResponse {
+    /// What the scene changed, e.g. "color ff0000 -> ff8800"; empty if it was already applied
+    pub changes: Vec<String>,
+}
+
+/// Client: send a command to the running instance
+pub fn send_command(cmd: &Command) -> Result<Option<State>, Error> {
+    let path = socket_path();
+    
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|_| Error::Ipc("hypr-ringlight is not running".to_string()))?;
+    
+    let json = serde_json::to_string(cmd).map_err(|e| Error::Ipc(e.to_string()))?;
+    writeln!(stream, "{}", json).map_err(|e| Error::Ipc(e.to_string()))?;
+    
+    if matches!(cmd, Command::GetState) {
+        let reader = BufReader::new(stream);
+        if let Some(Ok(line)) = reader.lines().next() {
+            let state: State = serde_json::from_str(&line).map_err(|e| Error::Ipc(e.to_string()))?;
+            return Ok(Some(state));
+        }
+    }
+    
+    Ok(None)
+}
+
+/// Client: get monitors from running instance
+pub fn get_monitors() -> Result<Vec<MonitorState>, Error> {
+    let path = socket_path();
+    
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|_| Error::Ipc("hypr-ringlight is not running".to_string()))?;
+    
+    let json = serde_json::to_string(&Command::GetMonitors).map_err(|e| Error::Ipc(e.to_string()))?;
+    writeln!(stream, "{}", json).map_err(|e| Error::Ipc(e.to_string()))?;
+    
+    let reader = BufReader::new(stream);
+    if let Some(Ok(line)) = reader.lines().next() {
+        let response: MonitorsResponse = serde_json::from_str(&line).map_err(|e| Error::Ipc(e.to_string()))?;
+        return Ok(response.monitors);
+    }
+    
+    Ok(Vec::new())
+}
+
+/// Client: get the events after `after` (all of them with `None`)
+pub fn get_events(after: Option<u64>) -> Result<Vec<Event>, Error> {
+    Session::connect()?.query::<EventsResponse>(&Command::GetEvents(after)).map(|r| r.events)
+}
+
+/// Client: get trigger and render statistics
+pub fn get_stats() -> Result<Stats, Error> {
+    Session::connect()?.query(&Command::GetStats)
+}
+
+/// Client: apply a scene, returning what it changed
+pub fn apply_scene(scene: &Scene) -> Result<Vec<String>, Error> {
+    let cmd = Command::ApplyScene(Box::new(scene.clone()));
+    Session::connect()?.query::<SceneResponse>(&cmd).map(|r| r.changes)
+}
+
+/// Client: set monitor enabled state
+pub fn set_monitor_enabled(id: &str, enabled: bool) -> Result<(), Error> {
+    let path = socket_path();
+    
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|_| Error::Ipc("hypr-ringlight is not running".to_string()))?;
+    
+    let cmd = Command::SetMonitorEnabled { id: id.to_string(), enabled };
+    let json = serde_json::to_string(&cmd).map_err(|e| Error::Ipc(e.to_string()))?;
+    writeln!(stream, "{}", json).map_err(|e| Error::Ipc(e.to_string()))?;
+    
+    Ok(())
+}
+
+/// A connection kept open for many commands, so a client sending a stream of
+/// them (like the TUI while a key is held) doesn't connect for each one
+pub struct Session {
+    stream: UnixStream,
+    reader: BufReader<UnixStream>,
+}
+
+fn connect_socket() -> Result<UnixStream, Error> {
+    UnixStream::connect(socket_path()).map_err(|_| Error::Ipc("hypr-ringlight is not running".to_string()))
+}
+
+impl Session {
+    pub fn connect() -> Result<Self, Error> {
+        let stream = connect_socket()?;
+        let reader = BufReader::new(stream.try_clone().map_err(|e| Error::Ipc(e.to_string()))?);
+        Ok(Self { stream, reader })
+    }
+
+    /// Send a command; if the daemon restarted since, connect again and resend
+    pub fn send(&mut self, cmd: &Command) -> Result<(), Error> {
+        let json = serde_json::to_string(cmd).map_err(|e| Error::Ipc(e.to_string()))?;
+        if writeln!(self.stream, "{}", json).is_err() {
+            *self = Self::connect()?;
+            writeln!(self.stream, "{}", json).map_err(|e| Error::Ipc(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Send a query and read its reply
+    pub fn query<T: DeserializeOwned>(&mut self, cmd: &Command) -> Result<T, Error> {
+        self.send(cmd)?;
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(n) if n > 0 => serde_json::from_str(&line).map_err(|e| Error::Ipc(e.to_string())),
+            _ => Err(Error::Ipc("No response from hypr-ringlight".to_string())),
+        }
+    }
+
+    pub fn get_state(&mut self) -> Result<State, Error> {
+        self.query(&Command::GetState)
+    }
+
+    pub fn get_monitors(&mut self) -> Result<Vec<MonitorState>, Error> {
+        self.query::<MonitorsResponse>(&Command::GetMonitors).map(|r| r.monitors)
+    }
+
+    pub fn set_monitor_enabled(&mut self, id: &str, enabled: bool) -> Result<(), Error> {
+        self.send(&Command::SetMonitorEnabled { id: id.to_string(), enabled })
+    }
+
+    /// States on a connection of their own: the current one, then each change,
+    /// until the daemon goes away
+    pub fn subscribe() -> Result<impl Iterator<Item = State>, Error> {
+        Ok(Self::updates()?.filter_map(|update| match update {
+            Update::State(state) => Some(*state),
+            Update::Notice(_) => None,
+        }))
+    }
+
+    /// Like `subscribe`, with the notices in between
+    pub fn updates() -> Result<impl Iterator<Item = Update>, Error> {
+        let mut stream = connect_socket()?;
+        let json = serde_json::to_string(&Command::Subscribe).map_err(|e| Error::Ipc(e.to_string()))?;
+        writeln!(stream, "{}", json).map_err(|e| Error::Ipc(e.to_string()))?;
+        Ok(BufReader::new(stream).lines().map_while(Result::ok).filter_map(|line| serde_json::from_str(&line).ok()))
+    }
+}
+
+/// Check if the server is running
+pub fn is_running() -> bool {
+    UnixStream::connect(socket_path()).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_lines() {
+        let notice: Update = serde_json::from_str(r#"{"event": "CountdownFinished"}"#).unwrap();
+        assert!(matches!(notice, Update::Notice(Notice::CountdownFinished)));
+        let json = r#"{"color": "ffffff", "thickness": 80, "opacity": 1.0, "glow": 0, "corner_radius": 1.0,
+            "corner_style": "round", "animation": "none", "cycle_duration": "3s", "visible": true}"#;
+        assert!(matches!(serde_json::from_str(json).unwrap(), Update::State(_)));
+    }
+
+    #[test]
+    fn test_state_from_older_daemon() {
+        let json = r#"{"color": "ffffff", "thickness": 80, "opacity": 1.0, "glow": 0, "corner_radius": 1.0,
+            "corner_style": "round", "animation": "none", "cycle_duration": "3s", "visible": true}"#;
+        let state: State = serde_json::from_str(json).unwrap();
+        assert_eq!(state.version, 1);
+        assert!(state.monitors.is_empty());
+
+        let monitor: MonitorState =
+            serde_json::from_str(r#"{"id": "DP-2", "display_name": "Dell", "enabled": false}"#).unwrap();
+        assert_eq!(monitor.bar, BarMargin::default());
+        assert_eq!(monitor.overrides, MonitorConfig::default());
+    }
+}
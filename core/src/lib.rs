@@ -0,0 +1,67 @@
+//! Core of hypr-ringlight: the ring renderer, config format and IPC protocol
+//!
+//! The `hypr-ringlight` binary adds the Wayland overlay, tray, TUI and
+//! integrations on top of this crate. Use it directly to draw the ring into
+//! your own buffers, read and write the config file, or control a running
+//! instance.
+//!
+//! Drawing a frame:
+//!
+//! ```
+//! use hypr_ringlight_core::config::{CornerStyle, GlowFalloff, Pattern, Taper};
+//! use hypr_ringlight_core::render::{render, Ring};
+//! use hypr_ringlight_core::shape::Shape;
+//!
+//! let (width, height) = (320, 200);
+//! let ring = Ring {
+//!     inset: 0.0,
+//!     thickness: 20.0,
+//!     glow: 10.0,
+//!     falloff: GlowFalloff::Cubic,
+//!     inner_glow: 0.0,
+//!     vignette: None,
+//!     fill: None,
+//!     catchlight: None,
+//!     shape: Shape { radii: [50.0; 4], style: CornerStyle::Round, notch: None, taper: Taper::default() },
+//!     color: (255, 255, 255),
+//!     texture: None,
+//!     opacity: 1.0,
+//!     progress: None,
+//!     comet: None,
+//!     spectrum: None,
+//!     segments: &[],
+//!     pattern: Pattern::Solid,
+//!     backdrop: false,
+//!     dither: false,
+//!     gamma_correct: false,
+//! };
+//! let mut pixels = vec![0u8; width * height * 4];
+//! render(&mut pixels, width as u32, height as u32, &ring);
+//!
+//! // Opaque white at the screen edge, transparent in the middle
+//! assert_eq!(pixels[..4], [255; 4]);
+//! let center = (height / 2 * width + width / 2) * 4;
+//! assert_eq!(pixels[center..center + 4], [0; 4]);
+//! ```
+//!
+//! Controlling the running instance:
+//!
+//! ```no_run
+//! use hypr_ringlight_core::ipc::{send_command, Command};
+//!
+//! send_command(&Command::SetColor("ff8800".to_string()))?;
+//! if let Some(state) = send_command(&Command::GetState)? {
+//!     println!("ring is {}", if state.visible { "on" } else { "off" });
+//! }
+//! # Ok::<(), hypr_ringlight_core::error::Error>(())
+//! ```
+
+pub mod check;
+pub mod color;
+pub mod config;
+pub mod error;
+pub mod frame;
+pub mod ipc;
+pub mod render;
+pub mod scene;
+pub mod shape;
@@ -0,0 +1,596 @@
+//! The ring renderer
+//!
+//! Draws a frame of the ring into premultiplied ARGB8888 pixels (wl_shm's
+//! `Argb8888` layout), and holds the animation math that decides its color
+//! and opacity over time. There are no Wayland types here, so the ring can be
+//! drawn into any buffer.
+
+use std::sync::OnceLock;
+
+use crate::color::{hsl_to_rgb, linear_to_srgb, srgb_to_linear};
+use crate::config::{GaugeMap, GlowFalloff, Pattern, ProgressOrigin, TextureMode};
+use crate::shape::{distance_to_inner_rounded_border, perimeter_fill, perimeter_position_from, Shape};
+
+/// Animation names, in the order of their modes
+pub const ANIMATIONS: [&str; 9] = ["none", "pulse", "rainbow", "breathe", "comet", "heartbeat", "spectrum", "system", "typing"];
+
+/// Animation mode from its name: its index in `ANIMATIONS`, none (0) if unknown
+pub fn animation_from_name(name: &str) -> u8 {
+    ANIMATIONS.iter().position(|a| a.eq_ignore_ascii_case(name)).unwrap_or(0) as u8
+}
+
+/// Name of an animation mode
+pub fn animation_name(mode: u8) -> &'static str {
+    ANIMATIONS.get(mode as usize).copied().unwrap_or("none")
+}
+
+/// Rise time of a heartbeat thump, as a fraction of the cycle
+const THUMP_RISE: f64 = 0.04;
+/// How fast a thump fades, per cycle
+const THUMP_DECAY: f64 = 8.0;
+
+/// Brightness (0.0 - 1.0) of a heartbeat `t` into its cycle: two quick
+/// thumps, the second a little weaker, then a long decay
+fn heartbeat(t: f64) -> f64 {
+    let thump = |start: f64, peak: f64| {
+        let dt = t - start;
+        if dt < 0.0 {
+            0.0
+        } else if dt < THUMP_RISE {
+            peak * dt / THUMP_RISE
+        } else {
+            peak * (-(dt - THUMP_RISE) * THUMP_DECAY).exp()
+        }
+    };
+    thump(0.0, 1.0).max(thump(0.22, 0.8))
+}
+
+/// Color and opacity of a ring animated with `mode`, `phase` cycles since it started
+pub fn animate(mode: u8, color: (u8, u8, u8), opacity: f64, phase: f64) -> ((u8, u8, u8), f64) {
+    match mode {
+        1 => {
+            let pulse = (phase * 2.0 * std::f64::consts::PI).sin();
+            (color, opacity * (0.5 + 0.5 * pulse))
+        }
+        2 => (hsl_to_rgb(phase % 1.0, 1.0, 0.5), opacity),
+        3 => {
+            let breathe = (phase * std::f64::consts::PI).sin();
+            (color, opacity * breathe.abs().max(0.1))
+        }
+        5 => (color, opacity * (0.15 + 0.85 * heartbeat(phase.rem_euclid(1.0)))),
+        _ => (color, opacity),
+    }
+}
+
+/// Hue of a gauge at rest, green
+const GAUGE_LOW_HUE: f64 = 1.0 / 3.0;
+
+/// Color and opacity of a ring showing a system gauge at `level` (0.0 - 1.0):
+/// green through yellow to red, or dim to full brightness down to `floor`
+pub fn gauge(map: GaugeMap, level: f64, color: (u8, u8, u8), opacity: f64, floor: f64) -> ((u8, u8, u8), f64) {
+    let level = level.clamp(0.0, 1.0);
+    match map {
+        GaugeMap::Hue => (hsl_to_rgb(GAUGE_LOW_HUE * (1.0 - level), 1.0, 0.5), opacity),
+        GaugeMap::Brightness => (color, opacity * (floor + (1.0 - floor) * level)),
+    }
+}
+
+/// Alpha of a ring pixel given its distance past the inner border.
+///
+/// Fully opaque inside the band, easing along `falloff` through the glow,
+/// transparent in the middle of the screen.
+pub fn ring_alpha(dist_to_inner: f64, glow: f64, falloff: GlowFalloff, opacity: f64) -> f64 {
+    if dist_to_inner <= 0.0 {
+        0.0
+    } else if dist_to_inner > glow {
+        opacity
+    } else {
+        opacity * falloff.apply(dist_to_inner / glow)
+    }
+}
+
+/// Fade of a floating ring's outside, `dist_to_inner` past its inner border.
+///
+/// A ring `inset` from the screen edge glows outwards too, mirroring the
+/// inner glow from the band's outer edge. A ring at the edge has no outside.
+pub fn outer_fade(dist_to_inner: f64, thickness: f64, glow: f64, falloff: GlowFalloff, inset: f64) -> f64 {
+    if inset <= 0.0 {
+        return 1.0;
+    }
+    ring_alpha(2.0 * glow + thickness - dist_to_inner, glow, falloff, 1.0)
+}
+
+/// A partially lit perimeter, for progress bars and the focus timer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Lit fraction of the perimeter, 0.0 - 1.0
+    pub fraction: f64,
+    /// Where the lit part starts, going clockwise
+    pub origin: ProgressOrigin,
+    /// Brightness of the unlit part
+    pub track: f64,
+}
+
+/// Comets chasing clockwise around the perimeter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comet {
+    /// Perimeter position of the first head, 0.0 - 1.0 from the top-left corner
+    pub head: f64,
+    /// Head plus tail, as a fraction of the perimeter
+    pub length: f64,
+    /// Comets evenly spaced around the ring
+    pub count: u32,
+}
+
+impl Comet {
+    /// Brightness at perimeter `position`: full at a head, fading along its tail
+    pub fn brightness(&self, position: f64) -> f64 {
+        let spacing = 1.0 / self.count.max(1) as f64;
+        let length = self.length.min(spacing);
+        if length <= 0.0 {
+            return 0.0;
+        }
+        let behind = (self.head - position).rem_euclid(spacing);
+        let t = (1.0 - behind / length).max(0.0);
+        t * t
+    }
+}
+
+/// A stretch of the perimeter in a color of its own
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    /// Where it starts and ends, 0.0 - 1.0 clockwise from the top-left
+    /// corner; ending before it starts, it wraps around the corner
+    pub start: f64,
+    pub end: f64,
+    pub color: (u8, u8, u8),
+}
+
+impl Segment {
+    pub fn contains(&self, position: f64) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&position)
+        } else {
+            position >= self.start || position < self.end
+        }
+    }
+}
+
+/// Shortest dash or gap, in pixels, however thin the ring
+const MIN_DASH: f64 = 4.0;
+
+/// Brightness of a ring pixel `along` pixels clockwise from the top-left
+/// corner, on a `perimeter` pixels long ring drawn with `pattern`.
+///
+/// Dashes are sized from the band `thickness` (3:1.5 for dashed, 1:1 for
+/// dotted) and stretched a little so a whole number of them fits the
+/// perimeter, leaving no seam where it wraps around. Their ends are smoothed
+/// over a pixel.
+pub fn pattern_mask(pattern: Pattern, along: f64, perimeter: f64, thickness: f64) -> f64 {
+    let unit = thickness.max(MIN_DASH);
+    let (on, off) = match pattern {
+        Pattern::Solid => return 1.0,
+        Pattern::Dashed => (3.0 * unit, 1.5 * unit),
+        Pattern::Dotted => (unit, unit),
+    };
+    let count = (perimeter / (on + off)).round().max(1.0);
+    let scale = perimeter / (count * (on + off));
+    let (on, period) = (on * scale, (on + off) * scale);
+    let t = along.rem_euclid(period);
+    // Distance to the nearest dash end, negative in the gap
+    let inside = if t < on { t.min(on - t) } else { -(t - on).min(period - t) };
+    (inside + 0.5).clamp(0.0, 1.0)
+}
+
+/// The ring dimming with the distance from a webcam
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Catchlight {
+    /// Corner or edge center the webcam sits at
+    pub origin: ProgressOrigin,
+    /// Brightness of the point farthest from it
+    pub floor: f64,
+}
+
+impl Catchlight {
+    /// Brightness at `x`, `y`: full at the webcam, `floor` at the farthest corner
+    pub fn brightness(&self, x: f64, y: f64, w: f64, h: f64) -> f64 {
+        let (cx, cy) = match self.origin {
+            ProgressOrigin::TopLeft => (0.0, 0.0),
+            ProgressOrigin::Top => (w / 2.0, 0.0),
+            ProgressOrigin::TopRight => (w, 0.0),
+            ProgressOrigin::Right => (w, h / 2.0),
+            ProgressOrigin::BottomRight => (w, h),
+            ProgressOrigin::Bottom => (w / 2.0, h),
+            ProgressOrigin::BottomLeft => (0.0, h),
+            ProgressOrigin::Left => (0.0, h / 2.0),
+        };
+        let farthest = cx.max(w - cx).hypot(cy.max(h - cy));
+        let near = 1.0 - ((x - cx).hypot(y - cy) / farthest).min(1.0);
+        self.floor + (1.0 - self.floor) * near
+    }
+}
+
+/// An audio spectrum around the perimeter, one segment per band
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spectrum<'a> {
+    /// Level of each band, 0.0 - 1.0, lowest frequency first
+    pub levels: &'a [f64],
+    /// Where the first band starts, going clockwise
+    pub origin: ProgressOrigin,
+    /// Brightness of a silent band
+    pub floor: f64,
+}
+
+impl Spectrum<'_> {
+    /// Brightness at perimeter `position` (measured from `origin`)
+    pub fn brightness(&self, position: f64) -> f64 {
+        let Some(last) = self.levels.len().checked_sub(1) else { return 1.0 };
+        let level = self.levels[((position * self.levels.len() as f64) as usize).min(last)];
+        self.floor + (1.0 - self.floor) * level.clamp(0.0, 1.0)
+    }
+}
+
+/// Decoded RGBA image filling the ring band
+#[derive(Debug, Clone, PartialEq)]
+pub struct Texture {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major, straight (not premultiplied) alpha
+    pub pixels: Vec<[u8; 4]>,
+    pub mode: TextureMode,
+}
+
+impl Texture {
+    /// Nearest texel for screen pixel `x`, `y` of a `w` x `h` screen
+    pub fn sample(&self, x: usize, y: usize, w: u32, h: u32) -> [u8; 4] {
+        if self.pixels.is_empty() {
+            return [0; 4];
+        }
+        let (tw, th) = (self.width as usize, self.height as usize);
+        let (tx, ty) = match self.mode {
+            TextureMode::Tile => (x % tw, y % th),
+            TextureMode::Stretch => ((x * tw / w as usize).min(tw - 1), (y * th / h as usize).min(th - 1)),
+        };
+        self.pixels[ty * tw + tx]
+    }
+}
+
+/// One frame of the ring
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ring<'a> {
+    /// Gap between the screen edge and the band, in pixels
+    pub inset: f64,
+    /// Solid band width in pixels
+    pub thickness: f64,
+    /// Glow width in pixels, fading towards the center of the screen
+    pub glow: f64,
+    pub falloff: GlowFalloff,
+    /// How far the glow fades into the solid band, feathering its inside edge
+    pub inner_glow: f64,
+    /// Fade from the edge over this many pixels instead of drawing a band
+    pub vignette: Option<f64>,
+    /// Color and opacity washing over the whole surface, under the ring
+    pub fill: Option<((u8, u8, u8), f64)>,
+    pub catchlight: Option<Catchlight>,
+    pub shape: Shape,
+    pub color: (u8, u8, u8),
+    /// Fills the band instead of `color`; its alpha cuts holes into the ring
+    pub texture: Option<&'a Texture>,
+    /// Overall opacity, 0.0 - 1.0
+    pub opacity: f64,
+    pub progress: Option<Progress>,
+    pub comet: Option<Comet>,
+    pub spectrum: Option<Spectrum<'a>>,
+    /// Stretches colored apart from `color`; the last one listed wins where they overlap
+    pub segments: &'a [Segment],
+    pub pattern: Pattern,
+    /// Draw over an opaque black band instead of transparency, for
+    /// screenshots and buffers without alpha
+    pub backdrop: bool,
+    /// Ordered dithering, hiding 8-bit banding in large soft glows
+    pub dither: bool,
+    /// Fade in linear light instead of sRGB, so mid-alphas don't look muddy
+    pub gamma_correct: bool,
+}
+
+/// Steps of the linear-to-sRGB lookup table
+const SRGB_STEPS: usize = 4096;
+
+/// Encode linear light as sRGB, from a table: `powf` per pixel is too slow
+fn encode_srgb(v: f64) -> f64 {
+    static TABLE: OnceLock<Vec<f32>> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        (0..SRGB_STEPS).map(|i| linear_to_srgb(i as f64 / (SRGB_STEPS - 1) as f64) as f32).collect()
+    });
+    table[(v.clamp(0.0, 1.0) * (SRGB_STEPS - 1) as f64).round() as usize] as f64
+}
+
+/// Decode an 8-bit sRGB channel to linear light, from a table
+fn decode_srgb(v: u8) -> f64 {
+    static TABLE: OnceLock<[f64; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| std::array::from_fn(|i| srgb_to_linear(i as f64 / 255.0)))[v as usize]
+}
+
+/// 4x4 Bayer matrix for ordered dithering
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Rounding threshold (0.0 - 1.0) for the pixel at `x`, `y`
+fn dither_threshold(x: usize, y: usize) -> f64 {
+    (BAYER[y % 4][x % 4] as f64 + 0.5) / 16.0
+}
+
+/// Draw `ring` into `canvas`, `width` x `height` premultiplied ARGB8888
+/// pixels in native byte order. Pixels away from the ring are cleared, or
+/// take the fill; with a backdrop the whole band is opaque.
+pub fn render(canvas: &mut [u8], width: u32, height: u32, ring: &Ring) {
+    let w = width as f64;
+    let h = height as f64;
+    // A vignette is all glow: a band of no thickness, fading over its distance
+    let (thickness, glow, inner_glow) = match ring.vignette {
+        Some(distance) => (0.0, distance, 0.0),
+        None => (ring.thickness, ring.glow, ring.inner_glow),
+    };
+    let total_ring = ring.inset + thickness + glow;
+    let glow_span = glow + inner_glow.min(thickness);
+
+    canvas.chunks_exact_mut(4).enumerate().for_each(|(index, chunk)| {
+        let (px, py) = (index % width as usize, index / width as usize);
+        let (x, y) = (px as f64, py as f64);
+
+        let dist_to_inner = distance_to_inner_rounded_border(x, y, w, h, total_ring, &ring.shape);
+        let mut alpha = ring_alpha(dist_to_inner, glow_span, ring.falloff, ring.opacity)
+            * outer_fade(dist_to_inner, thickness, glow, ring.falloff, ring.inset);
+        let (r, g, b) = match ring.texture {
+            Some(texture) if alpha > 0.001 => {
+                let [r, g, b, a] = texture.sample(px, py, width, height);
+                alpha *= a as f64 / 255.0;
+                (r, g, b)
+            }
+            _ if !ring.segments.is_empty() && alpha > 0.001 => {
+                let position = perimeter_position_from(x, y, w, h, ProgressOrigin::TopLeft);
+                ring.segments.iter().rev().find(|s| s.contains(position)).map_or(ring.color, |s| s.color)
+            }
+            _ => ring.color,
+        };
+        if let Some(p) = ring.progress {
+            alpha *= perimeter_fill(perimeter_position_from(x, y, w, h, p.origin), p.fraction, 2.0 * (w + h), p.track);
+        }
+        if let Some(comet) = ring.comet.filter(|_| alpha > 0.001) {
+            alpha *= comet.brightness(perimeter_position_from(x, y, w, h, ProgressOrigin::TopLeft));
+        }
+        if let Some(spectrum) = ring.spectrum.filter(|_| alpha > 0.001) {
+            alpha *= spectrum.brightness(perimeter_position_from(x, y, w, h, spectrum.origin));
+        }
+        if let Some(catchlight) = ring.catchlight.filter(|_| alpha > 0.001) {
+            alpha *= catchlight.brightness(x, y, w, h);
+        }
+        if ring.pattern != Pattern::Solid && alpha > 0.001 {
+            let perimeter = 2.0 * (w + h);
+            let along = perimeter_position_from(x, y, w, h, ProgressOrigin::TopLeft) * perimeter;
+            alpha *= pattern_mask(ring.pattern, along, perimeter, ring.thickness);
+        }
+
+        // The ring over the fill, as one straight color; without a fill
+        // this is the ring's own
+        let (r, g, b, alpha) = match ring.fill {
+            Some(((fr, fg, fb), fill)) if fill > 0.0 => {
+                let under = fill * (1.0 - alpha);
+                let total = alpha + under;
+                let mix = |c: u8, f: u8| ((c as f64 * alpha + f as f64 * under) / total).round() as u8;
+                (mix(r, fr), mix(g, fg), mix(b, fb), total)
+            }
+            _ => (r, g, b, alpha),
+        };
+
+        if alpha > 0.001 || (ring.backdrop && dist_to_inner > 0.0) {
+            // Whole values stay put, so only the gradients get dithered
+            let threshold = if ring.dither { dither_threshold(px, py) } else { 0.0 };
+            let quantize = |v: f64| ((v * 255.0 + threshold) as u32).min(255);
+            let (a, pr, pg, pb) = if ring.gamma_correct {
+                // Scale the color in linear light; encoding the alpha the same
+                // way keeps the premultiplied color within it
+                let [lr, lg, lb] = [r, g, b].map(|c| quantize(encode_srgb(decode_srgb(c) * alpha)));
+                (quantize(encode_srgb(alpha)), lr, lg, lb)
+            } else {
+                let a = quantize(alpha);
+                (a, r as u32 * a / 255, g as u32 * a / 255, b as u32 * a / 255)
+            };
+            // Over black, the premultiplied color is already the blended one
+            let a = if ring.backdrop { 255 } else { a };
+            let pixel = (a << 24) | (pr << 16) | (pg << 8) | pb;
+            chunk.copy_from_slice(&pixel.to_ne_bytes());
+        } else {
+            chunk.copy_from_slice(&[0, 0, 0, 0]);
+        }
+    });
+}
+
+/// Clear the `(x, y, width, height)` rectangle, clipped to the canvas
+pub fn clear_rect(canvas: &mut [u8], width: u32, height: u32, rect: (i32, i32, u32, u32)) {
+    let (x, y, w, h) = rect;
+    let clip = |start: i32, len: u32, max: u32| {
+        let end = (start as i64 + len as i64).clamp(0, max as i64) as usize;
+        (start.max(0) as usize).min(end)..end
+    };
+    let (xs, ys) = (clip(x, w, width), clip(y, h, height));
+    for row in ys {
+        let offset = row * width as usize * 4;
+        canvas[offset + xs.start * 4..offset + xs.end * 4].fill(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CornerStyle;
+
+    #[test]
+    fn test_dither_thresholds() {
+        // One threshold per 1/16 step, so a 4x4 tile averages out to the true value
+        let mut thresholds: Vec<f64> = (0..16).map(|i| dither_threshold(i % 4, i / 4)).collect();
+        thresholds.sort_by(f64::total_cmp);
+        for (i, t) in thresholds.iter().enumerate() {
+            assert_eq!(*t, (i as f64 + 0.5) / 16.0);
+        }
+        let level = 100.25;
+        let total: u32 = thresholds.iter().map(|t| (level + t) as u32).sum();
+        assert_eq!(total as f64 / 16.0, level);
+    }
+
+    #[test]
+    fn test_heartbeat() {
+        // Peaks at both thumps, dips between them, nearly dark before the next beat
+        let first = heartbeat(THUMP_RISE);
+        let between = heartbeat(0.2);
+        let second = heartbeat(0.22 + THUMP_RISE);
+        assert_eq!(first, 1.0);
+        assert!(between < 0.5 && second > 0.7 && second < first);
+        assert!(heartbeat(0.99) < 0.01);
+    }
+
+    #[test]
+    fn test_outer_fade() {
+        let fade = |dist: f64, inset: f64| outer_fade(dist, 20.0, 10.0, GlowFalloff::Linear, inset);
+        // The glow ends 10px in, the band runs to 30px, the outer glow to 40px
+        assert_eq!(fade(25.0, 50.0), 1.0);
+        assert_eq!(fade(30.0, 50.0), 1.0);
+        assert_eq!(fade(35.0, 50.0), 0.5);
+        assert_eq!(fade(40.0, 50.0), 0.0);
+        // At the edge, the screen corners stay lit
+        assert_eq!(fade(45.0, 0.0), 1.0);
+    }
+
+    /// A plain white band, square-cornered, with nothing else on
+    fn ring() -> Ring<'static> {
+        Ring {
+            inset: 0.0,
+            thickness: 20.0,
+            glow: 0.0,
+            falloff: GlowFalloff::Linear,
+            inner_glow: 0.0,
+            vignette: None,
+            fill: None,
+            catchlight: None,
+            shape: Shape { radii: [0.0; 4], style: CornerStyle::Round, notch: None, taper: Default::default() },
+            color: (255, 255, 255),
+            texture: None,
+            opacity: 1.0,
+            progress: None,
+            comet: None,
+            spectrum: None,
+            segments: &[],
+            pattern: Pattern::Solid,
+            backdrop: false,
+            dither: false,
+            gamma_correct: false,
+        }
+    }
+
+    #[test]
+    fn test_vignette() {
+        let (width, height) = (200, 100);
+        let ring = Ring { thickness: 30.0, vignette: Some(40.0), ..ring() };
+        let mut canvas = vec![0u8; width * height * 4];
+        render(&mut canvas, width as u32, height as u32, &ring);
+        // No band: the light fades from the left edge and is gone 40px in
+        let alpha = |x: usize| canvas[(height / 2 * width + x) * 4 + 3];
+        assert!(alpha(0) > 250);
+        assert!(alpha(10) > alpha(20) && alpha(20) > alpha(30) && alpha(30) > 0);
+        assert_eq!(alpha(40), 0);
+    }
+
+    #[test]
+    fn test_fill() {
+        let (width, height) = (200, 100);
+        let ring = Ring { fill: Some(((255, 255, 255), 0.2)), color: (255, 0, 0), ..ring() };
+        let mut canvas = vec![0u8; width * height * 4];
+        render(&mut canvas, width as u32, height as u32, &ring);
+        let pixel = |x: usize, y: usize| u32::from_ne_bytes(canvas[(y * width + x) * 4..][..4].try_into().unwrap());
+        // The band covers the fill, and the fill covers the middle
+        assert_eq!(pixel(5, 50), 0xffff0000);
+        assert_eq!(pixel(100, 50), 0x33333333);
+    }
+
+    #[test]
+    fn test_comet_brightness() {
+        let comet = Comet { head: 0.25, length: 0.2, count: 2 };
+        let at = |position: f64| (comet.brightness(position) * 1000.0).round() / 1000.0;
+        assert_eq!(at(0.25), 1.0);
+        assert_eq!(at(0.15), 0.25);
+        // Just ahead of the head is dark, and so is the end of the tail
+        assert_eq!(at(0.3), 0.0);
+        assert_eq!(at(0.0), 0.0);
+        // The second comet trails half a lap behind
+        assert_eq!(at(0.75), 1.0);
+        assert_eq!(at(0.65), 0.25);
+    }
+
+    #[test]
+    fn test_gauge() {
+        assert_eq!(gauge(GaugeMap::Hue, 0.0, (0, 0, 255), 0.8, 0.2), ((0, 255, 0), 0.8));
+        assert_eq!(gauge(GaugeMap::Hue, 1.5, (0, 0, 255), 0.8, 0.2).0, (255, 0, 0));
+        assert_eq!(gauge(GaugeMap::Brightness, 0.0, (0, 0, 255), 0.8, 0.25), ((0, 0, 255), 0.2));
+        assert_eq!(gauge(GaugeMap::Brightness, 1.0, (0, 0, 255), 0.8, 0.25).1, 0.8);
+    }
+
+    #[test]
+    fn test_spectrum_brightness() {
+        let levels = [0.0, 1.0, 0.5, 2.0];
+        let spectrum = Spectrum { levels: &levels, origin: ProgressOrigin::Bottom, floor: 0.5 };
+        assert_eq!(spectrum.brightness(0.1), 0.5);
+        assert_eq!(spectrum.brightness(0.3), 1.0);
+        assert_eq!(spectrum.brightness(0.6), 0.75);
+        // Out of range levels and positions are clamped
+        assert_eq!(spectrum.brightness(1.0), 1.0);
+        assert_eq!(Spectrum { levels: &[], ..spectrum }.brightness(0.5), 1.0);
+    }
+
+    #[test]
+    fn test_catchlight_brightness() {
+        let catchlight = Catchlight { origin: ProgressOrigin::Top, floor: 0.4 };
+        let at = |x: f64, y: f64| (catchlight.brightness(x, y, 200.0, 100.0) * 1000.0).round() / 1000.0;
+        assert_eq!(at(100.0, 0.0), 1.0);
+        assert_eq!(at(0.0, 100.0), 0.4);
+        assert_eq!(at(200.0, 100.0), 0.4);
+        // Brighter along the top edge than down the sides
+        assert!(at(0.0, 0.0) > at(0.0, 50.0));
+    }
+
+    #[test]
+    fn test_pattern_mask() {
+        let at = |pattern, along| (pattern_mask(pattern, along, 1000.0, 20.0) * 1000.0).round() / 1000.0;
+        // 1000px at 20px thick: 60/30 dashes fit 11.1 times, stretched to 11
+        let period = 1000.0 / 11.0;
+        let on = period * 2.0 / 3.0;
+        assert_eq!(at(Pattern::Dashed, on / 2.0), 1.0);
+        assert_eq!(at(Pattern::Dashed, on + 10.0), 0.0);
+        assert_eq!(at(Pattern::Dashed, on), 0.5);
+        // No seam: the last gap ends exactly where the first dash starts
+        assert_eq!(at(Pattern::Dashed, 1000.0 - 0.25), 0.25);
+        // Dots are as long as the band is thick, 25 of them with as many gaps
+        assert_eq!(at(Pattern::Dotted, 1010.0), 1.0);
+        assert_eq!(at(Pattern::Dotted, 30.0), 0.0);
+        assert_eq!(at(Pattern::Solid, 123.0), 1.0);
+    }
+
+    #[test]
+    fn test_texture_sample() {
+        let pixels = (0..4).map(|i| [i, 0, 0, 255]).collect();
+        let mut texture = Texture { width: 2, height: 2, pixels, mode: TextureMode::Tile };
+        assert_eq!(texture.sample(3, 2, 100, 100)[0], 1);
+        // Stretched over 100x100, the bottom-right texel covers the bottom-right quarter
+        texture.mode = TextureMode::Stretch;
+        assert_eq!(texture.sample(99, 99, 100, 100)[0], 3);
+        assert_eq!(texture.sample(49, 50, 100, 100)[0], 2);
+    }
+
+    #[test]
+    fn test_clear_rect() {
+        let mut canvas = vec![255u8; 4 * 4 * 4];
+        // Hangs off the top-left corner: only the 2x1 part on screen is cleared
+        clear_rect(&mut canvas, 4, 4, (-1, -2, 3, 3));
+        let cleared: Vec<bool> = canvas.chunks(4).map(|p| p == [0, 0, 0, 0]).collect();
+        assert_eq!(cleared.iter().filter(|c| **c).count(), 2);
+        assert!(cleared[0] && cleared[1] && !cleared[2] && !cleared[4]);
+    }
+}
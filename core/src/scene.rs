@@ -0,0 +1,100 @@
+//! Scene files: the whole look of the ring in one file
+//!
+//! A scene describes the state automation wants, not the steps to get there.
+//! The daemon applies it in one go and only changes what differs, so applying
+//! the same scene twice is a no-op.
+//!
+//! ```toml
+//! progress = 0.5
+//! text = "ON AIR"
+//!
+//! [ring]
+//! color = "ff8800"
+//! animation = "breathe"
+//!
+//! [monitors]
+//! "HDMI-A-1" = false
+//!
+//! [colors]
+//! "DP-2" = "00ff88"
+//!
+//! [[segments]]
+//! start = 0.0
+//! end = 0.25
+//! color = "ff0000"
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Profile;
+use crate::error::Error;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scene {
+    /// Appearance; unset fields keep their current value
+    #[serde(default)]
+    pub ring: Profile,
+    /// Lit fraction of the perimeter; 1.0 is a full ring (progress mode off)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f64>,
+    /// Whether the ring shows on each monitor, by connector name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub monitors: BTreeMap<String, bool>,
+    /// Label in the top band; empty removes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Ring color of single monitors, by connector name, in place of
+    /// `ring.color`. Replaces all earlier ones; empty clears them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colors: Option<BTreeMap<String, String>>,
+    /// Stretches of the ring in colors of their own. Replaces all earlier
+    /// ones; empty clears them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<Segment>>,
+}
+
+/// A stretch of the perimeter, as fractions clockwise from the top-left corner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    /// Hex, CSS or palette name
+    pub color: String,
+}
+
+impl Scene {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = fs::read_to_string(path).map_err(|e| Error::Usage(format!("{}: {}", path.display(), e)))?;
+        toml::from_str(&text).map_err(|e| Error::Usage(format!("Invalid scene {}: {}", path.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scene() {
+        let scene: Scene = toml::from_str(
+            "progress = 0.5\ntext = \"On air\"\n[ring]\ncolor = \"ff8800\"\n[monitors]\n\"HDMI-A-1\" = false\n\
+             [colors]\n\"DP-2\" = \"teal\"\n[[segments]]\nstart = 0.75\nend = 0.25\ncolor = \"ff0000\"\n",
+        )
+        .unwrap();
+        assert_eq!(scene.ring.color.as_deref(), Some("ff8800"));
+        assert_eq!(scene.ring.thickness, None);
+        assert_eq!(scene.progress, Some(0.5));
+        assert_eq!(scene.monitors.get("HDMI-A-1"), Some(&false));
+        assert_eq!(scene.text.as_deref(), Some("On air"));
+        assert_eq!(scene.colors.unwrap().get("DP-2").map(String::as_str), Some("teal"));
+        assert_eq!(scene.segments.unwrap()[0], Segment { start: 0.75, end: 0.25, color: "ff0000".into() });
+
+        // Things the ring can't show are errors, not silently dropped
+        assert!(toml::from_str::<Scene>("sound = \"chime\"\n").is_err());
+    }
+}
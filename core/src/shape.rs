@@ -0,0 +1,209 @@
+//! Geometry of the ring's inner edge
+//!
+//! The inner edge is a rectangle inset from the screen edges whose corners
+//! are rounded, squircled or chamfered, each with its own radius. On
+//! laptops with a camera notch it dips around the notch at the top center,
+//! so the band keeps its thickness instead of disappearing under it.
+//!
+//! A taper scales the inset with the position along each edge, so the ring
+//! is thickest at the corners (or edge centers) and thins in between.
+
+use crate::config::{CornerStyle, ProgressOrigin, Taper, TaperAnchor};
+
+/// Inner edge geometry, in pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shape {
+    /// Corner radii: top-left, top-right, bottom-right, bottom-left
+    pub radii: [f64; 4],
+    pub style: CornerStyle,
+    /// Notch (width, height) at the top center of the screen
+    pub notch: Option<(f64, f64)>,
+    pub taper: Taper,
+}
+
+/// Signed distance from a point to the inner border; positive inside the ring band.
+pub fn distance_to_inner_rounded_border(x: f64, y: f64, w: f64, h: f64, inset: f64, shape: &Shape) -> f64 {
+    let inset = inset * taper_scale(x, y, w, h, shape.taper);
+    let left = inset;
+    let right = w - inset;
+    let top = inset;
+    let bottom = h - inset;
+
+    if right <= left || bottom <= top {
+        return 100.0;
+    }
+
+    let half_width = (right - left) / 2.0;
+    let half_height = (bottom - top) / 2.0;
+    let dx = x - (left + right) / 2.0;
+    let dy = y - (top + bottom) / 2.0;
+
+    let corner = match (dx < 0.0, dy < 0.0) {
+        (true, true) => 0,
+        (false, true) => 1,
+        (false, false) => 2,
+        (true, false) => 3,
+    };
+    let r = shape.radii[corner].min(half_width).min(half_height).max(0.0);
+
+    let qx = dx.abs() - (half_width - r);
+    let qy = dy.abs() - (half_height - r);
+    let dist = corner_distance(qx, qy, r, shape.style);
+
+    match shape.notch {
+        // Carve the notch, grown by the band width, out of the inner area
+        Some((notch_w, notch_h)) => dist.max(inset - box_distance((x - w / 2.0).abs() - notch_w / 2.0, y.abs() - notch_h)),
+        None => dist,
+    }
+}
+
+/// Position of (x, y) along the screen's perimeter, as a fraction going
+/// clockwise from the top center (0.0) all the way around (1.0)
+pub fn perimeter_position(x: f64, y: f64, w: f64, h: f64) -> f64 {
+    let nearest = x.min(w - x).min(y).min(h - y);
+    let along = if nearest == y {
+        // Top edge; its left half comes last
+        if x >= w / 2.0 { x - w / 2.0 } else { 1.5 * w + 2.0 * h + x }
+    } else if nearest == w - x {
+        w / 2.0 + y
+    } else if nearest == h - y {
+        1.5 * w + h - x
+    } else {
+        1.5 * w + 2.0 * h - y
+    };
+    along / (2.0 * (w + h))
+}
+
+/// Like `perimeter_position`, but starting from `origin`
+pub fn perimeter_position_from(x: f64, y: f64, w: f64, h: f64, origin: ProgressOrigin) -> f64 {
+    let (ox, oy) = match origin {
+        ProgressOrigin::TopLeft => (0.0, 0.0),
+        ProgressOrigin::Top => (w / 2.0, 0.0),
+        ProgressOrigin::TopRight => (w, 0.0),
+        ProgressOrigin::Right => (w, h / 2.0),
+        ProgressOrigin::BottomRight => (w, h),
+        ProgressOrigin::Bottom => (w / 2.0, h),
+        ProgressOrigin::BottomLeft => (0.0, h),
+        ProgressOrigin::Left => (0.0, h / 2.0),
+    };
+    (perimeter_position(x, y, w, h) - perimeter_position(ox, oy, w, h)).rem_euclid(1.0)
+}
+
+/// Brightness of a ring pixel at perimeter `position` when the first
+/// `progress` of the perimeter is filled; the rest is dimmed to `track`.
+/// The boundary is smoothed over a pixel or so of the `perimeter` length.
+pub fn perimeter_fill(position: f64, progress: f64, perimeter: f64, track: f64) -> f64 {
+    let t = ((progress - position) * perimeter + 0.5).clamp(0.0, 1.0);
+    track + (1.0 - track) * t
+}
+
+/// Inset multiplier at (x, y) for a tapered ring
+fn taper_scale(x: f64, y: f64, w: f64, h: f64, taper: Taper) -> f64 {
+    if taper.amount <= 0.0 {
+        return 1.0;
+    }
+    // Position along the nearest edge: 0 at its center, 1 at the corners
+    let along = if y.min(h - y) < x.min(w - x) {
+        (x - w / 2.0).abs() / (w / 2.0)
+    } else {
+        (y - h / 2.0).abs() / (h / 2.0)
+    };
+    let t = match taper.anchor {
+        TaperAnchor::Corners => along,
+        TaperAnchor::Centers => 1.0 - along,
+    };
+    1.0 - taper.amount * (1.0 - t.clamp(0.0, 1.0))
+}
+
+/// Distance to a corner of radius `r`, with (qx, qy) measured from the corner's center
+fn corner_distance(qx: f64, qy: f64, r: f64, style: CornerStyle) -> f64 {
+    let inside_dist = qx.max(qy).min(0.0);
+    match style {
+        CornerStyle::Round => {
+            let outside_dist = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+            outside_dist + inside_dist - r
+        }
+        CornerStyle::Squircle => {
+            let outside_dist = (qx.max(0.0).powi(4) + qy.max(0.0).powi(4)).powf(0.25);
+            outside_dist + inside_dist - r
+        }
+        CornerStyle::Chamfer => {
+            // Square box, cut by a 45° plane `r` from the corner along each edge
+            let (ex, ey) = (qx - r, qy - r);
+            box_distance(ex, ey).max((ex + ey + r) / std::f64::consts::SQRT_2)
+        }
+    }
+}
+
+/// Distance to a box, with (ex, ey) measured from its edges (negative inside)
+fn box_distance(ex: f64, ey: f64) -> f64 {
+    (ex.max(0.0).powi(2) + ey.max(0.0).powi(2)).sqrt() + ex.max(ey).min(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(style: CornerStyle, notch: Option<(f64, f64)>) -> Shape {
+        Shape { radii: [20.0, 20.0, 0.0, 20.0], style, notch, taper: Taper::default() }
+    }
+
+    #[test]
+    fn test_corner_styles() {
+        let (w, h, inset) = (400.0, 300.0, 50.0);
+        for style in [CornerStyle::Round, CornerStyle::Squircle, CornerStyle::Chamfer] {
+            let s = shape(style, None);
+            // Middle of the screen is inside, the screen edge is in the band
+            assert!(distance_to_inner_rounded_border(200.0, 150.0, w, h, inset, &s) < 0.0);
+            assert!((distance_to_inner_rounded_border(200.0, 10.0, w, h, inset, &s) - 40.0).abs() < 1e-9);
+            // Just inside the top-left corner of the inset rect is cut away by the radius
+            assert!(distance_to_inner_rounded_border(52.0, 52.0, w, h, inset, &s) > 0.0);
+            // The square bottom-right corner is not
+            assert!(distance_to_inner_rounded_border(348.0, 248.0, w, h, inset, &s) < 0.0);
+        }
+        // A squircle hugs the corner more tightly than a circle, a chamfer less
+        let at = |style| distance_to_inner_rounded_border(56.0, 56.0, w, h, inset, &shape(style, None));
+        assert!(at(CornerStyle::Squircle) < at(CornerStyle::Round));
+        assert!(at(CornerStyle::Chamfer) > at(CornerStyle::Round));
+    }
+
+    #[test]
+    fn test_notch() {
+        let s = shape(CornerStyle::Round, Some((100.0, 30.0)));
+        // Below the notch the band is as thick as the inset
+        assert!(distance_to_inner_rounded_border(200.0, 75.0, 400.0, 300.0, 50.0, &s) > 0.0);
+        assert!(distance_to_inner_rounded_border(200.0, 85.0, 400.0, 300.0, 50.0, &s) < 0.0);
+        // Away from the notch nothing changes
+        assert!(distance_to_inner_rounded_border(100.0, 75.0, 400.0, 300.0, 50.0, &s) < 0.0);
+    }
+
+    #[test]
+    fn test_taper() {
+        let mut s = shape(CornerStyle::Round, None);
+        s.taper = Taper { amount: 0.5, anchor: TaperAnchor::Corners };
+        // Half as thick in the middle of the top edge
+        assert!((distance_to_inner_rounded_border(200.0, 10.0, 400.0, 300.0, 50.0, &s) - 15.0).abs() < 1e-9);
+        // Full thickness near the corners
+        assert!(distance_to_inner_rounded_border(390.0, 45.0, 400.0, 300.0, 50.0, &s) > 0.0);
+
+        s.taper.anchor = TaperAnchor::Centers;
+        assert!((distance_to_inner_rounded_border(200.0, 10.0, 400.0, 300.0, 50.0, &s) - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_perimeter_position() {
+        let (w, h) = (400.0, 200.0);
+        assert_eq!(perimeter_position(200.0, 5.0, w, h), 0.0);
+        assert_eq!(perimeter_position(395.0, 100.0, w, h), 0.25);
+        assert_eq!(perimeter_position(200.0, 195.0, w, h), 0.5);
+        assert_eq!(perimeter_position(5.0, 100.0, w, h), 0.75);
+        assert!(perimeter_position(199.0, 5.0, w, h) > 0.99);
+
+        assert_eq!(perimeter_position_from(395.0, 100.0, w, h, ProgressOrigin::Right), 0.0);
+        assert!((perimeter_position_from(200.0, 5.0, w, h, ProgressOrigin::TopLeft) - 200.0 / 1200.0).abs() < 1e-9);
+        assert!((perimeter_position_from(5.0, 150.0, w, h, ProgressOrigin::BottomLeft) - 50.0 / 1200.0).abs() < 1e-9);
+
+        assert_eq!(perimeter_fill(0.25, 0.5, 1200.0, 0.15), 1.0);
+        assert_eq!(perimeter_fill(0.75, 0.5, 1200.0, 0.15), 0.15);
+    }
+}
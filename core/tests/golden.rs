@@ -0,0 +1,66 @@
+//! Golden-image tests: configs drawn offscreen the way `--render-to-file`
+//! does, compared with the PNGs in `tests/golden`
+//!
+//! After a change that is meant to alter the look, check the new frames and
+//! rewrite them with `UPDATE_GOLDEN=1 cargo test -p hypr-ringlight-core golden`.
+
+use std::path::Path;
+
+use hypr_ringlight_core::color::resolve_color;
+use hypr_ringlight_core::config::Config;
+use hypr_ringlight_core::frame::render_frame;
+
+/// Channel difference left to rounding in the float math
+const TOLERANCE: u8 = 1;
+
+const SIZE: (u32, u32) = (640, 400);
+
+/// Render `config` at 640x400 and compare it with `tests/golden/<name>.png`.
+/// With `UPDATE_GOLDEN=1` the golden image is rewritten instead.
+fn assert_golden(name: &str, config: &str) {
+    let cfg: Config = toml::from_str(config).unwrap();
+    let color = resolve_color(&cfg.color, &cfg.palette);
+    let frame = image::RgbaImage::from_raw(SIZE.0, SIZE.1, render_frame(&cfg, color, SIZE, None)).unwrap();
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.png", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        frame.save(&path).unwrap();
+        return;
+    }
+    let golden = image::open(&path)
+        .unwrap_or_else(|e| panic!("{}: {} (run with UPDATE_GOLDEN=1 to create it)", path.display(), e))
+        .into_rgba8();
+    assert_eq!(golden.dimensions(), frame.dimensions(), "{}", name);
+    let differ = golden.pixels().zip(frame.pixels())
+        .filter(|(a, b)| a.0.iter().zip(b.0).any(|(a, b)| a.abs_diff(b) > TOLERANCE))
+        .count();
+    if differ > 0 {
+        let actual = std::env::temp_dir().join(format!("hypr-ringlight-{}.png", name));
+        frame.save(&actual).unwrap();
+        panic!("{}: {} pixels differ from {}, rendered {}", name, differ, path.display(), actual.display());
+    }
+}
+
+#[test]
+fn test_golden_default() {
+    assert_golden("default", "");
+}
+
+#[test]
+fn test_golden_corners() {
+    assert_golden("squircle", "thickness = 40\nglow = 30\ncorner_style = \"squircle\"\ncorner_radius = 2.0");
+    assert_golden("chamfer", "thickness = 24\nglow = 0\ncorner_style = \"chamfer\"\ncorner_radii = [3.0, 0.0, 1.0, 0.5]");
+}
+
+#[test]
+fn test_golden_glow() {
+    assert_golden("linear_glow", "color = \"00aaff\"\nthickness = 30\nglow = 60\nglow_falloff = \"linear\"\ndither = false");
+    assert_golden("gamma_glow", "color = \"ff8800\"\nthickness = 30\nglow = 60\nglow_falloff = \"gaussian\"\ngamma_correct = true\ninner_glow = 10");
+    assert_golden("inset", "thickness = 12\nglow = 20\ninset = 16\nopacity = 0.7");
+}
+
+#[test]
+fn test_golden_effects() {
+    assert_golden("dashed_taper", "thickness = 30\npattern = \"dashed\"\ntaper = 0.6\ntaper_anchor = \"corners\"");
+    assert_golden("vignette", "color = \"ff0044\"\nbar_height = 0\n[vignette]\nenabled = true\ndistance = 50");
+    assert_golden("fill", "thickness = 16\n[fill]\nenabled = true\ncolor = \"ffcc88\"\nopacity = 0.25");
+}
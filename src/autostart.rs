@@ -0,0 +1,308 @@
+//! `hypr-ringlight autostart`: start the ring light with the session
+//!
+//! Either a Hyprland `exec-once` drop-in sourced from hyprland.conf, or a
+//! systemd user unit bound to graphical-session.target. Installing one method
+//! removes the other. Only files we own are written: the `source` line for
+//! the drop-in and `exec-once` lines added by hand are left for the user to
+//! change, and reported so the ring doesn't start twice.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::{Subcommand, ValueEnum};
+
+use crate::compositor::Compositor;
+use crate::error::Error;
+
+const UNIT_NAME: &str = "hypr-ringlight.service";
+
+/// Subcommands, so `exec-once = hypr-ringlight ctl ...` isn't taken for an autostart entry
+const SUBCOMMANDS: &[&str] = &["config", "ctl", "autostart", "watch", "events", "scene"];
+
+#[derive(Subcommand, Debug)]
+pub enum AutostartAction {
+    /// Start hypr-ringlight with the session
+    Install {
+        /// Hyprland exec-once drop-in, or systemd user unit [default: hyprland
+        /// under Hyprland, systemd elsewhere]
+        #[arg(long, value_enum)]
+        method: Option<Method>,
+    },
+    /// Stop starting hypr-ringlight with the session
+    Remove,
+    /// Show how hypr-ringlight is started, and warn about duplicates
+    Status,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    Hyprland,
+    Systemd,
+}
+
+fn config_dir() -> PathBuf {
+    dirs::config_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn hypr_dir() -> PathBuf {
+    config_dir().join("hypr")
+}
+
+fn drop_in_path() -> PathBuf {
+    hypr_dir().join("hypr-ringlight.conf")
+}
+
+fn unit_path() -> PathBuf {
+    config_dir().join("systemd").join("user").join(UNIT_NAME)
+}
+
+fn source_line() -> String {
+    format!("source = {}", drop_in_path().display())
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> Error {
+    Error::Other(format!("{}: {}", path.display(), e))
+}
+
+/// Run an `autostart` subcommand
+pub fn run(action: AutostartAction) -> Result<(), Error> {
+    match action {
+        AutostartAction::Install { method } => install(method.unwrap_or_else(default_method)),
+        AutostartAction::Remove => {
+            remove_hyprland()?;
+            remove_systemd()?;
+            println!("Autostart removed");
+            if is_sourced() {
+                println!("The drop-in is gone, remove this line from {}:", hypr_dir().join("hyprland.conf").display());
+                println!("  {}", source_line());
+            }
+            let manual = manual_entries();
+            if !manual.is_empty() {
+                println!("These entries still start hypr-ringlight:");
+                print_entries(&manual);
+            }
+            Ok(())
+        }
+        AutostartAction::Status => {
+            status();
+            Ok(())
+        }
+    }
+}
+
+/// Other compositors have no drop-in to source, so they get the unit
+fn default_method() -> Method {
+    match Compositor::detect() {
+        Compositor::Hyprland => Method::Hyprland,
+        _ => Method::Systemd,
+    }
+}
+
+fn install(method: Method) -> Result<(), Error> {
+    let exe = std::env::current_exe()
+        .map_err(|e| Error::Other(format!("Failed to locate the hypr-ringlight binary: {}", e)))?;
+
+    match method {
+        Method::Hyprland => {
+            let drop_in = drop_in_path();
+            fs::create_dir_all(hypr_dir()).map_err(|e| io_error(&hypr_dir(), e))?;
+            let contents = format!("# Managed by `hypr-ringlight autostart`\nexec-once = {}\n", exe.display());
+            fs::write(&drop_in, contents).map_err(|e| io_error(&drop_in, e))?;
+            remove_systemd()?;
+            println!("Installed {}", drop_in.display());
+        }
+        Method::Systemd => {
+            let unit = unit_path();
+            if let Some(dir) = unit.parent() {
+                fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+            }
+            fs::write(&unit, unit_file(&exe)).map_err(|e| io_error(&unit, e))?;
+            systemctl(&["daemon-reload"])?;
+            systemctl(&["enable", UNIT_NAME])?;
+            remove_hyprland()?;
+            println!("Installed and enabled {}", unit.display());
+        }
+    }
+
+    let manual = manual_entries();
+    if !manual.is_empty() {
+        println!("Remove or comment out these entries, or the ring starts twice:");
+        print_entries(&manual);
+    }
+    if method == Method::Hyprland && !is_sourced() {
+        println!("Add this line to {} to start it:", hypr_dir().join("hyprland.conf").display());
+        println!("  {}", source_line());
+    } else {
+        println!("hypr-ringlight will start with your next session");
+    }
+    Ok(())
+}
+
+fn unit_file(exe: &Path) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Ring light overlay for Hyprland\n\
+         PartOf=graphical-session.target\n\
+         After=graphical-session.target\n\
+         \n\
+         [Service]\n\
+         ExecStart={}\n\
+         Restart=on-failure\n\
+         # Bad config, no compositor, already running: restarting won't help\n\
+         RestartPreventExitStatus=2 3 4\n\
+         \n\
+         [Install]\n\
+         WantedBy=graphical-session.target\n",
+        exe.display()
+    )
+}
+
+fn systemctl(args: &[&str]) -> Result<(), Error> {
+    let status = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .status()
+        .map_err(|e| Error::Other(format!("Failed to run systemctl: {}", e)))?;
+    if !status.success() {
+        return Err(Error::Other(format!("systemctl --user {} failed", args.join(" "))));
+    }
+    Ok(())
+}
+
+/// Remove the exec-once drop-in
+fn remove_hyprland() -> Result<(), Error> {
+    let drop_in = drop_in_path();
+    if drop_in.exists() {
+        fs::remove_file(&drop_in).map_err(|e| io_error(&drop_in, e))?;
+    }
+    Ok(())
+}
+
+/// Whether hyprland.conf sources the drop-in
+fn is_sourced() -> bool {
+    let source = source_line();
+    fs::read_to_string(hypr_dir().join("hyprland.conf")).is_ok_and(|text| text.lines().any(|l| l.trim() == source))
+}
+
+/// Disable and remove the systemd user unit
+fn remove_systemd() -> Result<(), Error> {
+    let unit = unit_path();
+    if !unit.exists() {
+        return Ok(());
+    }
+    // The unit may already be disabled by hand
+    let _ = systemctl(&["disable", UNIT_NAME]);
+    fs::remove_file(&unit).map_err(|e| io_error(&unit, e))?;
+    let _ = systemctl(&["daemon-reload"]);
+    Ok(())
+}
+
+/// Whether `line` is an `exec-once`/`exec` line starting the ring light
+fn is_autostart_line(line: &str) -> bool {
+    let Some((key, cmd)) = line.trim().split_once('=') else {
+        return false;
+    };
+    if !matches!(key.trim(), "exec-once" | "exec") {
+        return false;
+    }
+    // Also matches wrapped commands like `uwsm app -- hypr-ringlight`
+    let words: Vec<&str> = cmd.split_whitespace().collect();
+    words
+        .iter()
+        .position(|w| Path::new(w).file_name().is_some_and(|n| n == "hypr-ringlight"))
+        .is_some_and(|i| !words.get(i + 1).is_some_and(|w| SUBCOMMANDS.contains(w)))
+}
+
+/// Hyprland config files, except our drop-in
+fn conf_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.is_dir() {
+            conf_files(&path, files);
+        } else if path.extension().is_some_and(|e| e == "conf") && path != drop_in_path() {
+            files.push(path);
+        }
+    }
+}
+
+/// Autostart entries added by hand: (file, line number, line)
+fn manual_entries() -> Vec<(PathBuf, usize, String)> {
+    let mut files = Vec::new();
+    conf_files(&hypr_dir(), &mut files);
+    let mut entries = Vec::new();
+    for file in files {
+        let Ok(text) = fs::read_to_string(&file) else {
+            continue;
+        };
+        for (i, line) in text.lines().enumerate().filter(|(_, l)| is_autostart_line(l)) {
+            entries.push((file.clone(), i + 1, line.trim().to_string()));
+        }
+    }
+    entries
+}
+
+fn print_entries(entries: &[(PathBuf, usize, String)]) {
+    for (file, line, text) in entries {
+        println!("  {}:{}: {}", file.display(), line, text);
+    }
+}
+
+fn status() {
+    let mut count = 0;
+    if drop_in_path().exists() {
+        count += 1;
+        println!("Hyprland: {}", drop_in_path().display());
+        if !is_sourced() {
+            println!("Warning: hyprland.conf doesn't source it; add `{}`", source_line());
+        }
+        let compositor = Compositor::detect();
+        if !matches!(compositor, Compositor::Hyprland | Compositor::Other) {
+            println!(
+                "Warning: running under {}, which doesn't read hyprland.conf; use `--method systemd`",
+                compositor.name()
+            );
+        }
+    }
+    if unit_path().exists() {
+        count += 1;
+        let enabled = Command::new("systemctl")
+            .args(["--user", "is-enabled", "--quiet", UNIT_NAME])
+            .status()
+            .is_ok_and(|s| s.success());
+        println!("systemd: {} ({})", unit_path().display(), if enabled { "enabled" } else { "disabled" });
+    }
+    let manual = manual_entries();
+    if !manual.is_empty() {
+        count += manual.len();
+        println!("Added by hand:");
+        print_entries(&manual);
+    }
+
+    match count {
+        0 => println!("hypr-ringlight is not started automatically"),
+        1 => {}
+        n => println!(
+            "Warning: hypr-ringlight is started {} times; run `hypr-ringlight autostart install` and remove the entries added by hand to keep one",
+            n
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autostart_lines() {
+        assert!(is_autostart_line("exec-once = hypr-ringlight"));
+        assert!(is_autostart_line("  exec-once=/usr/bin/hypr-ringlight --glow 40"));
+        assert!(is_autostart_line("exec-once = uwsm app -- hypr-ringlight"));
+        assert!(!is_autostart_line("# exec-once = hypr-ringlight"));
+        assert!(!is_autostart_line("exec-once = hypr-ringlight ctl progress off"));
+        assert!(!is_autostart_line("bind = SUPER, R, exec, hypr-ringlight"));
+        assert!(!is_autostart_line("exec-once = waybar"));
+    }
+}
@@ -0,0 +1,97 @@
+//! Room for the status bar
+//!
+//! The ring's layer surface is pulled in from the edge the bar sits on, so
+//! the bar isn't covered. With `bar_physical`, the configured position names
+//! an edge of the physical panel and follows the output's rotation. With
+//! `bar_auto`, the space Hyprland reserves for bars and docks is used instead,
+//! which may cover several edges at once.
+
+use hypr_ringlight_core::ipc::BarMargin;
+use wayland_client::protocol::wl_output::Transform;
+
+use crate::config::BarPosition;
+
+/// Room left for bars on one monitor, per logical edge
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Bar {
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub left: i32,
+}
+
+impl Bar {
+    /// A single bar of `height` on the logical edge `position`
+    pub fn new(position: BarPosition, height: i32) -> Self {
+        let mut bar = Self::default();
+        match position {
+            BarPosition::Top => bar.top = height,
+            BarPosition::Right => bar.right = height,
+            BarPosition::Bottom => bar.bottom = height,
+            BarPosition::Left => bar.left = height,
+        }
+        bar
+    }
+
+    /// Layer-shell margins: top, right, bottom, left
+    pub fn margin(&self) -> (i32, i32, i32, i32) {
+        (self.top, self.right, self.bottom, self.left)
+    }
+
+    /// Where the ring surface starts, from the monitor's top-left corner
+    pub fn offset(&self) -> (i32, i32) {
+        (self.left, self.top)
+    }
+}
+
+impl From<Bar> for BarMargin {
+    fn from(bar: Bar) -> Self {
+        Self { top: bar.top, right: bar.right, bottom: bar.bottom, left: bar.left }
+    }
+}
+
+/// The logical edge showing the physical panel edge `edge` on an output with `transform`
+pub fn rotate(edge: BarPosition, transform: Transform) -> BarPosition {
+    use BarPosition::*;
+
+    // wl_output rotates content counter-clockwise, so going clockwise
+    // around the panel walks the logical edges the other way
+    let clockwise = [Top, Right, Bottom, Left];
+    let turns = match transform {
+        Transform::_90 | Transform::Flipped90 => 1,
+        Transform::_180 | Transform::Flipped180 => 2,
+        Transform::_270 | Transform::Flipped270 => 3,
+        _ => 0,
+    };
+    let index = clockwise.iter().position(|&e| e == edge).unwrap_or(0);
+    let rotated = clockwise[(index + turns) % 4];
+    let flipped = matches!(
+        transform,
+        Transform::Flipped | Transform::Flipped90 | Transform::Flipped180 | Transform::Flipped270
+    );
+    match rotated {
+        Left if flipped => Right,
+        Right if flipped => Left,
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate() {
+        assert_eq!(rotate(BarPosition::Top, Transform::Normal), BarPosition::Top);
+        // A panel turned to portrait: its top edge is now the desktop's right
+        assert_eq!(rotate(BarPosition::Top, Transform::_90), BarPosition::Right);
+        assert_eq!(rotate(BarPosition::Left, Transform::_180), BarPosition::Right);
+        assert_eq!(rotate(BarPosition::Top, Transform::_270), BarPosition::Left);
+        assert_eq!(rotate(BarPosition::Left, Transform::Flipped), BarPosition::Right);
+        assert_eq!(rotate(BarPosition::Top, Transform::Flipped90), BarPosition::Left);
+
+        let bar = Bar::new(BarPosition::Left, 35);
+        assert_eq!(bar.margin(), (0, 0, 0, 35));
+        assert_eq!(bar.offset(), (35, 0));
+    }
+}
@@ -0,0 +1,115 @@
+//! Battery-aware power saving
+//!
+//! Reads the power supplies the kernel exposes in /sys/class/power_supply
+//! (the same data upower serves). While discharging at or below the
+//! configured level, the renderer caps its frame rate and the battery
+//! profile override can drop the glow or hide the ring. Plugging in undoes it.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::BatteryConfig;
+use crate::ipc::IpcState;
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// How often to re-read the battery; it changes slowly, but plugging in should feel prompt
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// State of the system batteries
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PowerStatus {
+    discharging: bool,
+    /// Charge of all batteries together, in percent
+    percent: f64,
+}
+
+fn read_attr(dir: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(dir.join(name)).ok().map(|s| s.trim().to_string())
+}
+
+/// Read the system batteries under `root`, or `None` if there are none (desktops)
+fn read_power_status(root: &Path) -> Option<PowerStatus> {
+    let mut discharging = false;
+    let mut capacities = Vec::new();
+    for entry in fs::read_dir(root).ok()?.filter_map(|e| e.ok()) {
+        let dir = entry.path();
+        // Skip peripherals (mice, headsets), which report scope "Device"
+        if read_attr(&dir, "type").as_deref() != Some("Battery")
+            || read_attr(&dir, "scope").as_deref() == Some("Device")
+        {
+            continue;
+        }
+        if let Some(capacity) = read_attr(&dir, "capacity").and_then(|c| c.parse::<f64>().ok()) {
+            capacities.push(capacity);
+        }
+        discharging |= read_attr(&dir, "status").as_deref() == Some("Discharging");
+    }
+    if capacities.is_empty() {
+        return None;
+    }
+    let percent = capacities.iter().sum::<f64>() / capacities.len() as f64;
+    Some(PowerStatus { discharging, percent })
+}
+
+/// Start the battery monitoring thread.
+///
+/// Does nothing without a battery or with `threshold = 0`.
+pub fn start_battery_monitor(state: Arc<IpcState>, cfg: BatteryConfig) {
+    if cfg.threshold == 0 || read_power_status(Path::new(POWER_SUPPLY_DIR)).is_none() {
+        return;
+    }
+    let profile = cfg.profile();
+
+    std::thread::spawn(move || {
+        let mut saving = false;
+        loop {
+            let now_saving = read_power_status(Path::new(POWER_SUPPLY_DIR))
+                .is_some_and(|s| s.discharging && s.percent <= cfg.threshold as f64);
+            if now_saving != saving {
+                saving = now_saving;
+                if saving {
+                    log::info!("Battery at or below {}%, saving power", cfg.threshold);
+                } else {
+                    log::info!("Battery power saving off");
+                }
+                state.set_battery_saving(saving);
+                state.log_event("battery", if saving { "power saving on" } else { "power saving off" });
+                if let Some(profile) = &profile {
+                    state.set_override("battery", saving.then(|| ("battery".to_string(), profile.clone())));
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(root: &Path, name: &str, attrs: &[(&str, &str)]) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        for (attr, value) in attrs {
+            fs::write(dir.join(attr), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_read_power_status() {
+        let root = std::env::temp_dir().join(format!("hypr-ringlight-battery-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        supply(&root, "AC", &[("type", "Mains"), ("online", "0")]);
+        supply(&root, "hidpp_battery_0", &[("type", "Battery"), ("scope", "Device"), ("capacity", "5")]);
+        assert_eq!(read_power_status(&root), None);
+
+        supply(&root, "BAT0", &[("type", "Battery"), ("capacity", "30"), ("status", "Discharging")]);
+        supply(&root, "BAT1", &[("type", "Battery"), ("capacity", "10"), ("status", "Unknown")]);
+        assert_eq!(read_power_status(&root), Some(PowerStatus { discharging: true, percent: 20.0 }));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
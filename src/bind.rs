@@ -0,0 +1,150 @@
+//! External data binding: values from a pipe drive a ring parameter
+//!
+//! `--bind-fifo PATH` reads one value per line from a named pipe (created if
+//! missing), or from stdin with `-`, for scripts that would rather `echo` than
+//! speak the socket protocol. A plain number from 0.0 to 1.0 sets the bound
+//! parameter; a JSON object sets any of them by name:
+//!
+//! ```text
+//! 0.42
+//! {"value": 0.42}
+//! {"opacity": 0.8, "hue": 0.6}
+//! {"progress": null}
+//! ```
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use hypr_ringlight_core::color::hsl_to_rgb;
+use serde_json::Value;
+
+use crate::ipc::IpcState;
+
+/// How long to wait before reopening a pipe that failed to open
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Parameter driven by plain numbers
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Param {
+    Opacity,
+    /// Progress bar fill
+    #[default]
+    Progress,
+    /// Ring color around the color wheel, full saturation
+    Hue,
+}
+
+impl Param {
+    fn from_name(name: &str) -> Option<Self> {
+        Self::from_str(name, true).ok()
+    }
+}
+
+/// One line from the pipe, as parameter updates; `None` clears the progress bar
+fn parse_line(line: &str, bound: Param) -> Result<Vec<(Param, Option<f64>)>, String> {
+    let line = line.trim();
+    if let Ok(value) = line.parse::<f64>() {
+        return Ok(vec![(bound, Some(value))]);
+    }
+    let Value::Object(fields) = serde_json::from_str::<Value>(line).map_err(|e| e.to_string())? else {
+        return Err("expected a number or a JSON object".to_string());
+    };
+    fields
+        .iter()
+        .map(|(key, value)| {
+            let param = if key == "value" { bound } else { Param::from_name(key).ok_or(format!("unknown parameter {}", key))? };
+            match value {
+                Value::Null => Ok((param, None)),
+                value => Ok((param, Some(value.as_f64().ok_or(format!("{} is not a number", key))?))),
+            }
+        })
+        .collect()
+}
+
+fn apply(state: &IpcState, param: Param, value: Option<f64>) {
+    let value = value.map(|v| v.clamp(0.0, 1.0));
+    match (param, value) {
+        (Param::Opacity, Some(v)) => state.change_appearance(|a| a.opacity = v),
+        (Param::Hue, Some(v)) => state.change_appearance(|a| {
+            a.color = hsl_to_rgb(v, 1.0, 0.5);
+            a.color_name = None;
+        }),
+        (Param::Progress, progress) => state.set_progress(progress),
+        (param, None) => log::warn!("bind: {:?} can't be cleared", param),
+    }
+}
+
+fn read_lines(reader: impl BufRead, state: &IpcState, bound: Param) {
+    for line in reader.lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_line(&line, bound) {
+            Ok(updates) => updates.into_iter().for_each(|(param, value)| apply(state, param, value)),
+            Err(e) => log::warn!("bind: ignoring '{}': {}", line.trim(), e),
+        }
+    }
+}
+
+/// Make `path` a named pipe unless something is already there
+fn ensure_fifo(path: &Path) -> Result<(), String> {
+    if path.exists() {
+        return Ok(());
+    }
+    let status = Command::new("mkfifo").arg(path).status().map_err(|e| format!("mkfifo: {}", e))?;
+    status.success().then_some(()).ok_or_else(|| format!("mkfifo {} failed", path.display()))
+}
+
+/// Read values from `path` (or stdin for `-`) and apply them to `param`
+pub fn start(state: Arc<IpcState>, path: PathBuf, param: Param) {
+    if path == Path::new("-") {
+        log::info!("Binding {:?} to stdin", param);
+        std::thread::spawn(move || read_lines(std::io::stdin().lock(), &state, param));
+        return;
+    }
+    if let Err(e) = ensure_fifo(&path) {
+        log::warn!("Failed to create {}: {}", path.display(), e);
+    }
+    log::info!("Binding {:?} to {}", param, path.display());
+    // Opening blocks until a writer shows up, and every writer closing ends the
+    // lines, so keep reopening for the next one
+    std::thread::spawn(move || loop {
+        match File::open(&path) {
+            Ok(file) => {
+                let fifo = file.metadata().is_ok_and(|m| m.file_type().is_fifo());
+                read_lines(BufReader::new(file), &state, param);
+                if !fifo {
+                    break;
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to open {}: {}", path.display(), e);
+                std::thread::sleep(RETRY_INTERVAL);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(parse_line("0.5\n", Param::Opacity), Ok(vec![(Param::Opacity, Some(0.5))]));
+        assert_eq!(parse_line(r#"{"value": 1}"#, Param::Hue), Ok(vec![(Param::Hue, Some(1.0))]));
+        assert_eq!(
+            parse_line(r#"{"opacity": 0.2, "progress": null}"#, Param::Hue),
+            Ok(vec![(Param::Opacity, Some(0.2)), (Param::Progress, None)])
+        );
+        assert!(parse_line(r#"{"thickness": 3}"#, Param::Hue).is_err());
+        assert!(parse_line("loud", Param::Hue).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! `hypr-ringlight config` subcommands: check the config file, and move a
+//! whole setup between machines
+//!
+//! A bundle is the full config as JSON, profiles and per-monitor overrides
+//! included, with a format version so older bundles keep importing.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Subcommand;
+use hypr_ringlight_core::check;
+use hypr_ringlight_core::ipc::{self, Command};
+use hypr_ringlight_core::scene::Scene;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, Profile};
+use crate::error::Error;
+
+/// Bundle format written by `export`
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Report syntax errors, unknown keys and invalid values in the config file
+    Check {
+        /// Config file to check (default: the one the daemon loads)
+        file: Option<PathBuf>,
+    },
+    /// Write the current config, with profiles and monitor overrides, to a JSON bundle
+    Export {
+        /// Bundle file to write
+        file: PathBuf,
+    },
+    /// Replace the config with a bundle, keeping a backup of the old one
+    Import {
+        /// Bundle file to read
+        file: PathBuf,
+        /// Also apply the ring's look to the running instance
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Bundle {
+    version: u32,
+    config: Config,
+}
+
+/// Run a `config` subcommand
+pub fn run(action: ConfigAction) -> Result<(), Error> {
+    match action {
+        ConfigAction::Check { file } => check(&file.unwrap_or_else(Config::path)),
+        ConfigAction::Export { file } => {
+            let bundle = Bundle { version: BUNDLE_VERSION, config: Config::try_load().map_err(Error::Config)? };
+            let json = serde_json::to_string_pretty(&bundle).map_err(|e| Error::Other(e.to_string()))?;
+            fs::write(&file, json + "\n")
+                .map_err(|e| Error::Other(format!("Failed to write {}: {}", file.display(), e)))?;
+            println!("Exported {} to {}", Config::path().display(), file.display());
+            Ok(())
+        }
+        ConfigAction::Import { file, apply } => {
+            let config = load(&file)?;
+            let path = Config::path();
+            if path.exists() {
+                let backup = path.with_extension("toml.bak");
+                fs::copy(&path, &backup)
+                    .map_err(|e| Error::Other(format!("Failed to back up {}: {}", path.display(), e)))?;
+                println!("Previous config saved to {}", backup.display());
+            }
+            config.save().map_err(Error::Other)?;
+            println!("Imported {} into {}", file.display(), path.display());
+            if apply {
+                apply_live(&config)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn check(path: &Path) -> Result<(), Error> {
+    let diagnostics = check::check_file(path).map_err(Error::Usage)?;
+    for diagnostic in &diagnostics {
+        println!("{}: {}", path.display(), diagnostic);
+    }
+    match diagnostics.len() {
+        0 => {
+            println!("{}: OK", path.display());
+            Ok(())
+        }
+        n => Err(Error::Usage(format!("{} problem{} found", n, if n == 1 { "" } else { "s" }))),
+    }
+}
+
+/// Read and validate a bundle
+fn load(file: &Path) -> Result<Config, Error> {
+    let text = fs::read_to_string(file).map_err(|e| Error::Usage(format!("{}: {}", file.display(), e)))?;
+    let bundle: Bundle = serde_json::from_str(&text)
+        .map_err(|e| Error::Usage(format!("Invalid bundle {}: {}", file.display(), e)))?;
+    if bundle.version > BUNDLE_VERSION {
+        return Err(Error::Usage(format!(
+            "{} is a version {} bundle, this build reads up to version {}",
+            file.display(),
+            bundle.version,
+            BUNDLE_VERSION
+        )));
+    }
+    validate(&bundle.config).map_err(|e| Error::Usage(format!("Invalid bundle {}: {}", file.display(), e)))?;
+    Ok(bundle.config)
+}
+
+/// Catch what deserializing lets through but would misbehave once imported
+fn validate(config: &Config) -> Result<(), String> {
+    match check::check_config(config).first() {
+        Some(diagnostic) => Err(diagnostic.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// Push the imported look to the running instance
+fn apply_live(config: &Config) -> Result<(), Error> {
+    let scene = Scene {
+        ring: Profile {
+            color: Some(config.color.clone()),
+            thickness: Some(config.thickness),
+            opacity: Some(config.opacity),
+            glow: Some(config.glow),
+            corner_radius: Some(config.corner_radius),
+            animation: Some(config.animation.clone()),
+            cycle_duration: Some(config.cycle_duration.clone()),
+            pattern: Some(config.pattern.clone()),
+            visible: None,
+        },
+        progress: None,
+        monitors: config.disabled_monitors.iter().map(|m| (m.clone(), false)).collect(),
+        ..Default::default()
+    };
+    for (name, color) in &config.palette {
+        ipc::send_command(&Command::SetPaletteColor { name: name.clone(), color: color.clone() })?;
+    }
+    let changes = ipc::apply_scene(&scene)?;
+    ipc::send_command(&Command::SetCornerStyle(config.corner_style.clone()))?;
+    if let Some(radii) = config.corner_radii {
+        ipc::send_command(&Command::SetCornerRadii(radii))?;
+    }
+    ipc::send_command(&Command::SetBarHeight(config.bar_height))?;
+    ipc::send_command(&Command::SetBarPosition(config.bar_position.clone()))?;
+    ipc::send_command(&Command::SetLayer(config.layer.clone()))?;
+    for change in changes {
+        println!("{}", change);
+    }
+    println!("Applied to the running instance; other settings take effect on restart");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let config: Config = toml::from_str(
+            "color = \"ff8800\"\n[profiles.call]\nthickness = 120\n[monitor.\"DP-2\"]\nbar_height = 0\n",
+        )
+        .unwrap();
+        let json = serde_json::to_string(&Bundle { version: BUNDLE_VERSION, config }).unwrap();
+        let bundle: Bundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(bundle.config.color, "ff8800");
+        assert_eq!(bundle.config.profiles["call"].thickness, Some(120));
+        assert_eq!(bundle.config.monitor["DP-2"].bar_height, Some(0));
+        assert!(validate(&bundle.config).is_ok());
+
+        let mut config = bundle.config;
+        config.color = "tangerine".to_string();
+        assert!(validate(&config).is_err());
+    }
+}
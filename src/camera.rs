@@ -1,85 +1,222 @@
 //! Camera detection for video call notifications
 //!
 //! Monitors video devices (/dev/video*) to detect when a camera becomes active.
-//! Sends a desktop notification when the camera starts being used.
+//! Sends a desktop notification when the camera starts being used, whose
+//! buttons can turn the ring on or keep quiet for an hour.
+//!
+//! Only nodes that capture video count: UVC cameras add a second node for
+//! metadata, which is skipped. `[camera] devices` and `exclude` narrow the
+//! watched nodes further, by path or by card name.
 
-use notify_rust::Notification;
+use notify_rust::{Notification, Urgency};
 use std::fs;
-use std::process::Command;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::time::Duration;
-
-/// Check if any video device is currently in use
-fn is_camera_in_use() -> bool {
-    // Find all video devices
-    let video_devices: Vec<_> = fs::read_dir("/dev")
-        .ok()
-        .map(|entries| {
-            entries
-                .filter_map(|e| e.ok())
-                .filter(|e| {
-                    e.file_name()
-                        .to_string_lossy()
-                        .starts_with("video")
-                })
-                .map(|e| e.path())
-                .collect()
+use std::time::{Duration, Instant};
+
+use hypr_ringlight_core::ipc::Command;
+
+use crate::config::{CameraConfig, TriggerConfig};
+use crate::ipc::{self, IpcState};
+use crate::trigger::Debouncer;
+
+/// A video device node
+struct Device {
+    path: PathBuf,
+    /// The card name, e.g. "Integrated Camera: Integrated C"
+    name: String,
+}
+
+/// Whether udev's record of a device (`/run/udev/data/c<major>:<minor>`)
+/// says it captures video, if it says anything
+fn udev_capture(data: &str) -> Option<bool> {
+    let caps = data.lines().find_map(|line| line.strip_prefix("E:ID_V4L_CAPABILITIES="))?;
+    Some(caps.contains(":capture:"))
+}
+
+/// Whether the video node `node` captures video, rather than e.g. carrying metadata
+fn is_capture_node(node: &str) -> bool {
+    let sys = Path::new("/sys/class/video4linux").join(node);
+    let udev = fs::read_to_string(sys.join("dev"))
+        .and_then(|dev| fs::read_to_string(format!("/run/udev/data/c{}", dev.trim())));
+    if let Some(capture) = udev.ok().as_deref().and_then(udev_capture) {
+        return capture;
+    }
+    // Without udev's word, a device's first node is the one that captures
+    fs::read_to_string(sys.join("index")).map_or(true, |index| index.trim() == "0")
+}
+
+/// Whether `pattern` names `device`: its path, or part of its card name
+fn matches(device: &Device, pattern: &str) -> bool {
+    if pattern.starts_with('/') {
+        return device.path == Path::new(pattern);
+    }
+    device.name.to_lowercase().contains(&pattern.to_lowercase())
+}
+
+/// Whether `device` is watched with `[camera] devices` and `exclude`
+fn is_watched(device: &Device, cfg: &CameraConfig) -> bool {
+    (cfg.devices.is_empty() || cfg.devices.iter().any(|p| matches(device, p)))
+        && !cfg.exclude.iter().any(|p| matches(device, p))
+}
+
+/// The video capture devices to watch
+fn video_devices(cfg: &CameraConfig) -> Vec<Device> {
+    let Ok(entries) = fs::read_dir("/dev") else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|node| node.starts_with("video") && is_capture_node(node))
+        .map(|node| {
+            let name = fs::read_to_string(Path::new("/sys/class/video4linux").join(&node).join("name")).unwrap_or_default();
+            Device { path: Path::new("/dev").join(&node), name: name.trim().to_string() }
         })
-        .unwrap_or_default();
-    
+        .filter(|device| is_watched(device, cfg))
+        .collect()
+}
+
+/// Name of the process `pid`, empty if it can't be read
+fn process_name(pid: &str) -> String {
+    fs::read_to_string(format!("/proc/{}/comm", pid)).map(|name| name.trim().to_string()).unwrap_or_default()
+}
+
+/// Processes using the watched video devices, by name; empty if none is in use
+fn camera_users(cfg: &CameraConfig) -> Vec<String> {
+    let mut users = Vec::new();
     // Check if any device is being used via fuser
-    for device in video_devices {
-        let output = Command::new("fuser")
-            .arg(device.to_string_lossy().as_ref())
+    for device in video_devices(cfg) {
+        let output = std::process::Command::new("fuser")
+            .arg(device.path.to_string_lossy().as_ref())
             .output();
         
         if let Ok(output) = output {
-            // fuser returns non-empty stdout if the file is in use
-            if !output.stdout.is_empty() || output.status.success() {
-                return true;
+            // fuser lists the PIDs on stdout, and succeeds if there are any
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let pids: Vec<&str> = stdout.split_whitespace().map(|p| p.trim_end_matches(|c: char| !c.is_ascii_digit())).collect();
+            if pids.is_empty() && output.status.success() {
+                users.push(String::new());
             }
+            users.extend(pids.into_iter().map(process_name));
         }
     }
     
-    false
+    users
 }
 
-/// Send a notification about the ring light
-fn send_notification() {
-    let _ = Notification::new()
-        .summary("Camera Active")
-        .body("Your webcam is now active. Consider enabling the ring light for better lighting!")
-        .icon("camera-web")
-        .hint(notify_rust::Hint::Urgency(notify_rust::Urgency::Low))
+/// Whether every user of the camera is one of `[camera] ignore_apps`
+fn only_ignored_apps(users: &[String], ignore: &[String]) -> bool {
+    !ignore.is_empty() && users.iter().all(|user| ignore.iter().any(|app| app.eq_ignore_ascii_case(user)))
+}
+
+/// Send a notification about the ring light, and follow its buttons
+fn send_notification(state: &Arc<IpcState>, cfg: &CameraConfig) {
+    let mut notification = Notification::new();
+    notification
+        .summary(&cfg.summary)
+        .body(&cfg.body)
+        .icon(&cfg.icon)
+        .urgency(Urgency::try_from(cfg.urgency.as_str()).unwrap_or(Urgency::Low))
         .hint(notify_rust::Hint::Category("device".to_string()))
-        .timeout(10000) // 10 seconds
-        .show();
+        .timeout(cfg.timeout());
+    if cfg.actions {
+        notification.action("enable", "Enable ring").action("ignore", "Ignore for 1h");
+    }
+    let Ok(handle) = notification.show() else {
+        return;
+    };
+    if !cfg.actions {
+        return;
+    }
+    // Waits until the notification is closed, so it gets a thread of its own
+    let state = state.clone();
+    std::thread::spawn(move || {
+        handle.wait_for_action(|action| match action {
+            "enable" => {
+                ipc::execute(&state, Command::SetVisible(true), "camera", "enable ring");
+            }
+            "ignore" => {
+                ipc::execute(&state, Command::SnoozeCamera("1h".to_string()), "camera", "ignore for 1h");
+            }
+            _ => {}
+        });
+    });
 }
 
 /// Start the camera monitoring thread
 /// 
 /// This runs in the background and checks periodically if the camera becomes active.
 /// When the camera is activated, it sends a notification to remind the user about the ring light.
-pub fn start_camera_monitor(ring_visible: Arc<AtomicBool>) {
+/// The current camera state is published in `IpcState::camera_active`, after
+/// debouncing so devices being probed by browsers don't make it flap. With
+/// `mute_in_dnd`, no notification is sent while Do-Not-Disturb is on, nor
+/// while snoozed or when only `ignore_apps` use the camera.
+pub fn start_camera_monitor(state: Arc<IpcState>, cfg: CameraConfig, trigger: TriggerConfig, mute_in_dnd: bool) {
     std::thread::spawn(move || {
-        let mut was_in_use = false;
+        let devices: Vec<String> = video_devices(&cfg).iter().map(|d| format!("{} ({})", d.path.display(), d.name)).collect();
+        log::debug!("Watching cameras: {}", devices.join(", "));
+        let mut debouncer = Debouncer::new("camera", &trigger);
         
         loop {
-            let is_in_use = is_camera_in_use();
+            let now = Instant::now();
+            let users = camera_users(&cfg);
+            let change = debouncer.update(!users.is_empty(), now);
+            state.publish_trigger(debouncer.stats(now));
             
-            // Camera just became active
-            if is_in_use && !was_in_use {
-                // Only notify if ring light is not currently visible
-                if !ring_visible.load(Ordering::Relaxed) {
-                    send_notification();
+            if let Some(active) = change {
+                state.camera_active.store(active, Ordering::Relaxed);
+                let mut names: Vec<&str> = users.iter().map(String::as_str).filter(|name| !name.is_empty()).collect();
+                names.sort();
+                names.dedup();
+                match active {
+                    true if !names.is_empty() => state.log_event("camera", format!("camera in use by {}", names.join(", "))),
+                    true => state.log_event("camera", "camera in use"),
+                    false => state.log_event("camera", "camera released"),
+                }
+                
+                // Camera just became active; only notify if ring light is not currently visible
+                let muted = (mute_in_dnd && state.is_dnd())
+                    || state.camera_snooze_left().is_some()
+                    || only_ignored_apps(&users, &cfg.ignore_apps);
+                if active && !state.is_visible() && !muted {
+                    send_notification(&state, &cfg);
                 }
             }
             
-            was_in_use = is_in_use;
-            
-            // Check every 5 seconds (balance between responsiveness and CPU usage)
-            std::thread::sleep(Duration::from_secs(5));
+            // Poll every second so the debounce timings are honored reasonably precisely
+            std::thread::sleep(Duration::from_secs(1));
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watched_devices() {
+        assert_eq!(udev_capture("S:v4l/by-id/usb-cam\nE:ID_V4L_CAPABILITIES=:capture:\n"), Some(true));
+        assert_eq!(udev_capture("E:ID_V4L_CAPABILITIES=:\n"), Some(false));
+        assert_eq!(udev_capture("E:ID_V4L_PRODUCT=Cam\n"), None);
+
+        let camera = Device { path: PathBuf::from("/dev/video0"), name: "Integrated Camera: Integrated C".to_string() };
+        let capture = Device { path: PathBuf::from("/dev/video2"), name: "USB3 Video: USB3 Video".to_string() };
+        let cfg = CameraConfig { exclude: vec!["/dev/video2".to_string()], ..Default::default() };
+        assert!(is_watched(&camera, &cfg) && !is_watched(&capture, &cfg));
+        let cfg = CameraConfig { devices: vec!["integrated".to_string()], ..Default::default() };
+        assert!(is_watched(&camera, &cfg) && !is_watched(&capture, &cfg));
+    }
+
+    #[test]
+    fn test_ignored_apps() {
+        let ignore = vec!["wf-recorder".to_string()];
+        let users = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert!(only_ignored_apps(&users(&["wf-recorder"]), &ignore));
+        assert!(!only_ignored_apps(&users(&["wf-recorder", "firefox"]), &ignore));
+        // A process we can't see might be anything
+        assert!(!only_ignored_apps(&users(&[""]), &ignore));
+        assert!(!only_ignored_apps(&users(&["firefox"]), &[]));
+    }
+}
@@ -0,0 +1,125 @@
+//! Click-to-interact: the ring band takes clicks, for setups without a tray
+//!
+//! The ring's surface is click-through. With `[click] enabled`, its input
+//! region becomes the ring band instead, so the rest of the screen still
+//! takes every click, and a click on the band runs its button's action:
+//! toggle the ring, or step through `[profiles]`. The band keeps taking
+//! clicks while the ring is hidden, so clicking the edge brings it back.
+
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::Arc;
+
+use smithay_client_toolkit::compositor::{CompositorState, Region};
+use smithay_client_toolkit::seat::pointer::{PointerEvent, PointerEventKind, PointerHandler};
+use wayland_client::{protocol::wl_pointer, Connection, QueueHandle};
+
+use hypr_ringlight_core::ipc::Command;
+
+use crate::config::{ClickAction, ClickConfig, Profile};
+use crate::ipc::{self, IpcState};
+use crate::RingLight;
+
+/// Button codes from linux/input-event-codes.h
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
+
+/// Input region covering a band `band` wide along the edges of a
+/// `width` x `height` surface
+pub fn band_region(compositor: &CompositorState, width: u32, height: u32, band: i32) -> Option<Region> {
+    let region = Region::new(compositor).ok()?;
+    let (w, h) = (width as i32, height as i32);
+    region.add(0, 0, w, h);
+    if w > 2 * band && h > 2 * band {
+        region.subtract(band, band, w - 2 * band, h - 2 * band);
+    }
+    Some(region)
+}
+
+/// The profile after `current`: they go in alphabetical order, then none
+fn next_profile<'a>(profiles: &'a BTreeMap<String, Profile>, current: Option<&str>) -> Option<(&'a String, &'a Profile)> {
+    match current {
+        None => profiles.iter().next(),
+        Some(name) => profiles.range::<str, _>((Bound::Excluded(name), Bound::Unbounded)).next(),
+    }
+}
+
+/// Apply the next profile picked by hand, with a click or a shortcut
+pub fn cycle_profile(state: &IpcState, profiles: &BTreeMap<String, Profile>) {
+    let current = state.override_name("manual");
+    let next = next_profile(profiles, current.as_deref()).map(|(name, p)| (name.clone(), p.clone()));
+    state.set_override("manual", next);
+}
+
+/// Actions bound to the buttons
+pub struct Clicks {
+    left: ClickAction,
+    right: ClickAction,
+    middle: ClickAction,
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Clicks {
+    pub fn new(cfg: &ClickConfig, profiles: &BTreeMap<String, Profile>) -> Self {
+        Self {
+            left: ClickAction::from_name(&cfg.left),
+            right: ClickAction::from_name(&cfg.right),
+            middle: ClickAction::from_name(&cfg.middle),
+            profiles: profiles.clone(),
+        }
+    }
+
+    /// Run the action of `button`
+    pub fn press(&self, state: &Arc<IpcState>, button: u32) {
+        let action = match button {
+            BTN_LEFT => self.left,
+            BTN_RIGHT => self.right,
+            BTN_MIDDLE => self.middle,
+            _ => ClickAction::None,
+        };
+        match action {
+            ClickAction::None => {}
+            ClickAction::Toggle => {
+                ipc::execute(state, Command::SetVisible(!state.is_visible()), "click", "toggle");
+            }
+            ClickAction::NextProfile => cycle_profile(state, &self.profiles),
+        }
+    }
+}
+
+impl PointerHandler for RingLight {
+    fn pointer_frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _pointer: &wl_pointer::WlPointer,
+        events: &[PointerEvent],
+    ) {
+        let Some(clicks) = self.clicks.as_ref() else {
+            return;
+        };
+        for event in events {
+            if let PointerEventKind::Press { button, .. } = event.kind {
+                clicks.press(&self.state.ipc, button);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_profile() {
+        let profiles: BTreeMap<String, Profile> =
+            ["focus", "call"].iter().map(|name| (name.to_string(), Profile::default())).collect();
+        let next = |current: Option<&str>| next_profile(&profiles, current).map(|(name, _)| name.as_str());
+        assert_eq!(next(None), Some("call"));
+        assert_eq!(next(Some("call")), Some("focus"));
+        assert_eq!(next(Some("focus")), None);
+        // A profile removed from the config moves on to the one after it
+        assert_eq!(next(Some("dim")), Some("focus"));
+    }
+}
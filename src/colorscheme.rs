@@ -0,0 +1,154 @@
+//! Light/dark adaptation: a profile for each system color scheme
+//!
+//! The preference comes from the xdg-desktop-portal Settings interface
+//! (`org.freedesktop.appearance` `color-scheme`), served by the GNOME, KDE,
+//! GTK and Hyprland portals, which signals changes as they happen. Without a
+//! portal, GNOME's `color-scheme` GSettings key is polled instead.
+
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::arg::{RefArg, Variant};
+use dbus::blocking::Connection;
+use dbus::message::MatchRule;
+
+use crate::config::{ColorSchemeConfig, Profile};
+use crate::ipc::IpcState;
+
+const PORTAL: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const SETTINGS: &str = "org.freedesktop.portal.Settings";
+const NAMESPACE: &str = "org.freedesktop.appearance";
+const KEY: &str = "color-scheme";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often GSettings is read when there is no portal
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scheme {
+    Dark,
+    Light,
+}
+
+impl Scheme {
+    /// The portal's value: 1 prefers dark, 2 prefers light, 0 has no preference
+    fn from_portal(value: u64) -> Option<Self> {
+        match value {
+            1 => Some(Scheme::Dark),
+            0 | 2 => Some(Scheme::Light),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Scheme::Dark => "dark",
+            Scheme::Light => "light",
+        }
+    }
+}
+
+/// Ask the portal; newer ones have `ReadOne`, older ones only the doubly wrapped `Read`
+fn portal_scheme(conn: &Connection) -> Option<Scheme> {
+    let proxy = conn.with_proxy(PORTAL, PORTAL_PATH, TIMEOUT);
+    let (value,): (Variant<Box<dyn RefArg>>,) = proxy
+        .method_call(SETTINGS, "ReadOne", (NAMESPACE, KEY))
+        .or_else(|_| proxy.method_call(SETTINGS, "Read", (NAMESPACE, KEY)))
+        .ok()?;
+    Scheme::from_portal(value.as_u64()?)
+}
+
+/// GNOME's setting, e.g. `'prefer-dark'`
+fn gsettings_scheme() -> Option<Scheme> {
+    let output = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", KEY])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let value = String::from_utf8_lossy(&output.stdout);
+    Some(if value.contains("dark") { Scheme::Dark } else { Scheme::Light })
+}
+
+/// Applies the profile of the current scheme, once per change
+struct Follower {
+    state: Arc<IpcState>,
+    cfg: ColorSchemeConfig,
+    profiles: BTreeMap<String, Profile>,
+    current: Option<Scheme>,
+}
+
+impl Follower {
+    fn update(&mut self, scheme: Scheme) {
+        if self.current == Some(scheme) {
+            return;
+        }
+        self.current = Some(scheme);
+        log::info!("System prefers {} mode", scheme.name());
+        self.state.log_event("scheme", format!("{} mode", scheme.name()));
+        let name = match scheme {
+            Scheme::Dark => &self.cfg.dark,
+            Scheme::Light => &self.cfg.light,
+        };
+        let profile = match self.profiles.get(name) {
+            Some(profile) => Some((name.clone(), profile.clone())),
+            None if name.is_empty() => None,
+            None => {
+                log::warn!("color_scheme: unknown profile {}", name);
+                None
+            }
+        };
+        self.state.set_override("scheme", profile);
+    }
+}
+
+/// Start following the system color scheme.
+///
+/// Does nothing unless a `[color_scheme]` profile is configured.
+pub fn start_color_scheme_monitor(state: Arc<IpcState>, cfg: ColorSchemeConfig, profiles: BTreeMap<String, Profile>) {
+    if !cfg.is_active() {
+        return;
+    }
+    let mut follower = Follower { state, cfg, profiles, current: None };
+
+    std::thread::spawn(move || {
+        let conn = Connection::new_session().ok();
+        match conn.as_ref().and_then(|c| Some((c, portal_scheme(c)?))) {
+            Some((conn, scheme)) => {
+                follower.update(scheme);
+                let rule = MatchRule::new_signal(SETTINGS, "SettingChanged");
+                let changes = conn.add_match(
+                    rule,
+                    move |(namespace, key, value): (String, String, Variant<Box<dyn RefArg>>), _, _| {
+                        if namespace == NAMESPACE && key == KEY {
+                            if let Some(scheme) = value.as_u64().and_then(Scheme::from_portal) {
+                                follower.update(scheme);
+                            }
+                        }
+                        true
+                    },
+                );
+                if let Err(e) = changes {
+                    log::warn!("Failed to watch the color scheme: {}", e);
+                    return;
+                }
+                while conn.process(Duration::from_secs(60)).is_ok() {}
+                log::warn!("Lost the session bus, no longer following the color scheme");
+            }
+            None if gsettings_scheme().is_none() => {
+                log::info!("No settings portal or GSettings, not following the color scheme");
+            }
+            None => {
+                log::info!("No settings portal, polling GSettings for the color scheme");
+                loop {
+                    if let Some(scheme) = gsettings_scheme() {
+                        follower.update(scheme);
+                    }
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,136 @@
+//! Which compositor we run under, and what the rules need from it
+//!
+//! The ring itself only needs layer-shell. The workspace, focus and window
+//! rules in `hyprland` also need to know what's on screen, which Hyprland,
+//! Sway and niri tell on their IPC sockets and river over its status
+//! protocol: each has a `Backend` that turns its event stream and queries
+//! into `Event`s, `Monitor`s and the focused `Window`. Under any other
+//! compositor the rules are off and the ring runs as a plain overlay.
+
+#[cfg(feature = "hyprland")]
+use std::io::BufRead;
+
+#[cfg(feature = "hyprland")]
+use serde::Deserialize;
+
+#[cfg(feature = "hyprland")]
+use crate::bar::Bar;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compositor {
+    Hyprland,
+    Sway,
+    Niri,
+    River,
+    /// Any other layer-shell compositor
+    Other,
+}
+
+impl Compositor {
+    /// Tell the compositor from the variables it sets for its clients
+    pub fn detect() -> Self {
+        let set = |name: &str| std::env::var_os(name).is_some_and(|v| !v.is_empty());
+        if set("HYPRLAND_INSTANCE_SIGNATURE") {
+            return Compositor::Hyprland;
+        }
+        if set("SWAYSOCK") {
+            return Compositor::Sway;
+        }
+        if set("NIRI_SOCKET") {
+            return Compositor::Niri;
+        }
+        let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+        if desktop.split(':').any(|d| d.eq_ignore_ascii_case("river")) {
+            return Compositor::River;
+        }
+        Compositor::Other
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Compositor::Hyprland => "Hyprland",
+            Compositor::Sway => "Sway",
+            Compositor::Niri => "niri",
+            Compositor::River => "river",
+            Compositor::Other => "an unknown compositor",
+        }
+    }
+}
+
+/// A change the rules react to
+#[cfg(feature = "hyprland")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// Workspaces or monitors changed; re-read the monitor layout
+    Layout,
+    /// Focus moved to another monitor
+    FocusedMonitor { monitor: String, workspace: String },
+    /// Focused window changed (class, title); empty when the event doesn't say
+    ActiveWindow { class: String, title: String },
+    /// The focused window entered or left fullscreen
+    Fullscreen(bool),
+    /// The compositor reloaded its config; the border color may have changed
+    ConfigReloaded,
+    /// A screen or window share started or stopped
+    Screencast(bool),
+}
+
+#[cfg(feature = "hyprland")]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WorkspaceRef {
+    pub id: i64,
+    pub name: String,
+}
+
+/// A monitor and the workspace it shows
+#[cfg(feature = "hyprland")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monitor {
+    pub name: String,
+    pub focused: bool,
+    pub workspace: WorkspaceRef,
+    /// Special workspace (scratchpad) open on top, without `special:`
+    pub special: Option<String>,
+    /// Space reserved for bars and docks, if the compositor tells
+    pub reserved: Option<Bar>,
+}
+
+/// The focused window, as far as window rules care
+#[cfg(feature = "hyprland")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Window {
+    pub class: String,
+    pub title: String,
+    pub fullscreen: bool,
+}
+
+/// A compositor's IPC
+#[cfg(feature = "hyprland")]
+pub trait Backend: Send {
+    /// Start listening to events, one per line; `None` if the compositor can't be reached
+    fn subscribe(&self) -> Option<Box<dyn BufRead + Send>>;
+
+    fn parse_event(&self, line: &str) -> Option<Event>;
+
+    fn monitors(&self) -> Option<Vec<Monitor>>;
+
+    /// The focused window, `None` when nothing has focus
+    fn active_window(&self) -> Option<Window>;
+
+    /// First color of the focused window's border, for the accent
+    fn border_color(&self) -> Option<(u8, u8, u8)> {
+        None
+    }
+}
+
+/// The IPC of `compositor`, if we speak it
+#[cfg(feature = "hyprland")]
+pub fn backend(compositor: Compositor) -> Option<Box<dyn Backend>> {
+    match compositor {
+        Compositor::Hyprland => Some(Box::new(crate::hyprland::Hyprland)),
+        Compositor::Sway => Some(Box::new(crate::sway::Sway)),
+        Compositor::Niri => Some(Box::new(crate::niri::Niri)),
+        Compositor::River => Some(Box::new(crate::river::River::default())),
+        Compositor::Other => None,
+    }
+}
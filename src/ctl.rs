@@ -0,0 +1,299 @@
+//! `hypr-ringlight ctl`: control the running instance from scripts and keybinds
+
+use clap::Subcommand;
+use hypr_ringlight_core::color::is_color;
+use hypr_ringlight_core::ipc::{self, Command, Notice, StrobeRequest, Update};
+
+use crate::config::{format_cycle_duration, parse_duration_ms};
+use crate::error::Error;
+
+#[derive(Subcommand, Debug)]
+pub enum CtlCommand {
+    /// Focus timer: the ring counts down, then flashes
+    Timer {
+        #[command(subcommand)]
+        action: TimerAction,
+    },
+    /// Count down before e.g. starting a recording: the lit perimeter shrinks to nothing
+    Countdown {
+        /// Seconds, or "stop" to cancel a running countdown
+        seconds: String,
+        /// Return only once the countdown has run out, failing if it's stopped
+        #[arg(long)]
+        wait: bool,
+    },
+    /// Flash the ring as an alert (at most 3 flashes a second, stops by itself)
+    Strobe {
+        #[command(subcommand)]
+        action: StrobeAction,
+    },
+    /// Light only part of the perimeter, like a progress bar
+    Progress {
+        /// Fraction from 0.0 to 1.0, a percentage like 40%, or "off" for a full ring
+        value: String,
+    },
+    /// Show render timings per monitor and the state of automatic triggers
+    Stats,
+    /// Change a palette color; the ring and profiles using the name follow it
+    Palette {
+        /// Palette name, e.g. call
+        name: String,
+        /// New color (hex)
+        color: String,
+    },
+    /// Show a color for a moment, then put the ring back, e.g. to confirm a keybinding
+    Flash {
+        /// Color (hex or palette name)
+        color: String,
+        /// How long, e.g. 300ms or 2s; a plain number is milliseconds
+        #[arg(default_value = "500ms")]
+        duration: String,
+    },
+    /// Show the ring only on the focused monitor (Hyprland)
+    FollowFocus {
+        /// on, off or toggle
+        #[arg(default_value = "toggle")]
+        value: String,
+    },
+    /// Print what puts you on air (camera, share, mic) as JSON for a Waybar custom module
+    OnAir {
+        /// Keep printing a line whenever it changes
+        #[arg(long)]
+        follow: bool,
+    },
+    /// Hold camera notifications back, e.g. during a day of calls
+    SnoozeCamera {
+        /// Duration, e.g. 30m or 2h, or "off" to show them again
+        #[arg(default_value = "1h")]
+        duration: String,
+    },
+    /// Show a text label in the top band of the ring
+    Label {
+        /// Text, e.g. "ON AIR"; leave out to remove the label
+        text: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TimerAction {
+    /// Start a timer, replacing any running one
+    Start {
+        /// Duration, e.g. 25m or "1h 30m"
+        duration: String,
+    },
+    /// Stop the running timer
+    Stop,
+    /// Show the time left
+    Status,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StrobeAction {
+    /// Start flashing, replacing any running strobe; unset options come from the config
+    Start {
+        /// Flashes per second
+        #[arg(short, long)]
+        frequency: Option<f64>,
+        /// Lit fraction of each flash (0.1 - 0.9)
+        #[arg(short, long)]
+        duty: Option<f64>,
+        /// Number of flashes
+        #[arg(short, long)]
+        count: Option<u32>,
+        /// Flash color (hex or palette name)
+        #[arg(long)]
+        color: Option<String>,
+    },
+    /// Stop flashing
+    Stop,
+}
+
+/// Run a `ctl` subcommand against the running instance
+pub fn run(cmd: CtlCommand) -> Result<(), Error> {
+    match cmd {
+        CtlCommand::Timer { action } => timer(action),
+        CtlCommand::Countdown { seconds, wait } => countdown(&seconds, wait),
+        CtlCommand::Strobe { action } => {
+            let cmd = match action {
+                StrobeAction::Start { frequency, duty, count, color } => {
+                    Command::StartStrobe(StrobeRequest { frequency, duty, count, color })
+                }
+                StrobeAction::Stop => Command::StopStrobe,
+            };
+            ipc::send_command(&cmd)?;
+            Ok(())
+        }
+        CtlCommand::Progress { value } => {
+            ipc::send_command(&Command::SetProgress(parse_progress(&value)?))?;
+            Ok(())
+        }
+        CtlCommand::Stats => stats(),
+        CtlCommand::Palette { name, color } => {
+            if !is_color(&color) {
+                return Err(Error::Usage(format!("Invalid color '{}', expected e.g. ff8800, orange or rgb(255,136,0)", color)));
+            }
+            ipc::send_command(&Command::SetPaletteColor { name, color })?;
+            Ok(())
+        }
+        CtlCommand::Flash { color, duration } => {
+            // Bare numbers are milliseconds, as keybinding tools tend to count
+            let duration = if duration.parse::<u64>().is_ok() { format!("{}ms", duration) } else { duration };
+            if parse_duration_ms(&duration).filter(|&ms| ms > 0).is_none() {
+                return Err(Error::Usage(format!("Invalid duration '{}', expected e.g. 300ms or 2s", duration)));
+            }
+            ipc::send_command(&Command::Flash { color, duration })?;
+            Ok(())
+        }
+        CtlCommand::FollowFocus { value } => {
+            let follow = match value.to_ascii_lowercase().as_str() {
+                "on" => true,
+                "off" => false,
+                "toggle" => {
+                    let state = ipc::send_command(&Command::GetState)?
+                        .ok_or_else(|| Error::Ipc("No response from hypr-ringlight".to_string()))?;
+                    !state.follow_focus
+                }
+                _ => return Err(Error::Usage(format!("Invalid value '{}', expected on, off or toggle", value))),
+            };
+            ipc::send_command(&Command::SetFollowFocus(follow))?;
+            Ok(())
+        }
+        CtlCommand::OnAir { follow } => on_air(follow),
+        CtlCommand::SnoozeCamera { duration } => {
+            let duration = if duration.eq_ignore_ascii_case("off") { "0s".to_string() } else { duration };
+            if parse_duration_ms(&duration).is_none() {
+                return Err(Error::Usage(format!("Invalid duration '{}', expected e.g. 30m or \"off\"", duration)));
+            }
+            ipc::send_command(&Command::SnoozeCamera(duration))?;
+            Ok(())
+        }
+        CtlCommand::Label { text } => {
+            ipc::send_command(&Command::SetLabel(text.unwrap_or_default()))?;
+            Ok(())
+        }
+    }
+}
+
+fn stats() -> Result<(), Error> {
+    let stats = ipc::get_stats()?;
+    println!("{:<12} {:>11} {:>10} {:>8} {:>5} {:>9} {:>9}", "MONITOR", "SIZE", "BUFFER", "FRAMES", "FPS", "AVG", "MAX");
+    for m in &stats.monitors {
+        println!(
+            "{:<12} {:>11} {:>10} {:>8} {:>5.0} {:>9} {:>9}",
+            m.monitor,
+            format!("{}x{}", m.width, m.height),
+            format!("{:.1} MiB", m.buffer_bytes as f64 / (1024.0 * 1024.0)),
+            m.frames,
+            m.fps,
+            format!("{:.2} ms", m.avg_render_us as f64 / 1000.0),
+            format!("{:.2} ms", m.max_render_us as f64 / 1000.0),
+        );
+    }
+    for t in &stats.triggers {
+        let state = if t.active { "active" } else { "inactive" };
+        println!("trigger {}: {} (raw {})", t.name, state, if t.raw { "on" } else { "off" });
+    }
+    Ok(())
+}
+
+/// Parse "0.4", "40%" or "off"
+fn parse_progress(value: &str) -> Result<Option<f64>, Error> {
+    if value.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    let parsed = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|p| p / 100.0),
+        None => value.parse::<f64>(),
+    };
+    match parsed {
+        Ok(v) if (0.0..=1.0).contains(&v) => Ok(Some(v)),
+        _ => Err(Error::Usage(format!("Invalid progress '{}', expected 0.0 - 1.0, a percentage or \"off\"", value))),
+    }
+}
+
+fn timer(action: TimerAction) -> Result<(), Error> {
+    match action {
+        TimerAction::Start { duration } => {
+            if parse_duration_ms(&duration).filter(|&ms| ms > 0).is_none() {
+                return Err(Error::Usage(format!("Invalid duration '{}', expected e.g. 25m or \"1h 30m\"", duration)));
+            }
+            ipc::send_command(&Command::StartTimer(duration))?;
+        }
+        TimerAction::Stop => {
+            ipc::send_command(&Command::StopTimer)?;
+        }
+        TimerAction::Status => {
+            let state = ipc::send_command(&Command::GetState)?
+                .ok_or_else(|| Error::Ipc("No response from hypr-ringlight".to_string()))?;
+            match state.timer {
+                Some(t) if t.remaining_ms > 0 => {
+                    println!("{} left of {}", format_secs(t.remaining_ms), format_secs(t.duration_ms));
+                }
+                Some(t) => println!("{} timer finished", format_secs(t.duration_ms)),
+                None => println!("No timer running"),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn countdown(seconds: &str, wait: bool) -> Result<(), Error> {
+    if seconds.eq_ignore_ascii_case("stop") {
+        ipc::send_command(&Command::StopCountdown)?;
+        return Ok(());
+    }
+    let seconds = match seconds.parse::<u32>() {
+        Ok(s) if s > 0 => s,
+        _ => return Err(Error::Usage(format!("Invalid countdown '{}', expected whole seconds or \"stop\"", seconds))),
+    };
+    if !wait {
+        ipc::send_command(&Command::StartCountdown(seconds))?;
+        return Ok(());
+    }
+    // Subscribed first, so the notice can't come before we listen
+    let updates = ipc::Session::updates()?;
+    ipc::send_command(&Command::StartCountdown(seconds))?;
+    let mut started = false;
+    for update in updates {
+        match update {
+            Update::Notice(Notice::CountdownFinished) if started => return Ok(()),
+            Update::State(state) if state.countdown.is_some() => started = true,
+            Update::State(_) if started => return Err(Error::Other("Countdown stopped".to_string())),
+            _ => {}
+        }
+    }
+    Err(Error::Ipc("hypr-ringlight went away".to_string()))
+}
+
+/// A Waybar custom module's line for the on-air state
+fn waybar_on_air(on_air: &str) -> String {
+    let (text, class, tooltip) = match on_air {
+        "" => ("", "off", "Off air".to_string()),
+        source => (source, source, format!("On air: {}", source)),
+    };
+    serde_json::json!({ "text": text, "alt": class, "class": class, "tooltip": tooltip }).to_string()
+}
+
+fn on_air(follow: bool) -> Result<(), Error> {
+    if !follow {
+        let state = ipc::send_command(&Command::GetState)?
+            .ok_or_else(|| Error::Ipc("No response from hypr-ringlight".to_string()))?;
+        println!("{}", waybar_on_air(&state.on_air));
+        return Ok(());
+    }
+    let mut last = None;
+    for update in ipc::Session::updates()? {
+        if let Update::State(state) = update {
+            if last.as_ref() != Some(&state.on_air) {
+                println!("{}", waybar_on_air(&state.on_air));
+                last = Some(state.on_air);
+            }
+        }
+    }
+    Err(Error::Ipc("hypr-ringlight went away".to_string()))
+}
+
+/// Format milliseconds rounded up to whole seconds, e.g. "24m 13s"
+fn format_secs(ms: u64) -> String {
+    format_cycle_duration((ms.div_ceil(1000) * 1000).min(u32::MAX as u64) as u32)
+}
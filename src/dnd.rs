@@ -0,0 +1,106 @@
+//! Do-Not-Disturb: the ring shows when notifications are held back
+//!
+//! There is no standard D-Bus API for it, so each daemon is asked its own way:
+//! SwayNC's `GetDnd`, dunst's `paused` property, or whether mako has the
+//! Do-Not-Disturb mode set. While it's on, `[dnd] profile` is applied (a dim
+//! purple ring, say), and the camera notification can be skipped.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+use dbus::blocking::Connection;
+
+use crate::config::{DndConfig, Profile};
+use crate::ipc::IpcState;
+
+const NOTIFICATIONS: &str = "org.freedesktop.Notifications";
+const SWAYNC: &str = "org.erikreider.swaync.cc";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Toggling DND is a keypress away, so don't lag far behind
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Whether someone owns `name`; calling a name nobody owns could D-Bus activate a daemon
+fn has_owner(conn: &Connection, name: &str) -> bool {
+    conn.with_proxy("org.freedesktop.DBus", "/org/freedesktop/DBus", TIMEOUT)
+        .method_call("org.freedesktop.DBus", "NameHasOwner", (name,))
+        .is_ok_and(|(owned,): (bool,)| owned)
+}
+
+fn swaync(conn: &Connection) -> Option<bool> {
+    let (dnd,): (bool,) = conn
+        .with_proxy(SWAYNC, "/org/erikreider/swaync/cc", TIMEOUT)
+        .method_call(SWAYNC, "GetDnd", ())
+        .ok()?;
+    Some(dnd)
+}
+
+fn dunst(conn: &Connection) -> Option<bool> {
+    conn.with_proxy(NOTIFICATIONS, "/org/freedesktop/Notifications", TIMEOUT)
+        .get("org.dunstproject.cmd0", "paused")
+        .ok()
+}
+
+/// mako 1.8 has several modes at once, older versions a single one
+fn mako(conn: &Connection, mode: &str) -> Option<bool> {
+    let proxy = conn.with_proxy(NOTIFICATIONS, "/fr/emersion/Mako", TIMEOUT);
+    let modes = match proxy.method_call("fr.emersion.Mako", "ListModes", ()) {
+        Ok((modes,)) => modes,
+        Err(_) => {
+            let (mode,): (String,) = proxy.method_call("fr.emersion.Mako", "GetMode", ()).ok()?;
+            vec![mode]
+        }
+    };
+    Some(modes.iter().any(|m: &String| m == mode))
+}
+
+/// Do-Not-Disturb of whichever daemon is running; `None` if none answers
+fn query(conn: &Connection, cfg: &DndConfig) -> Option<bool> {
+    if has_owner(conn, SWAYNC) {
+        if let Some(dnd) = swaync(conn) {
+            return Some(dnd);
+        }
+    }
+    if !has_owner(conn, NOTIFICATIONS) {
+        return None;
+    }
+    dunst(conn).or_else(|| mako(conn, &cfg.mako_mode))
+}
+
+/// Start following Do-Not-Disturb.
+///
+/// Does nothing unless `[dnd]` sets a profile or mutes the camera notification.
+pub fn start_dnd_monitor(state: Arc<IpcState>, cfg: DndConfig, profiles: BTreeMap<String, Profile>) {
+    if !cfg.is_active() {
+        return;
+    }
+    let profile = match profiles.get(&cfg.profile) {
+        Some(profile) => Some((cfg.profile.clone(), profile.clone())),
+        None if cfg.profile.is_empty() => None,
+        None => {
+            log::warn!("dnd: unknown profile {}", cfg.profile);
+            None
+        }
+    };
+    std::thread::spawn(move || {
+        let Ok(conn) = Connection::new_session() else {
+            log::info!("No session bus, not following Do-Not-Disturb");
+            return;
+        };
+        let mut dnd = false;
+        loop {
+            // A daemon that went away doesn't hold anything back
+            let now_dnd = query(&conn, &cfg).unwrap_or(false);
+            if now_dnd != dnd {
+                dnd = now_dnd;
+                log::info!("Do-Not-Disturb {}", if dnd { "on" } else { "off" });
+                state.log_event("dnd", if dnd { "do-not-disturb on" } else { "do-not-disturb off" });
+                state.set_dnd(dnd);
+                state.set_override("dnd", profile.clone().filter(|_| dnd));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,91 @@
+//! Event log: what changed the ring, and when
+//!
+//! The daemon keeps the last few hundred state changes and trigger
+//! activations in memory, tagged with their source, and serves them over IPC
+//! (`GetEvents`). `hypr-ringlight events --follow` tails them, to answer
+//! "why did my ring just turn red?".
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::Args;
+use hypr_ringlight_core::ipc::{self, Event};
+
+use crate::error::Error;
+
+/// Events kept in memory; older ones are dropped
+const CAPACITY: usize = 256;
+
+/// How often `--follow` asks for new events
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Args, Debug)]
+pub struct EventsArgs {
+    /// Keep printing new events as they happen
+    #[arg(short, long)]
+    follow: bool,
+}
+
+/// Ring buffer of recent events
+#[derive(Debug, Default)]
+pub struct EventLog {
+    events: VecDeque<Event>,
+    next_seq: u64,
+}
+
+impl EventLog {
+    pub fn push(&mut self, source: &str, message: String) {
+        let unix_ms = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+        if self.events.len() == CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(Event { seq: self.next_seq, unix_ms, source: source.to_string(), message });
+        self.next_seq += 1;
+    }
+
+    /// Events after the one numbered `after`, or all of them
+    pub fn since(&self, after: Option<u64>) -> Vec<Event> {
+        self.events.iter().filter(|e| after.is_none_or(|after| e.seq > after)).cloned().collect()
+    }
+}
+
+fn print_event(event: &Event) {
+    let time = UNIX_EPOCH + Duration::from_millis(event.unix_ms);
+    println!("{} {:<9} {}", humantime::format_rfc3339_seconds(time), event.source, event.message);
+}
+
+/// Print the event log, and with `--follow` keep printing new events
+pub fn run(args: EventsArgs) -> Result<(), Error> {
+    let mut last = None;
+    loop {
+        for event in ipc::get_events(last)? {
+            print_event(&event);
+            last = Some(event.seq);
+        }
+        if !args.follow {
+            return Ok(());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_log() {
+        let mut log = EventLog::default();
+        for i in 0..CAPACITY + 2 {
+            log.push("ipc", format!("event {}", i));
+        }
+        let all = log.since(None);
+        assert_eq!(all.len(), CAPACITY);
+        assert_eq!(all[0].seq, 2);
+        assert_eq!(all[0].message, "event 2");
+
+        let newer = log.since(Some(CAPACITY as u64));
+        assert_eq!(newer.iter().map(|e| e.seq).collect::<Vec<_>>(), [CAPACITY as u64 + 1]);
+        assert!(log.since(Some(CAPACITY as u64 + 1)).is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+//! Feral GameMode tracking
+//!
+//! GameMode's daemon counts the games that asked it for performance tweaks.
+//! While it has clients, a fullscreen window is taken for a game and the
+//! Hyprland listener suspends rendering (see `[gaming]`).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+use dbus::blocking::Connection;
+
+use crate::config::GamingConfig;
+use crate::ipc::IpcState;
+
+const GAMEMODE: &str = "com.feralinteractive.GameMode";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often the client count is read; games take longer than that to start
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Clients of a running daemon; asking for the property directly would
+/// D-Bus activate one
+fn client_count(conn: &Connection) -> Option<i32> {
+    let (running,): (bool,) = conn
+        .with_proxy("org.freedesktop.DBus", "/org/freedesktop/DBus", TIMEOUT)
+        .method_call("org.freedesktop.DBus", "NameHasOwner", (GAMEMODE,))
+        .ok()?;
+    if !running {
+        return Some(0);
+    }
+    conn.with_proxy(GAMEMODE, "/com/feralinteractive/GameMode", TIMEOUT)
+        .get(GAMEMODE, "ClientCount")
+        .ok()
+}
+
+/// Start polling GameMode, if game detection uses it.
+///
+/// Without a session bus or the daemon, GameMode simply never counts as active.
+pub fn start_gamemode_monitor(state: Arc<IpcState>, cfg: &GamingConfig) {
+    if !cfg.enabled || !cfg.gamemode {
+        return;
+    }
+    std::thread::spawn(move || {
+        let Ok(conn) = Connection::new_session() else {
+            log::info!("No session bus, GameMode detection disabled");
+            return;
+        };
+        let mut active = false;
+        loop {
+            let now_active = client_count(&conn).is_some_and(|n| n > 0);
+            if now_active != active {
+                active = now_active;
+                log::info!("GameMode {}", if active { "active" } else { "inactive" });
+                state.set_gamemode(active);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,327 @@
+//! HTTP control endpoint
+//!
+//! A small REST face on the IPC protocol for clients that can't reach the
+//! Unix socket, like Stream Deck plugins or phone shortcuts going through a
+//! tunnel. It listens on localhost unless configured otherwise:
+//!
+//! - `GET /state`: the `GetState` reply
+//! - `POST /set?color=ff0000&opacity=0.5`: set parameters by name, also
+//!   accepted as a JSON object body
+//! - `POST /toggle`: show or hide the ring
+//! - `POST /command`: any IPC command as its JSON body
+//!
+//! Requests are served by tiny_http, on a fixed number of worker threads.
+//!
+//! Browsers are kept out, since any page could otherwise reach localhost:
+//! requests carrying an `Origin` header are refused, bodies must be
+//! `application/json` (which a page can't send without a CORS preflight,
+//! never answered here), and without a token only requests addressed to
+//! localhost are answered, so a rebound DNS name can't read the state.
+
+use std::io::Read;
+use std::sync::Arc;
+
+use hypr_ringlight_core::ipc::Command;
+use serde_json::{json, Map, Value};
+use tiny_http::{Header, Server};
+
+use crate::config::HttpConfig;
+use crate::ipc::{self, IpcState};
+
+/// Longest body accepted; commands are tiny
+const MAX_BODY: usize = 64 * 1024;
+
+/// Requests handled at once; more wait their turn
+const WORKERS: usize = 4;
+
+/// Parameters `/set` accepts, and the commands setting them
+const PARAMS: &[(&str, &str)] = &[
+    ("color", "SetColor"),
+    ("thickness", "SetThickness"),
+    ("opacity", "SetOpacity"),
+    ("glow", "SetGlow"),
+    ("corner_radius", "SetCornerRadius"),
+    ("corner_style", "SetCornerStyle"),
+    ("animation", "SetAnimation"),
+    ("cycle_duration", "SetCycleDuration"),
+    ("pattern", "SetPattern"),
+    ("visible", "SetVisible"),
+    ("label", "SetLabel"),
+    ("progress", "SetProgress"),
+    ("layer", "SetLayer"),
+    ("bar_height", "SetBarHeight"),
+    ("bar_position", "SetBarPosition"),
+];
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    token: Option<String>,
+    /// Host the request was addressed to, without the port
+    host: Option<String>,
+    /// Sent by browsers, never by the clients this is for
+    origin: bool,
+    json: bool,
+    body: String,
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn json(body: String) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self { status, body: json!({ "error": message.into() }).to_string() }
+    }
+}
+
+/// The host of a `Host` header or listen address, without the port
+fn host_name(host: &str) -> &str {
+    match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or(v6),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    }
+}
+
+fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+fn read_request(request: &mut tiny_http::Request) -> Result<Request, Response> {
+    let header = |name: &'static str| {
+        request.headers().iter().find(|h| h.field.equiv(name)).map(|h| h.value.as_str().trim().to_string())
+    };
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let mut parsed = Request {
+        method: request.method().as_str().to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        token: header("Authorization").and_then(|v| v.strip_prefix("Bearer ").map(|t| t.trim().to_string())),
+        host: header("Host").map(|host| host_name(&host).to_string()),
+        origin: header("Origin").is_some(),
+        json: header("Content-Type").is_some_and(|v| v.split(';').next().is_some_and(|t| t.trim().eq_ignore_ascii_case("application/json"))),
+        body: String::new(),
+    };
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| Response::error(400, e.to_string()))?;
+    if body.len() > MAX_BODY {
+        return Err(Response::error(413, "body too large"));
+    }
+    parsed.body = String::from_utf8(body).map_err(|_| Response::error(400, "body is not UTF-8"))?;
+    Ok(parsed)
+}
+
+/// Why `request` is refused before it's routed, if it is
+fn refuse(cfg: &HttpConfig, request: &Request) -> Option<Response> {
+    if request.origin {
+        return Some(Response::error(403, "requests from web pages are refused"));
+    }
+    if cfg.token.is_empty() {
+        let listen = host_name(&cfg.listen);
+        let addressed = request.host.as_deref().is_some_and(|host| is_loopback(host) || host == listen);
+        if !addressed {
+            return Some(Response::error(403, "not addressed to this host; set a token to allow any"));
+        }
+    } else if request.token.as_deref() != Some(cfg.token.as_str()) {
+        return Some(Response::error(401, "missing or wrong token"));
+    }
+    if !request.body.trim().is_empty() && !request.json {
+        return Some(Response::error(415, "the body must be application/json"));
+    }
+    None
+}
+
+/// Decode a `application/x-www-form-urlencoded` component
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Commands for a `/set`, from the query string or a JSON object body
+fn set_commands(query: &str, body: &str) -> Result<Vec<Command>, String> {
+    let fields: Vec<(String, Value)> = if body.trim().is_empty() {
+        query
+            .split('&')
+            .filter(|p| !p.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (percent_decode(key), Value::String(percent_decode(value)))
+            })
+            .collect()
+    } else {
+        match serde_json::from_str(body).map_err(|e| e.to_string())? {
+            Value::Object(map) => map.into_iter().collect(),
+            _ => return Err("expected a JSON object".to_string()),
+        }
+    };
+    if fields.is_empty() {
+        return Err("nothing to set".to_string());
+    }
+    fields
+        .into_iter()
+        .map(|(key, value)| {
+            let (_, cmd) = PARAMS.iter().find(|(name, _)| *name == key).ok_or(format!("unknown parameter {}", key))?;
+            // Query values are all strings; numbers and booleans are tried after strings,
+            // so a color like 000000 stays a string
+            let retyped = match &value {
+                Value::String(s) => serde_json::from_str::<Value>(s).ok(),
+                _ => None,
+            };
+            std::iter::once(value)
+                .chain(retyped)
+                .find_map(|value| serde_json::from_value(json!({ "cmd": cmd, "value": value })).ok())
+                .ok_or(format!("invalid value for {}", key))
+        })
+        .collect()
+}
+
+fn route(state: &Arc<IpcState>, cfg: &HttpConfig, request: &Request) -> Response {
+    if let Some(refused) = refuse(cfg, request) {
+        return refused;
+    }
+    let line = |what: &str| format!("{} {}", request.method, what);
+    match (request.method.as_str(), request.path.trim_end_matches('/')) {
+        ("GET", "/state") => Response::json(serde_json::to_string(&state.get_state()).unwrap()),
+        ("POST", "/set") => match set_commands(&request.query, &request.body) {
+            Ok(commands) => {
+                let json = serde_json::to_string(&commands).unwrap();
+                ipc::execute(state, Command::Batch(commands), "http", &line(&json));
+                Response::json(serde_json::to_string(&state.get_state()).unwrap())
+            }
+            Err(e) => Response::error(400, e),
+        },
+        ("POST", "/toggle") => {
+            let visible = !state.is_visible();
+            ipc::execute(state, Command::SetVisible(visible), "http", &line("/toggle"));
+            Response::json(json!({ "visible": visible }).to_string())
+        }
+        ("POST", "/command") => match serde_json::from_str::<Command>(&request.body) {
+            Ok(cmd) => {
+                let reply = ipc::execute(state, cmd, "http", &line(request.body.trim()));
+                Response::json(reply.unwrap_or_else(|| Value::Object(Map::new()).to_string()))
+            }
+            Err(e) => Response::error(400, e.to_string()),
+        },
+        (_, "/state" | "/set" | "/toggle" | "/command") => Response::error(405, "wrong method"),
+        _ => Response::error(404, "no such endpoint"),
+    }
+}
+
+fn handle(mut request: tiny_http::Request, state: &Arc<IpcState>, cfg: &HttpConfig) {
+    let response = match read_request(&mut request) {
+        Ok(parsed) => {
+            log::debug!("HTTP {} {}", parsed.method, parsed.path);
+            route(state, cfg, &parsed)
+        }
+        Err(response) => response,
+    };
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let reply = tiny_http::Response::from_string(response.body)
+        .with_status_code(response.status)
+        .with_header(content_type);
+    let _ = request.respond(reply);
+}
+
+/// Start the HTTP endpoint, if enabled
+pub fn start_server(state: Arc<IpcState>, cfg: HttpConfig) {
+    if !cfg.enabled {
+        return;
+    }
+    let server = match Server::http(&cfg.listen) {
+        Ok(server) => Arc::new(server),
+        Err(e) => {
+            log::error!("Failed to listen for HTTP on {}: {}", cfg.listen, e);
+            return;
+        }
+    };
+    log::info!("HTTP control on http://{}", cfg.listen);
+    let cfg = Arc::new(cfg);
+    for _ in 0..WORKERS {
+        let (server, state, cfg) = (server.clone(), state.clone(), cfg.clone());
+        std::thread::spawn(move || {
+            while let Ok(request) = server.recv() {
+                handle(request, &state, &cfg);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(host: &str, origin: bool, token: Option<&str>, json: bool, body: &str) -> Request {
+        Request {
+            method: "POST".into(),
+            path: "/command".into(),
+            query: String::new(),
+            token: token.map(str::to_string),
+            host: Some(host_name(host).to_string()),
+            origin,
+            json,
+            body: body.into(),
+        }
+    }
+
+    #[test]
+    fn test_refuse() {
+        let open = HttpConfig { enabled: true, ..Default::default() };
+        let status = |cfg: &HttpConfig, request: Request| refuse(cfg, &request).map(|r| r.status);
+        assert_eq!(status(&open, request("127.0.0.1:7272", false, None, true, "{}")), None);
+        assert_eq!(status(&open, request("[::1]:7272", false, None, false, "")), None);
+        // A page in the browser, a rebound DNS name, a form post
+        assert_eq!(status(&open, request("localhost:7272", true, None, true, "{}")), Some(403));
+        assert_eq!(status(&open, request("evil.example:7272", false, None, true, "{}")), Some(403));
+        assert_eq!(status(&open, request("localhost:7272", false, None, false, "{}")), Some(415));
+
+        // With a token, any host that has it
+        let locked = HttpConfig { token: "s3cret".into(), ..open };
+        assert_eq!(status(&locked, request("ringlight.lan", false, Some("s3cret"), true, "{}")), None);
+        assert_eq!(status(&locked, request("localhost", false, Some("guess"), true, "{}")), Some(401));
+    }
+
+    #[test]
+    fn test_set_commands() {
+        let commands = set_commands("color=000000&opacity=0.5&label=On+air%21&visible=true", "").unwrap();
+        let json: Vec<String> = commands.iter().map(|c| serde_json::to_string(c).unwrap()).collect();
+        assert_eq!(json, [
+            r#"{"cmd":"SetColor","value":"000000"}"#,
+            r#"{"cmd":"SetOpacity","value":0.5}"#,
+            r#"{"cmd":"SetLabel","value":"On air!"}"#,
+            r#"{"cmd":"SetVisible","value":true}"#,
+        ]);
+        assert!(matches!(set_commands("", r#"{"progress": null}"#).unwrap()[..], [Command::SetProgress(None)]));
+        assert!(set_commands("thickness=wide", "").is_err());
+        assert!(set_commands("volume=3", "").is_err());
+    }
+}
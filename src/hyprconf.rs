@@ -0,0 +1,187 @@
+//! Hyprland config as a theme source: the ring takes the window border color
+//!
+//! Reads `hyprland.conf` the way Hyprland does as far as colors go: `source`
+//! includes (with `~` and `*` globs), `$variables`, `general { ... }` blocks
+//! and `general:` prefixed keys. The last `col.active_border` wins, like a
+//! later line overriding an earlier one.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::color::{self, is_hex_color};
+use crate::config::expand_home;
+
+/// How deep `source` includes are followed, in case two files source each other
+const MAX_DEPTH: usize = 8;
+
+/// The main Hyprland config
+pub fn config_path() -> PathBuf {
+    dirs::config_dir().unwrap_or_else(|| PathBuf::from(".")).join("hypr").join("hyprland.conf")
+}
+
+/// Colors of the `col.active_border` gradient, in order; empty if it isn't set
+pub fn active_border(path: &Path) -> Vec<(u8, u8, u8)> {
+    let mut parser = Parser::default();
+    parser.read(path, 0);
+    parser.border
+}
+
+/// The config file and every file it sources, to watch for changes
+pub fn config_files(path: &Path) -> Vec<PathBuf> {
+    let mut parser = Parser::default();
+    parser.read(path, 0);
+    parser.files
+}
+
+#[derive(Default)]
+struct Parser {
+    variables: BTreeMap<String, String>,
+    border: Vec<(u8, u8, u8)>,
+    files: Vec<PathBuf>,
+}
+
+impl Parser {
+    fn read(&mut self, path: &Path, depth: usize) {
+        if depth > MAX_DEPTH {
+            log::warn!("Not following {}: sourced more than {} levels deep", path.display(), MAX_DEPTH);
+            return;
+        }
+        self.files.push(path.to_path_buf());
+        match fs::read_to_string(path) {
+            Ok(content) => self.parse(&content, path.parent().unwrap_or(Path::new(".")), depth),
+            Err(e) => log::debug!("Skipping {}: {}", path.display(), e),
+        }
+    }
+
+    fn parse(&mut self, content: &str, dir: &Path, depth: usize) {
+        let mut section: Vec<&str> = Vec::new();
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line == "}" {
+                section.pop();
+                continue;
+            }
+            if let Some(name) = line.strip_suffix('{') {
+                section.push(name.trim());
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let (key, value) = (key.trim(), self.expand(value.trim()));
+            if let Some(name) = key.strip_prefix('$') {
+                self.variables.insert(name.to_string(), value);
+                continue;
+            }
+            let key: Vec<&str> = section.iter().copied().chain([key]).collect();
+            match key.join(":").as_str() {
+                "source" => {
+                    for path in source_paths(&value, dir) {
+                        self.read(&path, depth + 1);
+                    }
+                }
+                "general:col.active_border" => {
+                    let colors = parse_gradient(&value);
+                    if !colors.is_empty() {
+                        self.border = colors;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Substitute `$variables`, longest names first so `$a` doesn't eat `$ab`
+    fn expand(&self, value: &str) -> String {
+        let mut names: Vec<&String> = self.variables.keys().collect();
+        names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        names.into_iter().fold(value.to_string(), |value, name| {
+            value.replace(&format!("${}", name), &self.variables[name])
+        })
+    }
+}
+
+/// Files a `source = ...` line includes; relative paths are relative to `dir`
+fn source_paths(value: &str, dir: &Path) -> Vec<PathBuf> {
+    let path = dir.join(expand_home(value));
+    let Some(pattern) = path.file_name().and_then(|n| n.to_str()).filter(|n| n.contains('*')) else {
+        return vec![path];
+    };
+    let (prefix, suffix) = pattern.split_once('*').unwrap_or((pattern, ""));
+    let Some(parent) = path.parent() else { return Vec::new() };
+    let mut paths: Vec<PathBuf> = fs::read_dir(parent)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.len() >= prefix.len() + suffix.len() && n.starts_with(prefix) && n.ends_with(suffix))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Colors of a gradient like `rgba(33ccffee) rgba(00ff99ee) 45deg`, skipping the angle
+fn parse_gradient(value: &str) -> Vec<(u8, u8, u8)> {
+    let mut tokens = Vec::new();
+    let (mut start, mut depth) = (0, 0);
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if c.is_whitespace() && depth == 0 => {
+                tokens.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    tokens.push(&value[start..]);
+    tokens.into_iter().filter(|t| !t.is_empty()).filter_map(parse_color).collect()
+}
+
+/// Parse `rgba(33ccffee)`, `rgb(33ccff)`, `rgba(51, 204, 255, 0.9)` or legacy `0xee33ccff`
+fn parse_color(token: &str) -> Option<(u8, u8, u8)> {
+    if let Some(argb) = token.strip_prefix("0x") {
+        return color::parse_argb(argb);
+    }
+    let inner = token
+        .strip_prefix("rgba(")
+        .or_else(|| token.strip_prefix("rgb("))?
+        .strip_suffix(')')?
+        .trim();
+    if inner.contains(',') {
+        let channels: Vec<u8> = inner.split(',').take(3).filter_map(|c| c.trim().parse().ok()).collect();
+        return match channels[..] {
+            [r, g, b] => Some((r, g, b)),
+            _ => None,
+        };
+    }
+    inner.get(..6).filter(|rgb| matches!(inner.len(), 6 | 8) && is_hex_color(rgb)).and_then(color::parse_color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_border() {
+        let mut parser = Parser::default();
+        parser.parse(
+            "$active = rgb(89b4fa)\n\
+             general {\n    gaps_in = 5\n    col.active_border = rgba(33ccffee) rgba(00ff99ee) 45deg # default\n}\n\
+             decoration {\n    rounding = 10\n}\n\
+             general:col.active_border = $active rgba(243, 139, 168, 0.9)\n\
+             general:col.inactive_border = 0xff595959\n",
+            Path::new("."),
+            0,
+        );
+        assert_eq!(parser.border, vec![(137, 180, 250), (243, 139, 168)]);
+
+        assert_eq!(parse_gradient("rgba(33ccffee) rgba(00ff99ee) 45deg"), vec![(0x33, 0xcc, 0xff), (0x00, 0xff, 0x99)]);
+        assert_eq!(parse_color("0xee33ccff"), Some((0x33, 0xcc, 0xff)));
+        assert_eq!(parse_color("rgb(nope)"), None);
+    }
+}
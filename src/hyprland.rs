@@ -0,0 +1,713 @@
+//! Workspace, focus and window rules, and Hyprland's IPC
+//!
+//! Follows the compositor's workspace and monitor focus changes (see
+//! `compositor`: Hyprland's event socket `.socket2.sock`, Sway's and niri's
+//! event streams, or river's status protocol), and hides the ring per monitor according to the
+//! `[hyprland]` rules in the config. Rule-driven hiding is kept separate from
+//! the user's own per-monitor toggle, so it is never written to the config.
+//!
+//! Workspaces can have a profile, applied while the focused monitor shows
+//! them. Special workspaces (scratchpads, Hyprland only) can dim the ring, hide it or apply a
+//! profile while they are open on top. Window rules match the focused window's class/title and apply a
+//! profile (or hide the ring) until focus moves to a window no rule matches.
+//! The accent mode tints the ring in the focused window's color: one mapped
+//! from its class, or else the active border color (Hyprland only).
+//!
+//! With `[gaming]`, rendering is suspended while a game has focus: a window
+//! whose class matches the list, or a fullscreen one while GameMode is active.
+//!
+//! Screen sharing is followed for the on-air state (Hyprland only).
+//!
+//! With `bar_auto`, the area each monitor reserves for bars and docks is read
+//! from `hyprctl monitors` and handed to the renderer as the ring's margins.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use hypr_ringlight_core::color::{color_to_hex, parse_argb};
+
+use crate::bar::Bar;
+use crate::compositor::{self, Backend, Compositor, Event, Monitor, Window, WorkspaceRef};
+use crate::config::{Config, GamingConfig, HyprlandConfig, Profile, TriggerConfig, WindowRule};
+use crate::ipc::IpcState;
+use crate::trigger::Debouncer;
+
+/// Parse one `EVENT>>DATA` line from socket2
+pub fn parse_event(line: &str) -> Option<Event> {
+    let (name, data) = line.split_once(">>")?;
+    match name {
+        "workspace" | "workspacev2" | "moveworkspace" | "moveworkspacev2" | "createworkspace"
+        | "createworkspacev2" | "destroyworkspace" | "destroyworkspacev2" | "renameworkspace"
+        | "monitoradded" | "monitoraddedv2" | "monitorremoved" | "monitorremovedv2" | "openlayer"
+        | "closelayer" | "activespecial" | "activespecialv2" => Some(Event::Layout),
+        "focusedmon" => {
+            let (monitor, workspace) = data.split_once(',')?;
+            Some(Event::FocusedMonitor {
+                monitor: monitor.to_string(),
+                workspace: workspace.to_string(),
+            })
+        }
+        "activewindow" => {
+            let (class, title) = data.split_once(',').unwrap_or((data, ""));
+            Some(Event::ActiveWindow {
+                class: class.to_string(),
+                title: title.to_string(),
+            })
+        }
+        "fullscreen" => Some(Event::Fullscreen(data == "1")),
+        "configreloaded" => Some(Event::ConfigReloaded),
+        "screencast" => Some(Event::Screencast(data.split(',').next() == Some("1"))),
+        _ => None,
+    }
+}
+
+/// Subset of `hyprctl -j monitors`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HyprMonitor {
+    name: String,
+    focused: bool,
+    active_workspace: WorkspaceRef,
+    /// Open special workspace, with an empty name when there is none
+    #[serde(default)]
+    special_workspace: Option<WorkspaceRef>,
+    /// Space taken by layer surfaces with an exclusive zone: left, top, right, bottom
+    #[serde(default)]
+    reserved: [i32; 4],
+}
+
+impl HyprMonitor {
+    /// Name of the special workspace shown on this monitor, without `special:`
+    fn special(&self) -> Option<&str> {
+        let ws = self.special_workspace.as_ref().filter(|ws| !ws.name.is_empty())?;
+        Some(ws.name.strip_prefix("special:").unwrap_or(&ws.name))
+    }
+
+    fn reserved_bar(&self) -> Bar {
+        let [left, top, right, bottom] = self.reserved;
+        Bar { top, right, bottom, left }
+    }
+
+    fn into_monitor(self) -> Monitor {
+        Monitor {
+            special: self.special().map(str::to_string),
+            reserved: Some(self.reserved_bar()),
+            name: self.name,
+            focused: self.focused,
+            workspace: self.active_workspace,
+        }
+    }
+}
+
+/// Directory holding Hyprland's sockets for the current instance
+fn socket_dir() -> Option<PathBuf> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+    let runtime = std::env::var("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("/tmp"));
+    let dir = runtime.join("hypr").join(&signature);
+    if dir.exists() {
+        Some(dir)
+    } else {
+        // Hyprland < 0.40 kept its sockets in /tmp
+        Some(PathBuf::from("/tmp/hypr").join(signature))
+    }
+}
+
+/// Send a request on the command socket (like `hyprctl`) and return the reply
+fn request(cmd: &str) -> Option<String> {
+    let mut stream = UnixStream::connect(socket_dir()?.join(".socket.sock")).ok()?;
+    stream.set_read_timeout(Some(Duration::from_secs(2))).ok()?;
+    stream.write_all(cmd.as_bytes()).ok()?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).ok()?;
+    Some(reply)
+}
+
+fn query_monitors() -> Option<Vec<HyprMonitor>> {
+    serde_json::from_str(&request("j/monitors")?).ok()
+}
+
+/// `hyprctl -j activewindow`; `{}` when nothing has focus
+fn query_active_window() -> Option<Window> {
+    let value: serde_json::Value = serde_json::from_str(&request("j/activewindow")?).ok()?;
+    let class = value.get("class")?.as_str()?.to_string();
+    let title = value.get("title").and_then(|t| t.as_str()).unwrap_or_default().to_string();
+    // A bool in older Hyprland, a fullscreen mode (0 = none) in newer ones
+    let fullscreen = match value.get("fullscreen") {
+        Some(serde_json::Value::Bool(b)) => *b,
+        Some(v) => v.as_u64().unwrap_or(0) != 0,
+        None => false,
+    };
+    Some(Window { class, title, fullscreen })
+}
+
+/// First color of the active border, from `hyprctl -j getoption general:col.active_border`
+fn query_border_color() -> Option<(u8, u8, u8)> {
+    parse_border_option(&request("j/getoption general:col.active_border")?)
+}
+
+/// Gradients come as `custom: "ee33ccff ee00ff99 45deg"`, colors as AARRGGBB
+fn parse_border_option(reply: &str) -> Option<(u8, u8, u8)> {
+    let value: serde_json::Value = serde_json::from_str(reply).ok()?;
+    let argb = match value.get("custom").and_then(|c| c.as_str()) {
+        Some(custom) => custom.split_whitespace().next()?.to_string(),
+        None => format!("{:08x}", value.get("int")?.as_i64()? as u32),
+    };
+    parse_argb(&argb)
+}
+
+/// Hyprland's sockets
+pub struct Hyprland;
+
+impl Backend for Hyprland {
+    fn subscribe(&self) -> Option<Box<dyn BufRead + Send>> {
+        let stream = UnixStream::connect(socket_dir()?.join(".socket2.sock")).ok()?;
+        Some(Box::new(BufReader::new(stream)))
+    }
+
+    fn parse_event(&self, line: &str) -> Option<Event> {
+        parse_event(line)
+    }
+
+    fn monitors(&self) -> Option<Vec<Monitor>> {
+        Some(query_monitors()?.into_iter().map(HyprMonitor::into_monitor).collect())
+    }
+
+    fn active_window(&self) -> Option<Window> {
+        query_active_window()
+    }
+
+    fn border_color(&self) -> Option<(u8, u8, u8)> {
+        query_border_color()
+    }
+}
+
+/// A window rule with its regexes compiled and its action resolved to a profile
+struct CompiledRule {
+    class: Option<Regex>,
+    title: Option<Regex>,
+    fullscreen: Option<bool>,
+    name: String,
+    profile: Profile,
+}
+
+impl CompiledRule {
+    fn new(rule: &WindowRule, profiles: &BTreeMap<String, Profile>) -> Result<Self, String> {
+        let compile = |pattern: &Option<String>| {
+            pattern.as_deref().map(Regex::new).transpose().map_err(|e| e.to_string())
+        };
+        let (name, mut profile) = match &rule.profile {
+            Some(name) => {
+                let profile = profiles.get(name).ok_or_else(|| format!("unknown profile '{}'", name))?;
+                (name.clone(), profile.clone())
+            }
+            None if rule.hide => ("hide".to_string(), Profile::default()),
+            None => return Err("rule needs a profile or hide = true".to_string()),
+        };
+        if rule.hide {
+            profile.visible = Some(false);
+        }
+        Ok(Self {
+            class: compile(&rule.class)?,
+            title: compile(&rule.title)?,
+            fullscreen: rule.fullscreen,
+            name,
+            profile,
+        })
+    }
+
+    fn matches(&self, window: &Window) -> bool {
+        self.class.as_ref().is_none_or(|re| re.is_match(&window.class))
+            && self.title.as_ref().is_none_or(|re| re.is_match(&window.title))
+            && self.fullscreen.is_none_or(|f| f == window.fullscreen)
+    }
+}
+
+/// Profiles of the `[hyprland.workspaces]` entries that have one
+fn workspace_profiles(cfg: &Config) -> BTreeMap<String, (String, Profile)> {
+    cfg.hyprland.workspaces.iter()
+        .filter(|(_, name)| *name != HIDE)
+        .filter_map(|(workspace, name)| match cfg.profiles.get(name) {
+            Some(profile) => Some((workspace.clone(), (name.clone(), profile.clone()))),
+            None => {
+                log::warn!("Ignoring workspace {}: unknown profile '{}'", workspace, name);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Profiles of the `[hyprland.special]` entries, with "dim" lowering the opacity
+fn special_profiles(cfg: &Config) -> BTreeMap<String, (String, Profile)> {
+    cfg.hyprland.special.iter()
+        .filter(|(_, name)| *name != HIDE)
+        .filter_map(|(special, name)| {
+            let profile = if name == DIM {
+                Some(Profile { opacity: Some(cfg.hyprland.special_dim), ..Default::default() })
+            } else {
+                cfg.profiles.get(name).cloned()
+            };
+            match profile {
+                Some(profile) => Some((special.clone(), (name.clone(), profile))),
+                None => {
+                    log::warn!("Ignoring special workspace {}: unknown profile '{}'", special, name);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Looks up a special workspace by name, then the `*` entry
+fn lookup_special<'a, T>(map: &'a BTreeMap<String, T>, special: &str) -> Option<&'a T> {
+    map.get(special).or_else(|| map.get("*"))
+}
+
+/// Looks up a workspace by name, then by id
+fn lookup<'a, T>(map: &'a BTreeMap<String, T>, ws: &WorkspaceRef) -> Option<&'a T> {
+    map.get(&ws.name).or_else(|| map.get(&ws.id.to_string()))
+}
+
+/// Class regexes of `[gaming]`, skipping invalid ones
+fn compile_game_classes(cfg: &GamingConfig) -> Vec<Regex> {
+    cfg.classes.iter()
+        .filter_map(|class| match Regex::new(class) {
+            Ok(re) => Some(re),
+            Err(e) => {
+                log::warn!("Ignoring game class '{}': {}", class, e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn compile_rules(cfg: &Config) -> Vec<CompiledRule> {
+    cfg.hyprland.window_rules.iter()
+        .enumerate()
+        .filter_map(|(i, rule)| match CompiledRule::new(rule, &cfg.profiles) {
+            Ok(rule) => Some(rule),
+            Err(e) => {
+                log::warn!("Ignoring window rule #{}: {}", i + 1, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Workspace entry hiding the ring rather than applying a profile
+const HIDE: &str = "hide";
+
+/// Special workspace entry lowering the ring's opacity to `special_dim`
+const DIM: &str = "dim";
+
+/// How often the reserved area is re-read; hiding a bar sends no event
+const RESERVED_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Tracks the Hyprland state our rules depend on
+struct Tracker {
+    backend: Box<dyn Backend>,
+    rules: HyprlandConfig,
+    bar_auto: bool,
+    reserved_polled: Instant,
+    trigger: TriggerConfig,
+    state: Arc<IpcState>,
+    monitors: Vec<Monitor>,
+    /// Debounced "hide" decision per monitor, so fast workspace switching doesn't flash the ring
+    hide: HashMap<String, Debouncer>,
+    workspace_profiles: BTreeMap<String, (String, Profile)>,
+    special_profiles: BTreeMap<String, (String, Profile)>,
+    window_rules: Vec<CompiledRule>,
+    window: Option<Window>,
+    /// Active border color, for the accent of windows without their own
+    border: Option<(u8, u8, u8)>,
+    gaming: GamingConfig,
+    game_classes: Vec<Regex>,
+}
+
+impl Tracker {
+    fn refresh_layout(&mut self) {
+        if let Some(monitors) = self.backend.monitors() {
+            self.monitors = monitors;
+        }
+        self.reserved_polled = Instant::now();
+        if self.bar_auto {
+            for m in &self.monitors {
+                if let Some(bar) = m.reserved {
+                    self.state.set_reserved(&m.name, bar);
+                }
+            }
+        }
+    }
+
+    /// Called while the socket is quiet: settle debounces and follow bars being toggled
+    fn tick(&mut self) {
+        if self.bar_auto && self.reserved_polled.elapsed() >= RESERVED_POLL_INTERVAL {
+            self.refresh_layout();
+        }
+        // GameMode starts and stops without a Hyprland event
+        self.apply_gaming();
+        self.apply();
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Layout => {
+                self.refresh_layout();
+                self.apply_workspace_profile();
+                self.apply_special_profile();
+            }
+            Event::FocusedMonitor { monitor, .. } => {
+                for m in &mut self.monitors {
+                    m.focused = m.name == monitor;
+                }
+                // The event only carries the workspace name, re-read to stay exact
+                self.refresh_layout();
+                self.apply_workspace_profile();
+                self.apply_special_profile();
+            }
+            Event::ActiveWindow { class, title } => {
+                // The event lacks the fullscreen state, ask for the full picture
+                self.window = self.backend.active_window().or_else(|| {
+                    (!class.is_empty()).then_some(Window { class, title, fullscreen: false })
+                });
+                self.apply_window_rules();
+                self.apply_accent();
+                self.apply_gaming();
+                return;
+            }
+            Event::ConfigReloaded => {
+                self.refresh_border();
+                self.apply_accent();
+                return;
+            }
+            Event::Screencast(sharing) => {
+                self.state.set_screen_sharing(sharing);
+                return;
+            }
+            Event::Fullscreen(fullscreen) => {
+                if let Some(window) = &mut self.window {
+                    window.fullscreen = fullscreen;
+                }
+                self.apply_window_rules();
+                self.apply_gaming();
+                return;
+            }
+        }
+        self.apply();
+    }
+
+    /// Apply the profile of the workspace on the focused monitor, if it has one
+    fn apply_workspace_profile(&self) {
+        if self.workspace_profiles.is_empty() {
+            return;
+        }
+        let profile = self.monitors.iter()
+            .find(|m| m.focused)
+            .and_then(|m| lookup(&self.workspace_profiles, &m.workspace));
+        self.state.set_override("workspace", profile.cloned());
+    }
+
+    /// Dim the ring or apply a profile while the focused monitor shows a special workspace;
+    /// clearing the override when it closes restores the ring
+    fn apply_special_profile(&self) {
+        if self.special_profiles.is_empty() {
+            return;
+        }
+        let profile = self.monitors.iter()
+            .find(|m| m.focused)
+            .and_then(|m| m.special.as_deref())
+            .and_then(|special| lookup_special(&self.special_profiles, special));
+        self.state.set_override("special", profile.cloned());
+    }
+
+    /// Apply the profile of the first rule matching the focused window, if any
+    fn apply_window_rules(&self) {
+        if self.window_rules.is_empty() {
+            return;
+        }
+        let matched = self.window.as_ref()
+            .and_then(|w| self.window_rules.iter().find(|r| r.matches(w)));
+        self.state.set_override("window", matched.map(|r| (r.name.clone(), r.profile.clone())));
+    }
+
+    fn refresh_border(&mut self) {
+        if self.rules.accent.enabled && self.rules.accent.border {
+            self.border = self.backend.border_color();
+        }
+    }
+
+    /// Tint the ring in the focused window's color; nothing focused leaves it as it was
+    fn apply_accent(&self) {
+        let accent = &self.rules.accent;
+        if !accent.enabled {
+            return;
+        }
+        let color = self.window.as_ref().and_then(|w| {
+            let mapped = accent.colors.iter().find(|(class, _)| class.eq_ignore_ascii_case(&w.class));
+            match mapped {
+                Some((_, color)) => Some((w.class.clone(), color.clone())),
+                None => self.border.filter(|_| accent.border).map(|(r, g, b)| (w.class.clone(), color_to_hex(r, g, b))),
+            }
+        });
+        let profile = color.map(|(class, color)| (class, Profile { color: Some(color), ..Default::default() }));
+        self.state.set_override("accent", profile);
+    }
+
+    /// Suspend rendering while the focused window is a game, resume once it isn't
+    fn apply_gaming(&self) {
+        if !self.gaming.enabled {
+            return;
+        }
+        #[cfg(feature = "gamemode")]
+        let gamemode = self.gaming.gamemode && self.state.is_gamemode();
+        #[cfg(not(feature = "gamemode"))]
+        let gamemode = false;
+        let gaming = self.window.as_ref().is_some_and(|w| {
+            (w.fullscreen && gamemode)
+                || self.game_classes.iter().any(|re| re.is_match(&w.class))
+        });
+        if gaming != self.state.is_gaming() {
+            let what = if gaming { "game running, rendering suspended" } else { "game over, rendering resumed" };
+            log::info!("{}", what);
+            self.state.log_event("hyprland", what);
+            self.state.set_gaming(gaming);
+        }
+    }
+
+    /// Evaluate the rules for every monitor and push the debounced result into IpcState.
+    /// Also called periodically so pending debounces complete without new events.
+    fn apply(&mut self) {
+        let follow_focus = self.state.is_follow_focus();
+        // Keep going once anything was hidden, so turning focus-follow off shows the ring again
+        if !self.rules.is_active() && !follow_focus && self.hide.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        for m in &self.monitors {
+            let ws = &m.workspace;
+            let hidden_workspace = self.rules.hide_on_workspaces.iter()
+                .any(|w| *w == ws.name || *w == ws.id.to_string())
+                || lookup(&self.rules.workspaces, ws).is_some_and(|p| p == HIDE)
+                || m.special.as_deref().and_then(|s| lookup_special(&self.rules.special, s)).is_some_and(|p| p == HIDE);
+            let unfocused = follow_focus && !m.focused;
+            
+            let debouncer = self.hide.entry(m.name.clone())
+                .or_insert_with(|| Debouncer::new(format!("hyprland:{}", m.name), &self.trigger));
+            if let Some(hidden) = debouncer.update(hidden_workspace || unfocused, now) {
+                let what = if hidden { "hidden" } else { "shown" };
+                self.state.log_event("hyprland", format!("ring {} on {}", what, m.name));
+            }
+            self.state.set_monitor_suppressed(&m.name, debouncer.is_active());
+            self.state.publish_trigger(debouncer.stats(now));
+        }
+    }
+}
+
+/// Start following the compositor's events in a background thread.
+///
+/// Does nothing under compositors without an IPC we speak. Runs even without
+/// rules, since focus-follow can be turned on at runtime. Reconnects if the
+/// compositor restarts.
+pub fn start_listener(state: Arc<IpcState>, cfg: &Config) {
+    let compositor = Compositor::detect();
+    let Some(backend) = compositor::backend(compositor) else {
+        log::info!("No IPC to follow under {}, workspace and window rules disabled", compositor.name());
+        return;
+    };
+    let mut tracker = Tracker {
+        backend,
+        rules: cfg.hyprland.clone(),
+        bar_auto: cfg.bar_auto,
+        reserved_polled: Instant::now(),
+        trigger: cfg.triggers.hyprland.clone(),
+        state,
+        monitors: Vec::new(),
+        hide: HashMap::new(),
+        workspace_profiles: workspace_profiles(cfg),
+        special_profiles: special_profiles(cfg),
+        window_rules: compile_rules(cfg),
+        window: None,
+        border: None,
+        gaming: cfg.gaming.clone(),
+        game_classes: compile_game_classes(&cfg.gaming),
+    };
+    std::thread::spawn(move || {
+        loop {
+            let Some(mut stream) = tracker.backend.subscribe() else {
+                std::thread::sleep(Duration::from_secs(5));
+                continue;
+            };
+
+            tracker.refresh_layout();
+            tracker.apply();
+            tracker.apply_workspace_profile();
+            tracker.apply_special_profile();
+            tracker.window = tracker.backend.active_window();
+            tracker.apply_window_rules();
+            tracker.refresh_border();
+            tracker.apply_accent();
+            tracker.apply_gaming();
+
+            // Lines are read on a thread of their own, so the rules can wake up
+            // regularly to let pending debounces settle; some streams are pipes
+            // from a child process, which can't time out
+            let (sender, lines) = mpsc::channel();
+            std::thread::spawn(move || {
+                let mut line = String::new();
+                while matches!(stream.read_line(&mut line), Ok(n) if n > 0) {
+                    if sender.send(std::mem::take(&mut line)).is_err() {
+                        break;
+                    }
+                }
+            });
+            loop {
+                match lines.recv_timeout(Duration::from_millis(250)) {
+                    Ok(line) => {
+                        if let Some(event) = tracker.backend.parse_event(line.trim_end()) {
+                            tracker.handle(event);
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => tracker.tick(),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+
+            // A share can't be followed without the events
+            tracker.state.set_screen_sharing(false);
+            log::warn!("Lost the {} event stream, retrying", compositor.name());
+            std::thread::sleep(Duration::from_secs(5));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event() {
+        assert_eq!(parse_event("workspace>>3"), Some(Event::Layout));
+        assert_eq!(
+            parse_event("focusedmon>>DP-2,5"),
+            Some(Event::FocusedMonitor { monitor: "DP-2".into(), workspace: "5".into() })
+        );
+        assert_eq!(
+            parse_event("activewindow>>firefox,Mozilla Firefox, with commas"),
+            Some(Event::ActiveWindow { class: "firefox".into(), title: "Mozilla Firefox, with commas".into() })
+        );
+        assert_eq!(parse_event("fullscreen>>1"), Some(Event::Fullscreen(true)));
+        assert_eq!(parse_event("closelayer>>waybar"), Some(Event::Layout));
+        assert_eq!(parse_event("activespecial>>special:term,DP-1"), Some(Event::Layout));
+        assert_eq!(parse_event("configreloaded>>"), Some(Event::ConfigReloaded));
+        assert_eq!(parse_event("screencast>>1,0"), Some(Event::Screencast(true)));
+        assert_eq!(parse_event("openwindow>>abc,1,kitty,term"), None);
+        assert_eq!(parse_event("garbage"), None);
+    }
+
+    #[test]
+    fn test_workspace_profiles() {
+        let cfg: Config = toml::from_str(r#"
+[profiles.live]
+color = "ff0000"
+
+[hyprland.workspaces]
+stream = "live"
+9 = "hide"
+4 = "missing"
+"#).unwrap();
+        let profiles = workspace_profiles(&cfg);
+        assert_eq!(profiles.len(), 1);
+
+        let ws = |id: i64, name: &str| WorkspaceRef { id, name: name.into() };
+        assert_eq!(lookup(&profiles, &ws(7, "stream")).map(|(n, _)| n.as_str()), Some("live"));
+        assert_eq!(lookup(&cfg.hyprland.workspaces, &ws(9, "9")).map(|p| p.as_str()), Some(HIDE));
+        assert!(lookup(&profiles, &ws(4, "4")).is_none());
+    }
+
+    #[test]
+    fn test_special_profiles() {
+        let cfg: Config = toml::from_str(r#"
+[profiles.notes]
+color = "00ff00"
+
+[hyprland]
+special_dim = 0.2
+
+[hyprland.special]
+notes = "notes"
+music = "hide"
+"*" = "dim"
+"#).unwrap();
+        let profiles = special_profiles(&cfg);
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(lookup_special(&profiles, "notes").map(|(n, _)| n.as_str()), Some("notes"));
+        assert_eq!(lookup_special(&profiles, "term").and_then(|(_, p)| p.opacity), Some(0.2));
+        assert_eq!(lookup_special(&cfg.hyprland.special, "music").map(|p| p.as_str()), Some(HIDE));
+
+        let monitor: HyprMonitor = serde_json::from_str(r#"{
+            "name": "DP-1", "focused": true,
+            "activeWorkspace": {"id": 1, "name": "1"},
+            "specialWorkspace": {"id": -98, "name": "special:term"}
+        }"#).unwrap();
+        assert_eq!(monitor.special(), Some("term"));
+        let closed = HyprMonitor { special_workspace: Some(WorkspaceRef { id: 0, name: String::new() }), ..monitor.clone() };
+        assert_eq!(closed.special(), None);
+        assert_eq!(monitor.into_monitor().special.as_deref(), Some("term"));
+    }
+
+    #[test]
+    fn test_parse_border_option() {
+        let gradient = r#"{"option": "general:col.active_border", "custom": "ee33ccff ee00ff99 45deg", "set": true}"#;
+        assert_eq!(parse_border_option(gradient), Some((0x33, 0xcc, 0xff)));
+        assert_eq!(parse_border_option(r#"{"option": "x", "int": -1426063361}"#), Some((0xff, 0xff, 0xff)));
+        assert_eq!(parse_border_option(r#"{"option": "x", "custom": "45deg"}"#), None);
+    }
+
+    #[test]
+    fn test_game_classes() {
+        let cfg = GamingConfig { classes: vec!["^steam_app_".into(), "(".into()], ..Default::default() };
+        let classes = compile_game_classes(&cfg);
+        assert_eq!(classes.len(), 1);
+        assert!(classes[0].is_match("steam_app_1145360"));
+        assert!(!classes[0].is_match("steam"));
+    }
+
+    #[test]
+    fn test_window_rules() {
+        let cfg: Config = toml::from_str(r#"
+[profiles.call]
+thickness = 120
+
+[[hyprland.window_rules]]
+class = "mpv"
+fullscreen = true
+hide = true
+
+[[hyprland.window_rules]]
+class = "(?i)^zoom"
+profile = "call"
+
+[[hyprland.window_rules]]
+class = "firefox"
+profile = "missing"
+"#).unwrap();
+        let rules = compile_rules(&cfg);
+        assert_eq!(rules.len(), 2);
+
+        let window = |class: &str, fullscreen| Window { class: class.into(), title: String::new(), fullscreen };
+        let first_match = |w: &Window| rules.iter().find(|r| r.matches(w)).map(|r| r.name.as_str());
+        assert_eq!(first_match(&window("mpv", true)), Some("hide"));
+        assert_eq!(first_match(&window("mpv", false)), None);
+        assert_eq!(first_match(&window("Zoom Meeting", false)), Some("call"));
+        assert_eq!(rules[0].profile.visible, Some(false));
+    }
+}
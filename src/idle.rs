@@ -0,0 +1,123 @@
+//! Idle detection
+//!
+//! Uses the ext-idle-notify-v1 protocol: the compositor tells us once the
+//! user has been idle for the configured timeout, and again on activity.
+//! Idle inhibitors (a playing video, a call) keep the ring lit. The renderer
+//! fades the ring towards the idle brightness so an unattended screen isn't
+//! left with a bright static frame burning into an OLED panel.
+
+use std::time::{Duration, Instant};
+
+use wayland_client::{globals::GlobalList, protocol::wl_seat, Connection, Dispatch, QueueHandle};
+use wayland_protocols::ext::idle_notify::v1::client::{ext_idle_notification_v1, ext_idle_notifier_v1};
+
+use crate::config::IdleConfig;
+use crate::RingLight;
+
+/// Ask the compositor to report idleness on `seat` after `timeout`.
+///
+/// Returns `None` if the compositor lacks ext-idle-notify-v1. The
+/// notification lives as long as the returned object.
+pub fn watch(
+    globals: &GlobalList,
+    qh: &QueueHandle<RingLight>,
+    seat: &wl_seat::WlSeat,
+    timeout: Duration,
+) -> Option<ext_idle_notification_v1::ExtIdleNotificationV1> {
+    let notifier: ext_idle_notifier_v1::ExtIdleNotifierV1 = globals.bind(qh, 1..=1, ()).ok()?;
+    let ms = timeout.as_millis().min(u32::MAX as u128) as u32;
+    Some(notifier.get_idle_notification(ms, seat, qh, ()))
+}
+
+impl Dispatch<ext_idle_notifier_v1::ExtIdleNotifierV1, ()> for RingLight {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ext_idle_notifier_v1::ExtIdleNotifierV1,
+        _event: ext_idle_notifier_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {}
+}
+
+impl Dispatch<ext_idle_notification_v1::ExtIdleNotificationV1, ()> for RingLight {
+    fn event(
+        state: &mut Self,
+        _proxy: &ext_idle_notification_v1::ExtIdleNotificationV1,
+        event: ext_idle_notification_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            ext_idle_notification_v1::Event::Idled => {
+                log::info!("User idle, fading out");
+                state.state.ipc.set_idle(true);
+                state.state.ipc.log_event("idle", "user idle");
+            }
+            ext_idle_notification_v1::Event::Resumed => {
+                log::info!("User active again, fading in");
+                state.state.ipc.set_idle(false);
+                state.state.ipc.log_event("idle", "user active");
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Brightness factor that follows the idle state at a fixed fade rate
+pub struct IdleFade {
+    level: f64,
+    updated: Instant,
+    /// Brightness while idle
+    brightness: f64,
+    fade: Duration,
+}
+
+impl IdleFade {
+    pub fn new(cfg: &IdleConfig) -> Self {
+        Self {
+            level: 1.0,
+            updated: Instant::now(),
+            brightness: cfg.brightness.clamp(0.0, 1.0),
+            fade: cfg.fade(),
+        }
+    }
+
+    /// Advance the fade to `now` and return the brightness factor
+    pub fn step(&mut self, idle: bool, now: Instant) -> f64 {
+        let dt = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.updated = now;
+
+        let target = if idle { self.brightness } else { 1.0 };
+        // Covering the whole 0..1 range takes `fade`
+        let step = if self.fade.is_zero() { 1.0 } else { dt / self.fade.as_secs_f64() };
+        self.level = if self.level < target {
+            (self.level + step).min(target)
+        } else {
+            (self.level - step).max(target)
+        };
+        self.level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_fade() {
+        let cfg = IdleConfig { timeout: "5m".into(), brightness: 0.2, fade: "2s".into() };
+        let mut fade = IdleFade::new(&cfg);
+        let t0 = fade.updated;
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+
+        assert_eq!(fade.step(false, at(100)), 1.0);
+        assert!((fade.step(true, at(1100)) - 0.5).abs() < 1e-9);
+        // Settles at the idle brightness
+        assert_eq!(fade.step(true, at(5000)), 0.2);
+        // Fades back in at the same rate
+        assert!((fade.step(false, at(6000)) - 0.7).abs() < 1e-9);
+        assert_eq!(fade.step(false, at(9000)), 1.0);
+    }
+}
@@ -0,0 +1,88 @@
+//! Optional integrations started next to the renderer
+//!
+//! Each one runs on its own thread and only talks to the renderer through
+//! `IpcState`, so a build without its cargo feature simply doesn't start it.
+
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::SharedState;
+
+/// Start every integration built in. Called once the monitors are known.
+pub fn start(state: &Arc<SharedState>, cfg: &Config) {
+    #[cfg(feature = "tray")]
+    crate::tray::start(state.clone());
+
+    // Follow theme changes, if the ring takes its color from the theme
+    #[cfg(feature = "theme")]
+    {
+        crate::theme::start_reload_on_signal(state.ipc.clone(), cfg.theme.clone());
+        if cfg.theme.watch && cfg.color == "ffffff" {
+            crate::theme::start_theme_watcher(state.ipc.clone(), cfg.theme.clone());
+        }
+    }
+
+    // Switch profiles with the system's light/dark preference
+    #[cfg(feature = "color-scheme")]
+    crate::colorscheme::start_color_scheme_monitor(state.ipc.clone(), cfg.color_scheme.clone(), cfg.profiles.clone());
+
+    // Follow what the media players are doing
+    #[cfg(feature = "mpris")]
+    crate::mpris::start_media_monitor(state.ipc.clone(), cfg.media.clone(), cfg.profiles.clone());
+
+    // Feed the spectrum animation from cava
+    #[cfg(feature = "spectrum")]
+    crate::spectrum::start_spectrum(state.ipc.clone(), cfg.spectrum.clone());
+
+    // Follow the notification daemon's Do-Not-Disturb
+    #[cfg(feature = "dnd")]
+    crate::dnd::start_dnd_monitor(state.ipc.clone(), cfg.dnd.clone(), cfg.profiles.clone());
+
+    // Sample the load or temperature for the system animation
+    crate::sysmon::start_system_monitor(state.ipc.clone(), cfg.system.clone());
+
+    // Control over HTTP, if enabled
+    #[cfg(feature = "http")]
+    crate::http::start_server(state.ipc.clone(), cfg.http.clone());
+
+    // Mirror the ring's color on RGB peripherals
+    #[cfg(feature = "openrgb")]
+    crate::openrgb::start_openrgb(state.ipc.clone(), cfg.openrgb.clone());
+
+    // Show up in Home Assistant as a light
+    #[cfg(feature = "mqtt")]
+    crate::mqtt::start_mqtt(state.ipc.clone(), cfg.mqtt.clone());
+
+    // Follow OBS recording and streaming
+    #[cfg(feature = "obs")]
+    crate::obs::start_obs(state.ipc.clone(), cfg.obs.clone(), cfg.profiles.clone());
+
+    // Apply Hyprland workspace/focus rules
+    #[cfg(feature = "hyprland")]
+    crate::hyprland::start_listener(state.ipc.clone(), cfg);
+
+    // Tell when GameMode is active, for game detection
+    #[cfg(feature = "gamemode")]
+    crate::gamemode::start_gamemode_monitor(state.ipc.clone(), &cfg.gaming);
+
+    // Keys for the ring's actions, bound through the desktop portal
+    #[cfg(feature = "shortcuts")]
+    crate::shortcuts::start_shortcuts(state.ipc.clone(), cfg.shortcuts.clone(), cfg.profiles.clone());
+
+    // Pause rendering while another session owns the screen
+    #[cfg(feature = "logind")]
+    crate::session::start_session_monitor(state.ipc.clone());
+
+    // Start camera monitor for video call notifications
+    #[cfg(feature = "camera")]
+    crate::camera::start_camera_monitor(state.ipc.clone(), cfg.camera.clone(), cfg.triggers.camera.clone(), cfg.dnd.mute_camera);
+
+    // Hide the ring (or show the lock color) while the screen is locked
+    crate::lock::start_lock_monitor(state.ipc.clone(), cfg.lock.clone());
+
+    // One on-air state from the camera, screen sharing and the microphone
+    crate::on_air::start_on_air(state.ipc.clone(), cfg.on_air.clone(), cfg.profiles.clone());
+
+    // Save power on a low battery
+    crate::battery::start_battery_monitor(state.ipc.clone(), cfg.battery.clone());
+}
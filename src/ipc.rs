@@ -1,61 +1,154 @@
-use std::io::{BufRead, BufReader, Write};
+//! IPC server: the daemon's shared state and the socket that modifies it
+//!
+//! The protocol itself lives in `hypr_ringlight_core::ipc`.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
 use std::os::unix::net::{UnixListener, UnixStream};
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
-use std::sync::{Arc, RwLock};
-use serde::{Deserialize, Serialize};
-
-/// Socket path
-pub fn socket_path() -> PathBuf {
-    std::env::var("XDG_RUNTIME_DIR")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("/tmp"))
-        .join("hypr-ringlight.sock")
-}
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-/// Commands that can be sent via IPC
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "cmd", content = "value")]
-pub enum Command {
-    SetColor(String),
-    SetThickness(u32),
-    SetOpacity(f64),
-    SetGlow(u32),
-    SetCornerRadius(f64),
-    SetAnimation(String),
-    SetAnimationSpeed(u32),
-    SetVisible(bool),
-    GetState,
-    GetMonitors,
-    SetMonitorEnabled { id: String, enabled: bool },
-    Quit,
-}
+use hypr_ringlight_core::color::{color_to_hex, color_to_hex_alpha, is_color, parse_color_alpha, resolve_color, resolve_color_alpha};
+use hypr_ringlight_core::ipc::{
+    socket_path, Command, EventsResponse, MonitorState, MonitorsResponse, Notice, RenderStats, SceneResponse, State,
+    Stats, TriggerStats, PROTOCOL_VERSION,
+};
+use hypr_ringlight_core::render::{animation_from_name, animation_name, Segment};
+use hypr_ringlight_core::scene::Scene;
+use smithay_client_toolkit::reexports::calloop::ping::Ping;
+
+use crate::bar::Bar;
+use crate::config::{
+    format_cycle_duration, frames_to_ms, parse_cycle_duration, parse_duration_ms, BarPosition, Config, CornerStyle,
+    MonitorConfig, OnAir, Pattern, Profile, RampCurve, RingLayer, StrobeConfig, WatchConfig, MIN_CYCLE_MS,
+};
+use crate::events::EventLog;
+use crate::strobe::{self, Strobe};
+use crate::timer::Timer;
+use crate::watch::{self, Watch};
 
-/// Response from the server
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct State {
-    pub color: String,
+/// The part of the state a profile can change
+#[derive(Debug, Clone, PartialEq)]
+pub struct Appearance {
+    pub color: (u8, u8, u8),
+    /// Alpha of the color, like "ff880080"; drawn as a lower `opacity`
+    pub alpha: f64,
+    /// Palette name `color` came from, so it follows changes to the palette
+    pub color_name: Option<String>,
     pub thickness: u32,
     pub opacity: f64,
     pub glow: u32,
     pub corner_radius: f64,
-    pub animation: String,
-    pub animation_speed: u32,
+    pub animation: u8,
+    pub cycle_ms: u32,
+    pub pattern: Pattern,
     pub visible: bool,
 }
 
-/// Monitor info for IPC
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MonitorState {
-    pub id: String,
-    pub display_name: String,
-    pub enabled: bool,
+impl Appearance {
+    /// Overwrite the fields the profile sets, looking color names up in `palette`
+    pub fn apply(&mut self, profile: &Profile, palette: &BTreeMap<String, String>) {
+        if let Some(color) = &profile.color {
+            (self.color, self.alpha) = resolve_color_alpha(color, palette);
+            self.color_name = palette.contains_key(color).then(|| color.clone());
+        }
+        if let Some(thickness) = profile.thickness {
+            self.thickness = thickness;
+        }
+        if let Some(opacity) = profile.opacity {
+            self.opacity = opacity.clamp(0.0, 1.0);
+        }
+        if let Some(glow) = profile.glow {
+            self.glow = glow;
+        }
+        if let Some(radius) = profile.corner_radius {
+            self.corner_radius = radius;
+        }
+        if let Some(animation) = &profile.animation {
+            self.animation = animation_from_name(animation);
+        }
+        if let Some(ms) = profile.cycle_duration.as_deref().and_then(parse_cycle_duration) {
+            self.cycle_ms = ms;
+        }
+        if let Some(pattern) = &profile.pattern {
+            self.pattern = Pattern::from_name(pattern);
+        }
+        if let Some(visible) = profile.visible {
+            self.visible = visible;
+        }
+    }
+
+    /// What differs in `to`, e.g. "color ff0000 -> ff8800"
+    pub fn changes(&self, to: &Appearance) -> Vec<String> {
+        let fields = [
+            ("color", color_to_hex_alpha(self.color, self.alpha), color_to_hex_alpha(to.color, to.alpha)),
+            ("thickness", self.thickness.to_string(), to.thickness.to_string()),
+            ("opacity", format!("{:.2}", self.opacity), format!("{:.2}", to.opacity)),
+            ("glow", self.glow.to_string(), to.glow.to_string()),
+            ("corner_radius", self.corner_radius.to_string(), to.corner_radius.to_string()),
+            ("animation", animation_name(self.animation).to_string(), animation_name(to.animation).to_string()),
+            ("cycle_duration", format_cycle_duration(self.cycle_ms), format_cycle_duration(to.cycle_ms)),
+            ("pattern", self.pattern.name().to_string(), to.pattern.name().to_string()),
+            ("visible", self.visible.to_string(), to.visible.to_string()),
+        ];
+        fields
+            .into_iter()
+            .filter(|(_, from, to)| from != to)
+            .map(|(name, from, to)| format!("{} {} -> {}", name, from, to))
+            .collect()
+    }
 }
 
-/// Response with monitors list
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MonitorsResponse {
-    pub monitors: Vec<MonitorState>,
+/// Sources that can apply a profile, lowest priority first
+const OVERRIDE_SOURCES: &[&str] = &["scheme", "media", "accent", "workspace", "special", "window", "manual", "on-air", "obs", "dnd", "battery", "watch", "flash", "lock"];
+
+/// A profile applied on behalf of an automatic source (e.g. window rules)
+#[derive(Debug, Clone, PartialEq)]
+struct Override {
+    source: String,
+    name: String,
+    profile: Profile,
+}
+
+/// Active overrides, plus the appearance to go back to once they are all gone
+#[derive(Debug, Default)]
+struct Overrides {
+    base: Option<Appearance>,
+    layers: Vec<Override>,
+}
+
+/// Frames kept for the average render time
+const RENDER_WINDOW: usize = 120;
+
+/// How often a subscriber's state is compared against what it was last sent
+const SUBSCRIBE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Render timings of one monitor, for `GetStats`
+#[derive(Debug, Default)]
+struct FrameStats {
+    size: (u32, u32),
+    buffer_bytes: usize,
+    frames: u64,
+    fps: f64,
+    recent: VecDeque<Duration>,
+    max: Duration,
+}
+
+impl FrameStats {
+    fn to_stats(&self, monitor: &str) -> RenderStats {
+        let total: Duration = self.recent.iter().sum();
+        RenderStats {
+            monitor: monitor.to_string(),
+            width: self.size.0,
+            height: self.size.1,
+            buffer_bytes: self.buffer_bytes as u64,
+            frames: self.frames,
+            fps: self.fps,
+            avg_render_us: (total / self.recent.len().max(1) as u32).as_micros() as u64,
+            max_render_us: self.max.as_micros() as u64,
+        }
+    }
 }
 
 /// Shared state that can be modified via IPC
@@ -63,46 +156,213 @@ pub struct IpcState {
     pub color_r: AtomicU8,
     pub color_g: AtomicU8,
     pub color_b: AtomicU8,
+    color_alpha: AtomicU32, // stored as alpha * 1000
     pub thickness: AtomicU32,
     pub opacity: AtomicU32, // stored as opacity * 1000
     pub glow: AtomicU32,
     pub corner_radius: AtomicU32, // stored as radius * 1000
+    pub corner_style: AtomicU8,
+    /// Per-corner radius multipliers, overriding `corner_radius` when set
+    corner_radii: RwLock<Option<[f64; 4]>>,
     pub animation_mode: AtomicU8,
-    pub animation_speed: AtomicU32,
+    pub cycle_ms: AtomicU32,
+    /// Warm-up ramp duration in milliseconds (0 = off)
+    pub warmup_ms: AtomicU32,
+    pub warmup_curve: AtomicU8,
+    pattern: AtomicU8,
     pub visible: std::sync::atomic::AtomicBool,
+    /// Draw tick marks and monitor labels into the ring (not persisted)
+    pub debug_overlay: AtomicBool,
+    /// Framing guide turned on by hand (not persisted)
+    pub framing_guide: AtomicBool,
+    /// Opaque backdrop for screenshots (not persisted)
+    solid_backdrop: AtomicBool,
+    /// Set when the compositor has no buffer format with alpha
+    no_alpha: AtomicBool,
+    /// Blend the glow in linear light
+    pub gamma_correct: AtomicBool,
+    pub vignette: AtomicBool,
+    pub fill: AtomicBool,
+    /// Image filling the ring band, empty for the flat color
+    texture: RwLock<String>,
+    bar_height: AtomicU32,
+    bar_position: RwLock<BarPosition>,
+    layer: AtomicU8,
+    /// Whether a webcam is currently in use (updated by the camera monitor)
+    pub camera_active: AtomicBool,
+    /// Whether the screen or a window is being shared (Hyprland)
+    screen_sharing: AtomicBool,
+    /// What puts the user on air, from the on-air monitor
+    on_air: RwLock<Option<OnAir>>,
+    /// Camera notifications are held back until then
+    camera_snoozed_until: Mutex<Option<Instant>>,
+    /// Whether the compositor reports the user as idle
+    idle: AtomicBool,
+    /// Input seen within `typing.pause`, for the typing glow
+    typing: AtomicBool,
+    /// Whether our login session is in the foreground (false after a VT/user switch)
+    session_active: AtomicBool,
+    /// Whether the laptop lid is closed (from logind)
+    lid_closed: AtomicBool,
+    /// Whether we're on a low battery and saving power
+    battery_saving: AtomicBool,
+    /// Whether GameMode has clients, polled over D-Bus
+    #[cfg(feature = "gamemode")]
+    gamemode: AtomicBool,
+    /// Whether the notification daemon is in Do-Not-Disturb
+    dnd: AtomicBool,
+    /// Whether a game has focus; rendering is suspended meanwhile
+    gaming: AtomicBool,
+    timer: Mutex<Option<Timer>>,
+    countdown: Mutex<Option<Timer>>,
+    /// Countdowns that ran out, for subscribers to notice each one
+    countdowns_finished: AtomicU64,
+    strobe: Mutex<Option<Strobe>>,
+    strobe_config: StrobeConfig,
+    watch: Mutex<Option<Watch>>,
+    /// Render timings by monitor
+    frame_stats: Mutex<BTreeMap<String, FrameStats>>,
+    /// Recent state changes, for `GetEvents`
+    events: Mutex<EventLog>,
+    /// Set by `Quit`, the tray or a signal; the event loop shuts down
+    quit: AtomicBool,
+    /// Wakes the event loop to notice `quit`
+    waker: Mutex<Option<Ping>>,
+    /// Held while writing the config, so shutdown doesn't cut a write short
+    config_lock: Mutex<()>,
+    watch_config: WatchConfig,
+    #[cfg(all(feature = "theme", feature = "tray"))]
+    theme_config: crate::config::ThemeConfig,
+    /// Progress bar value, `None` for a full ring
+    progress: RwLock<Option<f64>>,
+    /// Text in the top band (not persisted)
+    label: RwLock<String>,
+    /// Ring colors of single monitors, set by scenes (not persisted)
+    monitor_colors: RwLock<BTreeMap<String, (u8, u8, u8)>>,
+    /// Stretches of the ring in colors of their own, set by scenes (not persisted)
+    segments: RwLock<Vec<Segment>>,
+    /// Audio band levels for the spectrum animation, empty when not running
+    spectrum: RwLock<Vec<f64>>,
+    /// Level of the system gauge, stored as level * 1000
+    system_level: AtomicU32,
+    /// Number of the latest flash, so an older one doesn't end a newer one
+    flash: AtomicU64,
+    /// Color and opacity of the last frame drawn, as 0xRRGGBBAA
+    displayed: AtomicU32,
     /// Monitors list (id, display_name, enabled)
     pub monitors: RwLock<Vec<(String, String, bool)>>,
     /// List of monitor IDs that should be disabled (from config)
     disabled_monitors: RwLock<Vec<String>>,
+    /// Show the ring only on the focused monitor (`hyprland.focused_monitor_only`)
+    follow_focus: AtomicBool,
+    /// Monitor IDs hidden by automatic rules (Hyprland workspaces, focus).
+    /// Kept apart from `monitors` so rules never end up in the saved config.
+    suppressed_monitors: RwLock<Vec<String>>,
+    /// Space the compositor reserves for bars, per monitor (with `bar_auto`)
+    reserved: RwLock<BTreeMap<String, Bar>>,
+    /// Room left for bars on each monitor's ring, as last applied
+    bars: RwLock<BTreeMap<String, Bar>>,
+    /// `[monitor."ID"]` settings
+    monitor_config: BTreeMap<String, MonitorConfig>,
+    /// Named colors from `[palette]`
+    palette: RwLock<BTreeMap<String, String>>,
+    /// Palette name the ring color follows, if it was set by name
+    color_name: RwLock<Option<String>>,
+    /// Latest debounce state of each automatic trigger
+    trigger_stats: RwLock<Vec<TriggerStats>>,
+    overrides: Mutex<Overrides>,
+    /// Held for writing while a `Batch` applies, and for reading while a frame
+    /// takes its values, so no frame shows half a batch
+    frame_lock: RwLock<()>,
 }
 
 impl IpcState {
-    pub fn new(
-        color: (u8, u8, u8),
-        thickness: u32,
-        opacity: f64,
-        glow: u32,
-        corner_radius: f64,
-        animation: u8,
-        animation_speed: u32,
-        disabled_monitors: Vec<String>,
-    ) -> Self {
+    /// Build the live state from a loaded config; `color` is passed separately
+    /// because it may come from the theme instead of the config file.
+    pub fn new(cfg: &Config, color: (u8, u8, u8)) -> Self {
         Self {
             color_r: AtomicU8::new(color.0),
             color_g: AtomicU8::new(color.1),
             color_b: AtomicU8::new(color.2),
-            thickness: AtomicU32::new(thickness),
-            opacity: AtomicU32::new((opacity * 1000.0) as u32),
-            glow: AtomicU32::new(glow),
-            corner_radius: AtomicU32::new((corner_radius * 1000.0) as u32),
-            animation_mode: AtomicU8::new(animation),
-            animation_speed: AtomicU32::new(animation_speed),
+            color_alpha: AtomicU32::new((resolve_color_alpha(&cfg.color, &cfg.palette).1 * 1000.0) as u32),
+            thickness: AtomicU32::new(cfg.thickness),
+            opacity: AtomicU32::new((cfg.opacity * 1000.0) as u32),
+            glow: AtomicU32::new(cfg.glow),
+            corner_radius: AtomicU32::new((cfg.corner_radius * 1000.0) as u32),
+            corner_style: AtomicU8::new(cfg.corner_style_enum() as u8),
+            corner_radii: RwLock::new(cfg.corner_radii),
+            animation_mode: AtomicU8::new(cfg.animation_mode()),
+            cycle_ms: AtomicU32::new(cfg.cycle_ms()),
+            warmup_ms: AtomicU32::new(cfg.warmup_ms()),
+            warmup_curve: AtomicU8::new(cfg.warmup_curve_enum() as u8),
+            pattern: AtomicU8::new(cfg.pattern_enum() as u8),
             visible: std::sync::atomic::AtomicBool::new(true),
+            debug_overlay: AtomicBool::new(false),
+            framing_guide: AtomicBool::new(false),
+            solid_backdrop: AtomicBool::new(false),
+            no_alpha: AtomicBool::new(false),
+            gamma_correct: AtomicBool::new(cfg.gamma_correct),
+            vignette: AtomicBool::new(cfg.vignette.enabled),
+            fill: AtomicBool::new(cfg.fill.enabled),
+            texture: RwLock::new(cfg.texture.path.clone()),
+            bar_height: AtomicU32::new(cfg.bar_height),
+            bar_position: RwLock::new(cfg.bar_position_enum()),
+            layer: AtomicU8::new(cfg.layer_enum() as u8),
+            camera_active: AtomicBool::new(false),
+            screen_sharing: AtomicBool::new(false),
+            on_air: RwLock::new(None),
+            camera_snoozed_until: Mutex::new(None),
+            idle: AtomicBool::new(false),
+            typing: AtomicBool::new(false),
+            session_active: AtomicBool::new(true),
+            lid_closed: AtomicBool::new(false),
+            battery_saving: AtomicBool::new(false),
+            #[cfg(feature = "gamemode")]
+            gamemode: AtomicBool::new(false),
+            dnd: AtomicBool::new(false),
+            gaming: AtomicBool::new(false),
+            timer: Mutex::new(None),
+            countdown: Mutex::new(None),
+            countdowns_finished: AtomicU64::new(0),
+            strobe: Mutex::new(None),
+            strobe_config: cfg.strobe.clone(),
+            frame_stats: Mutex::new(BTreeMap::new()),
+            events: Mutex::new(EventLog::default()),
+            quit: AtomicBool::new(false),
+            waker: Mutex::new(None),
+            config_lock: Mutex::new(()),
+            watch: Mutex::new(None),
+            watch_config: cfg.watch.clone(),
+            #[cfg(all(feature = "theme", feature = "tray"))]
+            theme_config: cfg.theme.clone(),
+            progress: RwLock::new(None),
+            label: RwLock::new(cfg.label.text.clone()),
+            monitor_colors: RwLock::new(BTreeMap::new()),
+            segments: RwLock::new(Vec::new()),
+            spectrum: RwLock::new(Vec::new()),
+            system_level: AtomicU32::new(0),
+            flash: AtomicU64::new(0),
+            displayed: AtomicU32::new(0),
             monitors: RwLock::new(Vec::new()),
-            disabled_monitors: RwLock::new(disabled_monitors),
+            disabled_monitors: RwLock::new(cfg.disabled_monitors.clone()),
+            follow_focus: AtomicBool::new(cfg.hyprland.focused_monitor_only),
+            suppressed_monitors: RwLock::new(Vec::new()),
+            reserved: RwLock::new(BTreeMap::new()),
+            bars: RwLock::new(BTreeMap::new()),
+            monitor_config: cfg.monitor.clone(),
+            palette: RwLock::new(cfg.palette.clone()),
+            color_name: RwLock::new(cfg.palette.contains_key(&cfg.color).then(|| cfg.color.clone())),
+            trigger_stats: RwLock::new(Vec::new()),
+            overrides: Mutex::new(Overrides::default()),
+            frame_lock: RwLock::new(()),
         }
     }
 
+    /// Hold off batches while a frame reads the state
+    pub fn lock_frame(&self) -> RwLockReadGuard<'_, ()> {
+        self.frame_lock.read().unwrap_or_else(|e| e.into_inner())
+    }
+
     pub fn get_color(&self) -> (u8, u8, u8) {
         (
             self.color_r.load(Ordering::Relaxed),
@@ -111,10 +371,67 @@ impl IpcState {
         )
     }
 
+    /// Set an opaque color
     pub fn set_color(&self, r: u8, g: u8, b: u8) {
         self.color_r.store(r, Ordering::Relaxed);
         self.color_g.store(g, Ordering::Relaxed);
         self.color_b.store(b, Ordering::Relaxed);
+        self.set_color_alpha(1.0);
+        self.set_color_name(None);
+    }
+
+    /// Alpha of the color string, which scales the opacity when drawing
+    pub fn get_color_alpha(&self) -> f64 {
+        self.color_alpha.load(Ordering::Relaxed) as f64 / 1000.0
+    }
+
+    fn set_color_alpha(&self, alpha: f64) {
+        self.color_alpha.store((alpha.clamp(0.0, 1.0) * 1000.0) as u32, Ordering::Relaxed);
+    }
+
+    /// Palette name the ring color follows, if it was set by name
+    pub fn get_color_name(&self) -> Option<String> {
+        self.color_name.read().ok().and_then(|n| n.clone())
+    }
+
+    fn set_color_name(&self, name: Option<String>) {
+        if let Ok(mut n) = self.color_name.write() {
+            *n = name;
+        }
+    }
+
+    /// Set the color from hex or a palette name; a name keeps following the palette
+    pub fn set_color_value(&self, value: &str) {
+        let palette = self.get_palette();
+        if !is_color(value) && !palette.contains_key(value) {
+            log::warn!("Unknown color '{}', expected hex, CSS or a palette name", value);
+        }
+        self.change_appearance(|a| {
+            (a.color, a.alpha) = resolve_color_alpha(value, &palette);
+            a.color_name = palette.contains_key(value).then(|| value.to_string());
+        });
+    }
+
+    pub fn get_palette(&self) -> BTreeMap<String, String> {
+        self.palette.read().map(|p| p.clone()).unwrap_or_default()
+    }
+
+    /// Change a palette color; the ring, and any profile using the name, follow it
+    pub fn set_palette_color(&self, name: &str, value: &str) {
+        let Some((color, alpha)) = parse_color_alpha(value) else {
+            log::warn!("Ignoring palette color {} = '{}', expected hex or CSS", name, value);
+            return;
+        };
+        let palette = {
+            let Ok(mut palette) = self.palette.write() else { return };
+            palette.insert(name.to_string(), color_to_hex_alpha(color, alpha));
+            palette.clone()
+        };
+        self.change_appearance(|a| {
+            if a.color_name.as_deref() == Some(name) {
+                (a.color, a.alpha) = resolve_color_alpha(name, &palette);
+            }
+        });
     }
 
     pub fn get_opacity(&self) -> f64 {
@@ -133,6 +450,19 @@ impl IpcState {
         self.corner_radius.store((radius * 1000.0) as u32, Ordering::Relaxed);
     }
 
+    /// Radius multiplier of each corner (top-left, top-right, bottom-right, bottom-left)
+    pub fn get_corner_radii(&self) -> [f64; 4] {
+        self.corner_radii
+            .read()
+            .ok()
+            .and_then(|r| *r)
+            .unwrap_or([self.get_corner_radius(); 4])
+    }
+
+    pub fn get_corner_style(&self) -> CornerStyle {
+        CornerStyle::from_u8(self.corner_style.load(Ordering::Relaxed))
+    }
+
     pub fn get_thickness(&self) -> u32 {
         self.thickness.load(Ordering::Relaxed)
     }
@@ -145,14 +475,360 @@ impl IpcState {
         self.animation_mode.load(Ordering::Relaxed)
     }
 
-    pub fn get_animation_speed(&self) -> u32 {
-        self.animation_speed.load(Ordering::Relaxed)
+    /// Animation cycle duration in milliseconds
+    pub fn get_cycle_ms(&self) -> u32 {
+        self.cycle_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn set_cycle_ms(&self, ms: u32) {
+        self.cycle_ms.store(ms.max(MIN_CYCLE_MS), Ordering::Relaxed);
+    }
+
+    /// Warm-up ramp duration in milliseconds (0 = off)
+    pub fn get_warmup_ms(&self) -> u32 {
+        self.warmup_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn get_warmup_curve(&self) -> RampCurve {
+        RampCurve::from_u8(self.warmup_curve.load(Ordering::Relaxed))
+    }
+
+    pub fn get_pattern(&self) -> Pattern {
+        Pattern::from_u8(self.pattern.load(Ordering::Relaxed))
     }
 
     pub fn is_visible(&self) -> bool {
         self.visible.load(Ordering::Relaxed)
     }
 
+    pub fn is_camera_active(&self) -> bool {
+        self.camera_active.load(Ordering::Relaxed)
+    }
+
+    pub fn is_screen_sharing(&self) -> bool {
+        self.screen_sharing.load(Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "hyprland")]
+    pub fn set_screen_sharing(&self, sharing: bool) {
+        self.screen_sharing.store(sharing, Ordering::Relaxed);
+    }
+
+    pub fn get_on_air(&self) -> Option<OnAir> {
+        self.on_air.read().ok().and_then(|o| *o)
+    }
+
+    pub fn set_on_air(&self, on_air: Option<OnAir>) {
+        if let Ok(mut o) = self.on_air.write() {
+            *o = on_air;
+        }
+    }
+
+    /// Hold camera notifications back for `duration`; zero ends a snooze
+    pub fn snooze_camera(&self, duration: Duration) {
+        if let Ok(mut until) = self.camera_snoozed_until.lock() {
+            *until = Some(Instant::now() + duration).filter(|_| !duration.is_zero());
+        }
+    }
+
+    /// Time left before camera notifications are shown again
+    pub fn camera_snooze_left(&self) -> Option<Duration> {
+        let until = (*self.camera_snoozed_until.lock().ok()?)?;
+        Some(until.saturating_duration_since(Instant::now())).filter(|left| !left.is_zero())
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle.load(Ordering::Relaxed)
+    }
+
+    pub fn set_idle(&self, idle: bool) {
+        self.idle.store(idle, Ordering::Relaxed);
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.typing.load(Ordering::Relaxed)
+    }
+
+    pub fn set_typing(&self, typing: bool) {
+        self.typing.store(typing, Ordering::Relaxed);
+    }
+
+    pub fn is_session_active(&self) -> bool {
+        self.session_active.load(Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "logind")]
+    pub fn set_session_active(&self, active: bool) {
+        self.session_active.store(active, Ordering::Relaxed);
+        if active {
+            self.wake();
+        }
+    }
+
+    pub fn is_lid_closed(&self) -> bool {
+        self.lid_closed.load(Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "logind")]
+    pub fn set_lid_closed(&self, closed: bool) {
+        self.lid_closed.store(closed, Ordering::Relaxed);
+        if !closed {
+            self.wake();
+        }
+    }
+
+    pub fn is_battery_saving(&self) -> bool {
+        self.battery_saving.load(Ordering::Relaxed)
+    }
+
+    pub fn set_battery_saving(&self, saving: bool) {
+        self.battery_saving.store(saving, Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "gamemode")]
+    pub fn is_gamemode(&self) -> bool {
+        self.gamemode.load(Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "gamemode")]
+    pub fn set_gamemode(&self, active: bool) {
+        self.gamemode.store(active, Ordering::Relaxed);
+    }
+
+    pub fn is_dnd(&self) -> bool {
+        self.dnd.load(Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "dnd")]
+    pub fn set_dnd(&self, dnd: bool) {
+        self.dnd.store(dnd, Ordering::Relaxed);
+    }
+
+    pub fn is_gaming(&self) -> bool {
+        self.gaming.load(Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "hyprland")]
+    pub fn set_gaming(&self, gaming: bool) {
+        self.gaming.store(gaming, Ordering::Relaxed);
+        if !gaming {
+            self.wake();
+        }
+    }
+
+    /// Whether the rings should be unmapped: another session owns the screen, or a game runs
+    pub fn is_suspended(&self) -> bool {
+        !self.is_session_active() || self.is_gaming()
+    }
+
+    pub fn get_progress(&self) -> Option<f64> {
+        self.progress.read().ok().and_then(|p| *p)
+    }
+
+    pub fn set_progress(&self, progress: Option<f64>) {
+        if let Ok(mut p) = self.progress.write() {
+            *p = progress.map(|v| v.clamp(0.0, 1.0));
+        }
+    }
+
+    pub fn get_label(&self) -> String {
+        self.label.read().map(|l| l.clone()).unwrap_or_default()
+    }
+
+    /// Ring color a scene set for monitor `id`, if any
+    pub fn get_monitor_color(&self, id: &str) -> Option<(u8, u8, u8)> {
+        self.monitor_colors.read().ok()?.get(id).copied()
+    }
+
+    pub fn get_segments(&self) -> Vec<Segment> {
+        self.segments.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    pub fn get_spectrum(&self) -> Vec<f64> {
+        self.spectrum.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    #[cfg(feature = "spectrum")]
+    pub fn set_spectrum(&self, levels: Vec<f64>) {
+        if let Ok(mut spectrum) = self.spectrum.write() {
+            *spectrum = levels;
+        }
+    }
+
+    /// System gauge level, 0.0 - 1.0
+    pub fn get_system_level(&self) -> f64 {
+        self.system_level.load(Ordering::Relaxed) as f64 / 1000.0
+    }
+
+    pub fn set_system_level(&self, level: f64) {
+        self.system_level.store((level.clamp(0.0, 1.0) * 1000.0) as u32, Ordering::Relaxed);
+    }
+
+    /// Color and opacity the ring was last drawn with, animations included
+    #[cfg(feature = "openrgb")]
+    pub fn get_displayed(&self) -> ((u8, u8, u8), f64) {
+        let [r, g, b, a] = self.displayed.load(Ordering::Relaxed).to_be_bytes();
+        ((r, g, b), a as f64 / 255.0)
+    }
+
+    pub fn set_displayed(&self, (r, g, b): (u8, u8, u8), opacity: f64) {
+        let a = (opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+        self.displayed.store(u32::from_be_bytes([r, g, b, a]), Ordering::Relaxed);
+    }
+
+    /// Number a new flash, replacing any running one
+    pub fn start_flash(&self) -> u64 {
+        self.flash.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Clear the flash override unless a flash newer than `flash` started.
+    /// Checked under the override lock, so a newer flash can't set its
+    /// override between the check and the clear.
+    pub fn end_flash(&self, flash: u64) {
+        let Ok(mut overrides) = self.overrides.lock() else { return };
+        if self.flash.load(Ordering::Relaxed) == flash {
+            self.set_override_held(&mut overrides, "flash", None);
+        }
+    }
+
+    pub fn get_texture(&self) -> String {
+        self.texture.read().map(|t| t.clone()).unwrap_or_default()
+    }
+
+    pub fn get_bar_height(&self) -> u32 {
+        self.bar_height.load(Ordering::Relaxed)
+    }
+
+    pub fn get_bar_position(&self) -> BarPosition {
+        self.bar_position.read().map(|p| *p).unwrap_or_default()
+    }
+
+    pub fn get_layer(&self) -> RingLayer {
+        RingLayer::from_u8(self.layer.load(Ordering::Relaxed))
+    }
+
+    pub fn get_timer(&self) -> Option<Timer> {
+        self.timer.lock().ok().and_then(|t| *t)
+    }
+
+    pub fn set_timer(&self, timer: Option<Timer>) {
+        if let Ok(mut t) = self.timer.lock() {
+            *t = timer;
+        }
+    }
+
+    pub fn get_countdown(&self) -> Option<Timer> {
+        self.countdown.lock().ok().and_then(|c| *c)
+    }
+
+    pub fn set_countdown(&self, countdown: Option<Timer>) {
+        if let Ok(mut c) = self.countdown.lock() {
+            *c = countdown;
+        }
+    }
+
+    /// End `countdown` once it has run out, unless it was stopped or replaced
+    fn finish_countdown(&self, countdown: Timer) {
+        let Ok(mut current) = self.countdown.lock() else { return };
+        if *current != Some(countdown) {
+            return;
+        }
+        // Counted before it's cleared, so subscribers never see it gone without the notice
+        self.countdowns_finished.fetch_add(1, Ordering::Relaxed);
+        *current = None;
+        drop(current);
+        self.log_event("countdown", "countdown finished");
+    }
+
+    pub fn countdowns_finished(&self) -> u64 {
+        self.countdowns_finished.load(Ordering::Relaxed)
+    }
+
+    pub fn get_strobe(&self) -> Option<Strobe> {
+        self.strobe.lock().ok().and_then(|s| *s)
+    }
+
+    pub fn set_strobe(&self, strobe: Option<Strobe>) {
+        if let Ok(mut s) = self.strobe.lock() {
+            *s = strobe;
+        }
+    }
+
+    pub fn get_watch(&self) -> Option<Watch> {
+        self.watch.lock().ok().and_then(|w| *w)
+    }
+
+    pub fn set_watch(&self, watch: Option<Watch>) {
+        if let Ok(mut w) = self.watch.lock() {
+            *w = watch;
+        }
+    }
+
+    pub fn watch_config(&self) -> &WatchConfig {
+        &self.watch_config
+    }
+
+    #[cfg(all(feature = "theme", feature = "tray"))]
+    pub fn theme_config(&self) -> &crate::config::ThemeConfig {
+        &self.theme_config
+    }
+
+    pub fn is_debug_overlay(&self) -> bool {
+        self.debug_overlay.load(Ordering::Relaxed)
+    }
+
+    pub fn is_follow_focus(&self) -> bool {
+        self.follow_focus.load(Ordering::Relaxed)
+    }
+
+    pub fn set_follow_focus(&self, follow: bool) {
+        self.follow_focus.store(follow, Ordering::Relaxed);
+    }
+
+    /// Record a change made by `source` (ipc, tray, camera, ...) in the event log
+    pub fn log_event(&self, source: &str, message: impl Into<String>) {
+        let message = message.into();
+        log::debug!("[{}] {}", source, message);
+        if let Ok(mut events) = self.events.lock() {
+            events.push(source, message);
+        }
+    }
+
+    /// Ask the event loop to shut down cleanly
+    pub fn request_quit(&self) {
+        self.quit.store(true, Ordering::Relaxed);
+        self.wake();
+    }
+
+    /// Wake the event loop, so unmapped rings come back without waiting for the next check
+    fn wake(&self) {
+        if let Some(waker) = self.waker.lock().unwrap().as_ref() {
+            waker.ping();
+        }
+    }
+
+    pub fn quit_requested(&self) -> bool {
+        self.quit.load(Ordering::Relaxed)
+    }
+
+    pub fn set_waker(&self, waker: Ping) {
+        *self.waker.lock().unwrap() = Some(waker);
+    }
+
+    /// Wait for a config write in progress to finish
+    pub fn flush_config(&self) {
+        drop(self.config_lock.lock());
+    }
+
+    /// Whether to draw over an opaque band, by request or for lack of alpha
+    pub fn is_solid_backdrop(&self) -> bool {
+        self.solid_backdrop.load(Ordering::Relaxed) || self.no_alpha.load(Ordering::Relaxed)
+    }
+
+    pub fn set_no_alpha(&self, no_alpha: bool) {
+        self.no_alpha.store(no_alpha, Ordering::Relaxed);
+    }
+
     // Monitor management
     pub fn add_monitor(&self, id: String, display_name: String) {
         if let Ok(mut monitors) = self.monitors.write() {
@@ -171,14 +847,44 @@ impl IpcState {
         if let Ok(mut monitors) = self.monitors.write() {
             monitors.retain(|(mid, _, _)| mid != id);
         }
+        if let Ok(mut stats) = self.frame_stats.lock() {
+            stats.remove(id);
+        }
+        if let Ok(mut bars) = self.bars.write() {
+            bars.remove(id);
+        }
+    }
+
+    /// Record the room left for bars on a monitor's ring
+    pub fn set_monitor_bar(&self, id: &str, bar: Bar) {
+        if let Ok(mut bars) = self.bars.write() {
+            bars.insert(id.to_string(), bar);
+        }
     }
 
+    /// Record a rendered frame for `GetStats`
+    pub fn record_frame(&self, monitor: &str, size: (u32, u32), buffer_bytes: usize, fps: f64, render_time: Duration) {
+        let Ok(mut stats) = self.frame_stats.lock() else { return };
+        let entry = stats.entry(monitor.to_string()).or_default();
+        entry.size = size;
+        entry.buffer_bytes = buffer_bytes;
+        entry.frames += 1;
+        entry.fps = fps;
+        entry.max = entry.max.max(render_time);
+        if entry.recent.len() == RENDER_WINDOW {
+            entry.recent.pop_front();
+        }
+        entry.recent.push_back(render_time);
+    }
+
+    #[cfg(feature = "tray")]
     pub fn toggle_monitor(&self, id: &str) {
         if let Ok(mut monitors) = self.monitors.write() {
             if let Some((_, _, enabled)) = monitors.iter_mut().find(|(mid, _, _)| mid == id) {
                 *enabled = !*enabled;
             }
         }
+        self.wake();
     }
 
     pub fn set_monitor_enabled(&self, id: &str, enabled: bool) {
@@ -187,6 +893,7 @@ impl IpcState {
                 *en = enabled;
             }
         }
+        self.wake();
     }
 
     pub fn is_monitor_enabled(&self, id: &str) -> bool {
@@ -197,125 +904,640 @@ impl IpcState {
         }
     }
 
+    #[cfg(feature = "hyprland")]
+    /// Hide or unhide a monitor's ring on behalf of an automatic rule
+    pub fn set_monitor_suppressed(&self, id: &str, suppressed: bool) {
+        if let Ok(mut list) = self.suppressed_monitors.write() {
+            let present = list.iter().any(|m| m == id);
+            if suppressed && !present {
+                list.push(id.to_string());
+            } else if !suppressed && present {
+                list.retain(|m| m != id);
+                self.wake();
+            }
+        }
+    }
+
+    pub fn is_monitor_suppressed(&self, id: &str) -> bool {
+        self.suppressed_monitors
+            .read()
+            .map(|list| list.iter().any(|m| m == id))
+            .unwrap_or(false)
+    }
+
+    #[cfg(feature = "hyprland")]
+    /// Record the space reserved for bars on a monitor, as detected from the compositor
+    pub fn set_reserved(&self, id: &str, bar: Bar) {
+        if let Ok(mut map) = self.reserved.write() {
+            if map.insert(id.to_string(), bar) != Some(bar) {
+                log::info!("Detected bar space on {}: {:?}", id, bar.margin());
+            }
+        }
+    }
+
+    pub fn get_reserved(&self, id: &str) -> Option<Bar> {
+        self.reserved.read().ok()?.get(id).copied()
+    }
+
+    #[cfg(any(feature = "camera", feature = "hyprland"))]
+    /// Record the debounce state of a trigger (replaces any entry with the same name)
+    pub fn publish_trigger(&self, stats: TriggerStats) {
+        if let Ok(mut list) = self.trigger_stats.write() {
+            match list.iter_mut().find(|t| t.name == stats.name) {
+                Some(entry) => *entry = stats,
+                None => list.push(stats),
+            }
+        }
+    }
+
+    /// Everything `GetState` reports
+    pub fn get_state(&self) -> State {
+        let monitors = self.get_monitors();
+        State {
+            version: PROTOCOL_VERSION,
+            color: color_to_hex_alpha(self.get_color(), self.get_color_alpha()),
+            color_name: self.get_color_name(),
+            palette: self.get_palette(),
+            thickness: self.get_thickness(),
+            opacity: self.get_opacity(),
+            glow: self.get_glow(),
+            corner_radius: self.get_corner_radius(),
+            corner_style: self.get_corner_style().name().to_string(),
+            corner_radii: self.corner_radii.read().ok().and_then(|r| *r),
+            animation: animation_name(self.get_animation_mode()).to_string(),
+            cycle_duration: format_cycle_duration(self.get_cycle_ms()),
+            pattern: self.get_pattern().name().to_string(),
+            visible: self.is_visible(),
+            debug_overlay: self.is_debug_overlay(),
+            framing_guide: self.framing_guide.load(Ordering::Relaxed),
+            solid_backdrop: self.is_solid_backdrop(),
+            gamma_correct: self.gamma_correct.load(Ordering::Relaxed),
+            vignette: self.vignette.load(Ordering::Relaxed),
+            fill: self.fill.load(Ordering::Relaxed),
+            follow_focus: self.is_follow_focus(),
+            texture: self.get_texture(),
+            bar_height: self.get_bar_height(),
+            bar_position: self.get_bar_position().name().to_string(),
+            layer: self.get_layer().name().to_string(),
+            disabled_monitors: self.get_disabled_monitors(&monitors),
+            monitors,
+            active_profiles: self.active_profiles(),
+            idle: self.is_idle(),
+            battery_saving: self.is_battery_saving(),
+            dnd: self.is_dnd(),
+            gaming: self.is_gaming(),
+            watch: self.get_watch().map(|w| w.status(Instant::now())),
+            timer: self.get_timer().map(|t| t.status(Instant::now())),
+            countdown: self.get_countdown().map(|c| c.status(Instant::now())),
+            on_air: self.get_on_air().map_or("", OnAir::name).to_string(),
+            camera_snoozed_until_unix_ms: self.camera_snooze_left().map(|left| {
+                (SystemTime::now() + left).duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+            }),
+            strobe: self.get_strobe().is_some_and(|s| s.phase(Instant::now()).is_some()),
+            progress: self.get_progress(),
+            label: self.get_label(),
+        }
+    }
+
+    pub fn get_stats(&self) -> Stats {
+        Stats {
+            triggers: self.trigger_stats.read().map(|l| l.clone()).unwrap_or_default(),
+            monitors: self
+                .frame_stats
+                .lock()
+                .map(|s| s.iter().map(|(monitor, f)| f.to_stats(monitor)).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn appearance(&self) -> Appearance {
+        Appearance {
+            color: self.get_color(),
+            alpha: self.get_color_alpha(),
+            color_name: self.get_color_name(),
+            thickness: self.get_thickness(),
+            opacity: self.get_opacity(),
+            glow: self.get_glow(),
+            corner_radius: self.get_corner_radius(),
+            animation: self.get_animation_mode(),
+            cycle_ms: self.get_cycle_ms(),
+            pattern: self.get_pattern(),
+            visible: self.is_visible(),
+        }
+    }
+
+    fn set_appearance(&self, a: &Appearance) {
+        self.set_color(a.color.0, a.color.1, a.color.2);
+        self.set_color_alpha(a.alpha);
+        self.set_color_name(a.color_name.clone());
+        self.thickness.store(a.thickness, Ordering::Relaxed);
+        self.set_opacity(a.opacity);
+        self.glow.store(a.glow, Ordering::Relaxed);
+        self.set_corner_radius(a.corner_radius);
+        self.animation_mode.store(a.animation, Ordering::Relaxed);
+        self.set_cycle_ms(a.cycle_ms);
+        self.pattern.store(a.pattern as u8, Ordering::Relaxed);
+        self.visible.store(a.visible, Ordering::Relaxed);
+    }
+
+    /// Change the appearance the user chose. While profiles are applied it's
+    /// the appearance under them that changes, and what shows is worked out
+    /// again from it, so the change outlives the profiles and is what's saved.
+    pub fn change_appearance(&self, change: impl FnOnce(&mut Appearance)) {
+        let Ok(mut overrides) = self.overrides.lock() else { return };
+        let appearance = match overrides.base.as_mut() {
+            Some(base) => {
+                change(base);
+                let mut appearance = base.clone();
+                let palette = self.get_palette();
+                for layer in &overrides.layers {
+                    appearance.apply(&layer.profile, &palette);
+                }
+                appearance
+            }
+            None => {
+                let mut appearance = self.appearance();
+                change(&mut appearance);
+                appearance
+            }
+        };
+        self.set_appearance(&appearance);
+    }
+
+    /// Apply a profile on behalf of `source`, replacing that source's previous one.
+    /// `None` removes it; once no source has a profile, the appearance under
+    /// them is restored, with the changes made meanwhile.
+    pub fn set_override(&self, source: &str, profile: Option<(String, Profile)>) {
+        let Ok(mut overrides) = self.overrides.lock() else { return };
+        self.set_override_held(&mut overrides, source, profile);
+    }
+
+    /// `set_override` with the override lock already held
+    fn set_override_held(&self, overrides: &mut Overrides, source: &str, profile: Option<(String, Profile)>) {
+        let index = overrides.layers.iter().position(|o| o.source == source);
+        let layer = profile.map(|(name, profile)| Override { source: source.to_string(), name, profile });
+        match (index, layer) {
+            (Some(i), Some(layer)) if overrides.layers[i] == layer => return,
+            (Some(i), Some(layer)) => overrides.layers[i] = layer,
+            (Some(i), None) => { overrides.layers.remove(i); }
+            (None, Some(layer)) => overrides.layers.push(layer),
+            (None, None) => return,
+        }
+        match overrides.layers.iter().find(|l| l.source == source) {
+            Some(layer) => self.log_event(source, format!("profile {} applied", layer.name)),
+            None => self.log_event(source, "profile cleared"),
+        }
+        let priority = |source: &str| OVERRIDE_SOURCES.iter().position(|s| *s == source);
+        overrides.layers.sort_by_key(|l| priority(&l.source));
+
+        if overrides.layers.is_empty() {
+            log::info!("No profile active, restoring previous settings");
+            if let Some(base) = overrides.base.take() {
+                self.set_appearance(&base);
+            }
+            return;
+        }
+        let mut appearance = overrides.base.get_or_insert_with(|| self.appearance()).clone();
+        let palette = self.get_palette();
+        for layer in &overrides.layers {
+            appearance.apply(&layer.profile, &palette);
+        }
+        let names: Vec<&str> = overrides.layers.iter().map(|l| l.name.as_str()).collect();
+        log::info!("Active profiles: {}", names.join(", "));
+        self.set_appearance(&appearance);
+    }
+
+    /// Name of the profile `source` applied, if any
+    pub fn override_name(&self, source: &str) -> Option<String> {
+        let overrides = self.overrides.lock().ok()?;
+        overrides.layers.iter().find(|l| l.source == source).map(|l| l.name.clone())
+    }
+
+    /// Names of the profiles currently applied, lowest priority first
+    pub fn active_profiles(&self) -> Vec<String> {
+        self.overrides
+            .lock()
+            .map(|o| o.layers.iter().map(|l| l.name.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    #[cfg(feature = "tray")]
+    /// Appearance without any overrides, i.e. what should be saved to the config
+    fn base_appearance(&self) -> Appearance {
+        self.overrides
+            .lock()
+            .ok()
+            .and_then(|o| o.base.clone())
+            .unwrap_or_else(|| self.appearance())
+    }
+
+    /// Apply a scene in one go, returning what changed
+    pub fn apply_scene(&self, scene: &Scene) -> Vec<String> {
+        let _scene = self.frame_lock.write().unwrap_or_else(|e| e.into_inner());
+        self.apply_scene_held(scene)
+    }
+
+    /// `apply_scene`, with frames already held off
+    fn apply_scene_held(&self, scene: &Scene) -> Vec<String> {
+        // Through the appearance under any profiles, so the scene outlives them
+        let palette = self.get_palette();
+        let mut changes = Vec::new();
+        self.change_appearance(|a| {
+            let current = a.clone();
+            a.apply(&scene.ring, &palette);
+            changes = current.changes(a);
+        });
+
+        if let Some(fraction) = scene.progress {
+            let progress = (fraction < 1.0).then(|| fraction.max(0.0));
+            let current = self.get_progress();
+            if progress != current {
+                let show = |p: Option<f64>| p.map_or("off".to_string(), |p| format!("{:.2}", p));
+                changes.push(format!("progress {} -> {}", show(current), show(progress)));
+                self.set_progress(progress);
+            }
+        }
+
+        let monitors = self.get_monitors();
+        for (id, &enabled) in &scene.monitors {
+            match monitors.iter().find(|m| m.id == *id) {
+                Some(m) if m.enabled != enabled => {
+                    changes.push(format!("monitor {} {}", id, if enabled { "enabled" } else { "disabled" }));
+                    self.set_monitor_enabled(id, enabled);
+                }
+                Some(_) => {}
+                None => log::warn!("Scene sets monitor {}, which isn't connected", id),
+            }
+        }
+
+        if let Some(text) = &scene.text {
+            let current = self.get_label();
+            if *text != current {
+                changes.push(format!("label {:?} -> {:?}", current, text));
+                if let Ok(mut label) = self.label.write() {
+                    *label = text.clone();
+                }
+            }
+        }
+
+        if let Some(colors) = &scene.colors {
+            let target: BTreeMap<String, (u8, u8, u8)> =
+                colors.iter().map(|(id, color)| (id.clone(), resolve_color(color, &palette))).collect();
+            if let Ok(mut current) = self.monitor_colors.write() {
+                let hex = |c: Option<&(u8, u8, u8)>| c.map_or("none".to_string(), |&(r, g, b)| color_to_hex(r, g, b));
+                for id in current.keys().chain(target.keys().filter(|id| !current.contains_key(*id))) {
+                    let (before, after) = (current.get(id), target.get(id));
+                    if before != after {
+                        changes.push(format!("color of {} {} -> {}", id, hex(before), hex(after)));
+                    }
+                }
+                *current = target;
+            }
+        }
+
+        if let Some(segments) = &scene.segments {
+            let target: Vec<Segment> = segments.iter()
+                .map(|s| Segment {
+                    start: s.start.clamp(0.0, 1.0),
+                    end: s.end.clamp(0.0, 1.0),
+                    color: resolve_color(&s.color, &palette),
+                })
+                .collect();
+            if let Ok(mut current) = self.segments.write() {
+                if *current != target {
+                    changes.push(format!("segments {} -> {}", current.len(), target.len()));
+                    *current = target;
+                }
+            }
+        }
+
+        for change in &changes {
+            self.log_event("scene", change.as_str());
+        }
+        changes
+    }
+
     pub fn get_monitors(&self) -> Vec<MonitorState> {
+        let visible = self.is_visible();
+        let bars = self.bars.read().map(|b| b.clone()).unwrap_or_default();
         if let Ok(monitors) = self.monitors.read() {
-            monitors.iter().map(|(id, name, en)| MonitorState {
-                id: id.clone(),
-                display_name: name.clone(),
-                enabled: *en,
+            monitors.iter().map(|(id, name, en)| {
+                let suppressed = self.is_monitor_suppressed(id);
+                let bar = bars.get(id).copied().unwrap_or_default();
+                MonitorState {
+                    id: id.clone(),
+                    display_name: name.clone(),
+                    enabled: *en,
+                    suppressed,
+                    visible: visible && *en && !suppressed,
+                    bar: bar.into(),
+                    overrides: self.monitor_config.get(id).cloned().unwrap_or_default(),
+                }
             }).collect()
         } else {
             Vec::new()
         }
     }
-}
 
-fn parse_hex_color(hex: &str) -> (u8, u8, u8) {
-    let hex = hex.trim_start_matches('#');
-    if hex.len() < 6 {
-        return (255, 255, 255);
-    }
-    let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(255);
-    let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(255);
-    let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(255);
-    (r, g, b)
-}
-
-fn animation_from_string(s: &str) -> u8 {
-    match s.to_lowercase().as_str() {
-        "pulse" => 1,
-        "rainbow" => 2,
-        "breathe" => 3,
-        _ => 0,
+    /// Monitors turned off, connected or not
+    fn get_disabled_monitors(&self, monitors: &[MonitorState]) -> Vec<String> {
+        let mut disabled: Vec<String> = monitors.iter().filter(|m| !m.enabled).map(|m| m.id.clone()).collect();
+        if let Ok(configured) = self.disabled_monitors.read() {
+            disabled.extend(configured.iter().filter(|id| !monitors.iter().any(|m| &m.id == *id)).cloned());
+        }
+        disabled
     }
 }
 
-fn animation_to_string(mode: u8) -> String {
-    match mode {
-        1 => "pulse",
-        2 => "rainbow",
-        3 => "breathe",
-        _ => "none",
-    }.to_string()
-}
-
-fn color_to_hex(r: u8, g: u8, b: u8) -> String {
-    format!("{:02x}{:02x}{:02x}", r, g, b)
-}
-
 /// Handle a single client connection
-fn handle_client(mut stream: UnixStream, state: &Arc<IpcState>) -> bool {
-    let reader = BufReader::new(stream.try_clone().unwrap());
+fn handle_client(mut stream: UnixStream, state: &Arc<IpcState>) {
+    let Ok(read_half) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(read_half);
+    let mut line = String::new();
     
-    for line in reader.lines() {
-        let line = match line {
-            Ok(l) => l,
-            Err(_) => break,
-        };
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(n) if n > 0 => {}
+            _ => break,
+        }
         
         let cmd: Command = match serde_json::from_str(&line) {
             Ok(c) => c,
-            Err(_) => continue,
+            Err(e) => {
+                log::warn!("Invalid IPC command {}: {}", line.trim(), e);
+                continue;
+            }
         };
+        log::debug!("IPC command: {}", line.trim());
         
-        match cmd {
-            Command::SetColor(hex) => {
-                let (r, g, b) = parse_hex_color(&hex);
-                state.set_color(r, g, b);
-            }
-            Command::SetThickness(v) => {
-                state.thickness.store(v, Ordering::Relaxed);
-            }
-            Command::SetOpacity(v) => {
-                state.set_opacity(v);
+        if matches!(cmd, Command::Subscribe) {
+            push_state(stream, reader.get_ref(), state);
+            break;
+        }
+        let quit = matches!(cmd, Command::Quit);
+        if let Some(reply) = execute(state, cmd, "ipc", line.trim()) {
+            let _ = writeln!(stream, "{}", reply);
+        }
+        if quit {
+            break;
+        }
+    }
+}
+
+/// Write the state to a subscriber whenever it changes, until it hangs up
+fn push_state(mut stream: UnixStream, read_half: &UnixStream, state: &Arc<IpcState>) {
+    // Reading is only how a hang-up is noticed; it also paces the loop
+    if read_half.set_read_timeout(Some(SUBSCRIBE_INTERVAL)).is_err() {
+        return;
+    }
+    let mut sent = String::new();
+    let mut finished = state.countdowns_finished();
+    let mut buf = [0; 256];
+    while !state.quit_requested() {
+        let json = serde_json::to_string(&state.get_state()).unwrap();
+        // Read after the state, so a countdown gone from it is always announced first
+        let now_finished = state.countdowns_finished();
+        for _ in finished..now_finished {
+            let notice = serde_json::to_string(&Notice::CountdownFinished).unwrap();
+            if writeln!(stream, "{}", notice).is_err() {
+                return;
             }
-            Command::SetGlow(v) => {
-                state.glow.store(v, Ordering::Relaxed);
+        }
+        finished = now_finished;
+        if json != sent {
+            if writeln!(stream, "{}", json).is_err() {
+                return;
             }
-            Command::SetCornerRadius(v) => {
-                state.set_corner_radius(v);
+            sent = json;
+        }
+        match (&*read_half).read(&mut buf) {
+            Ok(0) => return,
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(_) => return,
+        }
+    }
+}
+
+/// Run a command from `source`, logging it as `line`. Queries return their JSON reply.
+pub fn execute(state: &Arc<IpcState>, cmd: Command, source: &str, line: &str) -> Option<String> {
+    // Scenes log what they change instead, batches each command
+    if !matches!(
+        cmd,
+        Command::GetState
+            | Command::GetMonitors
+            | Command::GetStats
+            | Command::GetEvents(_)
+            | Command::ApplyScene(_)
+            | Command::Batch(_)
+            | Command::Subscribe
+    ) {
+        state.log_event(source, line);
+    }
+    
+    match cmd {
+        Command::SetColor(value) => {
+            state.set_color_value(&value);
+        }
+        Command::SetPaletteColor { name, color } => {
+            state.set_palette_color(&name, &color);
+        }
+        Command::SetThickness(v) => {
+            state.change_appearance(|a| a.thickness = v);
+        }
+        Command::SetOpacity(v) => {
+            state.change_appearance(|a| a.opacity = v);
+        }
+        Command::SetGlow(v) => {
+            state.change_appearance(|a| a.glow = v);
+        }
+        Command::SetCornerRadius(v) => {
+            state.change_appearance(|a| a.corner_radius = v);
+            // A single radius applies to all corners
+            if let Ok(mut radii) = state.corner_radii.write() {
+                *radii = None;
             }
-            Command::SetAnimation(s) => {
-                state.animation_mode.store(animation_from_string(&s), Ordering::Relaxed);
+        }
+        Command::SetCornerRadii(v) => {
+            if let Ok(mut radii) = state.corner_radii.write() {
+                *radii = Some(v);
             }
-            Command::SetAnimationSpeed(v) => {
-                state.animation_speed.store(v, Ordering::Relaxed);
+        }
+        Command::SetTexture(path) => {
+            if let Ok(mut texture) = state.texture.write() {
+                *texture = path;
             }
-            Command::SetVisible(v) => {
-                state.visible.store(v, Ordering::Relaxed);
+        }
+        Command::SetBarHeight(v) => {
+            state.bar_height.store(v, Ordering::Relaxed);
+        }
+        Command::SetBarPosition(s) => {
+            if let Ok(mut position) = state.bar_position.write() {
+                *position = BarPosition::from_name(&s);
             }
-            Command::GetState => {
-                let (r, g, b) = state.get_color();
-                let response = State {
-                    color: color_to_hex(r, g, b),
-                    thickness: state.get_thickness(),
-                    opacity: state.get_opacity(),
-                    glow: state.get_glow(),
-                    corner_radius: state.get_corner_radius(),
-                    animation: animation_to_string(state.get_animation_mode()),
-                    animation_speed: state.get_animation_speed(),
-                    visible: state.is_visible(),
-                };
-                let json = serde_json::to_string(&response).unwrap();
-                let _ = writeln!(stream, "{}", json);
+        }
+        Command::SetLayer(s) => {
+            state.layer.store(RingLayer::from_name(&s) as u8, Ordering::Relaxed);
+        }
+        Command::SetCornerStyle(s) => {
+            state.corner_style.store(CornerStyle::from_name(&s) as u8, Ordering::Relaxed);
+        }
+        Command::SetAnimation(s) => {
+            state.change_appearance(|a| a.animation = animation_from_name(&s));
+        }
+        Command::SetCycleDuration(s) => {
+            if let Some(ms) = parse_cycle_duration(&s) {
+                state.change_appearance(|a| a.cycle_ms = ms);
             }
-            Command::GetMonitors => {
-                let response = MonitorsResponse {
-                    monitors: state.get_monitors(),
-                };
-                let json = serde_json::to_string(&response).unwrap();
-                let _ = writeln!(stream, "{}", json);
+        }
+        Command::SetAnimationSpeed(v) => {
+            state.change_appearance(|a| a.cycle_ms = frames_to_ms(v));
+        }
+        Command::SetPattern(s) => {
+            state.change_appearance(|a| a.pattern = Pattern::from_name(&s));
+        }
+        Command::SetVisible(v) => {
+            state.change_appearance(|a| a.visible = v);
+        }
+        #[cfg(feature = "label")]
+        Command::SetLabel(text) => {
+            if let Ok(mut label) = state.label.write() {
+                *label = text;
             }
-            Command::SetMonitorEnabled { id, enabled } => {
-                state.set_monitor_enabled(&id, enabled);
+        }
+        #[cfg(not(feature = "label"))]
+        Command::SetLabel(_) => {
+            log::warn!("Labels need the label feature, which isn't built in");
+        }
+        Command::SetDebugOverlay(v) => {
+            state.debug_overlay.store(v, Ordering::Relaxed);
+        }
+        Command::SetFramingGuide(v) => {
+            state.framing_guide.store(v, Ordering::Relaxed);
+        }
+        Command::SetSolidBackdrop(v) => {
+            state.solid_backdrop.store(v, Ordering::Relaxed);
+        }
+        Command::SetGammaCorrect(v) => {
+            state.gamma_correct.store(v, Ordering::Relaxed);
+        }
+        Command::SetVignette(v) => {
+            state.vignette.store(v, Ordering::Relaxed);
+        }
+        Command::SetFill(v) => {
+            state.fill.store(v, Ordering::Relaxed);
+        }
+        Command::SetFollowFocus(v) => {
+            state.set_follow_focus(v);
+        }
+        Command::SetProgress(v) => {
+            state.set_progress(v);
+        }
+        Command::StartTimer(s) => {
+            if let Some(ms) = parse_duration_ms(&s).filter(|&ms| ms > 0) {
+                let timer = Timer::new(Duration::from_millis(ms as u64));
+                state.set_timer(Some(timer));
+                #[cfg(feature = "notifications")]
+                crate::timer::notify_when_done(state.clone(), timer);
             }
-            Command::Quit => {
-                return true; // Signal to quit
+        }
+        Command::StopTimer => {
+            state.set_timer(None);
+        }
+        Command::StartCountdown(seconds) => {
+            let countdown = Timer::new(Duration::from_secs(seconds as u64));
+            state.set_countdown(Some(countdown));
+            let state = state.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(countdown.duration);
+                state.finish_countdown(countdown);
+            });
+        }
+        Command::StopCountdown => {
+            state.set_countdown(None);
+        }
+        Command::SnoozeCamera(duration) => match parse_duration_ms(&duration) {
+            Some(ms) => state.snooze_camera(Duration::from_millis(ms as u64)),
+            None => log::warn!("Invalid snooze duration '{}'", duration),
+        },
+        Command::StartStrobe(request) => {
+            state.set_strobe(Some(Strobe::new(&request, &state.strobe_config, &state.get_palette())));
+        }
+        Command::StopStrobe => {
+            state.set_strobe(None);
+        }
+        Command::Flash { color, duration } => match parse_duration_ms(&duration).filter(|&ms| ms > 0) {
+            Some(ms) => strobe::flash(state.clone(), color, Duration::from_millis(ms as u64)),
+            None => log::warn!("Invalid flash duration '{}'", duration),
+        },
+        Command::StartWatch(pid) => {
+            watch::start(state.clone(), pid);
+        }
+        Command::FinishWatch(success) => {
+            watch::finish(state.clone(), success);
+        }
+        Command::GetState => {
+            return Some(serde_json::to_string(&state.get_state()).unwrap());
+        }
+        Command::GetMonitors => {
+            let response = MonitorsResponse {
+                monitors: state.get_monitors(),
+            };
+            return Some(serde_json::to_string(&response).unwrap());
+        }
+        Command::SetMonitorEnabled { id, enabled } => {
+            state.set_monitor_enabled(&id, enabled);
+        }
+        Command::GetStats => {
+            return Some(serde_json::to_string(&state.get_stats()).unwrap());
+        }
+        Command::ApplyScene(scene) => {
+            let response = SceneResponse { changes: state.apply_scene(&scene) };
+            return Some(serde_json::to_string(&response).unwrap());
+        }
+        Command::GetEvents(after) => {
+            let events = state.events.lock().map(|e| e.since(after)).unwrap_or_default();
+            return Some(serde_json::to_string(&EventsResponse { events }).unwrap());
+        }
+        Command::Batch(commands) => {
+            let mut flat = Vec::new();
+            flatten_batch(commands, &mut flat);
+            let _batch = state.frame_lock.write().unwrap_or_else(|e| e.into_inner());
+            for cmd in flat {
+                // The lock is already held, and a batch has no answer to give
+                if let Command::ApplyScene(scene) = &cmd {
+                    state.apply_scene_held(scene);
+                    continue;
+                }
+                let line = serde_json::to_string(&cmd).unwrap();
+                execute(state, cmd, source, &line);
             }
         }
+        Command::Quit => {
+            state.request_quit();
+        }
+        // Only meaningful on a socket connection, see `push_state`
+        Command::Subscribe => {}
     }
-    
-    false
+    None
+}
+
+/// Commands of a batch in order, with nested batches spliced in
+fn flatten_batch(commands: Vec<Command>, out: &mut Vec<Command>) {
+    for cmd in commands {
+        match cmd {
+            Command::Batch(inner) => flatten_batch(inner, out),
+            cmd => out.push(cmd),
+        }
+    }
+}
+
+/// Remove the socket on shutdown, so clients don't find a stale one
+pub fn remove_socket() {
+    let _ = std::fs::remove_file(socket_path());
 }
 
 /// Start the IPC server in a background thread
@@ -328,7 +1550,7 @@ pub fn start_server(state: Arc<IpcState>) {
     let listener = match UnixListener::bind(&path) {
         Ok(l) => l,
         Err(e) => {
-            eprintln!("Failed to create IPC socket: {}", e);
+            log::error!("Failed to create IPC socket {}: {}", path.display(), e);
             return;
         }
     };
@@ -341,11 +1563,7 @@ pub fn start_server(state: Arc<IpcState>) {
             match stream {
                 Ok(stream) => {
                     let state = state.clone();
-                    std::thread::spawn(move || {
-                        if handle_client(stream, &state) {
-                            std::process::exit(0);
-                        }
-                    });
+                    std::thread::spawn(move || handle_client(stream, &state));
                 }
                 Err(_) => continue,
             }
@@ -353,71 +1571,15 @@ pub fn start_server(state: Arc<IpcState>) {
     });
 }
 
-/// Client: send a command to the running instance
-pub fn send_command(cmd: &Command) -> Result<Option<State>, String> {
-    let path = socket_path();
-    
-    let mut stream = UnixStream::connect(&path)
-        .map_err(|_| "hypr-ringlight is not running".to_string())?;
-    
-    let json = serde_json::to_string(cmd).map_err(|e| e.to_string())?;
-    writeln!(stream, "{}", json).map_err(|e| e.to_string())?;
-    
-    if matches!(cmd, Command::GetState) {
-        let reader = BufReader::new(stream);
-        if let Some(Ok(line)) = reader.lines().next() {
-            let state: State = serde_json::from_str(&line).map_err(|e| e.to_string())?;
-            return Ok(Some(state));
-        }
-    }
-    
-    Ok(None)
-}
-
-/// Client: get monitors from running instance
-pub fn get_monitors() -> Result<Vec<MonitorState>, String> {
-    let path = socket_path();
-    
-    let mut stream = UnixStream::connect(&path)
-        .map_err(|_| "hypr-ringlight is not running".to_string())?;
-    
-    let json = serde_json::to_string(&Command::GetMonitors).map_err(|e| e.to_string())?;
-    writeln!(stream, "{}", json).map_err(|e| e.to_string())?;
-    
-    let reader = BufReader::new(stream);
-    if let Some(Ok(line)) = reader.lines().next() {
-        let response: MonitorsResponse = serde_json::from_str(&line).map_err(|e| e.to_string())?;
-        return Ok(response.monitors);
-    }
-    
-    Ok(Vec::new())
-}
-
-/// Client: set monitor enabled state
-pub fn set_monitor_enabled(id: &str, enabled: bool) -> Result<(), String> {
-    let path = socket_path();
-    
-    let mut stream = UnixStream::connect(&path)
-        .map_err(|_| "hypr-ringlight is not running".to_string())?;
-    
-    let cmd = Command::SetMonitorEnabled { id: id.to_string(), enabled };
-    let json = serde_json::to_string(&cmd).map_err(|e| e.to_string())?;
-    writeln!(stream, "{}", json).map_err(|e| e.to_string())?;
-    
-    Ok(())
-}
-
-/// Check if the server is running
-pub fn is_running() -> bool {
-    UnixStream::connect(socket_path()).is_ok()
-}
-
 impl IpcState {
+    #[cfg(feature = "tray")]
     /// Save current state to config file
     pub fn save_to_config(&self) {
         use crate::config::Config;
         
-        // Load existing config to preserve bar settings
+        let _guard = self.config_lock.lock();
+        
+        // Load existing config to preserve settings that aren't live state
         let existing = Config::load();
         
         // Get list of disabled monitors
@@ -427,22 +1589,95 @@ impl IpcState {
             .map(|m| m.id)
             .collect();
         
-        let (r, g, b) = self.get_color();
+        // Profiles applied by rules are temporary, save what they replaced
+        let appearance = self.base_appearance();
+        let hyprland = crate::config::HyprlandConfig {
+            focused_monitor_only: self.is_follow_focus(),
+            ..existing.hyprland.clone()
+        };
         let config = Config {
-            color: color_to_hex(r, g, b),
-            thickness: self.get_thickness(),
-            opacity: self.get_opacity(),
-            glow: self.get_glow(),
-            corner_radius: self.get_corner_radius(),
-            animation: animation_to_string(self.get_animation_mode()),
-            animation_speed: self.get_animation_speed(),
-            bar_height: existing.bar_height,
-            bar_position: existing.bar_position,
+            color: appearance.color_name.unwrap_or_else(|| color_to_hex_alpha(appearance.color, appearance.alpha)),
+            thickness: appearance.thickness,
+            opacity: appearance.opacity,
+            glow: appearance.glow,
+            corner_radius: appearance.corner_radius,
+            corner_style: self.get_corner_style().name().to_string(),
+            corner_radii: self.corner_radii.read().ok().and_then(|r| *r),
+            animation: animation_name(appearance.animation).to_string(),
+            cycle_duration: format_cycle_duration(appearance.cycle_ms),
+            animation_speed: None,
+            pattern: appearance.pattern.name().to_string(),
+            bar_height: self.get_bar_height(),
+            bar_position: self.get_bar_position().name().to_string(),
+            layer: self.get_layer().name().to_string(),
             disabled_monitors,
+            palette: self.get_palette(),
+            hyprland,
+            ..existing
         };
         
         if let Err(e) = config.save() {
-            eprintln!("Warning: Failed to save config: {}", e);
+            log::warn!("Failed to save config: {}", e);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_under_override() {
+        let state = IpcState::new(&Config::default(), (255, 255, 255));
+        let call = Profile { color: Some("ff0000".to_string()), ..Profile::default() };
+        state.set_override("window", Some(("call".to_string(), call)));
+
+        // The profile keeps its color, the thickness shows right away
+        state.change_appearance(|a| {
+            a.color = (0, 0, 255);
+            a.thickness = 42;
+        });
+        assert_eq!(state.get_color(), (255, 0, 0));
+        assert_eq!(state.get_thickness(), 42);
+
+        // Both changes outlive the profile
+        state.set_override("window", None);
+        assert_eq!(state.get_color(), (0, 0, 255));
+        assert_eq!(state.get_thickness(), 42);
+    }
+
+    #[test]
+    fn test_apply_scene_twice() {
+        let state = IpcState::new(&Config::default(), (255, 255, 255));
+        let scene: Scene = toml::from_str(
+            "text = \"ON AIR\"\n[colors]\n\"DP-2\" = \"00ff88\"\n[[segments]]\nstart = 0.75\nend = 0.25\ncolor = \"ff0000\"\n",
+        )
+        .unwrap();
+        assert_eq!(state.apply_scene(&scene).len(), 3);
+        assert_eq!(state.get_monitor_color("DP-2"), Some((0, 255, 136)));
+        assert!(state.get_segments()[0].contains(0.1));
+        assert!(state.apply_scene(&scene).is_empty());
+
+        // Empty clears, left out keeps
+        let cleared: Scene = toml::from_str("colors = {}\n").unwrap();
+        assert_eq!(state.apply_scene(&cleared), ["color of DP-2 00ff88 -> none"]);
+        assert_eq!(state.get_label(), "ON AIR");
+    }
+
+    #[test]
+    fn test_apply_scene_under_override() {
+        let state = IpcState::new(&Config::default(), (255, 255, 255));
+        let lock = Profile { color: Some("ff0000".to_string()), ..Profile::default() };
+        state.set_override("lock", Some(("lock".to_string(), lock)));
+
+        let scene: Scene = toml::from_str("[ring]\ncolor = \"0000ff\"\nthickness = 42\n").unwrap();
+        assert_eq!(state.apply_scene(&scene).len(), 2);
+        assert_eq!(state.get_color(), (255, 0, 0));
+        assert_eq!(state.get_thickness(), 42);
+
+        // The scene is what's left once the profile goes
+        state.set_override("lock", None);
+        assert_eq!(state.get_color(), (0, 0, 255));
+        assert_eq!(state.get_thickness(), 42);
+    }
+}
@@ -0,0 +1,126 @@
+//! The label: a short text ("ON AIR", a clock) in the top band
+//!
+//! It is blended over the ring on a contrasting box, set in DejaVu Sans Bold,
+//! embedded and rasterized with fontdue, so any case and most scripts show.
+
+use std::sync::OnceLock;
+
+use fontdue::{Font, FontSettings};
+
+use crate::color::contrast_color;
+use crate::overlay::Canvas;
+
+/// The label's typeface
+const LABEL_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf");
+
+/// Height of the label font's capitals and depth of its descenders, in ems
+const CAP_HEIGHT: f32 = 0.729;
+const DESCENT: f32 = 0.236;
+
+impl Canvas<'_> {
+    /// Draw `color` at `alpha` over what's in the rectangle
+    pub fn blend_rect(&mut self, x: i64, y: i64, w: i64, h: i64, color: (u8, u8, u8), alpha: f64) {
+        for py in y.max(0)..(y + h).min(self.height as i64) {
+            for px in x.max(0)..(x + w).min(self.width as i64) {
+                self.blend_pixel(px, py, color, alpha);
+            }
+        }
+    }
+
+    /// Draw `color` at `alpha` over one pixel
+    pub fn blend_pixel(&mut self, x: i64, y: i64, color: (u8, u8, u8), alpha: f64) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let a = alpha.clamp(0.0, 1.0);
+        let (r, g, b) = color;
+        let offset = (y as usize * self.width as usize + x as usize) * 4;
+        let dst = u32::from_ne_bytes([0, 1, 2, 3].map(|i| self.data[offset + i]));
+        // Source over, premultiplied
+        let over = |shift: u32, src: u8| (src as f64 * a + (dst >> shift & 0xff) as f64 * (1.0 - a)).round() as u32;
+        let pixel = (over(24, 255) << 24) | (over(16, r) << 16) | (over(8, g) << 8) | over(0, b);
+        self.data[offset..offset + 4].copy_from_slice(&pixel.to_ne_bytes());
+    }
+}
+
+/// A text label in the top band
+pub struct Label<'a> {
+    pub text: &'a str,
+    /// Letter height in pixels, 0 to fit the band
+    pub size: u32,
+    pub color: (u8, u8, u8),
+    /// Opacity of the box behind the text, in the color contrasting with it
+    pub backing: f64,
+    /// Fades the whole label, e.g. along with the ring
+    pub opacity: f64,
+}
+
+fn label_font() -> &'static Font {
+    static FONT: OnceLock<Font> = OnceLock::new();
+    FONT.get_or_init(|| Font::from_bytes(LABEL_FONT, FontSettings::default()).expect("embedded font"))
+}
+
+/// Each letter of `text` set at `px` per em, with where its pen position is
+/// from the start, kerned; and the pen position after the last one
+fn layout(font: &Font, text: &str, px: f32) -> (Vec<(char, f32)>, f32) {
+    let mut pen = 0.0;
+    let mut previous = None;
+    let letters = text.chars()
+        .map(|c| {
+            if let Some(previous) = previous {
+                pen += font.horizontal_kern(previous, c, px).unwrap_or(0.0);
+            }
+            let at = pen;
+            pen += font.metrics(c, px).advance_width;
+            previous = Some(c);
+            (c, at)
+        })
+        .collect();
+    (letters, pen)
+}
+
+/// The local time in strftime `format`, empty if the format is broken
+pub fn clock(format: &str) -> String {
+    use std::fmt::Write;
+    let mut text = String::new();
+    if write!(text, "{}", chrono::Local::now().format(format)).is_err() {
+        text.clear();
+    }
+    text
+}
+
+/// Draw `label` centered along the top band, `band` pixels thick and `inset` from the top
+pub fn draw_label(canvas: &mut Canvas, inset: u32, band: u32, label: &Label) {
+    if label.text.is_empty() || label.opacity <= 0.0 {
+        return;
+    }
+    let font = label_font();
+    // The size is that of the capitals; smaller than 7px nothing reads anyway
+    let cap = match label.size {
+        0 => band as f32 * 3.0 / 5.0,
+        size => size as f32,
+    }
+    .max(7.0);
+    let px = cap / CAP_HEIGHT;
+    let (letters, text_w) = layout(font, label.text, px);
+    let (text_w, text_h) = (text_w.ceil() as i64, cap.round() as i64);
+    // Room for descenders below, and for accents above
+    let pad = (DESCENT * px).ceil() as i64;
+    let x = (canvas.width as i64 - text_w) / 2;
+    let y = inset as i64 + ((band as i64 - text_h) / 2).max(pad);
+    if label.backing > 0.0 {
+        let backing = contrast_color(label.color);
+        canvas.blend_rect(x - pad, y - pad, text_w + 2 * pad, text_h + 2 * pad, backing, label.backing * label.opacity);
+    }
+    let baseline = y + text_h;
+    for (c, pen) in letters {
+        let (metrics, coverage) = font.rasterize(c, px);
+        let left = x + (pen + metrics.xmin as f32).round() as i64;
+        let top = baseline - metrics.height as i64 - metrics.ymin as i64;
+        for (i, &alpha) in coverage.iter().enumerate().filter(|(_, &alpha)| alpha > 0) {
+            let (col, row) = ((i % metrics.width) as i64, (i / metrics.width) as i64);
+            canvas.blend_pixel(left + col, top + row, label.color, label.opacity * alpha as f64 / 255.0);
+        }
+    }
+}
+
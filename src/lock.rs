@@ -0,0 +1,53 @@
+//! Screen lock awareness
+//!
+//! Wayland doesn't let other clients observe an ext-session-lock, so we look
+//! for a running screen locker (hyprlock, swaylock, ...) instead. While one
+//! runs, the ring is hidden or switched to the lock color through a profile
+//! override, which is undone on unlock.
+
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::LockConfig;
+use crate::ipc::IpcState;
+
+/// The kernel truncates process names in /proc/<pid>/comm to this many bytes
+const COMM_LEN: usize = 15;
+
+/// Check if any of the given processes is running
+fn is_locker_running(lockers: &[String]) -> bool {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return false;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().bytes().all(|b| b.is_ascii_digit()))
+        .filter_map(|e| fs::read_to_string(e.path().join("comm")).ok())
+        .any(|comm| {
+            let comm = comm.trim_end();
+            lockers.iter().any(|l| l.get(..COMM_LEN).unwrap_or(l) == comm)
+        })
+}
+
+/// Start the lock monitoring thread.
+///
+/// Does nothing if `action = "none"`.
+pub fn start_lock_monitor(state: Arc<IpcState>, cfg: LockConfig) {
+    let Some(profile) = cfg.profile() else {
+        return;
+    };
+
+    std::thread::spawn(move || {
+        let mut locked = false;
+        loop {
+            let now_locked = is_locker_running(&cfg.lockers);
+            if now_locked != locked {
+                locked = now_locked;
+                log::info!("Screen {}", if locked { "locked" } else { "unlocked" });
+                state.set_override("lock", locked.then(|| ("locked".to_string(), profile.clone())));
+            }
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    });
+}
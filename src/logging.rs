@@ -0,0 +1,49 @@
+//! Log output: human-readable text, or JSON Lines for log collectors
+//!
+//! JSON records carry the syslog `priority` next to the level, so journald
+//! and friends can file them without parsing the message.
+
+use std::io::Write;
+use std::time::SystemTime;
+
+use clap::ValueEnum;
+use log::{Level, LevelFilter};
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Syslog priority of a log level
+fn priority(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// Set up logging. Without `level`, `RUST_LOG` decides, defaulting to warnings.
+pub fn init(level: Option<LevelFilter>, format: LogFormat) {
+    let mut builder = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"));
+    if let Some(level) = level {
+        builder.filter_level(level);
+    }
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let line = serde_json::json!({
+                "timestamp": humantime::format_rfc3339_millis(SystemTime::now()).to_string(),
+                "level": record.level().as_str().to_lowercase(),
+                "priority": priority(record.level()),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            });
+            writeln!(buf, "{}", line)
+        });
+    }
+    builder.init();
+}
@@ -1,24 +1,93 @@
+mod autostart;
+mod bar;
+mod battery;
+mod bind;
+mod bundle;
+mod click;
+mod compositor;
+#[cfg(feature = "camera")]
 mod camera;
-mod config;
+#[cfg(feature = "color-scheme")]
+mod colorscheme;
+mod ctl;
+#[cfg(feature = "dnd")]
+mod dnd;
+mod events;
+#[cfg(feature = "gamemode")]
+mod gamemode;
+#[cfg(feature = "theme")]
+mod hyprconf;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "hyprland")]
+mod hyprland;
+mod idle;
+mod integrations;
 mod ipc;
+#[cfg(feature = "label")]
+mod label;
+mod lock;
+mod logging;
+#[cfg(feature = "hyprland")]
+mod niri;
+#[cfg(feature = "mpris")]
+mod mpris;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "obs")]
+mod obs;
+mod on_air;
+#[cfg(feature = "openrgb")]
+mod openrgb;
+mod overlay;
+mod power;
+#[cfg(feature = "hyprland")]
+mod river;
+mod scene;
+#[cfg(feature = "texture")]
+mod texture;
+#[cfg(feature = "logind")]
+mod session;
+#[cfg(feature = "shortcuts")]
+mod shortcuts;
+mod snapshot;
+#[cfg(feature = "spectrum")]
+mod spectrum;
+mod strobe;
+#[cfg(feature = "hyprland")]
+mod sway;
+mod sysmon;
+#[cfg(feature = "theme")]
 mod theme;
+mod timer;
+mod touch;
+mod transition;
+#[cfg(feature = "tray")]
+mod tray;
+#[cfg(any(feature = "camera", feature = "hyprland"))]
+mod trigger;
+mod typing;
+#[cfg(feature = "tui")]
 mod tui;
+mod viewport;
+mod watch;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use clap::{Parser, Subcommand};
-use ksni::{menu::StandardItem, menu::SubMenu, menu::RadioGroup, menu::RadioItem, menu::CheckmarkItem, Tray, TrayService};
-use signal_hook::consts::SIGUSR2;
+use signal_hook::consts::{SIGINT, SIGTERM};
 use signal_hook::iterator::Signals;
 use smithay_client_toolkit::{
     compositor::{CompositorHandler, CompositorState, Region},
-    delegate_compositor, delegate_layer, delegate_output, delegate_registry, delegate_shm,
+    delegate_compositor, delegate_layer, delegate_output, delegate_pointer, delegate_registry,
+    delegate_seat, delegate_shm, delegate_touch,
     output::{OutputHandler, OutputState},
     registry::{ProvidesRegistryState, RegistryState},
     registry_handlers,
+    seat::{Capability, SeatHandler, SeatState},
     shell::{
         wlr_layer::{
             Anchor, KeyboardInteractivity, Layer, LayerShell, LayerShellHandler, LayerSurface,
@@ -26,17 +95,42 @@ use smithay_client_toolkit::{
         },
         WaylandSurface,
     },
-    shm::{slot::SlotPool, Shm, ShmHandler},
+    shm::{slot::{Buffer, SlotPool}, Shm, ShmHandler},
+    reexports::{
+        calloop::{
+            ping::make_ping,
+            timer::{TimeoutAction, Timer},
+            EventLoop,
+        },
+        calloop_wayland_source::WaylandSource,
+    },
 };
+use hypr_ringlight_core::{check, color, config, error, shape};
 use wayland_client::{
     globals::registry_queue_init,
-    protocol::{wl_output, wl_shm, wl_surface},
+    protocol::{wl_output, wl_pointer, wl_seat, wl_shm, wl_surface, wl_touch},
     Connection, QueueHandle, Proxy,
 };
-
-use config::{Config, BarPosition};
+use smithay_client_toolkit::reexports::protocols_wlr::output_power_management::v1::client::zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1;
+use wayland_protocols::wp::viewporter::client::{wp_viewport::WpViewport, wp_viewporter::WpViewporter};
+
+use error::Error;
+use config::{Config, BarPosition, CometConfig, ExcludeZone, FramingConfig, GlowFalloff, NotchConfig, ProgressConfig, ProgressOrigin, RingLayer, SpectrumConfig, SystemConfig, Taper, TransitionConfig};
+use color::resolve_color;
+use hypr_ringlight_core::render::{self, Catchlight, Comet, Progress, Ring, Spectrum};
+use shape::Shape;
+use timer::TimerPhase;
 use ipc::IpcState;
 
+/// Buffers per monitor: one on screen while the next frame is drawn into the other
+const BUFFERS_PER_MONITOR: usize = 2;
+
+/// Brightness of the elapsed part of a focus timer's countdown
+const TIMER_TRACK: f64 = 0.15;
+
+/// How often paused rings check whether they can resume
+const RESUME_CHECK: Duration = Duration::from_millis(500);
+
 /// Ring Light overlay for Hyprland/Wayland
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
@@ -44,7 +138,19 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
     
-    /// Ring color in hex format (e.g., ff0000 for red)
+    /// Log level: error, warn, info, debug, trace (default: RUST_LOG, or warn)
+    #[arg(long, global = true)]
+    log_level: Option<log::LevelFilter>,
+
+    /// Run or control a separate instance, with its own socket, config file and tray icon
+    #[arg(long, global = true, value_name = "NAME", env = "HYPR_RINGLIGHT_INSTANCE", value_parser = parse_instance)]
+    instance: Option<String>,
+
+    /// Log as text, or as JSON Lines for log collectors
+    #[arg(long, global = true, value_enum, default_value_t, env = "HYPR_RINGLIGHT_LOG_FORMAT")]
+    log_format: logging::LogFormat,
+
+    /// Ring color: hex (e.g., ff0000), a CSS color like red or rgb(255,0,0), or a palette name
     #[arg(short, long)]
     color: Option<String>,
 
@@ -64,14 +170,50 @@ struct Cli {
     #[arg(long)]
     corner_radius: Option<f64>,
 
-    /// Animation mode (none, pulse, rainbow, breathe)
+    /// Corner style (round, squircle, chamfer)
+    #[arg(long)]
+    corner_style: Option<String>,
+
+    /// Float the ring this many pixels away from the screen edge
+    #[arg(long)]
+    inset: Option<u32>,
+
+    /// Thin the ring away from the taper anchor (0.0 - 1.0)
+    #[arg(long)]
+    taper: Option<f64>,
+
+    /// Where the tapered ring is thickest (corners, centers)
+    #[arg(long)]
+    taper_anchor: Option<String>,
+
+    /// Glow falloff curve (linear, quadratic, cubic, exponential, gaussian)
+    #[arg(long)]
+    glow_falloff: Option<String>,
+
+    /// Animation mode (none, pulse, rainbow, breathe, comet, heartbeat, spectrum, system, typing)
     #[arg(short, long)]
     animation: Option<String>,
 
-    /// Animation speed (frames per cycle, lower = faster)
+    /// Duration of one animation cycle (e.g., 2s, 500ms)
     #[arg(long)]
+    cycle_duration: Option<String>,
+
+    /// Deprecated: animation speed in frames per cycle, use --cycle-duration
+    #[arg(long, hide = true)]
     animation_speed: Option<u32>,
 
+    /// Dash pattern along the band (solid, dashed, dotted)
+    #[arg(long)]
+    pattern: Option<String>,
+
+    /// Warm-up ramp when the ring turns on (e.g., 3s; 0s = instant)
+    #[arg(long)]
+    warmup: Option<String>,
+
+    /// Warm-up easing curve (linear, ease-in, ease-out, smoothstep)
+    #[arg(long)]
+    warmup_curve: Option<String>,
+
     /// Waybar/bar height in pixels (ring starts below/beside this)
     #[arg(long)]
     bar_height: Option<u32>,
@@ -79,56 +221,60 @@ struct Cli {
     /// Waybar/bar position (top, bottom, left, right)
     #[arg(long)]
     bar_position: Option<String>,
-}
 
-#[derive(Subcommand, Debug)]
-enum Commands {
-    /// Interactive configuration TUI (live preview)
-    Config,
-}
+    /// Ignore the config file and options, and run without camera, theme,
+    /// Hyprland and tray integrations (recovery if startup crashes)
+    #[arg(long, env = "HYPR_RINGLIGHT_SAFE")]
+    safe_mode: bool,
 
-fn parse_hex_color(hex: &str) -> (u8, u8, u8) {
-    let hex = hex.trim_start_matches('#');
-    if hex.len() < 6 {
-        return (255, 255, 255);
-    }
-    let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(255);
-    let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(255);
-    let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(255);
-    (r, g, b)
-}
+    /// Quit after this long (e.g. 5s), to preview a config
+    #[arg(long, value_name = "DURATION")]
+    once: Option<String>,
 
-fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (u8, u8, u8) {
-    if s == 0.0 {
-        let v = (l * 255.0) as u8;
-        return (v, v, v);
-    }
+    /// Named pipe (created if missing) or `-` for stdin, to read values from
+    #[arg(long, value_name = "PATH")]
+    bind_fifo: Option<std::path::PathBuf>,
 
-    let q = if l < 0.5 { l * (1.0 + s) } else { l + s - l * s };
-    let p = 2.0 * l - q;
+    /// Parameter set by plain numbers read from --bind-fifo
+    #[arg(long, value_enum, default_value_t, requires = "bind_fifo")]
+    bind_param: bind::Param,
 
-    let hue_to_rgb = |p: f64, q: f64, mut t: f64| -> f64 {
-        if t < 0.0 { t += 1.0; }
-        if t > 1.0 { t -= 1.0; }
-        if t < 1.0 / 6.0 { return p + (q - p) * 6.0 * t; }
-        if t < 1.0 / 2.0 { return q; }
-        if t < 2.0 / 3.0 { return p + (q - p) * (2.0 / 3.0 - t) * 6.0; }
-        p
-    };
+    /// Draw a single frame offscreen to a PNG and exit, without a compositor
+    #[cfg(feature = "snapshot")]
+    #[arg(long, num_args = 2, value_names = ["PATH", "WIDTHxHEIGHT"])]
+    render_to_file: Option<Vec<String>>,
 
-    (
-        (hue_to_rgb(p, q, h + 1.0 / 3.0) * 255.0) as u8,
-        (hue_to_rgb(p, q, h) * 255.0) as u8,
-        (hue_to_rgb(p, q, h - 1.0 / 3.0) * 255.0) as u8,
-    )
+    /// Time the drawing of frames at a monitor size (default 1920x1080) and exit
+    #[arg(long, hide = true, value_name = "WIDTHxHEIGHT", num_args = 0..=1, default_missing_value = "1920x1080")]
+    bench_frame: Option<String>,
 }
 
-/// Monitor info for tray menu (id + display name + enabled status)
-#[derive(Clone, Debug)]
-struct MonitorInfo {
-    id: String,           // Connector name (DP-2, HDMI-1, etc.) - used as unique ID
-    display_name: String, // Friendly name (brand/model) - shown in UI
-    enabled: bool,
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Interactive configuration TUI (live preview), or export/import the config
+    Config {
+        #[command(subcommand)]
+        action: Option<bundle::ConfigAction>,
+    },
+    /// Control the running instance
+    Ctl {
+        #[command(subcommand)]
+        command: ctl::CtlCommand,
+    },
+    /// Start hypr-ringlight with your session
+    Autostart {
+        #[command(subcommand)]
+        action: autostart::AutostartAction,
+    },
+    /// Pulse the ring while a command runs, then flash its result
+    Watch(watch::WatchArgs),
+    /// Show recent changes to the ring and what made them
+    Events(events::EventsArgs),
+    /// Apply scene files describing the whole look of the ring
+    Scene {
+        #[command(subcommand)]
+        action: scene::SceneAction,
+    },
 }
 
 /// Extended shared state with IPC support
@@ -137,26 +283,12 @@ struct SharedState {
 }
 
 impl SharedState {
-    fn new(
-        color: (u8, u8, u8),
-        thickness: u32,
-        opacity: f64,
-        glow: u32,
-        corner_radius: f64,
-        animation: u8,
-        animation_speed: u32,
-        disabled_monitors: Vec<String>,
-    ) -> Self {
+    fn new(cfg: &Config, color: (u8, u8, u8)) -> Self {
         Self {
-            ipc: Arc::new(IpcState::new(color, thickness, opacity, glow, corner_radius, animation, animation_speed, disabled_monitors)),
+            ipc: Arc::new(IpcState::new(cfg, color)),
         }
     }
     
-    fn toggle_monitor(&self, id: &str) {
-        self.ipc.toggle_monitor(id);
-        self.ipc.save_to_config();
-    }
-    
     fn is_monitor_enabled(&self, id: &str) -> bool {
         self.ipc.is_monitor_enabled(id)
     }
@@ -168,190 +300,60 @@ impl SharedState {
     fn remove_monitor(&self, id: &str) {
         self.ipc.remove_monitor(id);
     }
-    
-    fn get_monitors(&self) -> Vec<MonitorInfo> {
-        self.ipc.get_monitors().into_iter().map(|m| MonitorInfo {
-            id: m.id,
-            display_name: m.display_name,
-            enabled: m.enabled,
-        }).collect()
-    }
-}
-
-// Tray icon
-struct RingLightTray {
-    state: Arc<SharedState>,
-}
-
-impl Tray for RingLightTray {
-    fn id(&self) -> String {
-        "hypr-ringlight".into()
-    }
-
-    fn icon_name(&self) -> String {
-        "video-display".into()
-    }
-
-    fn title(&self) -> String {
-        "RingLight".into()
-    }
-
-    fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
-        let is_visible = self.state.ipc.is_visible();
-        let current_anim = self.state.ipc.get_animation_mode();
-        let current_thickness = self.state.ipc.get_thickness();
-        let monitors = self.state.get_monitors();
-        
-        // Map thickness to preset index
-        let thickness_idx = match current_thickness {
-            40 => 0,
-            80 => 1,
-            120 => 2,
-            160 => 3,
-            _ => 4,
-        };
-
-        let mut menu = vec![
-            // Show/Hide toggle
-            StandardItem {
-                label: if is_visible { "Hide Ring" } else { "Show Ring" }.into(),
-                activate: Box::new(|tray: &mut Self| {
-                    let current = tray.state.ipc.is_visible();
-                    tray.state.ipc.visible.store(!current, Ordering::Relaxed);
-                    tray.state.ipc.save_to_config();
-                }),
-                ..Default::default()
-            }.into(),
-            
-            ksni::MenuItem::Separator,
-            
-            // Width submenu
-            SubMenu {
-                label: format!("Width ({}px)", current_thickness),
-                submenu: vec![
-                    RadioGroup {
-                        selected: thickness_idx,
-                        select: Box::new(|tray: &mut Self, idx| {
-                            let val = match idx {
-                                0 => 40,
-                                1 => 80,
-                                2 => 120,
-                                3 => 160,
-                                _ => return,
-                            };
-                            tray.state.ipc.thickness.store(val, Ordering::Relaxed);
-                            tray.state.ipc.save_to_config();
-                        }),
-                        options: vec![
-                            RadioItem { label: "Subtle (40px)".into(), ..Default::default() },
-                            RadioItem { label: "Normal (80px)".into(), ..Default::default() },
-                            RadioItem { label: "Strong (120px)".into(), ..Default::default() },
-                            RadioItem { label: "Maximum (160px)".into(), ..Default::default() },
-                        ],
-                    }.into(),
-                    ksni::MenuItem::Separator,
-                    StandardItem {
-                        label: "Increase (+20px)".into(),
-                        icon_name: "list-add-symbolic".into(),
-                        activate: Box::new(|tray: &mut Self| {
-                            let current = tray.state.ipc.get_thickness();
-                            tray.state.ipc.thickness.store((current + 20).min(200), Ordering::Relaxed);
-                            tray.state.ipc.save_to_config();
-                        }),
-                        ..Default::default()
-                    }.into(),
-                    StandardItem {
-                        label: "Decrease (-20px)".into(),
-                        icon_name: "list-remove-symbolic".into(),
-                        activate: Box::new(|tray: &mut Self| {
-                            let current = tray.state.ipc.get_thickness();
-                            tray.state.ipc.thickness.store(current.saturating_sub(20).max(10), Ordering::Relaxed);
-                            tray.state.ipc.save_to_config();
-                        }),
-                        ..Default::default()
-                    }.into(),
-                ],
-                ..Default::default()
-            }.into(),
-            
-            // Animation submenu
-            SubMenu {
-                label: format!("Animation ({})", match current_anim {
-                    0 => "None",
-                    1 => "Pulse", 
-                    2 => "Rainbow",
-                    3 => "Breathe",
-                    _ => "Unknown",
-                }),
-                submenu: vec![
-                    RadioGroup {
-                        selected: current_anim as usize,
-                        select: Box::new(|tray: &mut Self, idx| {
-                            tray.state.ipc.animation_mode.store(idx as u8, Ordering::Relaxed);
-                            tray.state.ipc.save_to_config();
-                        }),
-                        options: vec![
-                            RadioItem { label: "None".into(), ..Default::default() },
-                            RadioItem { label: "Pulse".into(), ..Default::default() },
-                            RadioItem { label: "Rainbow".into(), ..Default::default() },
-                            RadioItem { label: "Breathe".into(), ..Default::default() },
-                        ],
-                    }.into(),
-                ],
-                ..Default::default()
-            }.into(),
-        ];
-        
-        // Monitors submenu (only if we have monitors)
-        if !monitors.is_empty() {
-            let enabled_count = monitors.iter().filter(|m| m.enabled).count();
-            let monitor_items: Vec<ksni::MenuItem<Self>> = monitors.iter().map(|m| {
-                let id = m.id.clone();
-                let label = if m.enabled {
-                    format!("[ON]  {}", m.display_name)
-                } else {
-                    format!("[OFF] {}", m.display_name)
-                };
-                CheckmarkItem {
-                    label,
-                    checked: m.enabled,
-                    activate: Box::new(move |tray: &mut Self| {
-                        tray.state.toggle_monitor(&id);
-                    }),
-                    ..Default::default()
-                }.into()
-            }).collect();
-            
-            menu.push(SubMenu {
-                label: format!("Monitors ({}/{})", enabled_count, monitors.len()),
-                submenu: monitor_items,
-                ..Default::default()
-            }.into());
-        }
-        
-        menu.push(ksni::MenuItem::Separator);
-        
-        // Quit
-        menu.push(StandardItem {
-            label: "Quit".into(),
-            activate: Box::new(|_| {
-                std::process::exit(0);
-            }),
-            ..Default::default()
-        }.into());
-        
-        menu
-    }
 }
 
 /// State for a single monitor's ring light
 struct MonitorRing {
     layer: LayerSurface,
-    pool: SlotPool,
+    /// Buffer pool, created on first draw and dropped while paused
+    pool: Option<SlotPool>,
+    /// Persistent buffers, all of `buffer_size`: one on screen, one to draw into
+    buffers: Vec<Buffer>,
+    buffer_size: (u32, u32),
     width: u32,
     height: u32,
+    /// Waiting for a configure before drawing: just created, or remapped after an output change
     first_configure: bool,
     output_name: String,
+    /// Output scale factor; buffers are drawn at this many pixels per surface pixel
+    scale_factor: i32,
+    /// Output size in logical pixels, to notice mode changes
+    logical_size: Option<(i32, i32)>,
+    /// Output rotation, which moves a physically placed bar
+    transform: wl_output::Transform,
+    /// Bar on this monitor, in logical coordinates
+    bar: bar::Bar,
+    /// Layer the surface was last put on
+    on_layer: RingLayer,
+    /// Frames drawn since `fps_since`, for the debug overlay
+    frame_count: u32,
+    fps_since: Instant,
+    fps: f64,
+    /// When the ring last turned on here, for the warm-up ramp
+    lit_since: Option<Instant>,
+    /// Scales a reduced-resolution buffer up to the output
+    viewport: Option<WpViewport>,
+    /// Fades the ring in and out as it's shown and hidden
+    transition: transition::Transition,
+    /// Live parameters, eased toward their latest values
+    smoothed: transition::Smoothed,
+    /// Unmapped while our session is inactive, the monitor is disabled or its screen is off
+    paused: bool,
+    /// Power mode events of the output, if the compositor reports them
+    _power: Option<power::OutputPower>,
+    /// The compositor blanked the output (DPMS)
+    powered_off: bool,
+    /// When the last frame was drawn, for the battery frame rate cap
+    last_draw: Option<Instant>,
+    /// Surface size and band width the input region was set for, when the band takes input
+    input_band: Option<(u32, u32, i32)>,
+}
+
+impl MonitorRing {
+    /// Whether nobody can see this ring: its output is blanked, or it's the built-in panel under a closed lid
+    fn is_dark(&self, ipc: &IpcState) -> bool {
+        self.powered_off || (ipc.is_lid_closed() && power::is_internal(&self.output_name))
+    }
 }
 
 struct RingLight {
@@ -360,6 +362,11 @@ struct RingLight {
     compositor: CompositorState,
     layer_shell: LayerShell,
     shm: Shm,
+    seat_state: SeatState,
+    /// Present while the ring band takes clicks and a seat has a pointer
+    pointer: Option<wl_pointer::WlPointer>,
+    /// Present while the ring band takes touches and a seat has a touchscreen
+    touch: Option<wl_touch::WlTouch>,
     
     /// Map from wl_surface id to monitor ring
     monitors: HashMap<u32, MonitorRing>,
@@ -368,28 +375,141 @@ struct RingLight {
     
     start_time: Instant,
     
-    // Static config (bar position can't change at runtime)
-    bar_height: i32,
-    bar_position: BarPosition,
+    // Static config
+    bar_physical: bool,
+    /// Follow the space the compositor reserves for bars
+    bar_auto: bool,
+    /// Per-monitor overrides of the bar settings
+    monitor_config: BTreeMap<String, config::MonitorConfig>,
+    notch: NotchConfig,
+    exclude: Vec<ExcludeZone>,
+    #[cfg(feature = "texture")]
+    texture_mode: config::TextureMode,
+    inset: f64,
+    vignette_distance: f64,
+    fill: config::FillConfig,
+    webcam: config::WebcamConfig,
+    taper: Taper,
+    falloff: GlowFalloff,
+    inner_glow: f64,
+    dither: bool,
+    framing: FramingConfig,
+    #[cfg(feature = "label")]
+    label: config::LabelConfig,
+    spectrum: SpectrumConfig,
+    system: SystemConfig,
+    progress: ProgressConfig,
+    comet: CometConfig,
+    /// Actions of clicks on the band, with `[click] enabled`
+    clicks: Option<click::Clicks>,
+    /// Touch gestures on the band, with `[touch] enabled`
+    gestures: Option<touch::Gestures>,
+    
+    /// Fades the ring while the user is idle
+    idle_fade: idle::IdleFade,
+    typing_glow: typing::TypingGlow,
+    transition: TransitionConfig,
+    /// Minimum time between frames while saving battery
+    battery_frame_interval: Duration,
+    /// Decoded texture fill, reloaded when its path changes
+    #[cfg(feature = "texture")]
+    texture: texture::TextureCache,
+    /// Present when drawing at `render_scale` below 1.0
+    viewporter: Option<WpViewporter>,
+    power_manager: Option<ZwlrOutputPowerManagerV1>,
+    render_scale: f64,
+    /// Argb8888, or Xrgb8888 drawn over a solid backdrop if the compositor can't blend
+    buffer_format: wl_shm::Format,
     
     // Shared state with tray and IPC
     state: Arc<SharedState>,
 }
 
 impl RingLight {
+    /// Remap rings paused while the session was inactive, a game ran or their monitor was disabled
+    fn resume_paused(&mut self) {
+        if self.state.ipc.is_suspended() {
+            return;
+        }
+        let ipc = &self.state.ipc;
+        let enabled = |m: &MonitorRing| ipc.is_monitor_enabled(&m.output_name) && !ipc.is_monitor_suppressed(&m.output_name);
+        for monitor in self.monitors.values_mut().filter(|m| m.paused && enabled(m) && !m.is_dark(ipc)) {
+            monitor.paused = false;
+            monitor.transition.resume(Instant::now());
+            // A commit without a buffer asks for a new configure, which redraws
+            monitor.layer.commit();
+        }
+    }
+
+    /// Follow a rotated output, moving a physically placed bar to its new edge
+    fn set_transform(&mut self, surface_id: u32, transform: wl_output::Transform) {
+        let Some(monitor) = self.monitors.get_mut(&surface_id).filter(|m| m.transform != transform) else {
+            return;
+        };
+        log::info!("Transform of {} changed to {:?}", monitor.output_name, transform);
+        monitor.transform = transform;
+        self.update_bar(surface_id);
+    }
+
+    /// Re-apply the margins of a monitor whose bar moved, resized or was toggled
+    fn update_bar(&mut self, surface_id: u32) {
+        let Some(bar) = self.monitors.get(&surface_id).map(|m| self.bar_for(&m.output_name, m.transform)) else {
+            return;
+        };
+        let Some(monitor) = self.monitors.get_mut(&surface_id).filter(|m| m.bar != bar) else {
+            return;
+        };
+        // The compositor answers the new margin with a configure, which redraws
+        monitor.bar = bar;
+        self.state.ipc.set_monitor_bar(&monitor.output_name, bar);
+        let (top, right, bottom, left) = bar.margin();
+        monitor.layer.set_margin(top, right, bottom, left);
+        monitor.layer.commit();
+    }
+
+    /// The bar on output `name` with `transform`
+    fn bar_for(&self, name: &str, transform: wl_output::Transform) -> bar::Bar {
+        let overrides = self.monitor_config.get(name);
+        let height = overrides.and_then(|m| m.bar_height).map(|h| h as i32);
+        // A height set for this monitor beats detection
+        if self.bar_auto && height.is_none() {
+            if let Some(reserved) = self.state.ipc.get_reserved(name) {
+                return reserved;
+            }
+        }
+        let edge = overrides
+            .and_then(|m| m.bar_position.as_deref())
+            .map_or_else(|| self.state.ipc.get_bar_position(), BarPosition::from_name);
+        let position = if self.bar_physical {
+            bar::rotate(edge, transform)
+        } else {
+            edge
+        };
+        bar::Bar::new(position, height.unwrap_or_else(|| self.state.ipc.get_bar_height() as i32))
+    }
+
     fn create_ring_for_output(&mut self, qh: &QueueHandle<Self>, output: &wl_output::WlOutput, id: String, display_name: String) {
         // Create surface
         let surface = self.compositor.create_surface(qh);
         
         // Create empty input region for click-through
-        let empty_region = Region::new(&self.compositor).expect("Failed to create region");
+        let empty_region = match Region::new(&self.compositor) {
+            Ok(region) => region,
+            Err(e) => {
+                log::error!("Failed to create input region for {}: {}", id, e);
+                return;
+            }
+        };
         surface.set_input_region(Some(empty_region.wl_region()));
+        let viewport = self.viewporter.as_ref().map(|v| v.get_viewport(&surface, qh, ()));
+        let info = self.output_state.info(output);
 
         // Create layer surface bound to this specific output
+        let on_layer = self.state.ipc.get_layer();
         let layer = self.layer_shell.create_layer_surface(
             qh, 
             surface.clone(), 
-            Layer::Overlay, 
+            wlr_layer(on_layer), 
             Some("ringlight"), 
             Some(output)
         );
@@ -400,184 +520,423 @@ impl RingLight {
         layer.set_exclusive_zone(-1);
         
         // Set margin for bar
-        match self.bar_position {
-            BarPosition::Top => layer.set_margin(self.bar_height, 0, 0, 0),
-            BarPosition::Bottom => layer.set_margin(0, 0, self.bar_height, 0),
-            BarPosition::Left => layer.set_margin(0, 0, 0, self.bar_height),
-            BarPosition::Right => layer.set_margin(0, self.bar_height, 0, 0),
-        }
+        let transform = info.as_ref().map_or(wl_output::Transform::Normal, |i| i.transform);
+        let bar = self.bar_for(&id, transform);
+        let (top, right, bottom, left) = bar.margin();
+        layer.set_margin(top, right, bottom, left);
 
         layer.commit();
 
-        // Create buffer pool
-        let pool = SlotPool::new(1920 * 1080 * 4, &self.shm).expect("Failed to create pool");
-        
         let surface_id = surface.id().protocol_id();
+        let output_power = self.power_manager.as_ref().map(|m| power::OutputPower::new(m, output, qh, surface_id));
         
         // Add to shared state
+        log::info!("Created ring for {} ({})", id, display_name);
         self.state.add_monitor(id.clone(), display_name);
+        self.state.ipc.set_monitor_bar(&id, bar);
 
         self.monitors.insert(surface_id, MonitorRing {
             layer,
-            pool,
+            pool: None,
+            buffers: Vec::new(),
+            buffer_size: (0, 0),
             width: 0,
             height: 0,
             first_configure: true,
             output_name: id,
+            scale_factor: info.as_ref().map_or(1, |i| i.scale_factor.max(1)),
+            logical_size: info.and_then(|i| i.logical_size),
+            transform,
+            bar,
+            on_layer,
+            frame_count: 0,
+            fps_since: Instant::now(),
+            fps: 0.0,
+            lit_since: None,
+            viewport,
+            transition: transition::Transition::new(&self.transition),
+            smoothed: transition::Smoothed::default(),
+            paused: false,
+            _power: output_power,
+            powered_off: false,
+            last_draw: None,
+            input_band: None,
         });
     }
+
+    /// Follow an output's DPMS state: a blanked screen pauses its ring like a disabled monitor
+    fn set_output_power(&mut self, surface_id: u32, on: bool) {
+        let Some(monitor) = self.monitors.get_mut(&surface_id).filter(|m| m.powered_off == on) else {
+            return;
+        };
+        log::info!("Output {} powered {}", monitor.output_name, if on { "on" } else { "off" });
+        monitor.powered_off = !on;
+        if on {
+            self.resume_paused();
+        }
+    }
     
     fn draw_monitor(&mut self, surface_id: u32, qh: &QueueHandle<Self>) {
+        self.update_bar(surface_id);
         let monitor = match self.monitors.get_mut(&surface_id) {
             Some(m) => m,
             None => return,
         };
         
+        // Check if this monitor is enabled (and not hidden by a workspace/focus rule)
+        let monitor_enabled = self.state.is_monitor_enabled(&monitor.output_name)
+            && !self.state.ipc.is_monitor_suppressed(&monitor.output_name);
+
+        // Another session owns the screen, a game runs, the screen is off, or the
+        // ring has faded out on a disabled monitor: unmap and free the buffers, and
+        // stop requesting frames until `resume_paused` brings the ring back
+        if self.state.ipc.is_suspended() || monitor.is_dark(&self.state.ipc) || (!monitor_enabled && monitor.transition.is_hidden()) {
+            if !monitor.paused {
+                log::debug!("Unmapping ring on {}", monitor.output_name);
+                monitor.paused = true;
+                monitor.pool = None;
+                monitor.buffers.clear();
+                monitor.layer.wl_surface().attach(None, 0, 0);
+                monitor.layer.commit();
+            }
+            return;
+        }
+        
+        // Takes effect with the commit of this frame
+        let layer = self.state.ipc.get_layer();
+        if monitor.on_layer != layer {
+            log::info!("Moving ring on {} to the {} layer", monitor.output_name, layer.name());
+            monitor.on_layer = layer;
+            monitor.layer.set_layer(wlr_layer(layer));
+        }
+        
         let width = monitor.width;
         let height = monitor.height;
         
-        if width == 0 || height == 0 {
+        if width == 0 || height == 0 || monitor.first_configure {
             return;
         }
         
-        // Check if this monitor is enabled
-        let monitor_enabled = self.state.is_monitor_enabled(&monitor.output_name);
+        // On a low battery, skip frames to cap the frame rate: keep the
+        // current buffer and just wait for the next frame callback
+        let now = Instant::now();
+        if self.state.ipc.is_battery_saving()
+            && monitor.last_draw.is_some_and(|t| now.duration_since(t) < self.battery_frame_interval)
+        {
+            monitor.layer.wl_surface().frame(qh, monitor.layer.wl_surface().clone());
+            monitor.layer.commit();
+            return;
+        }
+        monitor.last_draw = Some(now);
+        
+        monitor.frame_count += 1;
+        let since = monitor.fps_since.elapsed().as_secs_f64();
+        if since >= 1.0 {
+            monitor.fps = monitor.frame_count as f64 / since;
+            monitor.frame_count = 0;
+            monitor.fps_since = Instant::now();
+        }
 
+
+        // Buffer pixels per surface pixel: the output scale, lowered by
+        // `render_scale` when a viewport scales the buffer back up
+        let render_scale = match monitor.viewport {
+            Some(_) => monitor.scale_factor as f64 * self.render_scale,
+            None => {
+                monitor.layer.wl_surface().set_buffer_scale(monitor.scale_factor);
+                monitor.scale_factor as f64
+            }
+        };
+        let (width, height) = viewport::buffer_size(width, height, render_scale);
+
+        // Reallocate only when the size changes; otherwise draw into whichever
+        // buffer the compositor has released
+        if monitor.buffer_size != (width, height) {
+            monitor.buffers.clear();
+            monitor.pool = None;
+            monitor.buffer_size = (width, height);
+        }
         let stride = width as i32 * 4;
-        let (buffer, canvas) = monitor
-            .pool
-            .create_buffer(width as i32, height as i32, stride, wl_shm::Format::Argb8888)
-            .expect("create buffer");
+        let frame_bytes = stride as usize * height as usize;
+        let pool = match monitor.pool.take().map_or_else(|| SlotPool::new(frame_bytes * BUFFERS_PER_MONITOR, &self.shm), Ok) {
+            Ok(pool) => monitor.pool.insert(pool),
+            Err(e) => {
+                log::error!("Failed to create buffer pool for {}: {}", monitor.output_name, e);
+                return;
+            }
+        };
+        let free = monitor.buffers.iter().position(|b| b.canvas(pool).is_some());
+        let index = match free {
+            Some(index) => index,
+            None if monitor.buffers.len() < BUFFERS_PER_MONITOR => {
+                match pool.create_buffer(width as i32, height as i32, stride, self.buffer_format) {
+                    Ok((buffer, _)) => monitor.buffers.push(buffer),
+                    Err(e) => {
+                        log::error!("Failed to create buffer for {}: {}", monitor.output_name, e);
+                        return;
+                    }
+                }
+                monitor.buffers.len() - 1
+            }
+            None => {
+                // The compositor still holds every buffer: wait for the next frame
+                monitor.layer.wl_surface().frame(qh, monitor.layer.wl_surface().clone());
+                monitor.layer.commit();
+                return;
+            }
+        };
+        let pool_bytes = pool.len();
+        let buffer = &monitor.buffers[index];
+        let Some(canvas) = buffer.canvas(pool) else {
+            return;
+        };
 
+        // Keep a batch from landing halfway through reading the state
+        let ipc = self.state.ipc.clone();
+        let _frame = ipc.lock_frame();
+
+        // A focus timer takes over the ring, even when it's hidden
+        let timer = self.state.ipc.get_timer().map_or(TimerPhase::Done, |t| t.phase(now));
+        // A countdown shows over that the same way, without the flash at the end
+        let countdown = self.state.ipc.get_countdown().map(|c| c.phase(now));
+        let timer = countdown.filter(|p| matches!(p, TimerPhase::Running(_))).unwrap_or(timer);
+        // And a strobe alert over that, until it runs out
+        let strobe = self.state.ipc.get_strobe().and_then(|s| Some((s.phase(now)?, s.color)));
+        
         // Read all values from IpcState (allows real-time updates)
-        let is_visible = self.state.ipc.is_visible() && monitor_enabled;
+        let is_visible = (self.state.ipc.is_visible() || timer != TimerPhase::Done || strobe.is_some()) && monitor_enabled;
         let anim_mode = self.state.ipc.get_animation_mode();
-        let thickness = self.state.ipc.get_thickness() as f64;
-        let glow = self.state.ipc.get_glow() as f64;
-        let corner_radius = thickness * self.state.ipc.get_corner_radius();
-        let base_color = self.state.ipc.get_color();
-        let base_opacity = self.state.ipc.get_opacity();
-        let animation_speed = self.state.ipc.get_animation_speed();
+        // Fade in and out rather than snapping, optionally growing out of the edge
+        let shown = monitor.transition.step(is_visible, now);
+        let scale = if self.transition.scale_in { shown } else { 1.0 };
+        let params = monitor.smoothed.step(transition::Params {
+            thickness: self.state.ipc.get_thickness() as f64,
+            glow: self.state.ipc.get_glow() as f64,
+            // The alpha of a color like "ff880080" dims the ring like the opacity
+            opacity: self.state.ipc.get_opacity() * self.state.ipc.get_color_alpha(),
+            radii: self.state.ipc.get_corner_radii(),
+            color: monitor.transition.color(
+                self.state.ipc.get_monitor_color(&monitor.output_name).unwrap_or_else(|| self.state.ipc.get_color()),
+                now,
+            ),
+        }, now);
+        let thickness = params.thickness * scale * render_scale;
+        let glow = params.glow * scale * render_scale;
+        let inset = self.inset * render_scale;
+        let vignette = self.state.ipc.vignette.load(Ordering::Relaxed).then_some(self.vignette_distance * scale * render_scale);
+        let shape = Shape {
+            radii: params.radii.map(|m| thickness * m),
+            style: self.state.ipc.get_corner_style(),
+            notch: self.notch.size_for(&monitor.output_name).map(|(w, h)| (w * render_scale, h * render_scale)),
+            taper: self.taper,
+        };
+        // Clicks and touches land on the band, in surface coordinates
+        if self.clicks.is_some() || self.gestures.is_some() {
+            let band = (self.inset + params.thickness * scale).ceil().max(1.0) as i32;
+            let key = (monitor.width, monitor.height, band);
+            if monitor.input_band != Some(key) {
+                if let Some(region) = click::band_region(&self.compositor, monitor.width, monitor.height, band) {
+                    monitor.layer.wl_surface().set_input_region(Some(region.wl_region()));
+                    monitor.input_band = Some(key);
+                }
+            }
+        }
+        let base_color = params.color;
+        let base_opacity = params.opacity;
+        let cycle_secs = self.state.ipc.get_cycle_ms() as f64 / 1000.0;
         
-        // Animation frame
+        // Animation phase, in cycles since start
         let elapsed = self.start_time.elapsed().as_secs_f64();
-        let frame = (elapsed * 60.0) as u32;
+        let phase = elapsed / cycle_secs;
         
-        // Calculate animated color and opacity
-        let (color, opacity) = if !is_visible {
-            ((0, 0, 0), 0.0)
+        // Restart the warm-up ramp whenever this ring flips on, whatever the source
+        let warmup = if is_visible {
+            let lit_since = *monitor.lit_since.get_or_insert_with(Instant::now);
+            let warmup_ms = self.state.ipc.get_warmup_ms();
+            if warmup_ms > 0 {
+                let t = lit_since.elapsed().as_secs_f64() * 1000.0 / warmup_ms as f64;
+                self.state.ipc.get_warmup_curve().apply(t)
+            } else {
+                1.0
+            }
         } else {
-            match anim_mode {
-                0 => (base_color, base_opacity),
-                1 => {
-                    let pulse = ((frame as f64 / animation_speed as f64) * 2.0 * std::f64::consts::PI).sin();
-                    let opacity = base_opacity * (0.5 + 0.5 * pulse);
-                    (base_color, opacity)
-                }
-                2 => {
-                    let hue = (frame as f64 / animation_speed as f64) % 1.0;
-                    let color = hsl_to_rgb(hue, 1.0, 0.5);
-                    (color, base_opacity)
-                }
-                3 => {
-                    let breathe = ((frame as f64 / animation_speed as f64) * std::f64::consts::PI).sin();
-                    let opacity = base_opacity * breathe.abs().max(0.1);
-                    (base_color, opacity)
-                }
-                _ => (base_color, base_opacity),
+            monitor.lit_since = None;
+            1.0
+        };
+        
+        // Calculate animated color and opacity; a hidden ring keeps its look while fading out
+        let (color, opacity) = render::animate(anim_mode, base_color, base_opacity, phase);
+        let (color, opacity) = if anim_mode == 7 {
+            let level = self.state.ipc.get_system_level();
+            render::gauge(self.system.map_enum(), level, color, opacity, self.system.floor.clamp(0.0, 1.0))
+        } else {
+            (color, opacity)
+        };
+        let typing = if anim_mode == 8 { self.typing_glow.step(self.state.ipc.is_typing(), now) } else { 1.0 };
+        let opacity = opacity * shown * warmup * typing * self.idle_fade.step(self.state.ipc.is_idle(), now);
+        // Partially lit perimeter
+        let (opacity, progress) = match timer {
+            TimerPhase::Running(remaining) => {
+                (opacity, Some(Progress { fraction: remaining, origin: ProgressOrigin::Top, track: TIMER_TRACK }))
+            }
+            TimerPhase::Flashing(on) => (if on { opacity } else { 0.0 }, None),
+            TimerPhase::Done => {
+                let progress = self.state.ipc.get_progress().map(|fraction| Progress {
+                    fraction,
+                    origin: self.progress.origin_enum(),
+                    track: self.progress.track.clamp(0.0, 1.0),
+                });
+                (opacity, progress)
             }
         };
+        let (color, opacity, progress) = match strobe {
+            Some((on, strobe_color)) if monitor_enabled => {
+                (strobe_color.unwrap_or(base_color), if on { base_opacity } else { 0.0 }, None)
+            }
+            _ => (color, opacity, progress),
+        };
 
-        // Draw pixels
-        canvas.chunks_exact_mut(4).enumerate().for_each(|(index, chunk)| {
-            let x = (index % width as usize) as f64;
-            let y = (index / width as usize) as f64;
-            let w = width as f64;
-            let h = height as f64;
+        // One lap per cycle; the timer's countdown takes precedence
+        let comet = (anim_mode == 4 && timer == TimerPhase::Done && strobe.is_none()).then(|| Comet {
+            head: phase.fract(),
+            length: self.comet.length,
+            count: self.comet.count,
+        });
 
-            let total_ring = thickness + glow;
-            let dist_to_inner = distance_to_inner_rounded_border(x, y, w, h, total_ring, corner_radius);
-            
-            let alpha = if dist_to_inner <= 0.0 {
-                0.0
-            } else if dist_to_inner > glow {
-                opacity
-            } else {
-                let glow_progress = dist_to_inner / glow;
-                let smooth = glow_progress * glow_progress * glow_progress;
-                opacity * smooth
-            };
+        // Band levels from cava, replaced by the timer and strobe like the comet
+        let levels = if anim_mode == 6 { self.state.ipc.get_spectrum() } else { Vec::new() };
+        let spectrum = (anim_mode == 6 && timer == TimerPhase::Done && strobe.is_none()).then(|| Spectrum {
+            levels: &levels,
+            origin: self.spectrum.origin_enum(),
+            floor: self.spectrum.floor.clamp(0.0, 1.0),
+        });
 
-            if alpha > 0.001 {
-                let a = (alpha * 255.0) as u32;
-                let (r, g, b) = color;
-                let pr = ((r as u32) * a / 255) as u8;
-                let pg = ((g as u32) * a / 255) as u8;
-                let pb = ((b as u32) * a / 255) as u8;
-                let pixel = (a << 24) | ((pr as u32) << 16) | ((pg as u32) << 8) | (pb as u32);
-                chunk.copy_from_slice(&pixel.to_ne_bytes());
-            } else {
-                chunk.copy_from_slice(&[0, 0, 0, 0]);
-            }
+        // Colors of their own, but not over the timer or a strobe
+        let segments = if timer == TimerPhase::Done && strobe.is_none() { self.state.ipc.get_segments() } else { Vec::new() };
+
+        #[cfg(feature = "texture")]
+        let texture = self.texture.get(&self.state.ipc.get_texture(), self.texture_mode);
+        #[cfg(not(feature = "texture"))]
+        let texture = None;
+
+        let backdrop = self.state.ipc.is_solid_backdrop() && is_visible;
+        // Independent of the ring: it stays when the ring is hidden
+        let fill = self.state.ipc.fill.load(Ordering::Relaxed).then(|| {
+            (color::resolve_color(&self.fill.color, &self.state.ipc.get_palette()), self.fill.opacity.clamp(0.0, 1.0))
         });
+        let render_start = Instant::now();
+        render::render(canvas, width, height, &Ring {
+            inset,
+            thickness,
+            glow,
+            falloff: self.falloff,
+            inner_glow: self.inner_glow * render_scale,
+            vignette,
+            fill,
+            catchlight: self.webcam.position_on(&monitor.output_name).map(|origin| Catchlight {
+                origin,
+                floor: self.webcam.floor.clamp(0.0, 1.0),
+            }),
+            shape,
+            color,
+            texture,
+            opacity,
+            progress,
+            comet,
+            spectrum,
+            segments: &segments,
+            pattern: self.state.ipc.get_pattern(),
+            backdrop,
+            dither: self.dither,
+            gamma_correct: self.state.ipc.gamma_correct.load(Ordering::Relaxed),
+        });
+        let render_time = render_start.elapsed();
+        log::trace!("Rendered {} in {:?}", monitor.output_name, render_time);
+        self.state.ipc.record_frame(&monitor.output_name, (width, height), pool_bytes, monitor.fps, render_time);
+        self.state.ipc.set_displayed(color, opacity);
+
+        let framing = self.state.ipc.framing_guide.load(Ordering::Relaxed)
+            || (self.framing.on_camera && self.state.ipc.is_camera_active());
+        if framing && is_visible {
+            let mut canvas = overlay::Canvas { data: &mut *canvas, width, height };
+            let inset = (inset + vignette.unwrap_or(thickness + glow)) as u32;
+            overlay::draw_framing_guide(&mut canvas, inset, self.framing.style_enum(), self.framing.opacity);
+        }
+
+        #[cfg(feature = "label")]
+        let label = match self.state.ipc.get_label() {
+            label if label.is_empty() && !self.label.clock.is_empty() => label::clock(&self.label.clock),
+            label => label,
+        };
+        #[cfg(feature = "label")]
+        if !label.is_empty() && monitor_enabled {
+            let text_color = match self.label.color.as_str() {
+                "" => color::contrast_color(color),
+                name => color::resolve_color(name, &self.state.ipc.get_palette()),
+            };
+            let mut canvas = overlay::Canvas { data: &mut *canvas, width, height };
+            label::draw_label(&mut canvas, inset as u32, thickness as u32, &label::Label {
+                text: &label,
+                size: (self.label.size as f64 * scale * render_scale) as u32,
+                color: text_color,
+                backing: self.label.backing,
+                opacity: shown,
+            });
+        }
+
+        if self.state.ipc.is_debug_overlay() && monitor_enabled {
+            let label = format!("{} {}x{} {:.0} FPS", monitor.output_name, width, height, monitor.fps);
+            let mut canvas = overlay::Canvas { data: canvas, width, height };
+            overlay::draw_debug_overlay(&mut canvas, thickness as u32, &label);
+        }
+
+        // Zones are in monitor coordinates; the surface starts past the bar
+        let (dx, dy) = monitor.bar.offset();
+        let to_buffer = |v: i32| (v as f64 * render_scale).floor() as i32;
+        for zone in self.exclude.iter().filter(|z| z.applies_to(&monitor.output_name)) {
+            let (x, y) = (to_buffer(zone.x as i32 - dx), to_buffer(zone.y as i32 - dy));
+            // Round outwards so no scaled-up pixel straddles the zone's edge
+            let size = |v: u32| (v as f64 * render_scale).ceil() as u32 + 1;
+            render::clear_rect(canvas, width, height, (x, y, size(zone.width), size(zone.height)));
+        }
 
         // Damage and commit
         monitor.layer.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
         monitor.layer.wl_surface().frame(qh, monitor.layer.wl_surface().clone());
-        buffer.attach_to(monitor.layer.wl_surface()).expect("buffer attach");
+        if let Err(e) = buffer.attach_to(monitor.layer.wl_surface()) {
+            log::error!("Failed to attach buffer for {}: {}", monitor.output_name, e);
+            return;
+        }
         monitor.layer.commit();
     }
 }
 
-/// Calculate signed distance from a point to the inner rounded rectangle border.
-fn distance_to_inner_rounded_border(x: f64, y: f64, w: f64, h: f64, inset: f64, corner_radius: f64) -> f64 {
-    let left = inset;
-    let right = w - inset;
-    let top = inset;
-    let bottom = h - inset;
-    
-    if right <= left || bottom <= top {
-        return 100.0;
-    }
-    
-    let half_w = (right - left) / 2.0;
-    let half_h = (bottom - top) / 2.0;
-    let r = corner_radius.min(half_w).min(half_h).max(0.0);
-    
-    let cx = (left + right) / 2.0;
-    let cy = (top + bottom) / 2.0;
-    let half_width = (right - left) / 2.0;
-    let half_height = (bottom - top) / 2.0;
-    
-    let px = (x - cx).abs();
-    let py = (y - cy).abs();
-    
-    let qx = px - (half_width - r);
-    let qy = py - (half_height - r);
-    
-    let outside_dist = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
-    let inside_dist = qx.max(qy).min(0.0);
-    let sdf = outside_dist + inside_dist - r;
-    
-    sdf
-}
-
 impl CompositorHandler for RingLight {
     fn scale_factor_changed(
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _surface: &wl_surface::WlSurface,
-        _new_factor: i32,
-    ) {}
+        surface: &wl_surface::WlSurface,
+        new_factor: i32,
+    ) {
+        // The next frame is drawn into buffers of the new size
+        if let Some(monitor) = self.monitors.get_mut(&surface.id().protocol_id()) {
+            if monitor.scale_factor != new_factor {
+                log::info!("Scale of {} changed to {}", monitor.output_name, new_factor);
+                monitor.scale_factor = new_factor.max(1);
+            }
+        }
+    }
 
     fn transform_changed(
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _surface: &wl_surface::WlSurface,
-        _new_transform: wl_output::Transform,
-    ) {}
+        surface: &wl_surface::WlSurface,
+        new_transform: wl_output::Transform,
+    ) {
+        self.set_transform(surface.id().protocol_id(), new_transform);
+    }
 
     fn frame(
         &mut self,
@@ -638,11 +997,40 @@ impl OutputHandler for RingLight {
         }
     }
     
-    fn update_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: wl_output::WlOutput) {}
+    fn update_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, output: wl_output::WlOutput) {
+        let Some(info) = self.output_state.info(&output) else {
+            return;
+        };
+        let Some(name) = self.output_names.get(&output.id().protocol_id()).cloned() else {
+            return;
+        };
+        let surfaces: Vec<u32> = self.monitors.iter().filter(|(_, m)| m.output_name == name).map(|(&id, _)| id).collect();
+        for surface_id in surfaces {
+            self.set_transform(surface_id, info.transform);
+        }
+        for monitor in self.monitors.values_mut().filter(|m| m.output_name == name) {
+            let scale_factor = info.scale_factor.max(1);
+            if monitor.scale_factor == scale_factor && monitor.logical_size == info.logical_size {
+                continue;
+            }
+            log::info!("Output {} changed to {:?} at scale {}, resizing its ring", name, info.logical_size, scale_factor);
+            monitor.scale_factor = scale_factor;
+            monitor.logical_size = info.logical_size;
+            // Unmap and wait for a configure with the new size before drawing again
+            monitor.first_configure = true;
+            monitor.buffers.clear();
+            monitor.pool = None;
+            monitor.layer.wl_surface().attach(None, 0, 0);
+            monitor.layer.commit();
+            // Unmapped, the surface only gets that configure after a commit without a buffer
+            monitor.layer.commit();
+        }
+    }
     
     fn output_destroyed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, output: wl_output::WlOutput) {
         let output_id = output.id().protocol_id();
         if let Some(name) = self.output_names.remove(&output_id) {
+            log::info!("Output {} removed, destroying its ring", name);
             self.state.remove_monitor(&name);
             // Find and remove the monitor ring by name
             self.monitors.retain(|_, m| m.output_name != name);
@@ -653,11 +1041,13 @@ impl OutputHandler for RingLight {
 impl LayerShellHandler for RingLight {
     fn closed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, layer: &LayerSurface) {
         let surface_id = layer.wl_surface().id().protocol_id();
-        self.monitors.remove(&surface_id);
+        if let Some(monitor) = self.monitors.remove(&surface_id) {
+            log::info!("Compositor closed the ring on {}", monitor.output_name);
+        }
         
         // Exit if all monitors are gone
         if self.monitors.is_empty() {
-            std::process::exit(0);
+            self.state.ipc.request_quit();
         }
     }
 
@@ -672,8 +1062,12 @@ impl LayerShellHandler for RingLight {
         let surface_id = layer.wl_surface().id().protocol_id();
         
         if let Some(monitor) = self.monitors.get_mut(&surface_id) {
+            log::debug!("Configured {} at {}x{}", monitor.output_name, configure.new_size.0, configure.new_size.1);
             monitor.width = configure.new_size.0;
             monitor.height = configure.new_size.1;
+            if let Some(viewport) = &monitor.viewport {
+                viewport.set_destination(monitor.width as i32, monitor.height as i32);
+            }
 
             if monitor.first_configure {
                 monitor.first_configure = false;
@@ -681,7 +1075,7 @@ impl LayerShellHandler for RingLight {
             }
         }
         
-        self.draw_monitor(surface_id, &qh);
+        self.draw_monitor(surface_id, qh);
     }
 }
 
@@ -695,112 +1089,309 @@ delegate_compositor!(RingLight);
 delegate_output!(RingLight);
 delegate_shm!(RingLight);
 delegate_layer!(RingLight);
+delegate_seat!(RingLight);
+delegate_pointer!(RingLight);
+delegate_touch!(RingLight);
 delegate_registry!(RingLight);
 
+impl SeatHandler for RingLight {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _seat: wl_seat::WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Pointer && self.clicks.is_some() && self.pointer.is_none() {
+            match self.seat_state.get_pointer(qh, &seat) {
+                Ok(pointer) => self.pointer = Some(pointer),
+                Err(e) => log::warn!("Can't take clicks on the ring: {}", e),
+            }
+        }
+        if capability == Capability::Touch && self.gestures.is_some() && self.touch.is_none() {
+            match self.seat_state.get_touch(qh, &seat) {
+                Ok(touch) => self.touch = Some(touch),
+                Err(e) => log::warn!("Can't take touches on the ring: {}", e),
+            }
+        }
+    }
+
+    fn remove_capability(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _seat: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Pointer {
+            if let Some(pointer) = self.pointer.take() {
+                pointer.release();
+            }
+        }
+        if capability == Capability::Touch {
+            if let Some(touch) = self.touch.take() {
+                touch.release();
+            }
+        }
+    }
+
+    fn remove_seat(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _seat: wl_seat::WlSeat) {}
+}
+
 impl ProvidesRegistryState for RingLight {
     fn registry(&mut self) -> &mut RegistryState {
         &mut self.registry_state
     }
-    registry_handlers![OutputState];
+    registry_handlers![OutputState, SeatState];
 }
 
-fn main() {
-    env_logger::init();
-    
-    let cli = Cli::parse();
-    
-    // Handle subcommands
-    if let Some(Commands::Config) = cli.command {
-        if let Err(e) = tui::run() {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-        return;
+/// The layer-shell layer for the configured `layer`
+fn wlr_layer(layer: RingLayer) -> Layer {
+    match layer {
+        RingLayer::Background => Layer::Background,
+        RingLayer::Bottom => Layer::Bottom,
+        RingLayer::Top => Layer::Top,
+        RingLayer::Overlay => Layer::Overlay,
     }
-    
+}
+
+/// Load the config file, apply CLI overrides and pick the initial color
+fn load_config(cli: Cli) -> Result<(Config, (u8, u8, u8)), Error> {
     // Load config file, then override with CLI args
-    let mut cfg = Config::load();
+    let mut cfg = Config::try_load().map_err(Error::Config)?;
+    // Typos and out-of-range values still load; say what was ignored
+    let path = Config::path();
+    for diagnostic in check::check_file(&path).unwrap_or_default() {
+        log::warn!("{}: {}", path.display(), diagnostic);
+    }
+    // Environment variables sit between the file and the command line
+    let from_env = cfg.apply_env(std::env::vars()).map_err(Error::Config)?;
+    if !from_env.is_empty() {
+        log::info!("Set from the environment: {}", from_env.join(", "));
+    }
     
     // Track if color was explicitly set
+    #[cfg(feature = "theme")]
     let color_explicitly_set = cli.color.is_some();
     
     if let Some(v) = cli.color { cfg.color = v; }
     if let Some(v) = cli.thickness { cfg.thickness = v; }
     if let Some(v) = cli.opacity { cfg.opacity = v; }
     if let Some(v) = cli.glow { cfg.glow = v; }
-    if let Some(v) = cli.corner_radius { cfg.corner_radius = v; }
+    if let Some(v) = cli.corner_radius { cfg.corner_radius = v; cfg.corner_radii = None; }
+    if let Some(v) = cli.corner_style { cfg.corner_style = v; }
+    if let Some(v) = cli.inset { cfg.inset = v; }
+    if let Some(v) = cli.taper { cfg.taper = v; }
+    if let Some(v) = cli.taper_anchor { cfg.taper_anchor = v; }
+    if let Some(v) = cli.glow_falloff { cfg.glow_falloff = v; }
     if let Some(v) = cli.animation { cfg.animation = v; }
-    if let Some(v) = cli.animation_speed { cfg.animation_speed = v; }
+    if let Some(v) = cli.animation_speed { cfg.cycle_duration = config::format_cycle_duration(config::frames_to_ms(v)); }
+    if let Some(v) = cli.cycle_duration {
+        if config::parse_cycle_duration(&v).is_some() {
+            cfg.cycle_duration = v;
+        } else {
+            log::warn!("Invalid cycle duration '{}', expected e.g. 2s or 500ms", v);
+        }
+    }
+    if let Some(v) = cli.pattern { cfg.pattern = v; }
+    if let Some(v) = cli.warmup { cfg.warmup = v; }
+    if let Some(v) = cli.warmup_curve { cfg.warmup_curve = v; }
     if let Some(v) = cli.bar_height { cfg.bar_height = v; }
     if let Some(v) = cli.bar_position { cfg.bar_position = v; }
     
-    // If color wasn't explicitly set via CLI and config has default, try Omarchy theme
-    let initial_color = if !color_explicitly_set && cfg.color == "ffffff" {
-        // Try to get accent color from Omarchy theme
-        if let Some(color) = theme::get_accent_color() {
-            log::info!("Using Omarchy theme accent color: #{:02x}{:02x}{:02x}", color.0, color.1, color.2);
-            color
-        } else {
-            parse_hex_color(&cfg.color)
+    // If color wasn't explicitly set via CLI and config has default, try the theme
+    #[cfg(feature = "theme")]
+    if !color_explicitly_set && cfg.color == "ffffff" {
+        if let Some(color) = theme::get_accent_color(&cfg.theme) {
+            log::info!("Using {} theme accent color: #{:02x}{:02x}{:02x}", cfg.theme.source_enum().name(), color.0, color.1, color.2);
+            return Ok((cfg, color));
         }
+    }
+    let initial_color = resolve_color(&cfg.color, &cfg.palette);
+    
+    Ok((cfg, initial_color))
+}
+
+fn main() {
+    let cli = Cli::parse();
+    logging::init(cli.log_level, cli.log_format);
+    // Socket and config paths read it from the environment, which also hands it to child processes
+    if let Some(name) = &cli.instance {
+        std::env::set_var(hypr_ringlight_core::ipc::INSTANCE_ENV, name);
+    }
+
+    if let Err(e) = run(cli) {
+        eprintln!("Error: {}", e);
+        std::process::exit(e.exit_code());
+    }
+}
+
+fn parse_instance(name: &str) -> Result<String, String> {
+    if hypr_ringlight_core::ipc::is_valid_instance(name) {
+        Ok(name.to_string())
     } else {
-        parse_hex_color(&cfg.color)
+        Err("use letters, digits, '-' and '_'".to_string())
+    }
+}
+
+fn run(mut cli: Cli) -> Result<(), Error> {
+    
+    // Handle subcommands
+    match cli.command.take() {
+        Some(Commands::Config { action: Some(action) }) => return bundle::run(action),
+        #[cfg(feature = "tui")]
+        Some(Commands::Config { action: None }) => return tui::run().map_err(Error::Other),
+        #[cfg(not(feature = "tui"))]
+        Some(Commands::Config { action: None }) => {
+            return Err(Error::Usage("Built without the configuration TUI, see `config --help`".to_string()))
+        }
+        Some(Commands::Ctl { command }) => return ctl::run(command),
+        Some(Commands::Autostart { action }) => return autostart::run(action),
+        Some(Commands::Watch(args)) => std::process::exit(watch::run(args)?),
+        Some(Commands::Events(args)) => return events::run(args),
+        Some(Commands::Scene { action }) => return scene::run(action),
+        None => {}
+    }
+    
+    let safe_mode = cli.safe_mode;
+    let once = cli.once.as_deref()
+        .map(|s| humantime::parse_duration(s).map_err(|e| Error::Usage(format!("Invalid --once duration '{}': {}", s, e))))
+        .transpose()?;
+    let bind = cli.bind_fifo.take().map(|path| (path, cli.bind_param));
+    #[cfg(feature = "snapshot")]
+    let snapshot = cli.render_to_file.take()
+        .map(|args| Ok::<_, Error>((std::path::PathBuf::from(&args[0]), snapshot::parse_size(&args[1])?)))
+        .transpose()?;
+    let bench = cli.bench_frame.take().map(|size| snapshot::parse_size(&size)).transpose()?;
+    let (cfg, initial_color) = if safe_mode {
+        log::warn!("Safe mode: ignoring config and options, integrations disabled");
+        let cfg = Config::default();
+        let color = resolve_color(&cfg.color, &cfg.palette);
+        (cfg, color)
+    } else {
+        load_config(cli)?
     };
+
+    #[cfg(feature = "snapshot")]
+    if let Some((path, size)) = snapshot {
+        snapshot::render_to_file(&cfg, initial_color, &path, size)?;
+        log::info!("Rendered {}x{} to {}", size.0, size.1, path.display());
+        return Ok(());
+    }
+    if let Some(size) = bench {
+        return snapshot::bench_frame(&cfg, initial_color, size);
+    }
     
+    if hypr_ringlight_core::ipc::is_running() {
+        return Err(Error::AlreadyRunning);
+    }
+
     // Create shared state with all config values
-    let state = Arc::new(SharedState::new(
-        initial_color,
-        cfg.thickness,
-        cfg.opacity,
-        cfg.glow,
-        cfg.corner_radius,
-        cfg.animation_mode(),
-        cfg.animation_speed,
-        cfg.disabled_monitors.clone(),
-    ));
+    let state = Arc::new(SharedState::new(&cfg, initial_color));
 
     // Start IPC server for live config updates
     ipc::start_server(state.ipc.clone());
 
-    // Set up SIGUSR2 handler for Omarchy theme reload
-    let signal_state = state.clone();
-    std::thread::spawn(move || {
-        let mut signals = Signals::new(&[SIGUSR2]).expect("Failed to create signal handler");
-        for _ in signals.forever() {
-            // Reload theme colors from Omarchy
-            if let Some((r, g, b)) = theme::get_accent_color() {
-                signal_state.ipc.set_color(r, g, b);
-                log::info!("Reloaded Omarchy theme color: #{:02x}{:02x}{:02x}", r, g, b);
-            }
-        }
-    });
-
     // Connect to Wayland
-    let conn = Connection::connect_to_env().expect("Failed to connect to Wayland");
-    let (globals, mut event_queue) = registry_queue_init(&conn).expect("Failed to init registry");
+    let conn = Connection::connect_to_env()
+        .map_err(|e| Error::Compositor(format!("Failed to connect to Wayland: {}", e)))?;
+    let (globals, mut event_queue) = registry_queue_init(&conn)
+        .map_err(|e| Error::Compositor(format!("Failed to init registry: {}", e)))?;
     let qh = event_queue.handle();
 
     // Bind globals
-    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
-    let layer_shell = LayerShell::bind(&globals, &qh).expect("layer shell not available");
-    let shm = Shm::bind(&globals, &qh).expect("wl_shm not available");
-
+    let compositor = CompositorState::bind(&globals, &qh)
+        .map_err(|_| Error::Compositor("wl_compositor not available".to_string()))?;
+    let layer_shell = LayerShell::bind(&globals, &qh)
+        .map_err(|_| Error::Compositor("Compositor lacks wlr-layer-shell".to_string()))?;
+    let shm = Shm::bind(&globals, &qh)
+        .map_err(|_| Error::Compositor("wl_shm not available".to_string()))?;
+
+    let render_scale = viewport::render_scale(cfg.render_scale);
     let mut ring_light = RingLight {
         registry_state: RegistryState::new(&globals),
         output_state: OutputState::new(&globals, &qh),
         compositor,
         layer_shell,
         shm,
+        seat_state: SeatState::new(&globals, &qh),
+        pointer: None,
+        touch: None,
         monitors: HashMap::new(),
         output_names: HashMap::new(),
         start_time: Instant::now(),
-        bar_height: cfg.bar_height as i32,
-        bar_position: cfg.bar_position_enum(),
+        bar_physical: cfg.bar_physical,
+        bar_auto: cfg.bar_auto,
+        monitor_config: cfg.monitor.clone(),
+        notch: cfg.notch.clone(),
+        exclude: cfg.exclude.clone(),
+        #[cfg(feature = "texture")]
+        texture_mode: cfg.texture.mode_enum(),
+        inset: cfg.inset as f64,
+        vignette_distance: cfg.vignette.distance as f64,
+        fill: cfg.fill.clone(),
+        webcam: cfg.webcam.clone(),
+        taper: cfg.taper_enum(),
+        falloff: cfg.glow_falloff_enum(),
+        inner_glow: cfg.inner_glow as f64,
+        dither: cfg.dither,
+        framing: cfg.framing.clone(),
+        #[cfg(feature = "label")]
+        label: cfg.label.clone(),
+        spectrum: cfg.spectrum.clone(),
+        system: cfg.system.clone(),
+        progress: cfg.progress.clone(),
+        comet: cfg.comet.clone(),
+        clicks: cfg.click.enabled.then(|| click::Clicks::new(&cfg.click, &cfg.profiles)),
+        gestures: cfg.touch.enabled.then(touch::Gestures::default),
+        idle_fade: idle::IdleFade::new(&cfg.idle),
+        typing_glow: typing::TypingGlow::new(&cfg.typing),
+        transition: cfg.transition.clone(),
+        battery_frame_interval: cfg.battery.frame_interval(),
+        #[cfg(feature = "texture")]
+        texture: texture::TextureCache::default(),
+        viewporter: viewport::bind(&globals, &qh, render_scale),
+        power_manager: if cfg.dpms { power::bind(&globals, &qh) } else { None },
+        render_scale,
+        buffer_format: wl_shm::Format::Argb8888,
         state: state.clone(),
     };
 
     // Initial roundtrip to get output info
-    event_queue.roundtrip(&mut ring_light).expect("Initial roundtrip failed");
+    event_queue
+        .roundtrip(&mut ring_light)
+        .map_err(|e| Error::Compositor(format!("Initial roundtrip failed: {}", e)))?;
+
+    // wl_shm formats are known now. Argb8888 is mandatory, but without it
+    // the best we can do is make the glow blend into a black band
+    if !ring_light.shm.formats().contains(&wl_shm::Format::Argb8888) {
+        log::warn!("Compositor lacks Argb8888 buffers, the ring can't be see-through");
+        ring_light.buffer_format = wl_shm::Format::Xrgb8888;
+        state.ipc.set_no_alpha(true);
+    }
+    
+    // Fade out when idle, if the compositor can tell us
+    let _idle_notification = cfg.idle.timeout().and_then(|timeout| {
+        let seat = ring_light.seat_state.seats().next()?;
+        let notification = idle::watch(&globals, &qh, &seat, timeout);
+        if notification.is_none() {
+            log::warn!("Compositor lacks ext-idle-notify-v1, idle fade disabled");
+        }
+        notification
+    });
+
+    // Pauses in input for the typing glow, cheap enough to watch whatever the animation
+    let _typing_notification = ring_light
+        .seat_state
+        .seats()
+        .next()
+        .and_then(|seat| typing::watch(&globals, &qh, &seat, cfg.typing.pause()));
     
     // Create rings for all existing outputs
     let outputs: Vec<_> = ring_light.output_state.outputs().collect();
@@ -825,29 +1416,79 @@ fn main() {
         }
     }
 
-    // Start tray AFTER monitors are discovered
-    let tray_state = state.clone();
-    std::thread::spawn(move || {
-        let service = TrayService::new(RingLightTray {
-            state: tray_state,
-        });
-        let _ = service.run();
-    });
+    // Integrations, all skipped in safe mode
+    if !safe_mode {
+        integrations::start(&state, &cfg);
+        if let Some((path, param)) = bind {
+            bind::start(state.ipc.clone(), path, param);
+        }
+    }
+
+    // Event loop: Wayland events, a timer to resume paused rings, and a
+    // ping to wake up for shutdown
+    let mut event_loop: EventLoop<RingLight> = EventLoop::try_new()
+        .map_err(|e| Error::Other(format!("Failed to create event loop: {}", e)))?;
+    WaylandSource::new(conn.clone(), event_queue)
+        .insert(event_loop.handle())
+        .map_err(|e| Error::Other(format!("Failed to add Wayland source: {}", e)))?;
+    event_loop
+        .handle()
+        .insert_source(Timer::from_duration(RESUME_CHECK), |_, _, ring_light| {
+            ring_light.resume_paused();
+            TimeoutAction::ToDuration(RESUME_CHECK)
+        })
+        .map_err(|e| Error::Other(format!("Failed to add timer: {}", e)))?;
+    if let Some(once) = once {
+        let ipc = state.ipc.clone();
+        event_loop
+            .handle()
+            .insert_source(Timer::from_duration(once), move |_, _, _| {
+                log::info!("Preview over after {}", humantime::format_duration(once));
+                ipc.request_quit();
+                TimeoutAction::Drop
+            })
+            .map_err(|e| Error::Other(format!("Failed to add timer: {}", e)))?;
+    }
+    let (ping, ping_source) = make_ping().map_err(|e| Error::Other(format!("Failed to create waker: {}", e)))?;
+    event_loop
+        .handle()
+        .insert_source(ping_source, |_, _, ring_light| ring_light.resume_paused())
+        .map_err(|e| Error::Other(format!("Failed to add waker: {}", e)))?;
+    state.ipc.set_waker(ping);
+    quit_on_signal(state.ipc.clone());
+
+    let mut result = Ok(());
+    while !state.ipc.quit_requested() {
+        if let Err(e) = event_loop.dispatch(None, &mut ring_light) {
+            result = Err(Error::Compositor(format!("Lost connection to the compositor: {}", e)));
+            break;
+        }
+    }
+
+    log::info!("Shutting down");
+    // Dropping the rings destroys their layer surfaces
+    ring_light.monitors.clear();
+    let _ = conn.flush();
+    state.ipc.flush_config();
+    ipc::remove_socket();
+    result
+}
 
-    // Start camera monitor for video call notifications
-    let camera_visible = Arc::new(std::sync::atomic::AtomicBool::new(true));
-    let camera_visible_ref = camera_visible.clone();
-    let camera_state = state.clone();
+/// Shut down cleanly on SIGTERM and SIGINT; a second signal exits right away
+fn quit_on_signal(state: Arc<IpcState>) {
+    let mut signals = match Signals::new([SIGTERM, SIGINT]) {
+        Ok(signals) => signals,
+        Err(e) => {
+            log::error!("Failed to create signal handler: {}", e);
+            return;
+        }
+    };
     std::thread::spawn(move || {
-        loop {
-            camera_visible_ref.store(camera_state.ipc.is_visible(), Ordering::Relaxed);
-            std::thread::sleep(std::time::Duration::from_secs(1));
+        for signal in signals.forever() {
+            if state.quit_requested() {
+                std::process::exit(128 + signal);
+            }
+            state.request_quit();
         }
     });
-    camera::start_camera_monitor(camera_visible);
-
-    // Event loop
-    loop {
-        event_queue.blocking_dispatch(&mut ring_light).expect("Wayland dispatch failed");
-    }
 }
@@ -0,0 +1,167 @@
+//! Media players: the ring follows what's playing
+//!
+//! Players publish their state over MPRIS (`org.mpris.MediaPlayer2.*` on the
+//! session bus): Spotify, mpv, browsers, most music apps. A profile can be
+//! applied while a track plays and another while it's paused, and the ring can
+//! fill up as the track goes on, like a progress bar.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::arg::{PropMap, RefArg};
+use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+use dbus::blocking::Connection;
+
+use crate::config::{MediaConfig, Profile};
+use crate::ipc::IpcState;
+
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER: &str = "org.mpris.MediaPlayer2.Player";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Position only changes by polling, so a second keeps the progress smooth enough
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Playback {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+impl Playback {
+    fn from_status(status: &str) -> Self {
+        match status {
+            "Playing" => Playback::Playing,
+            "Paused" => Playback::Paused,
+            _ => Playback::Stopped,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Playback::Playing => "playing",
+            Playback::Paused => "paused",
+            Playback::Stopped => "stopped",
+        }
+    }
+}
+
+/// What a player is doing
+#[derive(Debug, Clone, PartialEq)]
+struct PlayerStatus {
+    name: String,
+    playback: Playback,
+    /// Played fraction of the track, if it has a length
+    progress: Option<f64>,
+}
+
+/// Bus names of the running players matching `filter` (all if empty)
+fn players(conn: &Connection, filter: &str) -> Vec<String> {
+    let proxy = conn.with_proxy("org.freedesktop.DBus", "/org/freedesktop/DBus", TIMEOUT);
+    let names: Result<(Vec<String>,), _> = proxy.method_call("org.freedesktop.DBus", "ListNames", ());
+    let filter = filter.to_lowercase();
+    names
+        .map(|(names,)| names)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|n| n.strip_prefix(MPRIS_PREFIX).is_some_and(|p| p.to_lowercase().contains(&filter)))
+        .collect()
+}
+
+fn player_status(conn: &Connection, bus_name: &str) -> Option<PlayerStatus> {
+    let proxy = conn.with_proxy(bus_name, MPRIS_PATH, TIMEOUT);
+    let status: String = proxy.get(PLAYER, "PlaybackStatus").ok()?;
+    let length = proxy
+        .get::<PropMap>(PLAYER, "Metadata")
+        .ok()
+        .and_then(|metadata| metadata.get("mpris:length")?.as_i64())
+        .filter(|&length| length > 0);
+    let position = proxy.get::<i64>(PLAYER, "Position").ok();
+    Some(PlayerStatus {
+        name: bus_name.trim_start_matches(MPRIS_PREFIX).to_string(),
+        playback: Playback::from_status(&status),
+        progress: length.zip(position).map(|(length, position)| (position as f64 / length as f64).clamp(0.0, 1.0)),
+    })
+}
+
+/// The player to follow: the first one playing, else the first one paused
+fn current_player(conn: &Connection, filter: &str) -> Option<PlayerStatus> {
+    let statuses: Vec<PlayerStatus> = players(conn, filter).iter().filter_map(|p| player_status(conn, p)).collect();
+    [Playback::Playing, Playback::Paused]
+        .into_iter()
+        .find_map(|playback| statuses.iter().find(|s| s.playback == playback).cloned())
+}
+
+/// Applies the profile and progress of the followed player
+struct Follower {
+    state: Arc<IpcState>,
+    cfg: MediaConfig,
+    profiles: BTreeMap<String, Profile>,
+    playback: Playback,
+    /// Whether the ring shows our progress, so a stop doesn't clear someone else's
+    showing_progress: bool,
+}
+
+impl Follower {
+    fn update(&mut self, status: Option<PlayerStatus>) {
+        let playback = status.as_ref().map_or(Playback::Stopped, |s| s.playback);
+        if playback != self.playback {
+            self.playback = playback;
+            let player = status.as_ref().map_or("", |s| s.name.as_str());
+            log::info!("Media {} {}", playback.name(), player);
+            self.state.log_event("media", format!("{} {}", playback.name(), player).trim_end().to_string());
+            let name = match playback {
+                Playback::Playing => &self.cfg.playing,
+                Playback::Paused => &self.cfg.paused,
+                Playback::Stopped => "",
+            };
+            let profile = match self.profiles.get(name) {
+                Some(profile) => Some((name.to_string(), profile.clone())),
+                None if name.is_empty() => None,
+                None => {
+                    log::warn!("media: unknown profile {}", name);
+                    None
+                }
+            };
+            self.state.set_override("media", profile);
+        }
+
+        if self.cfg.progress {
+            match status.and_then(|s| s.progress) {
+                Some(progress) => {
+                    self.state.set_progress(Some(progress));
+                    self.showing_progress = true;
+                }
+                None if self.showing_progress => {
+                    self.state.set_progress(None);
+                    self.showing_progress = false;
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+/// Start following media players.
+///
+/// Does nothing unless `[media]` sets a profile or progress.
+pub fn start_media_monitor(state: Arc<IpcState>, cfg: MediaConfig, profiles: BTreeMap<String, Profile>) {
+    if !cfg.is_active() {
+        return;
+    }
+    std::thread::spawn(move || {
+        let Ok(conn) = Connection::new_session() else {
+            log::info!("No session bus, not following media players");
+            return;
+        };
+        let filter = cfg.player.clone();
+        let mut follower = Follower { state, cfg, profiles, playback: Playback::Stopped, showing_progress: false };
+        loop {
+            follower.update(current_player(&conn, &filter));
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
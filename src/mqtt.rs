@@ -0,0 +1,281 @@
+//! Home Assistant light over MQTT
+//!
+//! Connects to an MQTT broker and announces the ring through Home
+//! Assistant's MQTT discovery as a light with the JSON schema: on/off is the
+//! ring's visibility, brightness its opacity, the color its color and the
+//! effects its animations. Automations can then drive it like any smart bulb,
+//! and changes made here show up in Home Assistant.
+//!
+//! MQTT 3.1.1 is spoken through rumqttc's blocking client: QoS 0, a retained
+//! discovery message, and a last will marking the light unavailable if we
+//! vanish.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use hypr_ringlight_core::color::color_to_hex;
+use hypr_ringlight_core::ipc::{instance, Command};
+use hypr_ringlight_core::render::{animation_name, ANIMATIONS};
+use rumqttc::{Client, ConnectReturnCode, ConnectionError, Event, LastWill, MqttOptions, Packet, QoS, RecvTimeoutError};
+use serde_json::{json, Value};
+
+use crate::config::MqttConfig;
+use crate::ipc::{self, IpcState};
+
+/// Asked of the broker, rumqttc pings it in time
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// How often the ring's state is compared with what was last published
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait before connecting again after losing the broker
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|h| h.trim().to_string())
+        .ok()
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+/// The ring as a Home Assistant light: its topics and payloads
+struct Light {
+    id: String,
+    name: String,
+    topic: String,
+    discovery_prefix: String,
+}
+
+impl Light {
+    fn new(cfg: &MqttConfig) -> Self {
+        // Named instances are separate lights
+        let host = match instance() {
+            Some(name) => format!("{}-{}", hostname(), name),
+            None => hostname(),
+        };
+        let id = format!("hypr_ringlight_{}", host.replace(|c: char| !c.is_ascii_alphanumeric(), "_"));
+        Self {
+            name: if cfg.name.is_empty() { format!("Ring light on {}", host) } else { cfg.name.clone() },
+            topic: if cfg.topic.is_empty() { format!("hypr-ringlight/{}", host) } else { cfg.topic.clone() },
+            discovery_prefix: cfg.discovery_prefix.clone(),
+            id,
+        }
+    }
+
+    fn state_topic(&self) -> String {
+        format!("{}/state", self.topic)
+    }
+
+    fn command_topic(&self) -> String {
+        format!("{}/set", self.topic)
+    }
+
+    fn availability_topic(&self) -> String {
+        format!("{}/availability", self.topic)
+    }
+
+    fn discovery_topic(&self) -> String {
+        format!("{}/light/{}/config", self.discovery_prefix, self.id)
+    }
+
+    /// Home Assistant announces itself here when it (re)starts
+    fn birth_topic(&self) -> String {
+        format!("{}/status", self.discovery_prefix)
+    }
+
+    fn discovery(&self) -> String {
+        json!({
+            "name": self.name,
+            "unique_id": self.id,
+            "schema": "json",
+            "state_topic": self.state_topic(),
+            "command_topic": self.command_topic(),
+            "availability_topic": self.availability_topic(),
+            "brightness": true,
+            "supported_color_modes": ["rgb"],
+            "effect": true,
+            "effect_list": ANIMATIONS,
+            "device": {
+                "identifiers": [self.id],
+                "name": self.name,
+                "manufacturer": "hypr-ringlight",
+                "model": "Ring light",
+                "sw_version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+        .to_string()
+    }
+}
+
+fn state_payload(state: &IpcState) -> String {
+    let (r, g, b) = state.get_color();
+    json!({
+        "state": if state.is_visible() { "ON" } else { "OFF" },
+        "brightness": (state.get_opacity() * 255.0).round() as u8,
+        "color_mode": "rgb",
+        "color": { "r": r, "g": g, "b": b },
+        "effect": animation_name(state.get_animation_mode()),
+    })
+    .to_string()
+}
+
+/// IPC commands for a JSON schema command from Home Assistant
+fn light_commands(payload: &str) -> Result<Vec<Command>, String> {
+    let value: Value = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+    let mut commands = Vec::new();
+    if let Some(state) = value.get("state").and_then(Value::as_str) {
+        commands.push(Command::SetVisible(state.eq_ignore_ascii_case("ON")));
+    }
+    if let Some(brightness) = value.get("brightness").and_then(Value::as_f64) {
+        commands.push(Command::SetOpacity((brightness / 255.0).clamp(0.0, 1.0)));
+    }
+    if let Some(color) = value.get("color") {
+        let channel = |c: &str| color.get(c).and_then(Value::as_u64).map(|v| v.min(255) as u8);
+        match (channel("r"), channel("g"), channel("b")) {
+            (Some(r), Some(g), Some(b)) => commands.push(Command::SetColor(color_to_hex(r, g, b))),
+            _ => return Err("color needs r, g and b".to_string()),
+        }
+    }
+    if let Some(effect) = value.get("effect").and_then(Value::as_str) {
+        commands.push(Command::SetAnimation(effect.to_string()));
+    }
+    Ok(commands)
+}
+
+fn connack_reason(code: ConnectReturnCode) -> &'static str {
+    match code {
+        ConnectReturnCode::RefusedProtocolVersion => "unsupported protocol version",
+        ConnectReturnCode::BadClientId => "client id rejected",
+        ConnectReturnCode::ServiceUnavailable => "broker unavailable",
+        ConnectReturnCode::BadUserNamePassword => "bad username or password",
+        ConnectReturnCode::NotAuthorized => "not authorized",
+        ConnectReturnCode::Success => "unknown reason",
+    }
+}
+
+/// Host and port of `host:port` or `[v6]:port`, MQTT's 1883 if no port is given
+fn broker_address(broker: &str) -> Result<(&str, u16), String> {
+    let (host, port) = match broker.strip_prefix('[') {
+        Some(v6) => {
+            let (host, rest) = v6.split_once(']').ok_or_else(|| format!("missing ']' in '{}'", broker))?;
+            (host, rest.strip_prefix(':'))
+        }
+        None => match broker.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (broker, None),
+        },
+    };
+    match port {
+        Some(port) => Ok((host, port.parse().map_err(|_| format!("bad port '{}'", port))?)),
+        None => Ok((host, 1883)),
+    }
+}
+
+/// One connection to the broker, until it drops
+fn session(state: &Arc<IpcState>, cfg: &MqttConfig, light: &Light) -> Result<(), String> {
+    let (host, port) = broker_address(&cfg.broker)?;
+    let availability = light.availability_topic();
+    let mut options = MqttOptions::new(&light.id, host, port);
+    options.set_keep_alive(KEEP_ALIVE);
+    options.set_last_will(LastWill::new(&availability, "offline", QoS::AtMostOnce, true));
+    if !cfg.username.is_empty() {
+        options.set_credentials(&cfg.username, &cfg.password);
+    }
+    let (client, mut connection) = Client::new(options, 16);
+    let publish = |topic: &str, payload: &str| {
+        client.publish(topic, QoS::AtMostOnce, true, payload.as_bytes()).map_err(|e| e.to_string())
+    };
+
+    let mut connected = false;
+    let mut published: Option<String> = None;
+    loop {
+        match connection.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(Event::Incoming(Packet::ConnAck(_)))) => {
+                log::info!("Connected to MQTT broker {} as {}", cfg.broker, light.topic);
+                connected = true;
+                for topic in [light.command_topic(), light.birth_topic()] {
+                    client.subscribe(topic, QoS::AtMostOnce).map_err(|e| e.to_string())?;
+                }
+                publish(&light.discovery_topic(), &light.discovery())?;
+                publish(&availability, "online")?;
+            }
+            Ok(Ok(Event::Incoming(Packet::Publish(message)))) => {
+                let payload = String::from_utf8_lossy(&message.payload);
+                if message.topic == light.command_topic() {
+                    match light_commands(&payload) {
+                        // All at once, so no frame shows half of them
+                        Ok(commands) => {
+                            ipc::execute(state, Command::Batch(commands), "mqtt", payload.trim());
+                        }
+                        Err(e) => log::warn!("mqtt: ignoring command '{}': {}", payload.trim(), e),
+                    }
+                } else if message.topic == light.birth_topic() && payload == "online" {
+                    // Home Assistant restarted and forgot us
+                    publish(&light.discovery_topic(), &light.discovery())?;
+                    published = None;
+                }
+            }
+            Ok(Ok(_)) | Err(RecvTimeoutError::Timeout) => {}
+            Ok(Err(ConnectionError::ConnectionRefused(code))) => {
+                return Err(format!("broker refused the connection: {}", connack_reason(code)));
+            }
+            Ok(Err(e)) => return Err(e.to_string()),
+            Err(RecvTimeoutError::Disconnected) => return Err("connection closed".to_string()),
+        }
+        if !connected {
+            continue;
+        }
+
+        let current = state_payload(state);
+        if published.as_ref() != Some(&current) {
+            publish(&light.state_topic(), &current)?;
+            published = Some(current);
+        }
+    }
+}
+
+/// Connect to the broker and keep the light in sync with the ring.
+///
+/// Does nothing unless `[mqtt]` sets a broker.
+pub fn start_mqtt(state: Arc<IpcState>, cfg: MqttConfig) {
+    if !cfg.is_active() {
+        return;
+    }
+    std::thread::spawn(move || {
+        let light = Light::new(&cfg);
+        loop {
+            if let Err(e) = session(&state, &cfg, &light) {
+                log::warn!("MQTT broker {}: {}, reconnecting in {}s", cfg.broker, e, RETRY_INTERVAL.as_secs());
+            }
+            std::thread::sleep(RETRY_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_broker_address() {
+        assert_eq!(broker_address("homeassistant.local:1883"), Ok(("homeassistant.local", 1883)));
+        assert_eq!(broker_address("10.0.0.2"), Ok(("10.0.0.2", 1883)));
+        assert_eq!(broker_address("[::1]:8883"), Ok(("::1", 8883)));
+        assert_eq!(broker_address("[fd00::2]"), Ok(("fd00::2", 1883)));
+        assert!(broker_address("broker:mqtt").is_err());
+    }
+
+    #[test]
+    fn test_light_commands() {
+        let commands = light_commands(r#"{"state": "ON", "brightness": 51, "color": {"r": 255, "g": 136, "b": 0}}"#).unwrap();
+        let json: Vec<String> = commands.iter().map(|c| serde_json::to_string(c).unwrap()).collect();
+        assert_eq!(json, [
+            r#"{"cmd":"SetVisible","value":true}"#,
+            r#"{"cmd":"SetOpacity","value":0.2}"#,
+            r#"{"cmd":"SetColor","value":"ff8800"}"#,
+        ]);
+        assert!(matches!(&light_commands(r#"{"effect": "rainbow"}"#).unwrap()[..], [Command::SetAnimation(a)] if a == "rainbow"));
+        assert!(light_commands(r#"{"color": {"r": 1}}"#).is_err());
+    }
+}
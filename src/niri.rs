@@ -0,0 +1,137 @@
+//! niri's IPC, for the workspace, focus and window rules
+//!
+//! niri takes one JSON request per line on `$NIRI_SOCKET` and answers with
+//! one line. Asked for the `EventStream`, it keeps the connection open and
+//! sends an event per line, starting with the current state. Workspaces are
+//! known to the rules by their index on the monitor, or their name if set.
+//! niri has no special workspaces, reserved areas or border color to report,
+//! nor the focused window's fullscreen state.
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::compositor::{Backend, Event, Monitor, Window, WorkspaceRef};
+
+fn connect(request: &str) -> Option<BufReader<UnixStream>> {
+    let mut stream = UnixStream::connect(std::env::var_os("NIRI_SOCKET")?).ok()?;
+    stream.write_all(format!("\"{}\"\n", request).as_bytes()).ok()?;
+    Some(BufReader::new(stream))
+}
+
+/// Send a request and return what it answered, without the `{"Ok": {request: ...}}` around it
+fn request(request: &str) -> Option<Value> {
+    let mut reader = connect(request)?;
+    reader.get_ref().set_read_timeout(Some(Duration::from_secs(2))).ok()?;
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut reply: Value = serde_json::from_str(&line).ok()?;
+    Some(reply.get_mut("Ok")?.get_mut(request)?.take())
+}
+
+fn window(value: &Value) -> Option<Window> {
+    let field = |key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    value.is_object().then(|| Window { class: field("app_id"), title: field("title"), fullscreen: false })
+}
+
+/// Parse one event line
+fn parse_event(line: &str) -> Option<Event> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    let (name, data) = value.as_object()?.iter().next()?;
+    match name.as_str() {
+        "WorkspacesChanged" | "WorkspaceActivated" => Some(Event::Layout),
+        // Only the focused window's id is given, ask for the rest
+        "WindowFocusChanged" | "WindowsChanged" | "WindowClosed" => {
+            Some(Event::ActiveWindow { class: String::new(), title: String::new() })
+        }
+        // Any window can open or change, only the focused one matters
+        "WindowOpenedOrChanged" => {
+            let opened = data.get("window")?;
+            if opened.get("is_focused").and_then(|v| v.as_bool()) != Some(true) {
+                return None;
+            }
+            let Window { class, title, .. } = window(opened)?;
+            Some(Event::ActiveWindow { class, title })
+        }
+        _ => None,
+    }
+}
+
+/// A monitor for each active workspace
+fn parse_workspaces(value: &Value) -> Option<Vec<Monitor>> {
+    let monitors = value.as_array()?.iter()
+        .filter(|ws| ws.get("is_active").and_then(|v| v.as_bool()).unwrap_or(false))
+        .filter_map(|ws| {
+            let idx = ws.get("idx")?.as_i64()?;
+            Some(Monitor {
+                name: ws.get("output")?.as_str()?.to_string(),
+                focused: ws.get("is_focused").and_then(|v| v.as_bool()).unwrap_or(false),
+                workspace: WorkspaceRef {
+                    id: idx,
+                    name: ws.get("name").and_then(|v| v.as_str()).map_or_else(|| idx.to_string(), str::to_string),
+                },
+                special: None,
+                reserved: None,
+            })
+        })
+        .collect();
+    Some(monitors)
+}
+
+/// niri's IPC socket
+pub struct Niri;
+
+impl Backend for Niri {
+    fn subscribe(&self) -> Option<Box<dyn BufRead + Send>> {
+        Some(Box::new(connect("EventStream")?))
+    }
+
+    fn parse_event(&self, line: &str) -> Option<Event> {
+        parse_event(line)
+    }
+
+    fn monitors(&self) -> Option<Vec<Monitor>> {
+        parse_workspaces(&request("Workspaces")?)
+    }
+
+    fn active_window(&self) -> Option<Window> {
+        window(&request("FocusedWindow")?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event() {
+        assert_eq!(parse_event(r#"{"WorkspaceActivated":{"id":3,"focused":true}}"#), Some(Event::Layout));
+        assert_eq!(
+            parse_event(r#"{"WindowFocusChanged":{"id":7}}"#),
+            Some(Event::ActiveWindow { class: String::new(), title: String::new() })
+        );
+        assert_eq!(
+            parse_event(r#"{"WindowOpenedOrChanged":{"window":{"id":7,"app_id":"firefox","title":"Meet","is_focused":true}}}"#),
+            Some(Event::ActiveWindow { class: "firefox".into(), title: "Meet".into() })
+        );
+        assert_eq!(parse_event(r#"{"WindowOpenedOrChanged":{"window":{"id":8,"app_id":"kitty","is_focused":false}}}"#), None);
+        // The answer to the EventStream request itself
+        assert_eq!(parse_event(r#"{"Ok":"Handled"}"#), None);
+    }
+
+    #[test]
+    fn test_parse_workspaces() {
+        let workspaces = serde_json::json!([
+            {"id": 1, "idx": 1, "name": null, "output": "DP-1", "is_active": true, "is_focused": false},
+            {"id": 2, "idx": 2, "name": null, "output": "DP-1", "is_active": false, "is_focused": false},
+            {"id": 3, "idx": 1, "name": "stream", "output": "HDMI-A-1", "is_active": true, "is_focused": true},
+        ]);
+        let monitors = parse_workspaces(&workspaces).unwrap();
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(monitors[0].workspace, WorkspaceRef { id: 1, name: "1".into() });
+        assert!(monitors[1].focused);
+        assert_eq!(monitors[1].workspace, WorkspaceRef { id: 1, name: "stream".into() });
+    }
+}
@@ -0,0 +1,268 @@
+//! OBS Studio: profiles while recording or streaming
+//!
+//! Connects to obs-websocket (version 5, built into OBS 28 and later) and
+//! applies `[obs] recording` or `streaming` while that output is active,
+//! reverting when it stops. With `dropped_frames` set, the encoder and
+//! renderer are polled while an output runs, and the ring flashes that color
+//! whenever frames were skipped since the last look.
+//!
+//! obs-websocket is spoken over tungstenite, plain `ws://` only; the
+//! password handshake takes SHA-256 and base64.
+
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tungstenite::{client::IntoClientRequest, http::HeaderValue, Message, WebSocket};
+
+use crate::config::{ObsConfig, Profile};
+use crate::ipc::IpcState;
+
+/// How often frames are read from the socket, and the state compared
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often skipped frames are counted while an output runs
+const STATS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long the ring flashes for dropped frames
+const FLASH_DURATION: Duration = Duration::from_millis(300);
+
+/// How long to wait before connecting again after losing OBS
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Event subscription bit of the output events
+const OUTPUTS_EVENTS: u64 = 1 << 6;
+
+const OP_HELLO: u64 = 0;
+const OP_IDENTIFY: u64 = 1;
+const OP_IDENTIFIED: u64 = 2;
+const OP_EVENT: u64 = 5;
+const OP_REQUEST: u64 = 6;
+const OP_RESPONSE: u64 = 7;
+
+/// The answer to a Hello's challenge: base64(sha256(base64(sha256(password + salt)) + challenge))
+fn authentication(password: &str, salt: &str, challenge: &str) -> String {
+    let secret = STANDARD.encode(Sha256::digest(format!("{}{}", password, salt)));
+    STANDARD.encode(Sha256::digest(format!("{}{}", secret, challenge)))
+}
+
+/// `ws://host:port`, or just `host:port`, as an address to connect to
+fn address(url: &str) -> Result<&str, String> {
+    if url.starts_with("wss://") {
+        return Err("wss:// is not supported, use ws://".to_string());
+    }
+    let rest = url.strip_prefix("ws://").unwrap_or(url);
+    Ok(rest.split('/').next().unwrap_or(rest))
+}
+
+/// Which outputs are on, from events and request responses
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Outputs {
+    recording: bool,
+    streaming: bool,
+}
+
+impl Outputs {
+    /// Note an `outputActive` from an event or response of `kind`
+    fn update(&mut self, kind: &str, data: &Value) {
+        let Some(active) = data["outputActive"].as_bool() else { return };
+        match kind {
+            "RecordStateChanged" | "GetRecordStatus" => self.recording = active,
+            "StreamStateChanged" | "GetStreamStatus" => self.streaming = active,
+            _ => {}
+        }
+    }
+
+    fn any(self) -> bool {
+        self.recording || self.streaming
+    }
+}
+
+struct Client {
+    socket: WebSocket<TcpStream>,
+    requests: u64,
+}
+
+impl Client {
+    fn connect(address: &str) -> Result<Self, String> {
+        let mut request = format!("ws://{}/", address).into_client_request().map_err(|e| e.to_string())?;
+        request.headers_mut().insert("Sec-WebSocket-Protocol", HeaderValue::from_static("obswebsocket.json"));
+        let stream = TcpStream::connect(address).map_err(|e| e.to_string())?;
+        let (socket, _) = tungstenite::client(request, stream).map_err(|e| e.to_string())?;
+        socket.get_ref().set_read_timeout(Some(POLL_INTERVAL)).map_err(|e| e.to_string())?;
+        Ok(Self { socket, requests: 0 })
+    }
+
+    fn send(&mut self, op: u64, data: Value) -> Result<(), String> {
+        let text = json!({ "op": op, "d": data }).to_string();
+        self.socket.send(Message::text(text)).map_err(|e| e.to_string())
+    }
+
+    fn request(&mut self, kind: &str) -> Result<(), String> {
+        self.requests += 1;
+        let id = self.requests.to_string();
+        self.send(OP_REQUEST, json!({ "requestType": kind, "requestId": id }))
+    }
+
+    /// Messages that arrived within the poll interval
+    fn receive(&mut self) -> Result<Vec<Value>, String> {
+        let mut messages = Vec::new();
+        loop {
+            // Pings are answered by tungstenite while reading
+            match self.socket.read() {
+                Ok(Message::Text(text)) => messages.extend(serde_json::from_str(&text).ok()),
+                Ok(Message::Close(frame)) => {
+                    let reason = frame.map(|f| f.reason.to_string()).unwrap_or_default();
+                    return Err(format!("closed by OBS: {}", reason));
+                }
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e)) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    return Ok(messages);
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+}
+
+/// Applies the profile of the active outputs, once per change
+struct Follower {
+    state: Arc<IpcState>,
+    recording: Option<(String, Profile)>,
+    streaming: Option<(String, Profile)>,
+    applied: Option<Outputs>,
+}
+
+impl Follower {
+    fn new(state: Arc<IpcState>, cfg: &ObsConfig, profiles: &BTreeMap<String, Profile>) -> Self {
+        // Unknown names are reported by `--check`
+        let lookup = |name: &str| profiles.get(name).map(|profile| (name.to_string(), profile.clone()));
+        Self { state, recording: lookup(&cfg.recording), streaming: lookup(&cfg.streaming), applied: None }
+    }
+
+    fn update(&mut self, outputs: Outputs) {
+        if self.applied == Some(outputs) {
+            return;
+        }
+        if self.applied.is_some_and(|a| a.recording != outputs.recording) {
+            self.state.log_event("obs", if outputs.recording { "recording started" } else { "recording stopped" });
+        }
+        if self.applied.is_some_and(|a| a.streaming != outputs.streaming) {
+            self.state.log_event("obs", if outputs.streaming { "streaming started" } else { "streaming stopped" });
+        }
+        self.applied = Some(outputs);
+        // Streaming is the more public of the two
+        let profile = match outputs {
+            Outputs { streaming: true, .. } if self.streaming.is_some() => self.streaming.clone(),
+            Outputs { recording: true, .. } => self.recording.clone(),
+            _ => None,
+        };
+        self.state.set_override("obs", profile);
+    }
+}
+
+/// Frames skipped by the renderer and encoder, from a `GetStats` response
+fn skipped_frames(data: &Value) -> Option<u64> {
+    Some(data["renderSkippedFrames"].as_u64()? + data["outputSkippedFrames"].as_u64()?)
+}
+
+/// One connection to OBS, until it drops
+fn session(cfg: &ObsConfig, follower: &mut Follower) -> Result<(), String> {
+    let mut client = Client::connect(address(&cfg.url)?)?;
+    let mut outputs = Outputs::default();
+    let mut skipped: Option<u64> = None;
+    let mut last_stats = Instant::now();
+    loop {
+        for message in client.receive()? {
+            let data = &message["d"];
+            match message["op"].as_u64() {
+                Some(OP_HELLO) => {
+                    let mut identify = json!({ "rpcVersion": 1, "eventSubscriptions": OUTPUTS_EVENTS });
+                    if let Some(auth) = data.get("authentication") {
+                        if cfg.password.is_empty() {
+                            return Err("OBS asks for a password, set [obs] password".to_string());
+                        }
+                        let (salt, challenge) = (auth["salt"].as_str().unwrap_or(""), auth["challenge"].as_str().unwrap_or(""));
+                        identify["authentication"] = json!(authentication(&cfg.password, salt, challenge));
+                    }
+                    client.send(OP_IDENTIFY, identify)?;
+                }
+                Some(OP_IDENTIFIED) => {
+                    log::info!("Connected to OBS at {}", cfg.url);
+                    client.request("GetRecordStatus")?;
+                    client.request("GetStreamStatus")?;
+                }
+                Some(OP_EVENT) => outputs.update(data["eventType"].as_str().unwrap_or(""), &data["eventData"]),
+                Some(OP_RESPONSE) => {
+                    let kind = data["requestType"].as_str().unwrap_or("");
+                    let response = &data["responseData"];
+                    outputs.update(kind, response);
+                    if kind == "GetStats" {
+                        let now = skipped_frames(response);
+                        if let (Some(before), Some(now)) = (skipped, now) {
+                            if now > before {
+                                log::info!("OBS skipped {} frames", now - before);
+                                crate::strobe::flash(follower.state.clone(), cfg.dropped_frames.clone(), FLASH_DURATION);
+                            }
+                        }
+                        skipped = now;
+                    }
+                }
+                _ => {}
+            }
+        }
+        follower.update(outputs);
+
+        if !cfg.dropped_frames.is_empty() && outputs.any() && last_stats.elapsed() >= STATS_INTERVAL {
+            client.request("GetStats")?;
+            last_stats = Instant::now();
+        }
+    }
+}
+
+/// Connect to OBS and follow its recording and streaming.
+///
+/// Does nothing unless `[obs]` is enabled.
+pub fn start_obs(state: Arc<IpcState>, cfg: ObsConfig, profiles: BTreeMap<String, Profile>) {
+    if !cfg.enabled {
+        return;
+    }
+    let mut follower = Follower::new(state, &cfg, &profiles);
+    std::thread::spawn(move || loop {
+        if let Err(e) = session(&cfg, &mut follower) {
+            log::warn!("OBS at {}: {}, reconnecting in {}s", cfg.url, e, RETRY_INTERVAL.as_secs());
+        }
+        // Whatever OBS was doing, it can't tell us when it stops
+        follower.update(Outputs::default());
+        std::thread::sleep(RETRY_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authentication() {
+        // The example of the obs-websocket protocol documentation
+        assert_eq!(
+            authentication("supersecretpassword", "lM1GncleQOaCu9lT1yeUZhFYnqhsLLP1G5lAGo3ixaI=", "+IxH4CnCiqpX1rM9scsNynZzbOe4KhDeYcTNS3PDaeY="),
+            "1Ct943GAT+6YQUUX47Ia/ncufilbe6+oD6lY+5kaCu4="
+        );
+    }
+
+    #[test]
+    fn test_outputs() {
+        let mut outputs = Outputs::default();
+        outputs.update("RecordStateChanged", &json!({ "outputActive": true, "outputState": "OBS_WEBSOCKET_OUTPUT_STARTED" }));
+        outputs.update("GetStreamStatus", &json!({ "outputActive": false }));
+        assert_eq!(outputs, Outputs { recording: true, streaming: false });
+        outputs.update("RecordStateChanged", &json!({ "outputState": "OBS_WEBSOCKET_OUTPUT_STOPPING" }));
+        assert!(outputs.recording);
+    }
+}
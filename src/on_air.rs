@@ -0,0 +1,118 @@
+//! On air: one state from the camera, screen sharing and the microphone
+//!
+//! The camera comes from the camera monitor, screen sharing from Hyprland's
+//! `screencast` event, and the microphone from the ALSA capture streams in
+//! /proc/asound (PipeWire and PulseAudio keep those running only while
+//! something records). Of the sources that are on, the first in
+//! `[on_air] priority` wins; its profile is applied, and the state shows in
+//! `GetState` as `on_air` for bars to follow.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{OnAir, OnAirConfig, Profile};
+use crate::ipc::IpcState;
+
+const ASOUND_DIR: &str = "/proc/asound";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether any capture stream of a sound card under `root` is running
+fn is_mic_in_use(root: &Path) -> bool {
+    let Ok(cards) = fs::read_dir(root) else {
+        return false;
+    };
+    let dirs = |dir: &Path, prefix: &str, suffix: &str| -> Vec<_> {
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter(|e| {
+                        let name = e.file_name().to_string_lossy().to_string();
+                        name.starts_with(prefix) && name.ends_with(suffix)
+                    })
+                    .map(|e| e.path())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    cards
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with("card"))
+        .flat_map(|card| dirs(&card.path(), "pcm", "c"))
+        .flat_map(|pcm| dirs(&pcm, "sub", ""))
+        .any(|sub| fs::read_to_string(sub.join("status")).is_ok_and(|status| status.contains("state: RUNNING")))
+}
+
+/// The first source in `priority` that is on
+fn on_air(priority: &[OnAir], on: impl Fn(OnAir) -> bool) -> Option<OnAir> {
+    priority.iter().copied().find(|&source| on(source))
+}
+
+/// Start following the on-air sources.
+///
+/// Does nothing with an empty `priority`.
+pub fn start_on_air(state: Arc<IpcState>, cfg: OnAirConfig, profiles: BTreeMap<String, Profile>) {
+    let priority = cfg.sources();
+    if priority.is_empty() {
+        return;
+    }
+    std::thread::spawn(move || {
+        let mut current = None;
+        loop {
+            let now = on_air(&priority, |source| match source {
+                OnAir::Camera => state.is_camera_active(),
+                OnAir::Share => state.is_screen_sharing(),
+                OnAir::Mic => is_mic_in_use(Path::new(ASOUND_DIR)),
+            });
+            if now != current {
+                current = now;
+                state.set_on_air(now);
+                state.log_event("on-air", now.map_or("off air".to_string(), |source| format!("on air: {}", source.name())));
+                let profile = now.and_then(|source| {
+                    let name = cfg.profile(source);
+                    profiles.get(name).map(|profile| (name.to_string(), profile.clone()))
+                });
+                state.set_override("on-air", profile);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_on_air() {
+        let priority = [OnAir::Camera, OnAir::Share, OnAir::Mic];
+        assert_eq!(on_air(&priority, |_| false), None);
+        assert_eq!(on_air(&priority, |s| s != OnAir::Camera), Some(OnAir::Share));
+        assert_eq!(on_air(&priority, |_| true), Some(OnAir::Camera));
+        // Left out of the priority, the camera doesn't count
+        assert_eq!(on_air(&[OnAir::Mic], |s| s == OnAir::Camera), None);
+    }
+
+    #[test]
+    fn test_mic_in_use() {
+        let root = std::env::temp_dir().join(format!("hypr-ringlight-asound-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let status = |path: &str, state: &str| {
+            let dir = root.join(path);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("status"), state).unwrap();
+        };
+        status("card0/pcm0c/sub0", "closed\n");
+        status("card0/pcm0p/sub0", "state: RUNNING\nowner_pid   : 1234\n");
+        assert!(!is_mic_in_use(&root));
+
+        status("card1/pcm2c/sub0", "state: RUNNING\nowner_pid   : 1234\n");
+        assert!(is_mic_in_use(&root));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,250 @@
+//! OpenRGB: keyboard, mouse and case lighting in the ring's color
+//!
+//! Talks to OpenRGB's SDK server (TCP, port 6742 by default). The devices
+//! are switched to their direct mode and every LED is set to the color the
+//! ring was last drawn with, scaled by its brightness, so pulses and the
+//! rainbow carry over at up to `rate` updates per second. A hidden ring turns
+//! them dark.
+//!
+//! The client doesn't negotiate a protocol version, so the server answers in
+//! version 0, the one every release understands.
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::OpenRgbConfig;
+use crate::ipc::IpcState;
+
+const MAGIC: &[u8; 4] = b"ORGB";
+const REQUEST_CONTROLLER_COUNT: u32 = 0;
+const REQUEST_CONTROLLER_DATA: u32 = 1;
+const SET_CLIENT_NAME: u32 = 50;
+const UPDATE_LEDS: u32 = 1050;
+const SET_CUSTOM_MODE: u32 = 1100;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait before connecting again after losing the server
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+fn packet(device: u32, id: u32, data: &[u8]) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    for field in [device, id, data.len() as u32] {
+        out.extend_from_slice(&field.to_le_bytes());
+    }
+    out.extend_from_slice(data);
+    out
+}
+
+/// Every LED of a device in one color; OpenRGB colors are 0x00BBGGRR
+fn update_leds(leds: u16, (r, g, b): (u8, u8, u8)) -> Vec<u8> {
+    let size = 4 + 2 + 4 * leds as u32;
+    let mut data = size.to_le_bytes().to_vec();
+    data.extend_from_slice(&leds.to_le_bytes());
+    for _ in 0..leds {
+        data.extend_from_slice(&[r, g, b, 0]);
+    }
+    data
+}
+
+/// Reads the little-endian fields of a reply
+struct Fields<'a> {
+    data: &'a [u8],
+}
+
+impl Fields<'_> {
+    fn take(&mut self, n: usize) -> Option<&[u8]> {
+        let (head, rest) = (self.data.get(..n)?, self.data.get(n..)?);
+        self.data = rest;
+        Some(head)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    /// Length-prefixed and NUL-terminated
+    fn string(&mut self) -> Option<String> {
+        let len = self.u16()? as usize;
+        Some(String::from_utf8_lossy(self.take(len)?).trim_end_matches('\0').to_string())
+    }
+}
+
+/// Name and LED count from a version 0 controller description
+fn parse_controller(data: &[u8]) -> Option<(String, u16)> {
+    let mut f = Fields { data };
+    f.u32()?; // data size
+    f.u32()?; // device type
+    let name = f.string()?;
+    for _ in 0..4 {
+        f.string()?; // description, version, serial, location
+    }
+    let modes = f.u16()?;
+    f.u32()?; // active mode
+    for _ in 0..modes {
+        f.string()?;
+        // value, flags, speed min/max, colors min/max, speed, direction, color mode
+        f.take(9 * 4)?;
+        let colors = f.u16()? as usize;
+        f.take(colors * 4)?;
+    }
+    let zones = f.u16()?;
+    for _ in 0..zones {
+        f.string()?;
+        // type, LEDs min/max/count
+        f.take(4 * 4)?;
+        let matrix = f.u16()? as usize;
+        f.take(matrix)?;
+    }
+    Some((name, f.u16()?))
+}
+
+struct Device {
+    index: u32,
+    name: String,
+    leds: u16,
+}
+
+struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    fn send(&mut self, device: u32, id: u32, data: &[u8]) -> std::io::Result<()> {
+        self.stream.write_all(&packet(device, id, data))
+    }
+
+    /// Reply to a request, skipping notifications sent in between
+    fn reply(&mut self, id: u32) -> std::io::Result<Vec<u8>> {
+        loop {
+            let mut header = [0; 16];
+            self.stream.read_exact(&mut header)?;
+            let field = |i: usize| u32::from_le_bytes(header[i..i + 4].try_into().unwrap());
+            let mut data = vec![0; field(12) as usize];
+            self.stream.read_exact(&mut data)?;
+            if &header[..4] == MAGIC && field(8) == id {
+                return Ok(data);
+            }
+        }
+    }
+
+    fn devices(&mut self, wanted: &[String]) -> std::io::Result<Vec<Device>> {
+        self.send(0, REQUEST_CONTROLLER_COUNT, &[])?;
+        let count = self.reply(REQUEST_CONTROLLER_COUNT)?;
+        let count = count.get(..4).map_or(0, |c| u32::from_le_bytes(c.try_into().unwrap()));
+        let mut devices = Vec::new();
+        for index in 0..count {
+            self.send(index, REQUEST_CONTROLLER_DATA, &[])?;
+            let Some((name, leds)) = parse_controller(&self.reply(REQUEST_CONTROLLER_DATA)?) else {
+                log::warn!("openrgb: can't read the description of device {}", index);
+                continue;
+            };
+            let lower = name.to_lowercase();
+            if wanted.is_empty() || wanted.iter().any(|w| lower.contains(&w.to_lowercase())) {
+                devices.push(Device { index, name, leds });
+            }
+        }
+        Ok(devices)
+    }
+}
+
+/// One connection to the server, until it drops
+fn session(state: &IpcState, cfg: &OpenRgbConfig) -> std::io::Result<()> {
+    let stream = TcpStream::connect(&cfg.server)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    let mut client = Client { stream };
+    client.send(0, SET_CLIENT_NAME, b"hypr-ringlight\0")?;
+    let devices = client.devices(&cfg.devices)?;
+    if devices.is_empty() {
+        log::warn!("openrgb: no devices to light on {}", cfg.server);
+    } else {
+        let names: Vec<&str> = devices.iter().map(|d| d.name.as_str()).collect();
+        log::info!("Lighting OpenRGB devices: {}", names.join(", "));
+    }
+    for device in &devices {
+        client.send(device.index, SET_CUSTOM_MODE, &[])?;
+    }
+
+    let interval = Duration::from_secs(1) / cfg.rate.clamp(1, 60);
+    let mut sent = None;
+    loop {
+        let color = match state.get_displayed() {
+            _ if !state.is_visible() => (0, 0, 0),
+            ((r, g, b), opacity) => {
+                let scale = |c: u8| (c as f64 * opacity).round() as u8;
+                (scale(r), scale(g), scale(b))
+            }
+        };
+        if sent != Some(color) {
+            for device in &devices {
+                client.send(device.index, UPDATE_LEDS, &update_leds(device.leds, color))?;
+            }
+            sent = Some(color);
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/// Light OpenRGB devices in the ring's color.
+///
+/// Does nothing unless `[openrgb]` is enabled.
+pub fn start_openrgb(state: Arc<IpcState>, cfg: OpenRgbConfig) {
+    if !cfg.enabled {
+        return;
+    }
+    std::thread::spawn(move || loop {
+        if let Err(e) = session(&state, &cfg) {
+            log::warn!("OpenRGB server {}: {}, reconnecting in {}s", cfg.server, e, RETRY_INTERVAL.as_secs());
+        }
+        std::thread::sleep(RETRY_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Vec<u8> {
+        let mut out = ((s.len() + 1) as u16).to_le_bytes().to_vec();
+        out.extend_from_slice(s.as_bytes());
+        out.push(0);
+        out
+    }
+
+    #[test]
+    fn test_parse_controller() {
+        let mut data = vec![0; 8];
+        for s in ["Keyboard", "desc", "1.0", "", "HID"] {
+            data.extend(string(s));
+        }
+        // One mode with two colors
+        data.extend([1, 0, 0, 0, 0, 0]);
+        data.extend(string("Direct"));
+        data.extend([0; 36]);
+        data.extend([2, 0]);
+        data.extend([0; 8]);
+        // One zone with a 1x1 matrix
+        data.extend([1, 0]);
+        data.extend(string("Keys"));
+        data.extend([0; 16]);
+        data.extend([12, 0]);
+        data.extend([0; 12]);
+        data.extend([104, 0]);
+        assert_eq!(parse_controller(&data), Some(("Keyboard".to_string(), 104)));
+        assert_eq!(parse_controller(&data[..40]), None);
+    }
+
+    #[test]
+    fn test_packets() {
+        let leds = update_leds(2, (0xff, 0x88, 0x00));
+        assert_eq!(leds, [14, 0, 0, 0, 2, 0, 0xff, 0x88, 0, 0, 0xff, 0x88, 0, 0]);
+        let header = packet(3, UPDATE_LEDS, &leds);
+        assert_eq!(&header[..16], [b'O', b'R', b'G', b'B', 3, 0, 0, 0, 0x1a, 0x04, 0, 0, 14, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,174 @@
+//! Overlays drawn directly into the ring's buffer
+//!
+//! The debug overlay renders tick marks along every edge and a small label
+//! (monitor name, resolution, FPS) with a built-in 5x7 bitmap font, so it's
+//! obvious which surface belongs to which output while troubleshooting.
+//!
+//! The framing guide draws faint 1px lines inside the ring's inner edge to
+//! help stay centered on camera.
+
+use crate::config::FramingStyle;
+
+/// Glyph size of the bitmap font (before scaling)
+const GLYPH_W: u32 = 5;
+const GLYPH_H: u32 = 7;
+
+/// Overlay colors (ARGB, premultiplied)
+const FOREGROUND: u32 = 0xffffffff;
+const SHADOW: u32 = 0xff000000;
+
+/// 5x7 bitmap glyphs, one byte per row, bit 4 is the leftmost column
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        '-' => [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000],
+        '.' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100],
+        ':' => [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000],
+        '/' => [0b00001, 0b00010, 0b00010, 0b00100, 0b01000, 0b01000, 0b10000],
+        '%' => [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011],
+        ' ' => [0; 7],
+        _ => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100],
+    }
+}
+
+/// Mutable view over an ARGB8888 buffer
+pub struct Canvas<'a> {
+    pub data: &'a mut [u8],
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Canvas<'_> {
+    pub fn put_pixel(&mut self, x: i64, y: i64, argb: u32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let offset = (y as usize * self.width as usize + x as usize) * 4;
+        self.data[offset..offset + 4].copy_from_slice(&argb.to_ne_bytes());
+    }
+
+    pub fn fill_rect(&mut self, x: i64, y: i64, w: i64, h: i64, argb: u32) {
+        for py in y..y + h {
+            for px in x..x + w {
+                self.put_pixel(px, py, argb);
+            }
+        }
+    }
+
+    /// Draw `text` with its top-left corner at (x, y), with a 1px drop shadow
+    pub fn draw_text(&mut self, x: i64, y: i64, text: &str, scale: u32, argb: u32) {
+        let scale = scale.max(1) as i64;
+        for (px, py) in glyph_cells(text, scale) {
+            self.fill_rect(x + px + 1, y + py + 1, scale, scale, SHADOW);
+            self.fill_rect(x + px, y + py, scale, scale, argb);
+        }
+    }
+}
+
+/// Top-left corners of the lit `scale` x `scale` cells of `text`, from its top-left
+fn glyph_cells(text: &str, scale: i64) -> impl Iterator<Item = (i64, i64)> + '_ {
+    text.chars().enumerate().flat_map(move |(i, c)| {
+        let origin_x = i as i64 * (GLYPH_W as i64 + 1) * scale;
+        glyph(c).into_iter().enumerate().flat_map(move |(row, bits)| {
+            (0..GLYPH_W as i64)
+                .filter(move |col| bits & (1 << (GLYPH_W as i64 - 1 - col)) != 0)
+                .map(move |col| (origin_x + col * scale, row as i64 * scale))
+        })
+    })
+}
+
+/// Pixel width of `text` rendered at `scale`
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    text.chars().count() as u32 * (GLYPH_W + 1) * scale
+}
+
+/// Draw tick marks along all four edges plus a label in the top band.
+///
+/// `band` is the ring thickness in pixels; ticks every 50px, with a longer
+/// tick every 250px so distances can be read off the screen edge.
+pub fn draw_debug_overlay(canvas: &mut Canvas, band: u32, label: &str) {
+    let (w, h) = (canvas.width as i64, canvas.height as i64);
+    let tick_len = |pos: i64| if pos % 250 == 0 { 16 } else { 6 };
+
+    for x in (0..w).step_by(50) {
+        canvas.fill_rect(x, 0, 1, tick_len(x), FOREGROUND);
+        canvas.fill_rect(x, h - tick_len(x), 1, tick_len(x), FOREGROUND);
+    }
+    for y in (0..h).step_by(50) {
+        canvas.fill_rect(0, y, tick_len(y), 1, FOREGROUND);
+        canvas.fill_rect(w - tick_len(y), y, tick_len(y), 1, FOREGROUND);
+    }
+
+    // Fit the label inside the band when possible, centered vertically in it
+    let scale = (band / (GLYPH_H * 2)).clamp(1, 3);
+    let text_h = (GLYPH_H * scale) as i64;
+    let y = ((band as i64 - text_h) / 2).max(20);
+    let x = (w - text_width(label, scale) as i64) / 2;
+    canvas.draw_text(x.max(20), y, label, scale, FOREGROUND);
+}
+
+/// Draw framing guides inside the area enclosed by the ring (`inset` pixels
+/// from each edge), in white at the given opacity.
+pub fn draw_framing_guide(canvas: &mut Canvas, inset: u32, style: FramingStyle, opacity: f64) {
+    let a = (opacity.clamp(0.0, 1.0) * 255.0) as u32;
+    // Premultiplied white
+    let argb = (a << 24) | (a << 16) | (a << 8) | a;
+
+    let (left, top) = (inset as i64, inset as i64);
+    let (w, h) = (canvas.width as i64 - 2 * left, canvas.height as i64 - 2 * top);
+    if w <= 0 || h <= 0 {
+        return;
+    }
+
+    if matches!(style, FramingStyle::Thirds | FramingStyle::Both) {
+        for i in 1..3 {
+            canvas.fill_rect(left + w * i / 3, top, 1, h, argb);
+            canvas.fill_rect(left, top + h * i / 3, w, 1, argb);
+        }
+    }
+
+    if matches!(style, FramingStyle::Head | FramingStyle::Both) {
+        // Head centered horizontally, eyes around the upper third line
+        let (box_w, box_h) = (w / 5, h * 2 / 5);
+        let (x, y) = (left + (w - box_w) / 2, top + h / 8);
+        canvas.fill_rect(x, y, box_w, 1, argb);
+        canvas.fill_rect(x, y + box_h, box_w, 1, argb);
+        canvas.fill_rect(x, y, 1, box_h, argb);
+        canvas.fill_rect(x + box_w, y, 1, box_h + 1, argb);
+    }
+}
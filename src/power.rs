@@ -0,0 +1,99 @@
+//! Screens that are off: DPMS and a closed lid
+//!
+//! wlr-output-power-management reports each output's power mode, so a ring
+//! on a screen the compositor has blanked can be unmapped and its buffers
+//! freed instead of drawing frames nobody sees. wlroots hands an output's
+//! power control to one client at a time, so following it keeps wlopm and
+//! other DPMS tools from setting it; that's why it takes `dpms = true`. A closed laptop lid (from
+//! logind) does the same for the built-in panel, whose output often stays on.
+//! Both resume as soon as the screen is back.
+
+use smithay_client_toolkit::reexports::protocols_wlr::output_power_management::v1::client::{
+    zwlr_output_power_manager_v1, zwlr_output_power_v1,
+};
+use wayland_client::{globals::GlobalList, protocol::wl_output, Connection, Dispatch, QueueHandle};
+
+use crate::RingLight;
+
+/// Bind the output power manager, if the compositor has one
+pub fn bind(
+    globals: &GlobalList,
+    qh: &QueueHandle<RingLight>,
+) -> Option<zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1> {
+    let manager = globals.bind(qh, 1..=1, ()).ok();
+    if manager.is_none() {
+        log::info!("Compositor lacks wlr-output-power-management, rings keep drawing on blanked screens");
+    }
+    manager
+}
+
+/// Power mode events for one output, stopped when dropped
+pub struct OutputPower(zwlr_output_power_v1::ZwlrOutputPowerV1);
+
+impl OutputPower {
+    /// Follow the power mode of `output`, reported for the ring on `surface_id`
+    pub fn new(
+        manager: &zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1,
+        output: &wl_output::WlOutput,
+        qh: &QueueHandle<RingLight>,
+        surface_id: u32,
+    ) -> Self {
+        Self(manager.get_output_power(output, qh, surface_id))
+    }
+}
+
+impl Drop for OutputPower {
+    fn drop(&mut self) {
+        self.0.destroy();
+    }
+}
+
+/// Whether an output is a laptop's built-in panel, which the lid covers
+pub fn is_internal(output_name: &str) -> bool {
+    ["eDP", "LVDS", "DSI"].iter().any(|prefix| output_name.starts_with(prefix))
+}
+
+impl Dispatch<zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1, ()> for RingLight {
+    fn event(
+        _state: &mut Self,
+        _proxy: &zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1,
+        _event: zwlr_output_power_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {}
+}
+
+impl Dispatch<zwlr_output_power_v1::ZwlrOutputPowerV1, u32> for RingLight {
+    fn event(
+        state: &mut Self,
+        _proxy: &zwlr_output_power_v1::ZwlrOutputPowerV1,
+        event: zwlr_output_power_v1::Event,
+        surface_id: &u32,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_output_power_v1::Event::Mode { mode } => {
+                let on = mode != wayland_client::WEnum::Value(zwlr_output_power_v1::Mode::Off);
+                state.set_output_power(*surface_id, on);
+            }
+            // Another client controls the output's power, or it's gone
+            zwlr_output_power_v1::Event::Failed => state.set_output_power(*surface_id, true),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_internal() {
+        assert!(is_internal("eDP-1"));
+        assert!(is_internal("LVDS-1"));
+        assert!(!is_internal("DP-2"));
+        assert!(!is_internal("HDMI-A-1"));
+    }
+}
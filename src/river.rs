@@ -0,0 +1,263 @@
+//! river's status protocol, for the workspace, focus and window rules
+//!
+//! river has no IPC socket; what's on screen is told over its own Wayland
+//! protocol, river-status: the focused tags of each output, the focused
+//! output of a seat and the title of its focused view. A connection of its
+//! own is dispatched on a thread that keeps the latest status and writes a
+//! line for each change, `layout` or `title>>TITLE`, for `parse_event`.
+//!
+//! Tags stand in for workspaces: an output showing a single tag is on the
+//! workspace of its number, 1 to 32; showing several, on the lowest one,
+//! named by all of them joined with `+`. river tells no app id, fullscreen
+//! state, special workspaces, reserved areas or border color.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
+
+use wayland_client::{
+    delegate_noop,
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::{wl_output::{self, WlOutput}, wl_registry::{self, WlRegistry}, wl_seat::WlSeat},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+
+use crate::compositor::{Backend, Event, Monitor, Window, WorkspaceRef};
+
+use self::protocol::{
+    zriver_output_status_v1::{self, ZriverOutputStatusV1},
+    zriver_seat_status_v1::{self, ZriverSeatStatusV1},
+    zriver_status_manager_v1::ZriverStatusManagerV1,
+};
+
+#[allow(dead_code, non_camel_case_types, non_upper_case_globals, unused_imports, clippy::all)]
+mod protocol {
+    use wayland_client;
+    use wayland_client::protocol::*;
+
+    pub mod __interfaces {
+        use wayland_client::backend as wayland_backend;
+        use wayland_client::protocol::__interfaces::*;
+        wayland_scanner::generate_interfaces!("protocols/river-status-unstable-v1.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_client_code!("protocols/river-status-unstable-v1.xml");
+}
+
+/// An output, known by its registry name
+#[derive(Debug, Default)]
+struct OutputStatus {
+    global: u32,
+    /// Connector name, once `wl_output` told it
+    name: Option<String>,
+    tags: u32,
+}
+
+/// The latest state river told
+#[derive(Debug, Default)]
+struct Status {
+    outputs: Vec<OutputStatus>,
+    focused: Option<u32>,
+    /// Title of the focused view, `None` when nothing has focus
+    title: Option<String>,
+}
+
+/// The workspace of an output showing `tags`
+fn workspace(tags: u32) -> WorkspaceRef {
+    let numbers: Vec<String> = (0..32).filter(|i| tags & (1 << i) != 0).map(|i| (i + 1).to_string()).collect();
+    WorkspaceRef {
+        // No tag at all is no workspace
+        id: if tags == 0 { 0 } else { tags.trailing_zeros() as i64 + 1 },
+        name: numbers.join("+"),
+    }
+}
+
+/// Parse one line of the status thread
+fn parse_event(line: &str) -> Option<Event> {
+    match line.split_once(">>") {
+        Some(("title", title)) => Some(Event::ActiveWindow { class: String::new(), title: title.to_string() }),
+        None if line == "layout" => Some(Event::Layout),
+        _ => None,
+    }
+}
+
+/// The connection's side: the protocol objects, and where changes are written
+struct Client {
+    status: Arc<Mutex<Status>>,
+    manager: ZriverStatusManagerV1,
+    outputs: HashMap<u32, (WlOutput, ZriverOutputStatusV1)>,
+    seat: Option<ZriverSeatStatusV1>,
+    /// Set once the initial state is in; `None` again when the listener went away
+    lines: Option<UnixStream>,
+}
+
+impl Client {
+    fn global(&mut self, registry: &WlRegistry, qh: &QueueHandle<Self>, global: u32, interface: &str, version: u32) {
+        match interface {
+            // The name event came with version 4
+            "wl_output" if version >= 4 => {
+                let output: WlOutput = registry.bind(global, 4, qh, global);
+                let status = self.manager.get_river_output_status(&output, qh, global);
+                self.outputs.insert(global, (output, status));
+                self.status.lock().unwrap().outputs.push(OutputStatus { global, ..Default::default() });
+            }
+            "wl_seat" if self.seat.is_none() => {
+                let seat: WlSeat = registry.bind(global, 1, qh, ());
+                self.seat = Some(self.manager.get_river_seat_status(&seat, qh, ()));
+            }
+            _ => {}
+        }
+    }
+
+    fn send(&mut self, line: &str) {
+        if let Some(lines) = &mut self.lines {
+            if lines.write_all(format!("{}\n", line).as_bytes()).is_err() {
+                self.lines = None;
+            }
+        }
+    }
+
+    fn set_output(&mut self, global: u32, change: impl FnOnce(&mut OutputStatus)) {
+        if let Some(output) = self.status.lock().unwrap().outputs.iter_mut().find(|o| o.global == global) {
+            change(output);
+        }
+        self.send("layout");
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for Client {
+    fn event(state: &mut Self, registry: &WlRegistry, event: wl_registry::Event, _: &GlobalListContents, _: &Connection, qh: &QueueHandle<Self>) {
+        match event {
+            wl_registry::Event::Global { name, interface, version } => state.global(registry, qh, name, &interface, version),
+            wl_registry::Event::GlobalRemove { name } => {
+                if let Some((output, status)) = state.outputs.remove(&name) {
+                    status.destroy();
+                    output.release();
+                    state.status.lock().unwrap().outputs.retain(|o| o.global != name);
+                    state.send("layout");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlOutput, u32> for Client {
+    fn event(state: &mut Self, _: &WlOutput, event: wl_output::Event, global: &u32, _: &Connection, _: &QueueHandle<Self>) {
+        if let wl_output::Event::Name { name } = event {
+            state.set_output(*global, |o| o.name = Some(name));
+        }
+    }
+}
+
+impl Dispatch<ZriverOutputStatusV1, u32> for Client {
+    fn event(state: &mut Self, _: &ZriverOutputStatusV1, event: zriver_output_status_v1::Event, global: &u32, _: &Connection, _: &QueueHandle<Self>) {
+        if let zriver_output_status_v1::Event::FocusedTags { tags } = event {
+            state.set_output(*global, |o| o.tags = tags);
+        }
+    }
+}
+
+impl Dispatch<ZriverSeatStatusV1, ()> for Client {
+    fn event(state: &mut Self, _: &ZriverSeatStatusV1, event: zriver_seat_status_v1::Event, _: &(), _: &Connection, _: &QueueHandle<Self>) {
+        match event {
+            zriver_seat_status_v1::Event::FocusedOutput { output } => {
+                state.status.lock().unwrap().focused = output.data::<u32>().copied();
+                state.send("layout");
+            }
+            zriver_seat_status_v1::Event::UnfocusedOutput { output } => {
+                let mut status = state.status.lock().unwrap();
+                if status.focused.is_some() && status.focused == output.data::<u32>().copied() {
+                    status.focused = None;
+                }
+            }
+            zriver_seat_status_v1::Event::FocusedView { title } => {
+                // A title can't break the line apart
+                let title = title.replace('\n', " ");
+                state.status.lock().unwrap().title = (!title.is_empty()).then(|| title.clone());
+                state.send(&format!("title>>{}", title));
+            }
+            _ => {}
+        }
+    }
+}
+
+delegate_noop!(Client: ZriverStatusManagerV1);
+delegate_noop!(Client: ignore WlSeat);
+
+/// river's status protocol
+#[derive(Default)]
+pub struct River {
+    status: Arc<Mutex<Status>>,
+}
+
+impl Backend for River {
+    fn subscribe(&self) -> Option<Box<dyn BufRead + Send>> {
+        let conn = Connection::connect_to_env().ok()?;
+        let (globals, mut queue) = registry_queue_init::<Client>(&conn).ok()?;
+        let qh = queue.handle();
+        let manager: ZriverStatusManagerV1 = globals.bind(&qh, 1..=1, ()).ok()?;
+        *self.status.lock().unwrap() = Status::default();
+        let mut client = Client { status: self.status.clone(), manager, outputs: HashMap::new(), seat: None, lines: None };
+        for global in globals.contents().clone_list() {
+            client.global(globals.registry(), &qh, global.name, &global.interface, global.version);
+        }
+        // The status objects send their state when created
+        queue.roundtrip(&mut client).ok()?;
+
+        let (reader, writer) = UnixStream::pair().ok()?;
+        client.lines = Some(writer);
+        std::thread::spawn(move || {
+            while client.lines.is_some() && queue.blocking_dispatch(&mut client).is_ok() {}
+        });
+        Some(Box::new(BufReader::new(reader)))
+    }
+
+    fn parse_event(&self, line: &str) -> Option<Event> {
+        parse_event(line)
+    }
+
+    fn monitors(&self) -> Option<Vec<Monitor>> {
+        let status = self.status.lock().unwrap();
+        let monitors = status.outputs.iter()
+            .filter_map(|o| {
+                Some(Monitor {
+                    name: o.name.clone()?,
+                    focused: status.focused == Some(o.global),
+                    workspace: workspace(o.tags),
+                    special: None,
+                    reserved: None,
+                })
+            })
+            .collect();
+        Some(monitors)
+    }
+
+    fn active_window(&self) -> Option<Window> {
+        let title = self.status.lock().unwrap().title.clone()?;
+        Some(Window { class: String::new(), title, fullscreen: false })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event() {
+        assert_eq!(parse_event("layout"), Some(Event::Layout));
+        assert_eq!(
+            parse_event("title>>Meet - Chromium"),
+            Some(Event::ActiveWindow { class: String::new(), title: "Meet - Chromium".into() })
+        );
+        assert_eq!(parse_event("mode>>normal"), None);
+    }
+
+    #[test]
+    fn test_workspace() {
+        assert_eq!(workspace(1 << 2), WorkspaceRef { id: 3, name: "3".into() });
+        assert_eq!(workspace(0b1010), WorkspaceRef { id: 2, name: "2+4".into() });
+    }
+}
@@ -0,0 +1,35 @@
+//! `hypr-ringlight scene`: apply a scene file to the running instance
+
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use hypr_ringlight_core::ipc;
+use hypr_ringlight_core::scene::Scene;
+
+use crate::error::Error;
+
+#[derive(Subcommand, Debug)]
+pub enum SceneAction {
+    /// Apply a scene, changing only what differs from the current state
+    Apply {
+        /// Scene file (TOML)
+        file: PathBuf,
+    },
+}
+
+/// Run a `scene` subcommand
+pub fn run(action: SceneAction) -> Result<(), Error> {
+    match action {
+        SceneAction::Apply { file } => {
+            let scene = Scene::load(&file)?;
+            let changes = ipc::apply_scene(&scene)?;
+            if changes.is_empty() {
+                println!("Already applied");
+            }
+            for change in changes {
+                println!("{}", change);
+            }
+            Ok(())
+        }
+    }
+}
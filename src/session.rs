@@ -0,0 +1,84 @@
+//! logind session tracking
+//!
+//! When the user switches VT or another user takes over the seat (fast user
+//! switching), logind marks our session inactive. The renderer then unmaps
+//! the rings and frees their buffers until the session is active again, so
+//! nothing bleeds into the other session and no cycles are wasted.
+//!
+//! logind also knows whether the laptop lid is closed; the ring on the
+//! built-in panel is paused the same way meanwhile.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+use dbus::blocking::Connection;
+use dbus::Path;
+
+use crate::ipc::IpcState;
+
+const LOGIND: &str = "org.freedesktop.login1";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Object path of our logind session
+fn session_path(conn: &Connection) -> Option<Path<'static>> {
+    let manager = conn.with_proxy(LOGIND, "/org/freedesktop/login1", TIMEOUT);
+    let (path,): (Path<'static>,) = match std::env::var("XDG_SESSION_ID") {
+        Ok(id) => manager.method_call("org.freedesktop.login1.Manager", "GetSession", (id,)).ok()?,
+        Err(_) => manager
+            .method_call("org.freedesktop.login1.Manager", "GetSessionByPID", (std::process::id(),))
+            .ok()?,
+    };
+    Some(path)
+}
+
+fn is_session_active(conn: &Connection, path: &Path<'static>) -> Option<bool> {
+    conn.with_proxy(LOGIND, path, TIMEOUT)
+        .get("org.freedesktop.login1.Session", "Active")
+        .ok()
+}
+
+/// `None` on machines without a lid switch, or an old logind
+fn is_lid_closed(conn: &Connection) -> Option<bool> {
+    conn.with_proxy(LOGIND, "/org/freedesktop/login1", TIMEOUT)
+        .get("org.freedesktop.login1.Manager", "LidClosed")
+        .ok()
+}
+
+/// Start the session monitoring thread.
+///
+/// Does nothing without a system bus or logind session.
+pub fn start_session_monitor(state: Arc<IpcState>) {
+    std::thread::spawn(move || {
+        let Ok(conn) = Connection::new_system() else {
+            log::info!("No system bus, session tracking disabled");
+            return;
+        };
+        let Some(path) = session_path(&conn) else {
+            log::info!("No logind session, session tracking disabled");
+            return;
+        };
+
+        let mut active = true;
+        let mut lid_closed = false;
+        loop {
+            if let Some(now_closed) = is_lid_closed(&conn) {
+                if now_closed != lid_closed {
+                    lid_closed = now_closed;
+                    log::info!("Lid {}", if lid_closed { "closed, pausing the built-in panel" } else { "opened" });
+                    state.set_lid_closed(lid_closed);
+                    state.log_event("session", if lid_closed { "lid closed" } else { "lid opened" });
+                }
+            }
+            if let Some(now_active) = is_session_active(&conn, &path) {
+                if now_active != active {
+                    active = now_active;
+                    log::info!("Session {}", if active { "active, resuming" } else { "inactive, pausing" });
+                    state.set_session_active(active);
+                    state.log_event("session", if active { "session active" } else { "session inactive" });
+                }
+            }
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    });
+}
@@ -0,0 +1,175 @@
+//! Global shortcuts through the xdg-desktop-portal GlobalShortcuts interface
+//!
+//! The ring registers its actions (toggle, brighter, dimmer, next profile)
+//! with the portal, which lets the user pick or confirm the keys in its own
+//! dialog and reports presses as `Activated` signals, so no compositor binds
+//! have to be written by hand. Portal calls answer through a `Request`
+//! object's `Response` signal, listened for before each call. Without the
+//! portal (or with one that lacks the interface) nothing is registered.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::Connection;
+use dbus::message::MatchRule;
+use dbus::Path;
+
+use hypr_ringlight_core::ipc::Command;
+
+use crate::config::{Profile, ShortcutsConfig};
+use crate::ipc::{self, IpcState};
+
+const PORTAL: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const GLOBAL_SHORTCUTS: &str = "org.freedesktop.portal.GlobalShortcuts";
+const REQUEST: &str = "org.freedesktop.portal.Request";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long the portal's dialog may wait for the user
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Opacity change of the brighter and dimmer shortcuts
+const STEP: f64 = 0.1;
+
+/// Shortcut ids and what the portal shows for them
+const SHORTCUTS: [(&str, &str); 4] = [
+    ("toggle", "Show or hide the ring light"),
+    ("brighter", "Make the ring light brighter"),
+    ("dimmer", "Make the ring light dimmer"),
+    ("next-profile", "Switch the ring light to the next profile"),
+];
+
+fn variant(value: impl RefArg + 'static) -> Variant<Box<dyn RefArg>> {
+    Variant(Box::new(value))
+}
+
+/// Call `method`, which answers through a request object, and wait for its results
+fn request<A: dbus::arg::AppendAll>(conn: &Connection, method: &str, token: &str, args: A) -> Result<PropMap, String> {
+    // The request object's path is known ahead, so the response can't slip by
+    let sender = conn.unique_name().trim_start_matches(':').replace('.', "_");
+    let path = Path::new(format!("{}/request/{}/{}", PORTAL_PATH, sender, token))?;
+    let response = Arc::new(Mutex::new(None));
+    let slot = response.clone();
+    let rule = MatchRule::new_signal(REQUEST, "Response").with_path(path);
+    let token = conn
+        .add_match(rule, move |(code, results): (u32, PropMap), _, _| {
+            if let Ok(mut slot) = slot.lock() {
+                *slot = Some((code, results));
+            }
+            false
+        })
+        .map_err(|e| e.to_string())?;
+
+    let called: Result<(Path,), _> = conn.with_proxy(PORTAL, PORTAL_PATH, TIMEOUT).method_call(GLOBAL_SHORTCUTS, method, args);
+    let deadline = Instant::now() + RESPONSE_TIMEOUT;
+    let result = match called {
+        Err(e) => Err(e.to_string()),
+        Ok(_) => loop {
+            if let Some((code, results)) = response.lock().ok().and_then(|mut r| r.take()) {
+                break match code {
+                    0 => Ok(results),
+                    1 => Err("cancelled".to_string()),
+                    _ => Err("refused by the portal".to_string()),
+                };
+            }
+            if Instant::now() > deadline {
+                break Err("no answer from the portal".to_string());
+            }
+            if let Err(e) = conn.process(Duration::from_secs(1)) {
+                break Err(e.to_string());
+            }
+        },
+    };
+    let _ = conn.remove_match(token);
+    result
+}
+
+/// Open a session and bind the shortcuts, with the triggers from `cfg` as suggestions
+fn bind(conn: &Connection, cfg: &ShortcutsConfig) -> Result<Path<'static>, String> {
+    let mut options = PropMap::new();
+    options.insert("handle_token".to_string(), variant("ringlight_session".to_string()));
+    options.insert("session_handle_token".to_string(), variant("ringlight".to_string()));
+    let results = request(conn, "CreateSession", "ringlight_session", (options,))?;
+    let session = results
+        .get("session_handle")
+        .and_then(|v| v.0.as_str())
+        .ok_or("no session handle")?
+        .to_string();
+    let session = Path::new(session)?;
+
+    let shortcuts: Vec<(String, PropMap)> = SHORTCUTS
+        .iter()
+        .map(|(id, description)| {
+            let mut props = PropMap::new();
+            props.insert("description".to_string(), variant(description.to_string()));
+            let trigger = cfg.trigger(id);
+            if !trigger.is_empty() {
+                props.insert("preferred_trigger".to_string(), variant(trigger.to_string()));
+            }
+            (id.to_string(), props)
+        })
+        .collect();
+    let mut options = PropMap::new();
+    options.insert("handle_token".to_string(), variant("ringlight_bind".to_string()));
+    request(conn, "BindShortcuts", "ringlight_bind", (session.clone(), shortcuts, "", options))?;
+    Ok(session)
+}
+
+/// Run the action of the shortcut `id`
+fn activate(state: &Arc<IpcState>, profiles: &BTreeMap<String, Profile>, id: &str) {
+    let line = format!("shortcut {}", id);
+    match id {
+        "toggle" => {
+            ipc::execute(state, Command::SetVisible(!state.is_visible()), "shortcut", &line);
+        }
+        "brighter" | "dimmer" => {
+            let step = if id == "brighter" { STEP } else { -STEP };
+            let opacity = (state.get_opacity() + step).clamp(STEP, 1.0);
+            ipc::execute(state, Command::SetOpacity(opacity), "shortcut", &line);
+        }
+        "next-profile" => crate::click::cycle_profile(state, profiles),
+        _ => {}
+    }
+}
+
+/// Register the global shortcuts and follow their presses.
+///
+/// Does nothing unless `[shortcuts]` is enabled.
+pub fn start_shortcuts(state: Arc<IpcState>, cfg: ShortcutsConfig, profiles: BTreeMap<String, Profile>) {
+    if !cfg.enabled {
+        return;
+    }
+    std::thread::spawn(move || {
+        let Ok(conn) = Connection::new_session() else {
+            log::info!("No session bus, no global shortcuts");
+            return;
+        };
+        let session = match bind(&conn, &cfg) {
+            Ok(session) => session,
+            Err(e) => {
+                log::warn!("Global shortcuts unavailable ({}), bind keys in the compositor instead", e);
+                return;
+            }
+        };
+        log::info!("Registered global shortcuts");
+
+        let rule = MatchRule::new_signal(GLOBAL_SHORTCUTS, "Activated");
+        let activated = conn.add_match(
+            rule,
+            move |(from, id, _, _): (Path, String, u64, PropMap), _, _| {
+                if from == session {
+                    activate(&state, &profiles, &id);
+                }
+                true
+            },
+        );
+        if let Err(e) = activated {
+            log::warn!("Failed to follow global shortcuts: {}", e);
+            return;
+        }
+        while conn.process(Duration::from_secs(60)).is_ok() {}
+        log::warn!("Lost the session bus, global shortcuts stopped");
+    });
+}
@@ -0,0 +1,132 @@
+//! `--render-to-file` and `--bench-frame`: draw frames offscreen
+//!
+//! The frames come from the core's headless renderer, `frame`, with the
+//! config's look at rest, plus the label and a texture loaded from disk.
+//! Needs no compositor, so configs can be previewed and documentation
+//! images generated anywhere.
+//!
+//! `--bench-frame` draws the same frame over and over and prints how long
+//! each took, at the config's `render_scale`, to compare machines and check
+//! the effect of changes to the pixel loop.
+
+#[cfg(feature = "snapshot")]
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[cfg(all(feature = "snapshot", feature = "label"))]
+use hypr_ringlight_core::color::{contrast_color, resolve_color};
+use hypr_ringlight_core::config::Config;
+#[cfg(feature = "snapshot")]
+use hypr_ringlight_core::frame;
+use hypr_ringlight_core::frame::{ring_at_rest, surface_size};
+use hypr_ringlight_core::render::{self, Texture};
+
+use crate::error::Error;
+#[cfg(all(feature = "snapshot", feature = "label"))]
+use crate::{label, overlay};
+
+/// How long `--bench-frame` keeps drawing
+const BENCH_TIME: Duration = Duration::from_secs(3);
+
+/// Parse a `WIDTHxHEIGHT` size, e.g. `2560x1440`
+pub fn parse_size(s: &str) -> Result<(u32, u32), Error> {
+    let invalid = || Error::Usage(format!("Invalid size '{}', expected WIDTHxHEIGHT like 2560x1440", s));
+    let (w, h) = s.split_once(['x', 'X']).ok_or_else(invalid)?;
+    match (w.trim().parse(), h.trim().parse()) {
+        (Ok(w), Ok(h)) if w > 0 && h > 0 => Ok((w, h)),
+        _ => Err(invalid()),
+    }
+}
+
+fn load_texture(cfg: &Config) -> Result<Option<Texture>, Error> {
+    #[cfg(feature = "texture")]
+    return (!cfg.texture.path.is_empty())
+        .then(|| crate::texture::load(&cfg.texture.path, cfg.texture.mode_enum()))
+        .transpose();
+    #[cfg(not(feature = "texture"))]
+    {
+        let _ = cfg;
+        Ok(None)
+    }
+}
+
+/// Render a monitor of `size` with `cfg` and write it to `path`
+#[cfg(feature = "snapshot")]
+pub fn render_to_file(cfg: &Config, color: (u8, u8, u8), path: &Path, size: (u32, u32)) -> Result<(), Error> {
+    render_frame(cfg, color, size)?
+        .save(path)
+        .map_err(|e| Error::Other(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Render a monitor of `size` with `cfg` into memory, straight (not premultiplied) RGBA
+#[cfg(feature = "snapshot")]
+pub fn render_frame(cfg: &Config, color: (u8, u8, u8), size: (u32, u32)) -> Result<image::RgbaImage, Error> {
+    let texture = load_texture(cfg)?;
+    #[cfg_attr(not(feature = "label"), allow(unused_mut))]
+    let mut canvas = frame::render_surface(cfg, color, size, texture.as_ref());
+    #[cfg(feature = "label")]
+    draw_label(cfg, &mut canvas, surface_size(cfg, size), ring_at_rest(cfg, color, None, 1.0).color);
+    let pixels = frame::to_monitor(cfg, &canvas, size);
+    Ok(image::RgbaImage::from_raw(size.0, size.1, pixels).expect("a frame of the monitor's size"))
+}
+
+/// The config's label, in the top band of a surface of `size`
+#[cfg(all(feature = "snapshot", feature = "label"))]
+fn draw_label(cfg: &Config, canvas: &mut [u8], (width, height): (u32, u32), ring_color: (u8, u8, u8)) {
+    let color = match cfg.label.color.as_str() {
+        "" => contrast_color(ring_color),
+        name => resolve_color(name, &cfg.palette),
+    };
+    let text = match cfg.label.text.as_str() {
+        "" if !cfg.label.clock.is_empty() => label::clock(&cfg.label.clock),
+        text => text.to_string(),
+    };
+    label::draw_label(&mut overlay::Canvas { data: canvas, width, height }, cfg.inset, cfg.thickness, &label::Label {
+        text: &text,
+        size: cfg.label.size,
+        color,
+        backing: cfg.label.backing,
+        opacity: 1.0,
+    });
+}
+
+/// Draw frames of a monitor of `size` for a few seconds and print the time per frame
+pub fn bench_frame(cfg: &Config, color: (u8, u8, u8), size: (u32, u32)) -> Result<(), Error> {
+    let scale = crate::viewport::render_scale(cfg.render_scale);
+    let (width, height) = surface_size(cfg, size);
+    let (width, height) = crate::viewport::buffer_size(width, height, scale);
+    let texture = load_texture(cfg)?;
+    let ring = ring_at_rest(cfg, color, texture.as_ref(), scale);
+    let mut canvas = vec![0u8; width as usize * height as usize * 4];
+
+    // The first frame fills the lookup tables and faults the canvas in
+    render::render(&mut canvas, width, height, &ring);
+    let mut times = Vec::new();
+    let start = Instant::now();
+    while start.elapsed() < BENCH_TIME {
+        let frame = Instant::now();
+        render::render(&mut canvas, width, height, &ring);
+        times.push(frame.elapsed());
+    }
+    times.sort();
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let median = times[times.len() / 2];
+    println!(
+        "{}x{} ({}x{} drawn): {:.2} ms/frame median, {:.2} ms fastest, {} frames, {:.0} fps",
+        size.0, size.1, width, height, ms(median), ms(times[0]), times.len(), 1.0 / median.as_secs_f64()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("2560x1440").unwrap(), (2560, 1440));
+        assert_eq!(parse_size("800X600").unwrap(), (800, 600));
+        assert!(parse_size("0x600").is_err());
+        assert!(parse_size("1920").is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! Audio spectrum analyzer around the ring
+//!
+//! cava does the audio capture (PipeWire, PulseAudio or ALSA, from the
+//! monitor of the default output) and the FFT. It's run with raw ASCII
+//! output, one line of band levels per frame, only while the `spectrum`
+//! animation is selected.
+
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+use hypr_ringlight_core::ipc::instance_id;
+use hypr_ringlight_core::render::animation_from_name;
+
+use crate::config::SpectrumConfig;
+use crate::ipc::IpcState;
+
+/// Levels cava reports go from 0 to this
+const MAX_RANGE: u32 = 1000;
+
+/// Frames per second asked from cava
+const FRAMERATE: u32 = 60;
+
+/// How often to check whether the spectrum animation got selected
+const IDLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait before starting cava again after it failed
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// cava config for `bars` bands in raw ASCII to stdout
+fn cava_config(bars: u32) -> String {
+    format!(
+        "[general]\nbars = {}\nframerate = {}\n\n\
+         [output]\nmethod = raw\nraw_target = /dev/stdout\ndata_format = ascii\nascii_max_range = {}\nchannels = mono\n",
+        bars, FRAMERATE, MAX_RANGE
+    )
+}
+
+/// Parse a frame like `12;840;1000;0;`
+fn parse_levels(line: &str) -> Vec<f64> {
+    line.split(';')
+        .filter_map(|v| v.trim().parse::<u32>().ok())
+        .map(|v| v.min(MAX_RANGE) as f64 / MAX_RANGE as f64)
+        .collect()
+}
+
+fn config_path() -> PathBuf {
+    dirs::runtime_dir().unwrap_or_else(std::env::temp_dir).join(format!("{}-cava.conf", instance_id()))
+}
+
+fn spawn_cava(cfg: &SpectrumConfig) -> Result<Child, String> {
+    let path = config_path();
+    std::fs::write(&path, cava_config(cfg.bars.clamp(1, 256))).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Command::new(&cfg.command)
+        .arg("-p")
+        .arg(&path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("{}: {}", cfg.command, e))
+}
+
+/// Run cava while the spectrum animation is selected, publishing its levels.
+///
+/// Does nothing until the animation is picked, from the config or live.
+pub fn start_spectrum(state: Arc<IpcState>, cfg: SpectrumConfig) {
+    let spectrum = animation_from_name("spectrum");
+    std::thread::spawn(move || loop {
+        if state.get_animation_mode() != spectrum {
+            std::thread::sleep(IDLE_INTERVAL);
+            continue;
+        }
+        let mut child = match spawn_cava(&cfg) {
+            Ok(child) => child,
+            Err(e) => {
+                log::warn!("Failed to start cava for the spectrum animation: {}", e);
+                std::thread::sleep(RETRY_INTERVAL);
+                continue;
+            }
+        };
+        log::info!("Started cava with {} bands", cfg.bars);
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                state.set_spectrum(parse_levels(&line));
+                if state.get_animation_mode() != spectrum {
+                    break;
+                }
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+        state.set_spectrum(Vec::new());
+        if state.get_animation_mode() == spectrum {
+            log::warn!("cava exited, restarting it in {}s", RETRY_INTERVAL.as_secs());
+            std::thread::sleep(RETRY_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_levels() {
+        assert_eq!(parse_levels("0;500;1000;1200;\n"), vec![0.0, 0.5, 1.0, 1.0]);
+        assert!(parse_levels("").is_empty());
+        assert!(cava_config(24).contains("bars = 24"));
+    }
+}
@@ -0,0 +1,97 @@
+//! Strobe alerts and flashes
+//!
+//! Scripts can flash the ring to grab attention, e.g. when a build fails.
+//! Whatever is asked for, the strobe stays under the photosensitive seizure
+//! threshold (three flashes a second) and stops by itself. A single flash,
+//! for confirming a keybinding, holds a color and then puts the ring back.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use hypr_ringlight_core::color::resolve_color;
+use hypr_ringlight_core::ipc::StrobeRequest;
+
+use crate::config::{Profile, StrobeConfig};
+use crate::ipc::IpcState;
+
+/// Fastest strobe allowed, in flashes per second (WCAG general flash threshold)
+pub const MAX_FREQUENCY: f64 = 3.0;
+/// Longest a strobe may run, however many flashes were asked for
+const MAX_DURATION: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Strobe {
+    pub started: Instant,
+    /// Flashes per second
+    pub frequency: f64,
+    /// Lit fraction of each flash
+    pub duty: f64,
+    /// Flashes before stopping
+    pub count: u32,
+    /// Flash color, or the ring color
+    pub color: Option<(u8, u8, u8)>,
+}
+
+impl Strobe {
+    /// A strobe from `request`, filling unset fields from `cfg` and clamping to safe limits
+    pub fn new(request: &StrobeRequest, cfg: &StrobeConfig, palette: &BTreeMap<String, String>) -> Self {
+        let frequency = request.frequency.unwrap_or(cfg.frequency);
+        if frequency > MAX_FREQUENCY {
+            log::warn!("Strobe frequency {} Hz capped at {} Hz", frequency, MAX_FREQUENCY);
+        }
+        let frequency = frequency.clamp(0.1, MAX_FREQUENCY);
+        let max_count = (MAX_DURATION.as_secs_f64() * frequency) as u32;
+        let color = request.color.as_deref().unwrap_or(&cfg.color);
+        Self {
+            started: Instant::now(),
+            frequency,
+            duty: request.duty.unwrap_or(cfg.duty).clamp(0.1, 0.9),
+            count: request.count.unwrap_or(cfg.count).clamp(1, max_count.max(1)),
+            color: (!color.is_empty()).then(|| resolve_color(color, palette)),
+        }
+    }
+
+    /// Whether the flash is on at `now`, or `None` once the strobe is over
+    pub fn phase(&self, now: Instant) -> Option<bool> {
+        let flashes = now.saturating_duration_since(self.started).as_secs_f64() * self.frequency;
+        (flashes < self.count as f64).then(|| flashes.fract() < self.duty)
+    }
+}
+
+/// Show `color` for `duration` over every profile but the lock screen's, then
+/// restore the ring as it was. A newer flash replaces a running one.
+pub fn flash(state: Arc<IpcState>, color: String, duration: Duration) {
+    let profile = Profile {
+        color: Some(color),
+        animation: Some("none".to_string()),
+        visible: Some(true),
+        ..Profile::default()
+    };
+    let flash = state.start_flash();
+    state.set_override("flash", Some(("flash".to_string(), profile)));
+    std::thread::spawn(move || {
+        std::thread::sleep(duration.min(MAX_DURATION));
+        state.end_flash(flash);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strobe_limits() {
+        let request = StrobeRequest { frequency: Some(20.0), duty: Some(1.0), count: Some(1000), color: None };
+        let strobe = Strobe::new(&request, &StrobeConfig::default(), &BTreeMap::new());
+        assert_eq!(strobe.frequency, MAX_FREQUENCY);
+        assert_eq!(strobe.duty, 0.9);
+        assert_eq!(strobe.count, 90);
+        assert_eq!(strobe.color, None);
+
+        let at = |ms: u64| strobe.started + Duration::from_millis(ms);
+        assert_eq!(strobe.phase(at(0)), Some(true));
+        assert_eq!(strobe.phase(at(320)), Some(false));
+        assert_eq!(strobe.phase(at(30_100)), None);
+    }
+}
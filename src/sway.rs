@@ -0,0 +1,241 @@
+//! Sway's IPC, for the workspace, focus and window rules
+//!
+//! Sway speaks the i3 IPC protocol on `$SWAYSOCK`: each message is the
+//! `i3-ipc` magic, the payload's length and type, then JSON. Events come on
+//! a socket subscribed to them, as messages whose type has the high bit set;
+//! they are turned into `TYPE>>JSON` lines for `parse_event`. Sway has no
+//! per-monitor special workspaces, reserved areas or border color to report.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::compositor::{Backend, Event, Monitor, Window, WorkspaceRef};
+
+const MAGIC: &[u8] = b"i3-ipc";
+
+const GET_WORKSPACES: u32 = 1;
+const SUBSCRIBE: u32 = 2;
+const GET_TREE: u32 = 4;
+
+/// Event types, with the high bit set
+const EVENTS: [(u32, &str); 3] = [(0x8000_0000, "workspace"), (0x8000_0001, "output"), (0x8000_0003, "window")];
+
+fn connect() -> Option<UnixStream> {
+    UnixStream::connect(std::env::var_os("SWAYSOCK")?).ok()
+}
+
+fn send(stream: &mut UnixStream, kind: u32, payload: &str) -> std::io::Result<()> {
+    let mut message = MAGIC.to_vec();
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&kind.to_ne_bytes());
+    message.extend_from_slice(payload.as_bytes());
+    stream.write_all(&message)
+}
+
+/// Read one message: its type and payload
+fn receive(stream: &mut impl Read) -> std::io::Result<(u32, Vec<u8>)> {
+    let mut header = [0; 14];
+    stream.read_exact(&mut header)?;
+    if &header[..6] != MAGIC {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "not an i3-ipc message"));
+    }
+    let len = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]) as usize;
+    let kind = u32::from_ne_bytes([header[10], header[11], header[12], header[13]]);
+    let mut payload = vec![0; len];
+    stream.read_exact(&mut payload)?;
+    Ok((kind, payload))
+}
+
+/// Send a request and parse its reply
+fn request(kind: u32, payload: &str) -> Option<Value> {
+    let mut stream = connect()?;
+    stream.set_read_timeout(Some(Duration::from_secs(2))).ok()?;
+    send(&mut stream, kind, payload).ok()?;
+    let (_, reply) = receive(&mut stream).ok()?;
+    serde_json::from_slice(&reply).ok()
+}
+
+/// Events from a subscribed socket, as `TYPE>>JSON` lines
+struct EventLines<R> {
+    stream: R,
+    line: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> Read for EventLines<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.line.len() {
+            let (kind, payload) = receive(&mut self.stream)?;
+            // Replies (to the subscription) and events we didn't ask for are skipped
+            let Some((_, name)) = EVENTS.iter().find(|(k, _)| *k == kind) else {
+                continue;
+            };
+            // JSON escapes newlines in strings, so the payload is one line
+            self.line = format!("{}>>{}\n", name, String::from_utf8_lossy(&payload).trim()).into_bytes();
+            self.pos = 0;
+        }
+        let n = buf.len().min(self.line.len() - self.pos);
+        buf[..n].copy_from_slice(&self.line[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Class of a container: the app id of Wayland windows, the X11 class of Xwayland ones
+fn window_class(container: &Value) -> String {
+    container.get("app_id").and_then(|v| v.as_str())
+        .or_else(|| container.pointer("/window_properties/class").and_then(|v| v.as_str()))
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn window(container: &Value) -> Window {
+    Window {
+        class: window_class(container),
+        title: container.get("name").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        fullscreen: container.get("fullscreen_mode").and_then(|v| v.as_u64()).unwrap_or(0) != 0,
+    }
+}
+
+/// Parse one `TYPE>>JSON` line
+fn parse_event(line: &str) -> Option<Event> {
+    let (name, data) = line.split_once(">>")?;
+    match name {
+        "workspace" | "output" => Some(Event::Layout),
+        "window" => {
+            let value: Value = serde_json::from_str(data).ok()?;
+            let container = value.get("container")?;
+            let focused = container.get("focused").and_then(|v| v.as_bool()).unwrap_or(false);
+            match value.get("change")?.as_str()? {
+                "focus" => {
+                    let Window { class, title, .. } = window(container);
+                    Some(Event::ActiveWindow { class, title })
+                }
+                // Any window's title can change, only the focused one's matters
+                "title" if focused => {
+                    let Window { class, title, .. } = window(container);
+                    Some(Event::ActiveWindow { class, title })
+                }
+                "fullscreen_mode" if focused => Some(Event::Fullscreen(window(container).fullscreen)),
+                // Focus moves on without a focus event when the last window closes
+                "close" => Some(Event::ActiveWindow { class: String::new(), title: String::new() }),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A monitor for each workspace `get_workspaces` reports visible
+fn parse_workspaces(value: &Value) -> Option<Vec<Monitor>> {
+    let monitors = value.as_array()?.iter()
+        .filter(|ws| ws.get("visible").and_then(|v| v.as_bool()).unwrap_or(false))
+        .filter_map(|ws| {
+            Some(Monitor {
+                name: ws.get("output")?.as_str()?.to_string(),
+                focused: ws.get("focused").and_then(|v| v.as_bool()).unwrap_or(false),
+                workspace: WorkspaceRef {
+                    id: ws.get("num").and_then(|v| v.as_i64()).unwrap_or(-1),
+                    name: ws.get("name")?.as_str()?.to_string(),
+                },
+                special: None,
+                reserved: None,
+            })
+        })
+        .collect();
+    Some(monitors)
+}
+
+/// The focused window in a `get_tree` node, searched depth first
+fn focused_window(node: &Value) -> Option<Window> {
+    let is_window = node.get("pid").is_some_and(|pid| !pid.is_null());
+    if is_window && node.get("focused").and_then(|v| v.as_bool()) == Some(true) {
+        return Some(window(node));
+    }
+    ["nodes", "floating_nodes"].iter()
+        .filter_map(|key| node.get(*key)?.as_array())
+        .flatten()
+        .find_map(focused_window)
+}
+
+/// Sway's IPC socket
+pub struct Sway;
+
+impl Backend for Sway {
+    fn subscribe(&self) -> Option<Box<dyn BufRead + Send>> {
+        let mut stream = connect()?;
+        send(&mut stream, SUBSCRIBE, r#"["workspace","output","window"]"#).ok()?;
+        let events = EventLines { stream, line: Vec::new(), pos: 0 };
+        Some(Box::new(BufReader::new(events)))
+    }
+
+    fn parse_event(&self, line: &str) -> Option<Event> {
+        parse_event(line)
+    }
+
+    fn monitors(&self) -> Option<Vec<Monitor>> {
+        parse_workspaces(&request(GET_WORKSPACES, "")?)
+    }
+
+    fn active_window(&self) -> Option<Window> {
+        focused_window(&request(GET_TREE, "")?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_lines() {
+        let mut messages = Vec::new();
+        for (kind, payload) in [(SUBSCRIBE, r#"{"success":true}"#), (0x8000_0000, r#"{"change":"focus"}"#), (0x8000_0001, "{}")] {
+            messages.extend_from_slice(MAGIC);
+            messages.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+            messages.extend_from_slice(&kind.to_ne_bytes());
+            messages.extend_from_slice(payload.as_bytes());
+        }
+        let events = EventLines { stream: messages.as_slice(), line: Vec::new(), pos: 0 };
+        let lines: Vec<String> = BufReader::new(events).lines().map_while(Result::ok).collect();
+        assert_eq!(lines, ["workspace>>{\"change\":\"focus\"}", "output>>{}"]);
+    }
+
+    #[test]
+    fn test_parse_event() {
+        assert_eq!(parse_event(r#"workspace>>{"change":"focus"}"#), Some(Event::Layout));
+        assert_eq!(
+            parse_event(r#"window>>{"change":"focus","container":{"app_id":null,"name":"Steam","window_properties":{"class":"steam"}}}"#),
+            Some(Event::ActiveWindow { class: "steam".into(), title: "Steam".into() })
+        );
+        assert_eq!(
+            parse_event(r#"window>>{"change":"fullscreen_mode","container":{"app_id":"mpv","focused":true,"fullscreen_mode":1}}"#),
+            Some(Event::Fullscreen(true))
+        );
+        assert_eq!(parse_event(r#"window>>{"change":"title","container":{"app_id":"kitty","focused":false}}"#), None);
+    }
+
+    #[test]
+    fn test_workspaces_and_tree() {
+        let workspaces = serde_json::json!([
+            {"num": 1, "name": "1", "visible": true, "focused": false, "output": "DP-1"},
+            {"num": 2, "name": "2:web", "visible": false, "focused": false, "output": "DP-1"},
+            {"num": -1, "name": "stream", "visible": true, "focused": true, "output": "HDMI-A-1"},
+        ]);
+        let monitors = parse_workspaces(&workspaces).unwrap();
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(monitors[1].name, "HDMI-A-1");
+        assert!(monitors[1].focused);
+        assert_eq!(monitors[1].workspace, WorkspaceRef { id: -1, name: "stream".into() });
+
+        let tree = serde_json::json!({"focused": false, "nodes": [
+            {"focused": true, "nodes": []},
+            {"focused": false, "nodes": [], "floating_nodes": [
+                {"pid": 42, "focused": true, "app_id": "obs", "name": "OBS", "fullscreen_mode": 0},
+            ]},
+        ]});
+        assert_eq!(focused_window(&tree), Some(Window { class: "obs".into(), title: "OBS".into(), fullscreen: false }));
+    }
+}
@@ -0,0 +1,153 @@
+//! System gauge: CPU load, GPU load or temperature on the whole ring
+//!
+//! Reads the kernel's own counters, /proc/stat for the CPU, amdgpu's (and
+//! compatible drivers') `gpu_busy_percent` in /sys/class/drm and the hwmon
+//! sensors for temperatures, once a second while the `system` animation is
+//! selected. The renderer maps the level to a hue or a brightness.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use hypr_ringlight_core::render::animation_from_name;
+
+use crate::config::{SystemConfig, SystemSource};
+use crate::ipc::IpcState;
+
+const PROC_STAT: &str = "/proc/stat";
+const DRM_DIR: &str = "/sys/class/drm";
+const HWMON_DIR: &str = "/sys/class/hwmon";
+const THERMAL_DIR: &str = "/sys/class/thermal";
+
+/// hwmon drivers of CPU temperature sensors, tried in order when no sensor is configured
+const CPU_SENSORS: &[&str] = &["k10temp", "coretemp", "zenpower", "cpu_thermal", "acpitz"];
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Failed readings in a row before the source is reported missing
+const MAX_MISSES: u32 = 3;
+
+/// Idle and total time of the `cpu` line of /proc/stat, in ticks
+fn cpu_times(stat: &str) -> Option<(u64, u64)> {
+    let line = stat.lines().find(|l| l.starts_with("cpu "))?;
+    let ticks: Vec<u64> = line.split_whitespace().skip(1).take(8).filter_map(|v| v.parse().ok()).collect();
+    // user nice system idle iowait irq softirq steal; waiting on I/O counts as idle
+    let idle = ticks.get(3)? + ticks.get(4).unwrap_or(&0);
+    Some((idle, ticks.iter().sum()))
+}
+
+/// Busy fraction of the GPUs, the busiest one if there are several
+fn gpu_load(root: &Path) -> Option<f64> {
+    fs::read_dir(root)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| fs::read_to_string(e.path().join("device").join("gpu_busy_percent")).ok())
+        .filter_map(|v| v.trim().parse::<f64>().ok())
+        .reduce(f64::max)
+        .map(|percent| percent / 100.0)
+}
+
+/// Hottest `temp*_input` of a hwmon device, in °C
+fn hwmon_temperature(dir: &Path) -> Option<f64> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_str().is_some_and(|n| n.starts_with("temp") && n.ends_with("_input")))
+        .filter_map(|e| fs::read_to_string(e.path()).ok()?.trim().parse::<f64>().ok())
+        .reduce(f64::max)
+        .map(|millidegrees| millidegrees / 1000.0)
+}
+
+/// Temperature of the configured sensor, or of the CPU, in °C
+fn temperature(sensor: &str) -> Option<f64> {
+    let devices: Vec<(String, PathBuf)> = fs::read_dir(HWMON_DIR)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| Some((fs::read_to_string(e.path().join("name")).ok()?.trim().to_string(), e.path())))
+        .collect();
+    let wanted: Vec<&str> = if sensor.is_empty() { CPU_SENSORS.to_vec() } else { vec![sensor] };
+    let from_hwmon = wanted
+        .iter()
+        .find_map(|name| devices.iter().filter(|(n, _)| n == name).find_map(|(_, dir)| hwmon_temperature(dir)));
+    if from_hwmon.is_some() || !sensor.is_empty() {
+        return from_hwmon;
+    }
+    // Some ARM boards only have thermal zones
+    fs::read_to_string(Path::new(THERMAL_DIR).join("thermal_zone0").join("temp"))
+        .ok()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .map(|millidegrees| millidegrees / 1000.0)
+}
+
+/// Samples one source; the CPU needs the previous reading for a load
+struct Sampler {
+    cfg: SystemConfig,
+    last_cpu: Option<(u64, u64)>,
+}
+
+impl Sampler {
+    /// Level of the configured source, 0.0 - 1.0
+    fn sample(&mut self) -> Option<f64> {
+        match self.cfg.source_enum() {
+            SystemSource::Cpu => {
+                let (idle, total) = cpu_times(&fs::read_to_string(PROC_STAT).ok()?)?;
+                let (last_idle, last_total) = self.last_cpu.replace((idle, total))?;
+                let elapsed = total.checked_sub(last_total).filter(|&t| t > 0)?;
+                Some(1.0 - idle.saturating_sub(last_idle) as f64 / elapsed as f64)
+            }
+            SystemSource::Gpu => gpu_load(Path::new(DRM_DIR)),
+            SystemSource::Temperature => {
+                let celsius = temperature(&self.cfg.sensor)?;
+                let span = (self.cfg.temp_max - self.cfg.temp_min).max(1.0);
+                Some((celsius - self.cfg.temp_min) / span)
+            }
+        }
+        .map(|level| level.clamp(0.0, 1.0))
+    }
+}
+
+/// Sample the configured source every second while the `system` animation is selected
+pub fn start_system_monitor(state: Arc<IpcState>, cfg: SystemConfig) {
+    let system = animation_from_name("system");
+    std::thread::spawn(move || {
+        let source = cfg.source.clone();
+        let mut sampler = Sampler { cfg, last_cpu: None };
+        // The first CPU sample only sets the baseline, so don't warn right away
+        let mut misses = 0;
+        loop {
+            if state.get_animation_mode() == system {
+                match sampler.sample() {
+                    Some(level) => {
+                        state.set_system_level(level);
+                        misses = 0;
+                    }
+                    None => {
+                        misses += 1;
+                        if misses == MAX_MISSES {
+                            log::warn!("No {} reading for the system animation", source);
+                        }
+                    }
+                }
+            } else {
+                sampler.last_cpu = None;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_times() {
+        let stat = "cpu  100 0 50 800 50 0 0 0 0 0\ncpu0 50 0 25 400 25 0 0 0 0 0\nintr 123\n";
+        assert_eq!(cpu_times(stat), Some((850, 1000)));
+        assert_eq!(cpu_times("intr 123\n"), None);
+    }
+}
@@ -0,0 +1,42 @@
+//! Texture fill: a PNG painted into the ring band instead of the flat color
+//!
+//! The image is decoded once and sampled per pixel by the renderer. The
+//! render loop reloads it whenever the path changes over IPC.
+
+use hypr_ringlight_core::config::{expand_home, TextureMode};
+use hypr_ringlight_core::render::Texture;
+
+use crate::error::Error;
+
+/// Decode the image at `path`
+pub fn load(path: &str, mode: TextureMode) -> Result<Texture, Error> {
+    let path = expand_home(path);
+    let image = image::open(&path)
+        .map_err(|e| Error::Config(format!("Failed to load texture {}: {}", path.display(), e)))?
+        .into_rgba8();
+    let (width, height) = image.dimensions();
+    let pixels = image.pixels().map(|p| p.0).collect();
+    Ok(Texture { width, height, pixels, mode })
+}
+
+/// The texture for the current path, reloaded when the path changes
+#[derive(Default)]
+pub struct TextureCache {
+    path: String,
+    texture: Option<Texture>,
+}
+
+impl TextureCache {
+    pub fn get(&mut self, path: &str, mode: TextureMode) -> Option<&Texture> {
+        if path != self.path {
+            self.path = path.to_string();
+            self.texture = (!path.is_empty())
+                .then(|| load(path, mode).inspect_err(|e| log::warn!("{}", e)).ok())
+                .flatten();
+            if let Some(texture) = &self.texture {
+                log::info!("Loaded texture {} ({}x{})", path, texture.width, texture.height);
+            }
+        }
+        self.texture.as_ref()
+    }
+}
@@ -1,17 +1,36 @@
-//! Omarchy theme integration
+//! Theme integration: Omarchy themes, base16 schemes, pywal and Hyprland's border color
 //!
-//! Reads the current Omarchy theme colors and applies them to the ring light.
-//! Listens for SIGUSR2 to reload theme colors (like other Omarchy apps).
+//! Reads the current theme colors and applies them to the ring light.
+//! Reloads them when the theme files change, or on SIGUSR2 (like other
+//! Omarchy apps) for switchers that write somewhere we don't watch.
 
+use rustix::fs::inotify::{self, CreateFlags, WatchFlags};
 use serde::Deserialize;
+use signal_hook::consts::SIGUSR2;
+use signal_hook::iterator::Signals;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
 use std::fs;
+use std::mem::MaybeUninit;
+use std::os::unix::ffi::OsStrExt;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
-/// Omarchy theme colors (subset of what's in colors.toml)
-#[derive(Debug, Deserialize)]
-pub struct OmarchyColors {
+use crate::color::{color_to_hex, is_hex_color, parse_color};
+use crate::config::{expand_home, ThemeConfig, ThemeSource};
+use crate::hyprconf;
+use crate::ipc::IpcState;
+
+/// Theme colors (subset of what's in Omarchy's colors.toml)
+#[derive(Debug, Default, Deserialize)]
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub struct ThemeColors {
     /// Accent color (used as ring light color)
     pub accent: Option<String>,
+    /// Second accent; Omarchy themes only have the terminal red for it
+    #[serde(alias = "color1")]
+    pub secondary: Option<String>,
     /// Background color
     pub background: Option<String>,
     /// Foreground color
@@ -29,34 +48,229 @@ fn omarchy_colors_path() -> PathBuf {
 }
 
 /// Load Omarchy theme colors
-pub fn load_omarchy_colors() -> Option<OmarchyColors> {
+fn load_omarchy_colors() -> Option<ThemeColors> {
     let path = omarchy_colors_path();
-    
+
     if !path.exists() {
         return None;
     }
-    
+
     let content = fs::read_to_string(&path).ok()?;
     toml::from_str(&content).ok()
 }
 
-/// Get the accent color from Omarchy theme as RGB tuple
-pub fn get_accent_color() -> Option<(u8, u8, u8)> {
-    let colors = load_omarchy_colors()?;
+/// The scheme tinty applied last, from its `current_scheme` file (e.g.
+/// `base16-catppuccin-mocha`) and its checkout of the schemes repository
+fn tinty_scheme_path() -> Option<PathBuf> {
+    let tinty = dirs::data_dir()?.join("tinted-theming").join("tinty");
+    let current = fs::read_to_string(tinty.join("current_scheme")).ok()?;
+    let (system, name) = current.trim().split_once('-')?;
+    Some(tinty.join("repos").join("schemes").join(system).join(format!("{}.yaml", name)))
+}
+
+/// The base16 scheme file to read
+fn base16_path(cfg: &ThemeConfig) -> Option<PathBuf> {
+    if cfg.base16.is_empty() {
+        tinty_scheme_path()
+    } else {
+        Some(expand_home(&cfg.base16))
+    }
+}
+
+/// Colors of a base16 scheme: base0D (functions, usually blue) is the accent,
+/// base08 (variables, usually red) the second accent.
+///
+/// Only `baseXX: "rrggbb"` lines are read, so both the classic flat layout and
+/// tinted-theming's `palette:` section work without a YAML parser.
+pub fn parse_base16(content: &str) -> ThemeColors {
+    let mut bases = BTreeMap::new();
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let key = key.trim().trim_matches('"').to_lowercase();
+        if key.len() != 6 || !key.starts_with("base") {
+            continue;
+        }
+        let value = value.trim();
+        let value = match value.strip_prefix(['"', '\'']) {
+            Some(quoted) => quoted.split(['"', '\'']).next().unwrap_or_default(),
+            None => value.split(" #").next().unwrap_or_default().trim(),
+        };
+        if is_hex_color(value) {
+            bases.insert(key, value.trim_start_matches('#').to_lowercase());
+        }
+    }
+    let mut base = |name: &str| bases.remove(name);
+    ThemeColors {
+        accent: base("base0d"),
+        secondary: base("base08"),
+        background: base("base00"),
+        foreground: base("base05"),
+    }
+}
+
+/// Load a base16 scheme
+fn load_base16_colors(cfg: &ThemeConfig) -> Option<ThemeColors> {
+    let path = base16_path(cfg)?;
+    match fs::read_to_string(&path) {
+        Ok(content) => Some(parse_base16(&content)),
+        Err(e) => {
+            log::warn!("Failed to read base16 scheme {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Colors pywal generated for the current wallpaper
+fn pywal_colors_path() -> PathBuf {
+    dirs::cache_dir().unwrap_or_else(|| PathBuf::from(".")).join("wal").join("colors.json")
+}
+
+/// Load pywal colors: `color4` (the blue slot) is the accent, `color1` (red) the second accent
+fn load_pywal_colors() -> Option<ThemeColors> {
+    let content = fs::read_to_string(pywal_colors_path()).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let color = |group: &str, name: &str| {
+        json[group][name].as_str().filter(|c| is_hex_color(c)).map(|c| c.trim_start_matches('#').to_string())
+    };
+    Some(ThemeColors {
+        accent: color("colors", "color4"),
+        secondary: color("colors", "color1"),
+        background: color("special", "background"),
+        foreground: color("special", "foreground"),
+    })
+}
+
+/// Take the accents from Hyprland's active border gradient
+fn load_hyprland_colors() -> Option<ThemeColors> {
+    let border = hyprconf::active_border(&hyprconf::config_path());
+    let hex = |i: usize| border.get(i).map(|&(r, g, b)| color_to_hex(r, g, b));
+    Some(ThemeColors {
+        accent: Some(hex(0)?),
+        secondary: hex(1),
+        ..ThemeColors::default()
+    })
+}
+
+/// Load the colors of the configured theme source
+pub fn load_colors(cfg: &ThemeConfig) -> Option<ThemeColors> {
+    match cfg.source_enum() {
+        ThemeSource::Omarchy => load_omarchy_colors(),
+        ThemeSource::Base16 => load_base16_colors(cfg),
+        ThemeSource::Pywal => load_pywal_colors(),
+        ThemeSource::Hyprland => load_hyprland_colors(),
+    }
+}
+
+/// Get the accent color from the theme as RGB tuple
+pub fn get_accent_color(cfg: &ThemeConfig) -> Option<(u8, u8, u8)> {
+    let colors = load_colors(cfg)?;
     let accent = colors.accent?;
-    Some(parse_hex_color(&accent))
+    parse_color(&accent)
 }
 
-/// Parse hex color string to RGB tuple
-fn parse_hex_color(hex: &str) -> (u8, u8, u8) {
-    let hex = hex.trim_start_matches('#');
-    if hex.len() < 6 {
-        return (255, 255, 255);
+/// Apply the theme's accent color, if it differs from the ring's
+fn reload(state: &IpcState, cfg: &ThemeConfig) {
+    let Some((r, g, b)) = get_accent_color(cfg) else { return };
+    if state.get_color() == (r, g, b) {
+        return;
     }
-    let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(255);
-    let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(255);
-    let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(255);
-    (r, g, b)
+    state.change_appearance(|a| {
+        a.color = (r, g, b);
+        a.color_name = None;
+    });
+    state.log_event("theme", format!("color {:02x}{:02x}{:02x}", r, g, b));
+    log::info!("Reloaded {} theme color: #{:02x}{:02x}{:02x}", cfg.source_enum().name(), r, g, b);
+}
+
+/// Reload the accent color on SIGUSR2, which Omarchy sends on theme changes
+pub fn start_reload_on_signal(state: Arc<IpcState>, cfg: ThemeConfig) {
+    std::thread::spawn(move || {
+        let mut signals = match Signals::new([SIGUSR2]) {
+            Ok(signals) => signals,
+            Err(e) => {
+                log::error!("Failed to create signal handler, theme reload disabled: {}", e);
+                return;
+            }
+        };
+        for _ in signals.forever() {
+            reload(&state, &cfg);
+        }
+    });
+}
+
+/// Theme switchers write several files in a row; wait for them to finish
+const SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+/// How often to look again when none of the theme's directories exist yet
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Files the configured theme is read from
+fn theme_files(cfg: &ThemeConfig) -> Vec<PathBuf> {
+    match cfg.source_enum() {
+        ThemeSource::Omarchy => vec![omarchy_colors_path()],
+        ThemeSource::Base16 => {
+            let current = dirs::data_dir().map(|d| d.join("tinted-theming").join("tinty").join("current_scheme"));
+            base16_path(cfg).into_iter().chain(current).collect()
+        }
+        ThemeSource::Pywal => vec![pywal_colors_path()],
+        ThemeSource::Hyprland => hyprconf::config_files(&hyprconf::config_path()),
+    }
+}
+
+/// Block until one of `files` changes.
+///
+/// Switchers replace files rather than write them in place, or swap a
+/// symlinked directory (Omarchy's `current/theme`), so the directory holding
+/// each file and the one above it are watched, for the names along the path.
+fn wait_for_change(files: &[PathBuf]) -> Result<(), String> {
+    let fd = inotify::init(CreateFlags::CLOEXEC).map_err(|e| e.to_string())?;
+    let mut names = BTreeSet::new();
+    let mut dirs = BTreeSet::new();
+    for file in files {
+        names.extend(file.file_name());
+        if let Some(dir) = file.parent() {
+            dirs.insert(dir);
+            names.extend(dir.file_name());
+            dirs.extend(dir.parent());
+        }
+    }
+    let flags = WatchFlags::CLOSE_WRITE
+        | WatchFlags::MOVED_TO
+        | WatchFlags::CREATE
+        | WatchFlags::DELETE
+        | WatchFlags::DELETE_SELF
+        | WatchFlags::MOVE_SELF;
+    let watched = dirs.iter().filter(|dir| inotify::add_watch(&fd, **dir, flags).is_ok()).count();
+    if watched == 0 {
+        return Err("none of the theme directories exist".to_string());
+    }
+
+    let mut buf = [MaybeUninit::uninit(); 4096];
+    let mut events = inotify::Reader::new(&fd, &mut buf);
+    loop {
+        let event = events.next().map_err(|e| e.to_string())?;
+        let name = event.file_name().map(|n| OsStr::from_bytes(n.to_bytes()));
+        if name.is_none_or(|n| names.contains(n)) {
+            return Ok(());
+        }
+    }
+}
+
+/// Reload the accent color whenever the theme files change
+pub fn start_theme_watcher(state: Arc<IpcState>, cfg: ThemeConfig) {
+    std::thread::spawn(move || loop {
+        // Watches are set up again after every change, as the files may have moved
+        match wait_for_change(&theme_files(&cfg)) {
+            Ok(()) => {
+                std::thread::sleep(SETTLE_DELAY);
+                reload(&state, &cfg);
+            }
+            Err(e) => {
+                log::debug!("Not watching {} theme files: {}", cfg.source_enum().name(), e);
+                std::thread::sleep(RETRY_INTERVAL);
+            }
+        }
+    });
 }
 
 #[cfg(test)]
@@ -64,9 +278,25 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_parse_hex_color() {
-        assert_eq!(parse_hex_color("#89b4fa"), (137, 180, 250));
-        assert_eq!(parse_hex_color("89b4fa"), (137, 180, 250));
-        assert_eq!(parse_hex_color("#ff0000"), (255, 0, 0));
+    fn test_parse_color() {
+        assert_eq!(parse_color("#89b4fa"), Some((137, 180, 250)));
+        assert_eq!(parse_color("89b4fa"), Some((137, 180, 250)));
+        assert_eq!(parse_color("#ff0000"), Some((255, 0, 0)));
+    }
+
+    #[test]
+    fn test_parse_base16() {
+        let classic = "scheme: \"Catppuccin Mocha\"\nbase00: \"1e1e2e\" # base\nbase05: cdd6f4\nbase08: \"f38ba8\"\nbase0D: \"89b4fa\"\n";
+        let colors = parse_base16(classic);
+        assert_eq!(colors.accent.as_deref(), Some("89b4fa"));
+        assert_eq!(colors.secondary.as_deref(), Some("f38ba8"));
+        assert_eq!(colors.background.as_deref(), Some("1e1e2e"));
+        assert_eq!(colors.foreground.as_deref(), Some("cdd6f4"));
+
+        let tinted = "system: \"base16\"\nname: \"Nord\"\npalette:\n  base08: \"#BF616A\"\n  base0D: '#81A1C1'\n";
+        let colors = parse_base16(tinted);
+        assert_eq!(colors.accent.as_deref(), Some("81a1c1"));
+        assert_eq!(colors.secondary.as_deref(), Some("bf616a"));
+        assert_eq!(colors.background, None);
     }
 }
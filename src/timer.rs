@@ -0,0 +1,103 @@
+//! Focus timer (pomodoro)
+//!
+//! While a timer runs the ring turns into a countdown: the lit part of the
+//! perimeter shrinks clockwise as time elapses. When it runs out the ring
+//! flashes for a few seconds and a notification is sent.
+
+#[cfg(feature = "notifications")]
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use hypr_ringlight_core::ipc::TimerStatus;
+#[cfg(feature = "notifications")]
+use notify_rust::Notification;
+
+#[cfg(feature = "notifications")]
+use crate::config::format_cycle_duration;
+#[cfg(feature = "notifications")]
+use crate::ipc::IpcState;
+
+/// How long the ring flashes once the timer runs out
+const FLASH_DURATION: Duration = Duration::from_secs(5);
+/// Flashes per second
+const FLASH_RATE: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timer {
+    pub started: Instant,
+    pub duration: Duration,
+}
+
+/// What the ring shows for a timer at a given moment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimerPhase {
+    /// Counting down; fraction of the time remaining
+    Running(f64),
+    /// Ran out; whether the flash is currently on
+    Flashing(bool),
+    /// Finished flashing, the ring is back to normal
+    Done,
+}
+
+impl Timer {
+    pub fn new(duration: Duration) -> Self {
+        Self { started: Instant::now(), duration }
+    }
+
+    pub fn phase(&self, now: Instant) -> TimerPhase {
+        let elapsed = now.saturating_duration_since(self.started);
+        if elapsed < self.duration {
+            let remaining = 1.0 - elapsed.as_secs_f64() / self.duration.as_secs_f64();
+            return TimerPhase::Running(remaining);
+        }
+        let over = (elapsed - self.duration).as_secs_f64();
+        if over < FLASH_DURATION.as_secs_f64() {
+            TimerPhase::Flashing((over * FLASH_RATE).fract() < 0.5)
+        } else {
+            TimerPhase::Done
+        }
+    }
+
+    pub fn status(&self, now: Instant) -> TimerStatus {
+        let elapsed = now.saturating_duration_since(self.started);
+        TimerStatus {
+            duration_ms: self.duration.as_millis() as u64,
+            remaining_ms: self.duration.saturating_sub(elapsed).as_millis() as u64,
+        }
+    }
+}
+
+/// Send a notification when `timer` runs out, unless it was stopped or replaced
+#[cfg(feature = "notifications")]
+pub fn notify_when_done(state: Arc<IpcState>, timer: Timer) {
+    std::thread::spawn(move || {
+        std::thread::sleep(timer.duration);
+        if state.get_timer() != Some(timer) {
+            return;
+        }
+        let _ = Notification::new()
+            .summary("Timer finished")
+            .body(&format!("{} focus session is over, time for a break!", format_cycle_duration(timer.duration.as_millis() as u32)))
+            .icon("alarm-symbolic")
+            .hint(notify_rust::Hint::Category("presence".to_string()))
+            .show();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer_phase() {
+        let timer = Timer::new(Duration::from_secs(60));
+        let at = |ms: u64| timer.started + Duration::from_millis(ms);
+
+        assert_eq!(timer.phase(at(0)), TimerPhase::Running(1.0));
+        assert_eq!(timer.phase(at(15_000)), TimerPhase::Running(0.75));
+        assert_eq!(timer.phase(at(60_100)), TimerPhase::Flashing(true));
+        assert_eq!(timer.phase(at(60_300)), TimerPhase::Flashing(false));
+        assert_eq!(timer.phase(at(66_000)), TimerPhase::Done);
+        assert_eq!(timer.status(at(15_000)).remaining_ms, 45_000);
+    }
+}
@@ -0,0 +1,233 @@
+//! Touch gestures on the ring band, for tablet mode
+//!
+//! With `[touch] enabled` the band takes touches like it takes clicks (see
+//! `click`). Dragging a finger along an edge sets the opacity: rightwards on
+//! the top and bottom edges and upwards on the sides brighten the ring, a
+//! full edge's length going from off to full. A double-tap toggles it.
+
+use std::sync::Arc;
+
+use smithay_client_toolkit::seat::touch::TouchHandler;
+use wayland_client::protocol::{wl_surface, wl_touch};
+use wayland_client::{Connection, Proxy, QueueHandle};
+
+use hypr_ringlight_core::ipc::Command;
+
+use crate::ipc::{self, IpcState};
+use crate::RingLight;
+
+/// Movement, in surface pixels, that turns a touch into a drag
+const SLOP: f64 = 12.0;
+
+/// Longest touch that is still a tap, in milliseconds
+const TAP_MS: u32 = 250;
+
+/// Longest pause between the two taps of a double-tap, in milliseconds
+const DOUBLE_TAP_MS: u32 = 400;
+
+/// Dimmest opacity a drag sets, so the ring can't vanish under the finger
+const MIN_OPACITY: f64 = 0.05;
+
+/// A finger on the band
+struct Touch {
+    id: i32,
+    start: (f64, f64),
+    time: u32,
+    size: (f64, f64),
+    /// On the top or bottom edge, rather than a side
+    horizontal: bool,
+    /// Opacity when the finger came down
+    opacity: f64,
+    dragging: bool,
+}
+
+/// What a finger did
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// Dragged along an edge to this opacity
+    Opacity(f64),
+    /// Lifted after a drag
+    DragEnd,
+    DoubleTap,
+}
+
+/// Turns the touch events of one finger at a time into gestures
+#[derive(Default)]
+pub struct Gestures {
+    touch: Option<Touch>,
+    /// Time and position of the last tap, waiting for a second one
+    last_tap: Option<(u32, (f64, f64))>,
+}
+
+impl Gestures {
+    /// A finger came down at `position` on a `size` surface lit at `opacity`
+    pub fn down(&mut self, id: i32, position: (f64, f64), size: (f64, f64), time: u32, opacity: f64) {
+        if self.touch.is_some() {
+            return;
+        }
+        let (x, y) = position;
+        let (w, h) = size;
+        let horizontal = y.min(h - y) <= x.min(w - x);
+        self.touch = Some(Touch { id, start: position, time, size, horizontal, opacity, dragging: false });
+    }
+
+    pub fn motion(&mut self, id: i32, position: (f64, f64)) -> Option<Gesture> {
+        let touch = self.touch.as_mut().filter(|t| t.id == id)?;
+        let (dx, dy) = (position.0 - touch.start.0, position.1 - touch.start.1);
+        let along = if touch.horizontal { dx } else { -dy };
+        touch.dragging |= along.abs() > SLOP;
+        if !touch.dragging {
+            return None;
+        }
+        let length = if touch.horizontal { touch.size.0 } else { touch.size.1 };
+        Some(Gesture::Opacity((touch.opacity + along / length.max(1.0)).clamp(MIN_OPACITY, 1.0)))
+    }
+
+    pub fn up(&mut self, id: i32, time: u32) -> Option<Gesture> {
+        let touch = self.touch.take_if(|t| t.id == id)?;
+        if touch.dragging {
+            return Some(Gesture::DragEnd);
+        }
+        if time.wrapping_sub(touch.time) > TAP_MS {
+            return None;
+        }
+        let (x, y) = touch.start;
+        match self.last_tap.take() {
+            Some((last, (lx, ly))) if time.wrapping_sub(last) <= DOUBLE_TAP_MS && (x - lx).hypot(y - ly) <= 4.0 * SLOP => {
+                Some(Gesture::DoubleTap)
+            }
+            _ => {
+                self.last_tap = Some((time, touch.start));
+                None
+            }
+        }
+    }
+
+    /// The compositor took the touch over, e.g. for a gesture of its own
+    pub fn cancel(&mut self) {
+        self.touch = None;
+        self.last_tap = None;
+    }
+}
+
+fn apply(state: &Arc<IpcState>, gesture: Gesture) {
+    match gesture {
+        // Like scrolling on the tray icon, without an event for every step
+        Gesture::Opacity(opacity) => state.change_appearance(|a| a.opacity = opacity),
+        Gesture::DragEnd => state.log_event("touch", format!("opacity {:.0}%", state.get_opacity() * 100.0)),
+        Gesture::DoubleTap => {
+            ipc::execute(state, Command::SetVisible(!state.is_visible()), "touch", "double-tap");
+        }
+    }
+}
+
+impl TouchHandler for RingLight {
+    fn down(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _touch: &wl_touch::WlTouch,
+        _serial: u32,
+        time: u32,
+        surface: wl_surface::WlSurface,
+        id: i32,
+        position: (f64, f64),
+    ) {
+        let Some(monitor) = self.monitors.get(&surface.id().protocol_id()) else {
+            return;
+        };
+        let size = (monitor.width as f64, monitor.height as f64);
+        let opacity = self.state.ipc.get_opacity();
+        if let Some(gestures) = self.gestures.as_mut() {
+            gestures.down(id, position, size, time, opacity);
+        }
+    }
+
+    fn up(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _touch: &wl_touch::WlTouch,
+        _serial: u32,
+        time: u32,
+        id: i32,
+    ) {
+        if let Some(gesture) = self.gestures.as_mut().and_then(|g| g.up(id, time)) {
+            apply(&self.state.ipc, gesture);
+        }
+    }
+
+    fn motion(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _touch: &wl_touch::WlTouch,
+        _time: u32,
+        id: i32,
+        position: (f64, f64),
+    ) {
+        if let Some(gesture) = self.gestures.as_mut().and_then(|g| g.motion(id, position)) {
+            apply(&self.state.ipc, gesture);
+        }
+    }
+
+    fn shape(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _touch: &wl_touch::WlTouch,
+        _id: i32,
+        _major: f64,
+        _minor: f64,
+    ) {}
+
+    fn orientation(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _touch: &wl_touch::WlTouch,
+        _id: i32,
+        _orientation: f64,
+    ) {}
+
+    fn cancel(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _touch: &wl_touch::WlTouch) {
+        if let Some(gestures) = self.gestures.as_mut() {
+            gestures.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag_along_edge() {
+        let mut gestures = Gestures::default();
+        // On the bottom edge of a 1000x500 surface, at half opacity
+        gestures.down(0, (500.0, 495.0), (1000.0, 500.0), 0, 0.5);
+        assert_eq!(gestures.motion(0, (505.0, 495.0)), None);
+        assert_eq!(gestures.motion(0, (700.0, 480.0)), Some(Gesture::Opacity(0.7)));
+        assert_eq!(gestures.motion(0, (0.0, 495.0)), Some(Gesture::Opacity(MIN_OPACITY)));
+        assert_eq!(gestures.up(0, 900), Some(Gesture::DragEnd));
+
+        // On the left side, upwards brightens
+        gestures.down(1, (5.0, 250.0), (1000.0, 500.0), 1000, 0.5);
+        assert_eq!(gestures.motion(1, (5.0, 150.0)), Some(Gesture::Opacity(0.7)));
+    }
+
+    #[test]
+    fn test_double_tap() {
+        let mut gestures = Gestures::default();
+        gestures.down(0, (10.0, 10.0), (1000.0, 500.0), 0, 1.0);
+        assert_eq!(gestures.up(0, 100), None);
+        gestures.down(0, (14.0, 12.0), (1000.0, 500.0), 250, 1.0);
+        assert_eq!(gestures.up(0, 300), Some(Gesture::DoubleTap));
+        // A third tap starts over
+        gestures.down(0, (14.0, 12.0), (1000.0, 500.0), 400, 1.0);
+        assert_eq!(gestures.up(0, 450), None);
+        // Too slow for a double-tap
+        gestures.down(0, (14.0, 12.0), (1000.0, 500.0), 2000, 1.0);
+        assert_eq!(gestures.up(0, 2050), None);
+    }
+}
@@ -0,0 +1,194 @@
+//! Show/hide transitions
+//!
+//! Each monitor's ring fades in and out instead of snapping whenever it is
+//! shown or hidden, whatever the cause: `SetVisible`, the tray, a monitor
+//! being disabled or a rule hiding it. A new base color, e.g. from a profile
+//! switch, cross-fades from the old one.
+//!
+//! Live tweaks (the TUI stepping thickness from 80 to 160, say) ease toward
+//! their new values too, so they animate instead of jumping.
+
+use std::time::{Duration, Instant};
+
+use crate::config::TransitionConfig;
+
+/// How much of a monitor's ring is shown, following its visibility
+pub struct Transition {
+    /// 0.0 = hidden, 1.0 = fully shown
+    level: f64,
+    updated: Instant,
+    fade_in: Duration,
+    fade_out: Duration,
+    /// Color being faded from, and the one being faded to since `color_since`
+    color_from: (u8, u8, u8),
+    color_to: Option<(u8, u8, u8)>,
+    color_since: Instant,
+}
+
+impl Transition {
+    /// Starts hidden, so the ring fades in on startup
+    pub fn new(cfg: &TransitionConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            level: 0.0,
+            updated: now,
+            fade_in: cfg.fade_in(),
+            fade_out: cfg.fade_out(),
+            color_from: (0, 0, 0),
+            color_to: None,
+            color_since: now,
+        }
+    }
+
+    /// Advance to `now` and return how much of the ring is shown
+    pub fn step(&mut self, visible: bool, now: Instant) -> f64 {
+        let dt = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.updated = now;
+
+        let (target, fade) = if visible { (1.0, self.fade_in) } else { (0.0, self.fade_out) };
+        let step = if fade.is_zero() { 1.0 } else { dt / fade.as_secs_f64() };
+        self.level = if self.level < target {
+            (self.level + step).min(target)
+        } else {
+            (self.level - step).max(target)
+        };
+        self.level
+    }
+
+    /// Pick up from `now` after frames stopped, so the time spent paused
+    /// isn't all counted into the next step, skipping the fade
+    pub fn resume(&mut self, now: Instant) {
+        self.updated = now;
+    }
+
+    /// Whether the ring has fully faded out
+    pub fn is_hidden(&self) -> bool {
+        self.level == 0.0
+    }
+
+    /// The base color to draw at `now`, cross-fading whenever `target` changes
+    pub fn color(&mut self, target: (u8, u8, u8), now: Instant) -> (u8, u8, u8) {
+        match self.color_to {
+            Some(to) if to != target => {
+                self.color_from = self.color_at(now);
+                self.color_to = Some(target);
+                self.color_since = now;
+            }
+            Some(_) => {}
+            None => {
+                self.color_from = target;
+                self.color_to = Some(target);
+            }
+        }
+        self.color_at(now)
+    }
+
+    fn color_at(&self, now: Instant) -> (u8, u8, u8) {
+        let Some(to) = self.color_to else {
+            return self.color_from;
+        };
+        let t = if self.fade_in.is_zero() {
+            1.0
+        } else {
+            (now.saturating_duration_since(self.color_since).as_secs_f64() / self.fade_in.as_secs_f64()).min(1.0)
+        };
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        (mix(self.color_from.0, to.0), mix(self.color_from.1, to.1), mix(self.color_from.2, to.2))
+    }
+}
+
+/// Time constant of live parameter easing; changes settle within about three
+const EASE_TAU: Duration = Duration::from_millis(50);
+
+/// Ring parameters that ease toward their live values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub thickness: f64,
+    pub glow: f64,
+    pub opacity: f64,
+    /// Corner radius multipliers
+    pub radii: [f64; 4],
+    pub color: (u8, u8, u8),
+}
+
+/// Eases `Params` toward their targets, frame by frame
+#[derive(Default)]
+pub struct Smoothed {
+    current: Option<(Params, [f64; 3])>,
+    updated: Option<Instant>,
+}
+
+impl Smoothed {
+    /// Move toward `target` by the time passed since the last step
+    pub fn step(&mut self, target: Params, now: Instant) -> Params {
+        let dt = self.updated.map_or(0.0, |t| now.saturating_duration_since(t).as_secs_f64());
+        self.updated = Some(now);
+        let target_rgb = [target.color.0, target.color.1, target.color.2].map(|c| c as f64);
+        let Some((current, rgb)) = self.current.as_mut() else {
+            self.current = Some((target, target_rgb));
+            return target;
+        };
+
+        let k = 1.0 - (-dt / EASE_TAU.as_secs_f64()).exp();
+        // Snap once close, so the ring settles on exact values
+        let ease = |from: &mut f64, to: f64, eps: f64| {
+            *from += (to - *from) * k;
+            if (to - *from).abs() < eps {
+                *from = to;
+            }
+        };
+        ease(&mut current.thickness, target.thickness, 0.1);
+        ease(&mut current.glow, target.glow, 0.1);
+        ease(&mut current.opacity, target.opacity, 0.001);
+        for (r, to) in current.radii.iter_mut().zip(target.radii) {
+            ease(r, to, 0.001);
+        }
+        for (c, to) in rgb.iter_mut().zip(target_rgb) {
+            ease(c, to, 0.5);
+        }
+        current.color = (rgb[0].round() as u8, rgb[1].round() as u8, rgb[2].round() as u8);
+        *current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transition() {
+        let cfg = TransitionConfig { fade_in: "1s".into(), fade_out: "500ms".into(), scale_in: false };
+        let mut transition = Transition::new(&cfg);
+        let t0 = transition.updated;
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+
+        assert!((transition.step(true, at(250)) - 0.25).abs() < 1e-9);
+        assert_eq!(transition.step(true, at(2000)), 1.0);
+        // Fading out is twice as fast
+        assert!((transition.step(false, at(2250)) - 0.5).abs() < 1e-9);
+        assert_eq!(transition.step(false, at(3000)), 0.0);
+
+        assert_eq!(transition.color((255, 0, 0), at(3000)), (255, 0, 0));
+        assert_eq!(transition.color((0, 0, 255), at(3500)), (255, 0, 0));
+        assert_eq!(transition.color((0, 0, 255), at(4000)), (128, 0, 128));
+        assert_eq!(transition.color((0, 0, 255), at(4500)), (0, 0, 255));
+
+        // Paused for a minute, the ring still fades back in
+        transition.resume(at(60_000));
+        assert!((transition.step(true, at(60_500)) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_smoothed() {
+        let params = |thickness| Params { thickness, glow: 0.0, opacity: 1.0, radii: [1.0; 4], color: (0, 0, 0) };
+        let mut smoothed = Smoothed::default();
+        let t0 = Instant::now();
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+
+        // The first value is taken as is, later ones are eased toward
+        assert_eq!(smoothed.step(params(80.0), at(0)).thickness, 80.0);
+        let eased = smoothed.step(params(160.0), at(50)).thickness;
+        assert!((eased - (160.0 - 80.0 / std::f64::consts::E)).abs() < 1e-9);
+        assert_eq!(smoothed.step(params(160.0), at(500)).thickness, 160.0);
+    }
+}
@@ -0,0 +1,574 @@
+//! System tray icon and menu
+
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ksni::{menu::StandardItem, menu::SubMenu, menu::RadioGroup, menu::RadioItem, menu::CheckmarkItem, Tray, TrayService};
+use hypr_ringlight_core::ipc::{instance, instance_id};
+use hypr_ringlight_core::render::animation_name;
+
+use crate::color::parse_color;
+use crate::config::{self, COLOR_PRESETS};
+use crate::ipc::IpcState;
+#[cfg(feature = "theme")]
+use crate::theme;
+use crate::SharedState;
+
+/// Monitor info for tray menu (id + display name + enabled status)
+#[derive(Clone, Debug)]
+struct MonitorInfo {
+    id: String,           // Connector name (DP-2, HDMI-1, etc.) - used as unique ID
+    display_name: String, // Friendly name (brand/model) - shown in UI
+    enabled: bool,
+}
+
+/// How long a scroll-wheel adjustment stays visible in the tray title
+const SCROLL_FEEDBACK_DURATION: Duration = Duration::from_secs(2);
+
+/// How long the tray's snooze holds camera notifications back
+#[cfg(feature = "camera")]
+const CAMERA_SNOOZE: &str = "1h";
+
+/// State shown by the tray (icon, title, menu), compared to detect changes
+#[derive(PartialEq)]
+struct TraySnapshot {
+    color: (u8, u8, u8),
+    visible: bool,
+    camera_active: bool,
+    camera_snoozed: bool,
+    thickness: u32,
+    opacity: u32,
+    animation: u8,
+    cycle_ms: u32,
+    monitors: Vec<(String, bool)>,
+}
+
+impl TraySnapshot {
+    fn capture(ipc: &IpcState) -> Self {
+        Self {
+            color: ipc.get_color(),
+            visible: ipc.is_visible(),
+            camera_active: ipc.is_camera_active(),
+            camera_snoozed: ipc.camera_snooze_left().is_some(),
+            thickness: ipc.get_thickness(),
+            opacity: ipc.opacity.load(Ordering::Relaxed),
+            animation: ipc.get_animation_mode(),
+            cycle_ms: ipc.get_cycle_ms(),
+            monitors: ipc.get_monitors().into_iter().map(|m| (m.id, m.enabled)).collect(),
+        }
+    }
+}
+
+/// Render the tray icon: a ring in the current color (dimmed when hidden),
+/// with a red dot in the corner while the camera is in use.
+fn tray_icon(size: i32, color: (u8, u8, u8), visible: bool, camera_active: bool) -> ksni::Icon {
+    let s = size as f64;
+    let center = s / 2.0;
+    let outer = s / 2.0 - 1.0;
+    let inner = outer * 0.55;
+    let dot_center = s * 0.78;
+    let dot_radius = s * 0.2;
+    let ring_alpha = if visible { 1.0 } else { 0.35 };
+    
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+            let d = ((px - center).powi(2) + (py - center).powi(2)).sqrt();
+            // One pixel of anti-aliasing on both edges of the ring
+            let coverage = (outer - d + 0.5).clamp(0.0, 1.0) * (d - inner + 0.5).clamp(0.0, 1.0);
+            let (mut a, mut r, mut g, mut b) = (coverage * ring_alpha, color.0, color.1, color.2);
+            
+            if camera_active {
+                let dd = ((px - dot_center).powi(2) + (py - dot_center).powi(2)).sqrt();
+                let dot = (dot_radius - dd + 0.5).clamp(0.0, 1.0);
+                if dot > 0.0 {
+                    let mix = |c: u8, t: u8| (c as f64 * (1.0 - dot) + t as f64 * dot) as u8;
+                    r = mix(r, 0xf3);
+                    g = mix(g, 0x3b);
+                    b = mix(b, 0x3b);
+                    a = a.max(dot);
+                }
+            }
+            
+            data.extend_from_slice(&[(a * 255.0) as u8, r, g, b]);
+        }
+    }
+    ksni::Icon { width: size, height: size, data }
+}
+
+// Tray icon
+struct RingLightTray {
+    state: Arc<SharedState>,
+    /// Value last adjusted with the scroll wheel, shown in the title until it expires
+    scroll_feedback: Arc<Mutex<Option<(String, Instant)>>>,
+}
+
+impl RingLightTray {
+    fn new(state: Arc<SharedState>) -> Self {
+        Self {
+            state,
+            scroll_feedback: Arc::new(Mutex::new(None)),
+        }
+    }
+    
+    /// Current scroll feedback text, if it hasn't expired yet
+    fn feedback(&self) -> Option<String> {
+        let feedback = self.scroll_feedback.lock().ok()?;
+        feedback.as_ref()
+            .filter(|(_, until)| Instant::now() < *until)
+            .map(|(text, _)| text.clone())
+    }
+    
+    fn set_feedback(&self, text: String) {
+        if let Ok(mut feedback) = self.scroll_feedback.lock() {
+            *feedback = Some((text, Instant::now() + SCROLL_FEEDBACK_DURATION));
+        }
+    }
+    
+    fn monitors(&self) -> Vec<MonitorInfo> {
+        self.state.ipc.get_monitors().into_iter().map(|m| MonitorInfo {
+            id: m.id,
+            display_name: m.display_name,
+            enabled: m.enabled,
+        }).collect()
+    }
+    
+    fn toggle_monitor(&self, id: &str) {
+        self.state.ipc.toggle_monitor(id);
+        self.changed(format!("monitor {} toggled", id));
+    }
+
+    /// Log a change made from the menu, and save it
+    fn changed(&self, message: String) {
+        self.state.ipc.log_event("tray", message);
+        self.state.ipc.save_to_config();
+    }
+}
+
+/// "RingLight", with the instance name for a named instance
+fn app_name() -> String {
+    match instance() {
+        Some(name) => format!("RingLight ({})", name),
+        None => "RingLight".into(),
+    }
+}
+
+impl Tray for RingLightTray {
+    fn id(&self) -> String {
+        instance_id()
+    }
+
+    fn icon_pixmap(&self) -> Vec<ksni::Icon> {
+        let color = self.state.ipc.get_color();
+        let visible = self.state.ipc.is_visible();
+        let camera_active = self.state.ipc.is_camera_active();
+        [22, 32, 48].iter()
+            .map(|&size| tray_icon(size, color, visible, camera_active))
+            .collect()
+    }
+
+    fn title(&self) -> String {
+        match self.feedback() {
+            Some(text) => format!("{}: {}", app_name(), text),
+            None => app_name(),
+        }
+    }
+
+    fn tool_tip(&self) -> ksni::ToolTip {
+        let description = self.feedback().unwrap_or_else(|| {
+            format!(
+                "{} · {}px · {:.0}%",
+                if self.state.ipc.is_visible() { "On" } else { "Off" },
+                self.state.ipc.get_thickness(),
+                self.state.ipc.get_opacity() * 100.0,
+            )
+        });
+        ksni::ToolTip {
+            title: app_name(),
+            description,
+            ..Default::default()
+        }
+    }
+
+    /// Scroll vertically to adjust opacity, horizontally to adjust thickness.
+    /// Negative deltas (wheel up / swipe left in most tray hosts) increase the value.
+    fn scroll(&mut self, delta: i32, dir: &str) {
+        if delta == 0 {
+            return;
+        }
+        let step = if delta < 0 { 1 } else { -1 };
+        
+        match dir {
+            "vertical" => {
+                let current = self.state.ipc.get_opacity();
+                let opacity = (current + 0.05 * step as f64).clamp(0.05, 1.0);
+                self.state.ipc.change_appearance(|a| a.opacity = opacity);
+                self.set_feedback(format!("Opacity {:.0}%", opacity * 100.0));
+                self.changed(format!("opacity {:.0}%", opacity * 100.0));
+            }
+            "horizontal" => {
+                let current = self.state.ipc.get_thickness() as i32;
+                let thickness = (current + 10 * step).clamp(10, 200) as u32;
+                self.state.ipc.change_appearance(|a| a.thickness = thickness);
+                self.set_feedback(format!("Width {}px", thickness));
+                self.changed(format!("thickness {}px", thickness));
+            }
+            _ => {}
+        }
+    }
+
+    fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
+        let is_visible = self.state.ipc.is_visible();
+        let current_anim = self.state.ipc.get_animation_mode();
+        let current_cycle_ms = self.state.ipc.get_cycle_ms();
+        let current_thickness = self.state.ipc.get_thickness();
+        let current_opacity = self.state.ipc.get_opacity();
+        let (r, g, b) = self.state.ipc.get_color();
+        let current_color = format!("{:02x}{:02x}{:02x}", r, g, b);
+        let monitors = self.monitors();
+        
+        // Index of the matching color preset (none selected for custom colors)
+        let color_idx = COLOR_PRESETS.iter()
+            .position(|(_, hex)| *hex == current_color)
+            .unwrap_or(COLOR_PRESETS.len());
+        let color_label = COLOR_PRESETS.get(color_idx)
+            .map(|(name, _)| name.to_string())
+            .unwrap_or_else(|| format!("#{}", current_color));
+        
+        // Map cycle duration to preset index
+        let speed_idx = match current_cycle_ms {
+            1000 => 0,
+            2000 => 1,
+            4000 => 2,
+            8000 => 3,
+            _ => 4,
+        };
+        
+        // Map opacity to preset index
+        let opacity_idx = match (current_opacity * 100.0).round() as u32 {
+            25 => 0,
+            50 => 1,
+            75 => 2,
+            100 => 3,
+            _ => 4,
+        };
+        
+        // Map thickness to preset index
+        let thickness_idx = match current_thickness {
+            40 => 0,
+            80 => 1,
+            120 => 2,
+            160 => 3,
+            _ => 4,
+        };
+
+        // Color submenu, with the theme accent when theme support is built in
+        #[cfg_attr(not(feature = "theme"), allow(unused_mut))]
+        let mut color_menu: Vec<ksni::MenuItem<Self>> = vec![
+            RadioGroup {
+                selected: color_idx,
+                select: Box::new(|tray: &mut Self, idx| {
+                    if let Some((_, hex)) = COLOR_PRESETS.get(idx) {
+                        let (r, g, b) = parse_color(hex).unwrap_or((255, 255, 255));
+                        tray.state.ipc.change_appearance(|a| {
+                            a.color = (r, g, b);
+                            a.color_name = None;
+                        });
+                        tray.changed(format!("color {}", hex));
+                    }
+                }),
+                options: COLOR_PRESETS.iter()
+                    .map(|(name, _)| RadioItem { label: name.to_string(), ..Default::default() })
+                    .collect(),
+            }.into(),
+        ];
+        #[cfg(feature = "theme")]
+        color_menu.extend([
+            ksni::MenuItem::Separator,
+            StandardItem {
+                label: "Use theme accent".into(),
+                icon_name: "preferences-desktop-theme-symbolic".into(),
+                enabled: theme::get_accent_color(self.state.ipc.theme_config()).is_some(),
+                activate: Box::new(|tray: &mut Self| {
+                    if let Some((r, g, b)) = theme::get_accent_color(tray.state.ipc.theme_config()) {
+                        tray.state.ipc.change_appearance(|a| {
+                            a.color = (r, g, b);
+                            a.color_name = None;
+                        });
+                        tray.changed(format!("color {:02x}{:02x}{:02x} (theme accent)", r, g, b));
+                    }
+                }),
+                ..Default::default()
+            }.into(),
+        ]);
+
+        let mut menu = vec![
+            // Show/Hide toggle
+            StandardItem {
+                label: if is_visible { "Hide Ring" } else { "Show Ring" }.into(),
+                activate: Box::new(|tray: &mut Self| {
+                    let current = tray.state.ipc.is_visible();
+                    tray.state.ipc.change_appearance(|a| a.visible = !current);
+                    tray.changed(format!("visible {}", !current));
+                }),
+                ..Default::default()
+            }.into(),
+            
+            ksni::MenuItem::Separator,
+            
+            // Color submenu
+            SubMenu {
+                label: format!("Color ({})", color_label),
+                submenu: color_menu,
+                ..Default::default()
+            }.into(),
+            
+            // Width submenu
+            SubMenu {
+                label: format!("Width ({}px)", current_thickness),
+                submenu: vec![
+                    RadioGroup {
+                        selected: thickness_idx,
+                        select: Box::new(|tray: &mut Self, idx| {
+                            let val = match idx {
+                                0 => 40,
+                                1 => 80,
+                                2 => 120,
+                                3 => 160,
+                                _ => return,
+                            };
+                            tray.state.ipc.change_appearance(|a| a.thickness = val);
+                            tray.changed(format!("thickness {}px", val));
+                        }),
+                        options: vec![
+                            RadioItem { label: "Subtle (40px)".into(), ..Default::default() },
+                            RadioItem { label: "Normal (80px)".into(), ..Default::default() },
+                            RadioItem { label: "Strong (120px)".into(), ..Default::default() },
+                            RadioItem { label: "Maximum (160px)".into(), ..Default::default() },
+                        ],
+                    }.into(),
+                    ksni::MenuItem::Separator,
+                    StandardItem {
+                        label: "Increase (+20px)".into(),
+                        icon_name: "list-add-symbolic".into(),
+                        activate: Box::new(|tray: &mut Self| {
+                            let thickness = (tray.state.ipc.get_thickness() + 20).min(200);
+                            tray.state.ipc.change_appearance(|a| a.thickness = thickness);
+                            tray.changed(format!("thickness {}px", thickness));
+                        }),
+                        ..Default::default()
+                    }.into(),
+                    StandardItem {
+                        label: "Decrease (-20px)".into(),
+                        icon_name: "list-remove-symbolic".into(),
+                        activate: Box::new(|tray: &mut Self| {
+                            let thickness = tray.state.ipc.get_thickness().saturating_sub(20).max(10);
+                            tray.state.ipc.change_appearance(|a| a.thickness = thickness);
+                            tray.changed(format!("thickness {}px", thickness));
+                        }),
+                        ..Default::default()
+                    }.into(),
+                ],
+                ..Default::default()
+            }.into(),
+            
+            // Opacity submenu
+            SubMenu {
+                label: format!("Opacity ({:.0}%)", current_opacity * 100.0),
+                submenu: vec![
+                    RadioGroup {
+                        selected: opacity_idx,
+                        select: Box::new(|tray: &mut Self, idx| {
+                            let val = match idx {
+                                0 => 0.25,
+                                1 => 0.5,
+                                2 => 0.75,
+                                3 => 1.0,
+                                _ => return,
+                            };
+                            tray.state.ipc.change_appearance(|a| a.opacity = val);
+                            tray.changed(format!("opacity {:.0}%", val * 100.0));
+                        }),
+                        options: vec![
+                            RadioItem { label: "25%".into(), ..Default::default() },
+                            RadioItem { label: "50%".into(), ..Default::default() },
+                            RadioItem { label: "75%".into(), ..Default::default() },
+                            RadioItem { label: "100%".into(), ..Default::default() },
+                        ],
+                    }.into(),
+                ],
+                ..Default::default()
+            }.into(),
+            
+            // Animation submenu
+            SubMenu {
+                label: format!("Animation ({})", match current_anim {
+                    0 => "None",
+                    1 => "Pulse", 
+                    2 => "Rainbow",
+                    3 => "Breathe",
+                    4 => "Comet",
+                    5 => "Heartbeat",
+                    6 => "Spectrum",
+                    7 => "System",
+                    8 => "Typing",
+                    _ => "Unknown",
+                }),
+                submenu: vec![
+                    RadioGroup {
+                        selected: current_anim as usize,
+                        select: Box::new(|tray: &mut Self, idx| {
+                            tray.state.ipc.change_appearance(|a| a.animation = idx as u8);
+                            tray.changed(format!("animation {}", animation_name(idx as u8)));
+                        }),
+                        options: vec![
+                            RadioItem { label: "None".into(), ..Default::default() },
+                            RadioItem { label: "Pulse".into(), ..Default::default() },
+                            RadioItem { label: "Rainbow".into(), ..Default::default() },
+                            RadioItem { label: "Breathe".into(), ..Default::default() },
+                            RadioItem { label: "Comet".into(), ..Default::default() },
+                            RadioItem { label: "Heartbeat".into(), ..Default::default() },
+                            RadioItem { label: "Spectrum".into(), ..Default::default() },
+                            RadioItem { label: "System".into(), ..Default::default() },
+                            RadioItem { label: "Typing".into(), ..Default::default() },
+                        ],
+                    }.into(),
+                    ksni::MenuItem::Separator,
+                    SubMenu {
+                        label: format!("Cycle ({})", config::format_cycle_duration(current_cycle_ms)),
+                        submenu: vec![
+                            RadioGroup {
+                                selected: speed_idx,
+                                select: Box::new(|tray: &mut Self, idx| {
+                                    let ms = match idx {
+                                        0 => 1000,
+                                        1 => 2000,
+                                        2 => 4000,
+                                        3 => 8000,
+                                        _ => return,
+                                    };
+                                    tray.state.ipc.change_appearance(|a| a.cycle_ms = ms);
+                                    tray.changed(format!("cycle {}", config::format_cycle_duration(ms)));
+                                }),
+                                options: vec![
+                                    RadioItem { label: "Fast (1s)".into(), ..Default::default() },
+                                    RadioItem { label: "Normal (2s)".into(), ..Default::default() },
+                                    RadioItem { label: "Slow (4s)".into(), ..Default::default() },
+                                    RadioItem { label: "Very Slow (8s)".into(), ..Default::default() },
+                                ],
+                            }.into(),
+                        ],
+                        ..Default::default()
+                    }.into(),
+                ],
+                ..Default::default()
+            }.into(),
+        ];
+        
+        // Monitors submenu (only if we have monitors)
+        if !monitors.is_empty() {
+            let enabled_count = monitors.iter().filter(|m| m.enabled).count();
+            let mut monitor_items: Vec<ksni::MenuItem<Self>> = monitors.iter().map(|m| {
+                let id = m.id.clone();
+                let label = if m.enabled {
+                    format!("[ON]  {}", m.display_name)
+                } else {
+                    format!("[OFF] {}", m.display_name)
+                };
+                CheckmarkItem {
+                    label,
+                    checked: m.enabled,
+                    activate: Box::new(move |tray: &mut Self| {
+                        tray.toggle_monitor(&id);
+                    }),
+                    ..Default::default()
+                }.into()
+            }).collect();
+            if monitors.len() > 1 {
+                let follow_focus = self.state.ipc.is_follow_focus();
+                monitor_items.push(ksni::MenuItem::Separator);
+                monitor_items.push(CheckmarkItem {
+                    label: "Only the focused monitor".into(),
+                    checked: follow_focus,
+                    activate: Box::new(move |tray: &mut Self| {
+                        tray.state.ipc.set_follow_focus(!follow_focus);
+                        tray.changed(format!("focus-follow {}", if follow_focus { "off" } else { "on" }));
+                    }),
+                    ..Default::default()
+                }.into());
+            }
+            
+            menu.push(SubMenu {
+                label: format!("Monitors ({}/{})", enabled_count, monitors.len()),
+                submenu: monitor_items,
+                ..Default::default()
+            }.into());
+        }
+        
+        // Snooze camera notifications, e.g. for a day of calls
+        #[cfg(feature = "camera")]
+        {
+            let snoozed = self.state.ipc.camera_snooze_left().is_some();
+            menu.push(CheckmarkItem {
+                label: "Snooze camera notifications (1h)".into(),
+                checked: snoozed,
+                activate: Box::new(move |tray: &mut Self| {
+                    let duration = if snoozed { "0s" } else { CAMERA_SNOOZE };
+                    let cmd = hypr_ringlight_core::ipc::Command::SnoozeCamera(duration.to_string());
+                    crate::ipc::execute(&tray.state.ipc, cmd, "tray", &format!("snooze camera {}", duration));
+                }),
+                ..Default::default()
+            }.into());
+        }
+        
+        menu.push(ksni::MenuItem::Separator);
+        
+        // Quit
+        menu.push(StandardItem {
+            label: "Quit".into(),
+            activate: Box::new(|tray: &mut Self| {
+                tray.state.ipc.request_quit();
+            }),
+            ..Default::default()
+        }.into());
+        
+        menu
+    }
+}
+
+/// Start the tray service.
+///
+/// Call after the monitors are discovered, so the menu lists them.
+pub fn start(state: Arc<SharedState>) {
+    let tray = RingLightTray::new(state.clone());
+    let scroll_feedback = tray.scroll_feedback.clone();
+    let service = TrayService::new(tray);
+    let tray_handle = service.handle();
+    service.spawn();
+    
+    // Refresh the tray whenever the state it shows changes (from IPC, camera, ...)
+    // or scroll feedback expires so the title reverts
+    std::thread::spawn(move || {
+        let mut last = TraySnapshot::capture(&state.ipc);
+        loop {
+            std::thread::sleep(Duration::from_millis(250));
+            let expired = match scroll_feedback.lock() {
+                Ok(mut feedback) => match *feedback {
+                    Some((_, until)) if Instant::now() >= until => {
+                        *feedback = None;
+                        true
+                    }
+                    _ => false,
+                },
+                Err(_) => false,
+            };
+            let current = TraySnapshot::capture(&state.ipc);
+            if expired || current != last {
+                last = current;
+                tray_handle.update(|_| {});
+            }
+        }
+    });
+}
@@ -0,0 +1,133 @@
+//! Debounce and cooldown for automatic triggers
+//!
+//! Raw trigger signals can flap, e.g. browsers open and close /dev/video*
+//! repeatedly while enumerating devices. A `Debouncer` only reports a change
+//! once the raw signal has been stable long enough, and rate-limits
+//! activations with a cooldown, so the ring doesn't strobe on and off.
+
+use std::time::{Duration, Instant};
+
+use hypr_ringlight_core::ipc::TriggerStats;
+
+use crate::config::TriggerConfig;
+
+pub struct Debouncer {
+    name: String,
+    /// Raw signal must stay on this long before the trigger activates
+    debounce: Duration,
+    /// Raw signal must stay off this long before the trigger deactivates
+    hold: Duration,
+    /// Minimum time between two activations
+    cooldown: Duration,
+    raw: bool,
+    active: bool,
+    pending_since: Option<Instant>,
+    last_activation: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(name: impl Into<String>, cfg: &TriggerConfig) -> Self {
+        Self {
+            name: name.into(),
+            debounce: cfg.debounce(),
+            hold: cfg.hold(),
+            cooldown: cfg.cooldown(),
+            raw: false,
+            active: false,
+            pending_since: None,
+            last_activation: None,
+        }
+    }
+
+    /// Feed a raw sample. Returns the new debounced state when it changes.
+    pub fn update(&mut self, raw: bool, now: Instant) -> Option<bool> {
+        self.raw = raw;
+        if raw == self.active {
+            self.pending_since = None;
+            return None;
+        }
+
+        let since = *self.pending_since.get_or_insert(now);
+        let delay = if raw { self.debounce } else { self.hold };
+        if now.duration_since(since) < delay {
+            return None;
+        }
+
+        if raw {
+            if self.cooldown_remaining(now) > Duration::ZERO {
+                return None;
+            }
+            self.last_activation = Some(now);
+        }
+        self.active = raw;
+        self.pending_since = None;
+        Some(raw)
+    }
+
+    #[cfg(feature = "hyprland")]
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    fn cooldown_remaining(&self, now: Instant) -> Duration {
+        self.last_activation
+            .map(|last| self.cooldown.saturating_sub(now.duration_since(last)))
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Snapshot for `GetStats`
+    pub fn stats(&self, now: Instant) -> TriggerStats {
+        TriggerStats {
+            name: self.name.clone(),
+            raw: self.raw,
+            active: self.active,
+            pending_ms: self.pending_since
+                .map(|since| now.duration_since(since).as_millis() as u64)
+                .unwrap_or(0),
+            cooldown_remaining_ms: self.cooldown_remaining(now).as_millis() as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debouncer(debounce: &str, hold: &str, cooldown: &str) -> Debouncer {
+        let cfg = TriggerConfig {
+            debounce: debounce.into(),
+            hold: hold.into(),
+            cooldown: cooldown.into(),
+        };
+        Debouncer::new("test", &cfg)
+    }
+
+    #[test]
+    fn test_debounce_and_hold() {
+        let mut d = debouncer("2s", "5s", "0s");
+        let t0 = Instant::now();
+        let at = |s: u64| t0 + Duration::from_secs(s);
+
+        assert_eq!(d.update(true, at(0)), None);
+        // A flap resets the pending activation
+        assert_eq!(d.update(false, at(1)), None);
+        assert_eq!(d.update(true, at(2)), None);
+        assert_eq!(d.update(true, at(4)), Some(true));
+        assert_eq!(d.update(false, at(5)), None);
+        assert_eq!(d.update(false, at(9)), None);
+        assert_eq!(d.update(false, at(10)), Some(false));
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let mut d = debouncer("0s", "0s", "60s");
+        let t0 = Instant::now();
+        let at = |s: u64| t0 + Duration::from_secs(s);
+
+        assert_eq!(d.update(true, at(0)), Some(true));
+        assert_eq!(d.update(false, at(1)), Some(false));
+        assert_eq!(d.update(true, at(2)), None);
+        assert!(d.stats(at(2)).cooldown_remaining_ms > 0);
+        assert_eq!(d.update(true, at(60)), Some(true));
+    }
+}
@@ -1,4 +1,5 @@
 use std::io::stdout;
+use std::sync::mpsc::{self, Receiver};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -8,8 +9,11 @@ use ratatui::{
     prelude::*,
     widgets::*,
 };
-use crate::config::Config;
-use crate::ipc::{self, Command, MonitorState};
+use hypr_ringlight_core::ipc::{Command, MonitorState, Session, State};
+use hypr_ringlight_core::render;
+use crate::config::{self, BarPosition, Config, COLOR_PRESETS};
+use crate::shape::{distance_to_inner_rounded_border, Shape};
+#[cfg(feature = "theme")]
 use crate::theme;
 
 /// UI color theme - loaded from Omarchy if available, otherwise Catppuccin Mocha defaults
@@ -24,8 +28,9 @@ struct UiTheme {
 
 impl UiTheme {
     fn load() -> Self {
-        // Try to load from Omarchy theme
-        if let Some(colors) = theme::load_omarchy_colors() {
+        // Try to load from the configured theme
+        #[cfg(feature = "theme")]
+        if let Some(colors) = theme::load_colors(&Config::load().theme) {
             let accent = colors.accent.as_ref()
                 .map(|c| hex_to_color(c))
                 .unwrap_or(Color::Rgb(203, 166, 247)); // mauve fallback
@@ -38,48 +43,32 @@ impl UiTheme {
                 .map(|c| hex_to_color(c))
                 .unwrap_or(Color::Rgb(205, 214, 244)); // text fallback
             
-            Self {
+            let secondary = colors.secondary.as_ref()
+                .map(|c| hex_to_color(c))
+                .unwrap_or(accent); // Use accent as secondary too
+
+            return Self {
                 accent,
-                secondary: accent, // Use accent as secondary too
+                secondary,
                 background,
                 text,
                 success: Color::Rgb(166, 227, 161),  // Keep green
                 warning: Color::Rgb(249, 226, 175),  // Keep yellow
-            }
-        } else {
-            // Catppuccin Mocha defaults
-            Self {
-                accent: Color::Rgb(203, 166, 247),   // mauve
-                secondary: Color::Rgb(137, 180, 250), // blue
-                background: Color::Rgb(49, 50, 68),  // surface0
-                text: Color::Rgb(205, 214, 244),     // text
-                success: Color::Rgb(166, 227, 161),  // green
-                warning: Color::Rgb(249, 226, 175),  // yellow
-            }
+            };
+        }
+        
+        // Catppuccin Mocha defaults
+        Self {
+            accent: Color::Rgb(203, 166, 247),   // mauve
+            secondary: Color::Rgb(137, 180, 250), // blue
+            background: Color::Rgb(49, 50, 68),  // surface0
+            text: Color::Rgb(205, 214, 244),     // text
+            success: Color::Rgb(166, 227, 161),  // green
+            warning: Color::Rgb(249, 226, 175),  // yellow
         }
     }
 }
 
-/// Color presets with hex values
-const COLOR_PRESETS: &[(&str, &str)] = &[
-    ("White", "ffffff"),
-    ("Red", "ff0000"),
-    ("Green", "00ff00"),
-    ("Blue", "0000ff"),
-    ("Cyan", "00ffff"),
-    ("Magenta", "ff00ff"),
-    ("Yellow", "ffff00"),
-    ("Orange", "ff6600"),
-    ("Pink", "ff1493"),
-    ("Catppuccin Blue", "89b4fa"),
-    ("Catppuccin Mauve", "cba6f7"),
-    ("Catppuccin Green", "a6e3a1"),
-    ("Catppuccin Red", "f38ba8"),
-    ("Catppuccin Peach", "fab387"),
-    ("Catppuccin Yellow", "f9e2af"),
-    ("Catppuccin Teal", "94e2d5"),
-];
-
 const THICKNESS_PRESETS: &[(&str, u32)] = &[
     ("Subtle", 40),
     ("Normal", 80),
@@ -92,6 +81,11 @@ const ANIMATION_PRESETS: &[(&str, &str)] = &[
     ("Pulse - Pulsing glow", "pulse"),
     ("Rainbow - Cycling colors", "rainbow"),
     ("Breathe - Gentle breathing", "breathe"),
+    ("Comet - Chasing around the edge", "comet"),
+    ("Heartbeat - Double-thump pulse", "heartbeat"),
+    ("Spectrum - Audio spectrum analyzer", "spectrum"),
+    ("System - CPU/GPU load or temperature gauge", "system"),
+    ("Typing - Glows while you type", "typing"),
 ];
 
 #[derive(PartialEq, Clone, Copy)]
@@ -103,7 +97,7 @@ enum Screen {
     Glow,
     CornerRadius,
     Animation,
-    AnimationSpeed,
+    CycleDuration,
     BarHeight,
     BarPosition,
     Monitors,
@@ -118,6 +112,8 @@ struct App {
     input_buffer: String,
     input_mode: bool,
     live_mode: bool, // true if connected to running instance
+    session: Option<Session>, // kept open so held keys don't reconnect each time
+    updates: Option<Receiver<State>>, // states pushed by the running instance
     monitors: Vec<MonitorState>, // cached monitors list
     visible: bool, // ring light visibility
     theme: UiTheme, // UI color theme
@@ -125,18 +121,25 @@ struct App {
 
 impl App {
     fn new() -> Self {
-        let live_mode = ipc::is_running();
-        let (config, visible) = if live_mode {
+        let mut session = Session::connect().ok();
+        let live_mode = session.is_some();
+        let (config, visible) = if let Some(session) = session.as_mut() {
             // Try to get current state from running instance
-            if let Ok(Some(state)) = ipc::send_command(&Command::GetState) {
+            if let Ok(state) = session.get_state() {
                 (Config {
-                    color: state.color,
+                    color: state.color_name.unwrap_or(state.color),
+                    palette: state.palette,
                     thickness: state.thickness,
                     opacity: state.opacity,
                     glow: state.glow,
                     corner_radius: state.corner_radius,
+                    corner_style: state.corner_style,
+                    corner_radii: state.corner_radii,
                     animation: state.animation,
-                    animation_speed: state.animation_speed,
+                    cycle_duration: state.cycle_duration,
+                    pattern: state.pattern,
+                    bar_height: state.bar_height,
+                    bar_position: state.bar_position,
                     ..Config::default()
                 }, state.visible)
             } else {
@@ -147,11 +150,8 @@ impl App {
         };
         
         // Get monitors if live
-        let monitors = if live_mode {
-            ipc::get_monitors().unwrap_or_default()
-        } else {
-            Vec::new()
-        };
+        let monitors = session.as_mut().and_then(|s| s.get_monitors().ok()).unwrap_or_default();
+        let updates = if live_mode { subscribe() } else { None };
         
         Self {
             config,
@@ -166,6 +166,8 @@ impl App {
             input_buffer: String::new(),
             input_mode: false,
             live_mode,
+            session,
+            updates,
             monitors,
             visible,
             theme: UiTheme::load(),
@@ -173,8 +175,26 @@ impl App {
     }
 
     fn refresh_monitors(&mut self) {
-        if self.live_mode {
-            self.monitors = ipc::get_monitors().unwrap_or_default();
+        if let Some(session) = self.session.as_mut() {
+            self.monitors = session.get_monitors().unwrap_or_default();
+        }
+    }
+
+    /// Follow changes made elsewhere (tray, hotkeys) to what the TUI shows
+    /// without an edit of its own; the rest would fight with keys in flight
+    fn apply_updates(&mut self) {
+        let Some(updates) = &self.updates else {
+            return;
+        };
+        if let Some(state) = updates.try_iter().last() {
+            self.visible = state.visible;
+        }
+    }
+
+    /// Send a command to the running instance (if live mode)
+    fn send(&mut self, cmd: &Command) {
+        if let Some(session) = self.session.as_mut() {
+            let _ = session.send(cmd);
         }
     }
 
@@ -193,7 +213,7 @@ impl App {
             "Glow".to_string(),
             "Corner Radius".to_string(),
             "Animation".to_string(),
-            "Animation Speed".to_string(),
+            "Cycle Duration".to_string(),
             "Bar Height".to_string(),
             "Bar Position".to_string(),
             "Monitors".to_string(),
@@ -209,24 +229,43 @@ impl App {
             return;
         }
         
-        // Send all current values
-        let _ = ipc::send_command(&Command::SetColor(self.config.color.clone()));
-        let _ = ipc::send_command(&Command::SetThickness(self.config.thickness));
-        let _ = ipc::send_command(&Command::SetOpacity(self.config.opacity));
-        let _ = ipc::send_command(&Command::SetGlow(self.config.glow));
-        let _ = ipc::send_command(&Command::SetCornerRadius(self.config.corner_radius));
-        let _ = ipc::send_command(&Command::SetAnimation(self.config.animation.clone()));
-        let _ = ipc::send_command(&Command::SetAnimationSpeed(self.config.animation_speed));
+        // Send all current values at once, so the ring never shows half of them
+        let corners = match self.config.corner_radii {
+            Some(radii) => Command::SetCornerRadii(radii),
+            None => Command::SetCornerRadius(self.config.corner_radius),
+        };
+        self.send(&Command::Batch(vec![
+            Command::SetColor(self.config.color.clone()),
+            Command::SetThickness(self.config.thickness),
+            Command::SetOpacity(self.config.opacity),
+            Command::SetGlow(self.config.glow),
+            corners,
+            Command::SetAnimation(self.config.animation.clone()),
+            Command::SetCycleDuration(self.config.cycle_duration.clone()),
+        ]));
+    }
+
+    /// Send the bar margin if it differs from `before`, so other changes never touch it
+    fn send_bar_update(&mut self, before: (u32, String)) {
+        if !self.live_mode || before == (self.config.bar_height, self.config.bar_position.clone()) {
+            return;
+        }
+        self.send(&Command::Batch(vec![
+            Command::SetBarHeight(self.config.bar_height),
+            Command::SetBarPosition(self.config.bar_position.clone()),
+        ]));
     }
 
     fn handle_input(&mut self, key: KeyCode) {
         if self.input_mode {
             match key {
                 KeyCode::Enter => {
+                    let bar = (self.config.bar_height, self.config.bar_position.clone());
                     self.apply_input();
                     self.input_mode = false;
                     self.input_buffer.clear();
                     self.send_live_update();
+                    self.send_bar_update(bar);
                 }
                 KeyCode::Esc => {
                     self.input_mode = false;
@@ -252,16 +291,14 @@ impl App {
                     self.selected = 0;
                 }
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                if self.selected > 0 {
-                    self.selected -= 1;
-                    // Skip separators (at index 1 and 12)
-                    if self.screen == Screen::Main && (self.selected == 1 || self.selected == 12) {
-                        if self.selected == 1 {
-                            self.selected = 0;
-                        } else {
-                            self.selected = 11;
-                        }
+            KeyCode::Up | KeyCode::Char('k') if self.selected > 0 => {
+                self.selected -= 1;
+                // Skip separators (at index 1 and 12)
+                if self.screen == Screen::Main && (self.selected == 1 || self.selected == 12) {
+                    if self.selected == 1 {
+                        self.selected = 0;
+                    } else {
+                        self.selected = 11;
                     }
                 }
             }
@@ -289,7 +326,7 @@ impl App {
             Screen::Thickness => THICKNESS_PRESETS.len() + 1,
             Screen::Animation => ANIMATION_PRESETS.len(),
             Screen::Opacity | Screen::Glow | Screen::CornerRadius | 
-            Screen::AnimationSpeed | Screen::BarHeight => 5,
+            Screen::CycleDuration | Screen::BarHeight => 5,
             Screen::BarPosition => 4,
             Screen::Monitors => self.monitors.len().max(1), // at least 1 for "no monitors" message
         }
@@ -301,9 +338,7 @@ impl App {
                 match self.selected {
                     0 => { // Toggle visibility
                         self.visible = !self.visible;
-                        if self.live_mode {
-                            let _ = ipc::send_command(&Command::SetVisible(self.visible));
-                        }
+                        self.send(&Command::SetVisible(self.visible));
                         self.message = Some(format!("Ring Light {}", if self.visible { "ON" } else { "OFF" }));
                     }
                     2 => { self.screen = Screen::Color; self.selected = 0; }
@@ -312,7 +347,7 @@ impl App {
                     5 => { self.screen = Screen::Glow; self.selected = 0; }
                     6 => { self.screen = Screen::CornerRadius; self.selected = 0; }
                     7 => { self.screen = Screen::Animation; self.selected = 0; }
-                    8 => { self.screen = Screen::AnimationSpeed; self.selected = 0; }
+                    8 => { self.screen = Screen::CycleDuration; self.selected = 0; }
                     9 => { self.screen = Screen::BarHeight; self.selected = 0; }
                     10 => { self.screen = Screen::BarPosition; self.selected = 0; }
                     11 => { // Monitors
@@ -386,12 +421,16 @@ impl App {
                 let values = [1.0, 2.5, 4.0, 6.0];
                 if self.selected < 4 {
                     self.config.corner_radius = values[self.selected];
+                    self.config.corner_radii = None;
                     self.send_live_update();
                     self.screen = Screen::Main;
                     self.selected = 0;
                 } else {
                     self.input_mode = true;
-                    self.input_buffer = self.config.corner_radius.to_string();
+                    self.input_buffer = match self.config.corner_radii {
+                        Some(radii) => radii.map(|r| r.to_string()).join(","),
+                        None => self.config.corner_radius.to_string(),
+                    };
                 }
             }
             Screen::Animation => {
@@ -400,25 +439,26 @@ impl App {
                 self.screen = Screen::Main;
                 self.selected = 0;
             }
-            Screen::AnimationSpeed => {
-                let values = [60, 120, 240, 480];
+            Screen::CycleDuration => {
+                let values = ["1s", "2s", "4s", "8s"];
                 if self.selected < 4 {
-                    self.config.animation_speed = values[self.selected];
+                    self.config.cycle_duration = values[self.selected].to_string();
                     self.send_live_update();
                     self.screen = Screen::Main;
                     self.selected = 0;
                 } else {
                     self.input_mode = true;
-                    self.input_buffer = self.config.animation_speed.to_string();
+                    self.input_buffer = self.config.cycle_duration.clone();
                 }
             }
             Screen::BarHeight => {
                 let values = [0, 25, 35, 45];
                 if self.selected < 4 {
+                    let bar = (self.config.bar_height, self.config.bar_position.clone());
                     self.config.bar_height = values[self.selected];
+                    self.send_bar_update(bar);
                     self.screen = Screen::Main;
                     self.selected = 0;
-                    self.message = Some("Bar height requires restart to apply".to_string());
                 } else {
                     self.input_mode = true;
                     self.input_buffer = self.config.bar_height.to_string();
@@ -426,10 +466,11 @@ impl App {
             }
             Screen::BarPosition => {
                 let positions = ["top", "bottom", "left", "right"];
+                let bar = (self.config.bar_height, self.config.bar_position.clone());
                 self.config.bar_position = positions[self.selected].to_string();
+                self.send_bar_update(bar);
                 self.screen = Screen::Main;
                 self.selected = 0;
-                self.message = Some("Bar position requires restart to apply".to_string());
             }
             Screen::Monitors => {
                 if !self.monitors.is_empty() && self.selected < self.monitors.len() {
@@ -438,7 +479,11 @@ impl App {
                     let id = monitor.id.clone();
                     
                     // Send command to toggle
-                    if let Err(e) = ipc::set_monitor_enabled(&id, new_enabled) {
+                    let result = match self.session.as_mut() {
+                        Some(session) => session.set_monitor_enabled(&id, new_enabled),
+                        None => Ok(()),
+                    };
+                    if let Err(e) = result {
                         self.message = Some(format!("Error: {}", e));
                     } else {
                         // Refresh local state
@@ -457,7 +502,12 @@ impl App {
     fn apply_input(&mut self) {
         match self.screen {
             Screen::Color => {
-                self.config.color = self.input_buffer.trim_start_matches('#').to_string();
+                let value = self.input_buffer.trim();
+                if crate::color::is_color(value) || self.config.palette.contains_key(value) {
+                    self.config.color = value.trim_start_matches('#').to_string();
+                } else {
+                    self.message = Some(format!("Invalid color '{}' (try ff8800, orange or rgb(255,136,0))", value));
+                }
             }
             Screen::Thickness => {
                 if let Ok(v) = self.input_buffer.parse() {
@@ -475,13 +525,22 @@ impl App {
                 }
             }
             Screen::CornerRadius => {
-                if let Ok(v) = self.input_buffer.parse() {
-                    self.config.corner_radius = v;
+                // One value for all corners, or four (top-left, top-right, bottom-right, bottom-left)
+                let values: Vec<f64> = self.input_buffer.split(',').filter_map(|v| v.trim().parse().ok()).collect();
+                match values[..] {
+                    [v] => {
+                        self.config.corner_radius = v;
+                        self.config.corner_radii = None;
+                    }
+                    [tl, tr, br, bl] => self.config.corner_radii = Some([tl, tr, br, bl]),
+                    _ => self.message = Some(format!("Invalid radius '{}' (try 2.5 or 3,3,0,0)", self.input_buffer)),
                 }
             }
-            Screen::AnimationSpeed => {
-                if let Ok(v) = self.input_buffer.parse() {
-                    self.config.animation_speed = v;
+            Screen::CycleDuration => {
+                if config::parse_cycle_duration(&self.input_buffer).is_some() {
+                    self.config.cycle_duration = self.input_buffer.trim().to_string();
+                } else {
+                    self.message = Some(format!("Invalid duration '{}' (try 2s or 500ms)", self.input_buffer));
                 }
             }
             Screen::BarHeight => {
@@ -496,15 +555,21 @@ impl App {
     }
 }
 
+/// "#ff8800" for hex colors, palette names as they are
+fn color_label(value: &str) -> String {
+    if crate::color::is_hex_color(value) { format!("#{}", value) } else { value.to_string() }
+}
+
+/// The ring color of `config`, with palette names looked up
+fn palette_color(config: &Config) -> Color {
+    let (r, g, b) = crate::color::resolve_color(&config.color, &config.palette);
+    Color::Rgb(r, g, b)
+}
+
 fn hex_to_color(hex: &str) -> Color {
-    let hex = hex.trim_start_matches('#');
-    if hex.len() >= 6 {
-        let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(255);
-        let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(255);
-        let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(255);
-        Color::Rgb(r, g, b)
-    } else {
-        Color::White
+    match crate::color::parse_color(hex) {
+        Some((r, g, b)) => Color::Rgb(r, g, b),
+        None => Color::White,
     }
 }
 
@@ -556,8 +621,8 @@ fn draw(frame: &mut Frame, app: &App) {
     let settings_text = vec![
         Line::from(vec![
             Span::styled("Color:          ", Style::default().fg(text)),
-            Span::styled(format!("#{} ", app.config.color), Style::default().fg(text)),
-            Span::styled(color_preview, Style::default().fg(hex_to_color(&app.config.color))),
+            Span::styled(format!("{} ", color_label(&app.config.color)), Style::default().fg(text)),
+            Span::styled(color_preview, Style::default().fg(palette_color(&app.config))),
         ]),
         Line::from(vec![
             Span::styled("Thickness:      ", Style::default().fg(text)),
@@ -573,15 +638,18 @@ fn draw(frame: &mut Frame, app: &App) {
         ]),
         Line::from(vec![
             Span::styled("Corner Radius:  ", Style::default().fg(text)),
-            Span::styled(format!("{}x", app.config.corner_radius), Style::default().fg(success)),
+            Span::styled(match app.config.corner_radii {
+                Some(radii) => radii.map(|r| format!("{}x", r)).join(" "),
+                None => format!("{}x", app.config.corner_radius),
+            }, Style::default().fg(success)),
         ]),
         Line::from(vec![
             Span::styled("Animation:      ", Style::default().fg(text)),
             Span::styled(&app.config.animation, Style::default().fg(success)),
         ]),
         Line::from(vec![
-            Span::styled("Anim Speed:     ", Style::default().fg(text)),
-            Span::styled(format!("{}", app.config.animation_speed), Style::default().fg(success)),
+            Span::styled("Cycle:          ", Style::default().fg(text)),
+            Span::styled(&app.config.cycle_duration, Style::default().fg(success)),
         ]),
         Line::from(vec![
             Span::styled("Bar:            ", Style::default().fg(text)),
@@ -589,13 +657,20 @@ fn draw(frame: &mut Frame, app: &App) {
         ]),
     ];
     
+    let settings_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+    
     let settings = Paragraph::new(settings_text)
         .block(Block::default()
             .title(" Current Settings ")
             .title_style(Style::default().fg(accent).bold())
             .borders(Borders::ALL)
             .border_style(Style::default().fg(secondary)));
-    frame.render_widget(settings, chunks[1]);
+    frame.render_widget(settings, settings_chunks[0]);
+    
+    draw_ring_preview(frame, settings_chunks[1], app);
     
     // Menu area
     let menu_title = match app.screen {
@@ -606,7 +681,7 @@ fn draw(frame: &mut Frame, app: &App) {
         Screen::Glow => " Select Glow ",
         Screen::CornerRadius => " Select Corner Radius ",
         Screen::Animation => " Select Animation ",
-        Screen::AnimationSpeed => " Select Animation Speed ",
+        Screen::CycleDuration => " Select Cycle Duration ",
         Screen::BarHeight => " Select Bar Height ",
         Screen::BarPosition => " Select Bar Position ",
         Screen::Monitors => " Monitors (Enter to toggle) ",
@@ -730,8 +805,8 @@ fn draw(frame: &mut Frame, app: &App) {
                 ListItem::new(format!(" {}", name)).style(style)
             }).collect()
         }
-        Screen::AnimationSpeed => {
-            ["Fast (60)", "Normal (120)", "Slow (240)", "Very Slow (480)", "✎  Custom..."]
+        Screen::CycleDuration => {
+            ["Fast (1s)", "Normal (2s)", "Slow (4s)", "Very Slow (8s)", "✎  Custom..."]
                 .iter().enumerate().map(|(i, item)| {
                 let style = if i == app.selected {
                     Style::default().fg(background).bg(accent).bold()
@@ -814,6 +889,125 @@ fn draw(frame: &mut Frame, app: &App) {
     frame.render_widget(help, chunks[3]);
 }
 
+/// Draw a rough approximation of the ring using half-block characters.
+///
+/// Every cell holds two square "pixels" (upper half as fg, lower half as bg)
+/// mapped onto a virtual 1080px-tall screen, and each pixel goes through the
+/// same SDF and glow falloff as the real renderer, blended over the UI
+/// background. Works offline, so no running instance is needed.
+fn draw_ring_preview(frame: &mut Frame, area: Rect, app: &App) {
+    let block = Block::default()
+        .title(" Preview ")
+        .title_style(Style::default().fg(app.theme.accent).bold())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.secondary));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    
+    if inner.width == 0 || inner.height == 0 {
+        return;
+    }
+    
+    let cols = inner.width as f64;
+    let rows = inner.height as f64 * 2.0;
+    let scale = 1080.0 / rows;
+    let screen_w = cols * scale;
+    let screen_h = rows * scale;
+    
+    // The layer surface sits next to the bar, so the ring starts after it
+    let bar = app.config.bar_height as f64;
+    let (off_x, off_y, w, h) = match app.config.bar_position_enum() {
+        BarPosition::Top => (0.0, bar, screen_w, screen_h - bar),
+        BarPosition::Bottom => (0.0, 0.0, screen_w, screen_h - bar),
+        BarPosition::Left => (bar, 0.0, screen_w - bar, screen_h),
+        BarPosition::Right => (0.0, 0.0, screen_w - bar, screen_h),
+    };
+    
+    let inset = app.config.inset as f64;
+    // A vignette is a band of no thickness, all glow
+    let (thickness, glow) = match app.config.vignette.enabled {
+        true => (0.0, app.config.vignette.distance as f64),
+        false => (app.config.thickness as f64, app.config.glow as f64),
+    };
+    let glow_span = glow + (app.config.inner_glow as f64).min(thickness);
+    let falloff = app.config.glow_falloff_enum();
+    let shape = Shape {
+        radii: app.config.corner_multipliers().map(|m| thickness * m),
+        style: app.config.corner_style_enum(),
+        notch: None,
+        taper: app.config.taper_enum(),
+    };
+    let opacity = app.config.opacity.clamp(0.0, 1.0);
+    let webcam = &app.config.webcam;
+    let catchlight = webcam.position_on(&webcam.monitor).map(|origin| render::Catchlight {
+        origin,
+        floor: webcam.floor.clamp(0.0, 1.0),
+    });
+    let base_color = crate::color::resolve_color(&app.config.color, &app.config.palette);
+    let rainbow = app.config.animation_mode() == 2;
+    let bg = match app.theme.background {
+        Color::Rgb(r, g, b) => (r, g, b),
+        _ => (0, 0, 0),
+    };
+    // The fill washes over the screen under the ring
+    let bg = match app.config.fill.enabled {
+        true => {
+            let (r, g, b) = crate::color::resolve_color(&app.config.fill.color, &app.config.palette);
+            let fill = app.config.fill.opacity.clamp(0.0, 1.0);
+            let blend = |c: u8, b: u8| (c as f64 * fill + b as f64 * (1.0 - fill)) as u8;
+            (blend(r, bg.0), blend(g, bg.1), blend(b, bg.2))
+        }
+        false => bg,
+    };
+    
+    let pixel = |px: f64, py: f64| -> Color {
+        let x = (px + 0.5) * scale - off_x;
+        let y = (py + 0.5) * scale - off_y;
+        if x < 0.0 || y < 0.0 || x >= w || y >= h {
+            return Color::DarkGray;
+        }
+        
+        let dist = distance_to_inner_rounded_border(x, y, w, h, inset + thickness + glow, &shape);
+        let alpha = render::ring_alpha(dist, glow_span, falloff, opacity)
+            * render::outer_fade(dist, thickness, glow, falloff, inset)
+            * catchlight.map_or(1.0, |c| c.brightness(x, y, w, h));
+        let (r, g, b) = if rainbow {
+            // Suggest the hue cycle by spreading it around the perimeter
+            let angle = (y - h / 2.0).atan2(x - w / 2.0);
+            crate::color::hsl_to_rgb(angle / std::f64::consts::TAU + 0.5, 1.0, 0.5)
+        } else {
+            base_color
+        };
+        let blend = |c: u8, b: u8| (c as f64 * alpha + b as f64 * (1.0 - alpha)) as u8;
+        Color::Rgb(blend(r, bg.0), blend(g, bg.1), blend(b, bg.2))
+    };
+    
+    let buf = frame.buffer_mut();
+    for row in 0..inner.height {
+        for col in 0..inner.width {
+            let top = pixel(col as f64, row as f64 * 2.0);
+            let bottom = pixel(col as f64, row as f64 * 2.0 + 1.0);
+            if let Some(cell) = buf.cell_mut((inner.x + col, inner.y + row)) {
+                cell.set_char('▀').set_fg(top).set_bg(bottom);
+            }
+        }
+    }
+}
+
+/// States pushed by the running instance, read on a thread of their own
+fn subscribe() -> Option<Receiver<State>> {
+    let states = Session::subscribe().ok()?;
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for state in states {
+            if tx.send(state).is_err() {
+                break;
+            }
+        }
+    });
+    Some(rx)
+}
+
 pub fn run() -> Result<(), String> {
     // Setup terminal
     enable_raw_mode().map_err(|e| e.to_string())?;
@@ -826,6 +1020,7 @@ pub fn run() -> Result<(), String> {
     
     // Main loop
     loop {
+        app.apply_updates();
         terminal.draw(|f| draw(f, &app)).map_err(|e| e.to_string())?;
         
         if event::poll(std::time::Duration::from_millis(100)).map_err(|e| e.to_string())? {
@@ -0,0 +1,110 @@
+//! Typing glow: the ring brightens while you type
+//!
+//! Uses the same ext-idle-notify-v1 protocol as the idle fade, with a short
+//! timeout: the compositor says when input pauses and when it resumes. On
+//! version 2 the input-only notification is used, so a playing video's idle
+//! inhibitor doesn't hold the glow on. Idle hints don't say which device the
+//! input came from, so moving the pointer counts as typing too.
+
+use std::time::{Duration, Instant};
+
+use wayland_client::{globals::GlobalList, protocol::wl_seat, Connection, Dispatch, Proxy, QueueHandle};
+use wayland_protocols::ext::idle_notify::v1::client::{ext_idle_notification_v1, ext_idle_notifier_v1};
+
+use crate::config::TypingConfig;
+use crate::RingLight;
+
+/// How long the glow takes to come up to full brightness
+const RISE: Duration = Duration::from_millis(100);
+
+/// User data of the typing notification, telling it apart from the idle one
+pub struct Activity;
+
+/// Ask the compositor to report pauses in input on `seat` longer than `pause`.
+///
+/// Returns `None` if the compositor lacks ext-idle-notify-v1. The
+/// notification lives as long as the returned object.
+pub fn watch(
+    globals: &GlobalList,
+    qh: &QueueHandle<RingLight>,
+    seat: &wl_seat::WlSeat,
+    pause: Duration,
+) -> Option<ext_idle_notification_v1::ExtIdleNotificationV1> {
+    let notifier: ext_idle_notifier_v1::ExtIdleNotifierV1 = globals.bind(qh, 1..=2, ()).ok()?;
+    let ms = pause.as_millis().min(u32::MAX as u128) as u32;
+    Some(if notifier.version() >= 2 {
+        notifier.get_input_idle_notification(ms, seat, qh, Activity)
+    } else {
+        notifier.get_idle_notification(ms, seat, qh, Activity)
+    })
+}
+
+impl Dispatch<ext_idle_notification_v1::ExtIdleNotificationV1, Activity> for RingLight {
+    fn event(
+        state: &mut Self,
+        _proxy: &ext_idle_notification_v1::ExtIdleNotificationV1,
+        event: ext_idle_notification_v1::Event,
+        _data: &Activity,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            ext_idle_notification_v1::Event::Idled => state.state.ipc.set_typing(false),
+            ext_idle_notification_v1::Event::Resumed => state.state.ipc.set_typing(true),
+            _ => {}
+        }
+    }
+}
+
+/// Brightness factor that jumps up on input and settles back when it stops
+pub struct TypingGlow {
+    level: f64,
+    updated: Instant,
+    /// Brightness while not typing
+    rest: f64,
+    fade: Duration,
+}
+
+impl TypingGlow {
+    pub fn new(cfg: &TypingConfig) -> Self {
+        let rest = cfg.rest.clamp(0.0, 1.0);
+        Self { level: rest, updated: Instant::now(), rest, fade: cfg.fade() }
+    }
+
+    /// Advance the glow to `now` and return the brightness factor
+    pub fn step(&mut self, typing: bool, now: Instant) -> f64 {
+        let dt = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.updated = now;
+
+        self.level = if typing {
+            (self.level + dt / RISE.as_secs_f64()).min(1.0)
+        } else if self.fade.is_zero() {
+            self.rest
+        } else {
+            // Going from full brightness to rest takes `fade`
+            (self.level - dt * (1.0 - self.rest) / self.fade.as_secs_f64()).max(self.rest)
+        };
+        self.level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typing_glow() {
+        let cfg = TypingConfig { pause: "500ms".into(), rest: 0.2, fade: "2s".into() };
+        let mut glow = TypingGlow::new(&cfg);
+        let t0 = glow.updated;
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+
+        assert_eq!(glow.step(false, at(100)), 0.2);
+        // Quick to light up
+        assert!((glow.step(true, at(150)) - 0.7).abs() < 1e-9);
+        assert_eq!(glow.step(true, at(300)), 1.0);
+        // Slow to settle
+        assert!((glow.step(false, at(1300)) - 0.6).abs() < 1e-9);
+        assert_eq!(glow.step(false, at(5000)), 0.2);
+    }
+}
@@ -0,0 +1,74 @@
+//! Reduced-resolution rendering
+//!
+//! The glow is soft anyway, so with `render_scale` below 1.0 the ring is
+//! drawn into a smaller buffer and wp_viewporter has the compositor scale it
+//! up to the output. Half resolution is a quarter of the pixels to draw and
+//! the memory to hold them, which adds up at 4K.
+
+use wayland_client::{globals::GlobalList, Connection, Dispatch, QueueHandle};
+use wayland_protocols::wp::viewporter::client::{wp_viewport, wp_viewporter};
+
+use crate::RingLight;
+
+/// Smallest supported render scale
+pub const MIN_SCALE: f64 = 0.25;
+
+/// The configured `render_scale`, within what's supported
+pub fn render_scale(configured: f64) -> f64 {
+    configured.clamp(MIN_SCALE, 1.0)
+}
+
+/// Bind wp_viewporter if rendering at reduced resolution.
+///
+/// Returns `None` at full resolution, or if the compositor lacks the protocol.
+pub fn bind(globals: &GlobalList, qh: &QueueHandle<RingLight>, scale: f64) -> Option<wp_viewporter::WpViewporter> {
+    if scale >= 1.0 {
+        return None;
+    }
+    let viewporter = globals.bind(qh, 1..=1, ()).ok();
+    if viewporter.is_none() {
+        log::warn!("Compositor lacks wp_viewporter, rendering at full resolution");
+    }
+    viewporter
+}
+
+/// Buffer size for a `width` x `height` surface drawn at `scale` buffer pixels per surface pixel
+pub fn buffer_size(width: u32, height: u32, scale: f64) -> (u32, u32) {
+    let scaled = |v: u32| ((v as f64 * scale).ceil() as u32).max(1);
+    (scaled(width), scaled(height))
+}
+
+impl Dispatch<wp_viewporter::WpViewporter, ()> for RingLight {
+    fn event(
+        _state: &mut Self,
+        _proxy: &wp_viewporter::WpViewporter,
+        _event: wp_viewporter::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {}
+}
+
+impl Dispatch<wp_viewport::WpViewport, ()> for RingLight {
+    fn event(
+        _state: &mut Self,
+        _proxy: &wp_viewport::WpViewport,
+        _event: wp_viewport::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_size() {
+        assert_eq!(buffer_size(3840, 2160, 0.5), (1920, 1080));
+        assert_eq!(buffer_size(1366, 768, 0.25), (342, 192));
+        assert_eq!(buffer_size(1920, 1080, 1.0), (1920, 1080));
+        assert_eq!(buffer_size(1280, 720, 2.0), (2560, 1440));
+    }
+}
@@ -0,0 +1,152 @@
+//! `hypr-ringlight watch`: a heartbeat for long-running commands
+//!
+//! While the watched command (or process) runs, the ring breathes through a
+//! `watch` profile override. When it exits the ring flashes the success or
+//! failure color for a moment, then goes back to normal. The daemon keeps an
+//! eye on the watcher process too, so killing it doesn't leave the ring
+//! pulsing forever.
+
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use hypr_ringlight_core::ipc::{self, Command, WatchStatus};
+use signal_hook::consts::SIGINT;
+
+use crate::config::Profile;
+use crate::error::Error;
+use crate::ipc::IpcState;
+
+/// How often liveness of a process is checked
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// Watch an already running process instead of starting a command
+    #[arg(long, conflicts_with = "command", required_unless_present = "command")]
+    pid: Option<u32>,
+
+    /// Command to run, after `--`
+    #[arg(last = true)]
+    command: Vec<String>,
+}
+
+/// A watch, as tracked by the daemon
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Watch {
+    /// Pulsing while the watcher process is alive
+    Running { since: Instant },
+    /// Flashing the result color
+    Finished { success: bool, since: Instant },
+}
+
+impl Watch {
+    pub fn status(&self, now: Instant) -> WatchStatus {
+        let (state, since) = match *self {
+            Watch::Running { since } => ("running", since),
+            Watch::Finished { success: true, since } => ("succeeded", since),
+            Watch::Finished { success: false, since } => ("failed", since),
+        };
+        WatchStatus {
+            state: state.to_string(),
+            elapsed_ms: now.saturating_duration_since(since).as_millis() as u64,
+        }
+    }
+}
+
+fn is_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// Tell the daemon; the command runs all the same when it can't be reached
+fn notify(cmd: Command) {
+    if let Err(e) = ipc::send_command(&cmd) {
+        log::warn!("Ring light not told about the watch: {}", e);
+    }
+}
+
+/// Run the command (or wait for the process) with the ring pulsing, and
+/// return the exit code to pass on
+pub fn run(args: WatchArgs) -> Result<i32, Error> {
+    notify(Command::StartWatch(std::process::id()));
+
+    let (success, code) = match args.pid {
+        Some(pid) => {
+            // Not our child, so there's no exit status to report
+            while is_alive(pid) {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            (true, 0)
+        }
+        None => {
+            // Ctrl+C goes to the command; stay around to report how it ended
+            let _ = signal_hook::flag::register(SIGINT, Arc::new(AtomicBool::new(false)));
+            let status = std::process::Command::new(&args.command[0])
+                .args(&args.command[1..])
+                .status();
+            match status {
+                Ok(status) => (status.success(), status.code().unwrap_or_else(|| 128 + status.signal().unwrap_or(0))),
+                Err(e) => {
+                    notify(Command::FinishWatch(false));
+                    return Err(Error::Other(format!("Failed to run {}: {}", args.command[0], e)));
+                }
+            }
+        }
+    };
+
+    notify(Command::FinishWatch(success));
+    Ok(code)
+}
+
+/// Daemon side: pulse the ring while the watcher process `pid` is alive
+pub fn start(state: Arc<IpcState>, pid: u32) {
+    let watch = Watch::Running { since: Instant::now() };
+    state.set_watch(Some(watch));
+    let profile = Profile {
+        animation: Some("breathe".to_string()),
+        visible: Some(true),
+        ..Profile::default()
+    };
+    state.set_override("watch", Some(("watch".to_string(), profile)));
+
+    std::thread::spawn(move || {
+        while state.get_watch() == Some(watch) {
+            if !is_alive(pid) {
+                log::info!("Watcher {} went away without reporting", pid);
+                state.set_watch(None);
+                state.set_override("watch", None);
+                return;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+/// Daemon side: flash the result color, then go back to normal
+pub fn finish(state: Arc<IpcState>, success: bool) {
+    let cfg = state.watch_config();
+    let watch = Watch::Finished { success, since: Instant::now() };
+    state.set_watch(Some(watch));
+    let profile = Profile {
+        color: Some(if success { cfg.success_color.clone() } else { cfg.failure_color.clone() }),
+        animation: Some("pulse".to_string()),
+        cycle_duration: Some("500ms".to_string()),
+        visible: Some(true),
+        ..Profile::default()
+    };
+    let name = if success { "watch-success" } else { "watch-failure" };
+    state.set_override("watch", Some((name.to_string(), profile)));
+
+    let flash = cfg.flash();
+    std::thread::spawn(move || {
+        std::thread::sleep(flash);
+        // Unless another watch started meanwhile
+        if state.get_watch() == Some(watch) {
+            state.set_watch(None);
+            state.set_override("watch", None);
+        }
+    });
+}